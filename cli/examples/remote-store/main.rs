@@ -0,0 +1,70 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_cli::cli_util::CliRunner;
+use jj_cli::cli_util::CommandHelper;
+use jj_cli::command_error::CommandError;
+use jj_cli::ui::Ui;
+use jj_lib::ref_name::WorkspaceName;
+use jj_lib::remote_store::RemoteBackend;
+use jj_lib::remote_store::RemoteOpStore;
+use jj_lib::repo::ReadonlyRepo;
+use jj_lib::signing::Signer;
+use jj_lib::workspace::default_working_copy_factory;
+use jj_lib::workspace::Workspace;
+use jj_lib::workspace::WorkspaceInitError;
+
+/// Repos backed by a remote store can be loaded by any `jj` binary built with
+/// the `remote-store` feature. This example only adds a command to create
+/// them. Use the `jj-remote-store-server` binary to serve a repo.
+#[derive(clap::Parser, Clone, Debug)]
+enum CustomCommand {
+    /// Initialize a workspace backed by a remote store server
+    InitRemote {
+        /// The address of the server
+        address: String,
+    },
+}
+
+fn run_custom_command(
+    _ui: &mut Ui,
+    command_helper: &CommandHelper,
+    command: CustomCommand,
+) -> Result<(), CommandError> {
+    match command {
+        CustomCommand::InitRemote { address } => {
+            let wc_path = command_helper.cwd();
+            let settings = command_helper.settings_for_new_workspace(wc_path)?;
+            Workspace::init_with_factories(
+                &settings,
+                wc_path,
+                &|_settings, store_path| Ok(Box::new(RemoteBackend::init(store_path, &address)?)),
+                Signer::from_settings(&settings).map_err(WorkspaceInitError::SignInit)?,
+                &|_settings, store_path, _root_data| {
+                    Ok(Box::new(RemoteOpStore::init(store_path, &address)?))
+                },
+                ReadonlyRepo::default_op_heads_store_initializer(),
+                ReadonlyRepo::default_index_store_initializer(),
+                ReadonlyRepo::default_submodule_store_initializer(),
+                &*default_working_copy_factory(),
+                WorkspaceName::DEFAULT.to_owned(),
+            )?;
+            Ok(())
+        }
+    }
+}
+
+fn main() -> std::process::ExitCode {
+    CliRunner::init().add_subcommand(run_custom_command).run()
+}
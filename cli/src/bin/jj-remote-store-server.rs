@@ -0,0 +1,95 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reference server of the remote store protocol, which serves the commits
+//! and operations of a local repo. See `docs/technical/remote-store.md`.
+
+use std::error::Error;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use jj_cli::config::config_from_environment;
+use jj_cli::config::default_config_layers;
+use jj_cli::config::ConfigEnv;
+use jj_lib::op_store::RootOperationData;
+use jj_lib::remote_store::RemoteStoreServer;
+use jj_lib::repo::StoreFactories;
+use jj_lib::settings::UserSettings;
+use jj_lib::workspace::DefaultWorkspaceLoaderFactory;
+use jj_lib::workspace::WorkspaceLoaderFactory as _;
+
+/// Serve the commits and operations of a local repo to remote clients
+#[derive(Parser, Debug)]
+struct Args {
+    /// Path to the workspace whose repo is served
+    #[arg(default_value = ".")]
+    workspace: PathBuf,
+
+    /// The address to listen on
+    ///
+    /// The server has no authentication, so the address must only be
+    /// reachable from trusted networks.
+    #[arg(long, default_value = "127.0.0.1:7777")]
+    address: String,
+
+    /// Maximum number of connections served concurrently
+    #[arg(long, default_value_t = 16)]
+    max_connections: usize,
+}
+
+fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let workspace_loader = DefaultWorkspaceLoaderFactory.create(&args.workspace)?;
+    let repo_path = workspace_loader.repo_path();
+
+    let mut config_env = ConfigEnv::from_environment();
+    let mut raw_config = config_from_environment(default_config_layers());
+    config_env.reload_user_config(&mut raw_config)?;
+    config_env.reset_repo_path(repo_path);
+    config_env.reload_repo_config(&mut raw_config)?;
+    let settings = UserSettings::from_config(config_env.resolve_config(&raw_config)?)?;
+
+    // Load the stores directly so that objects are served as is.
+    let store_factories = StoreFactories::default();
+    let backend = store_factories.load_backend(&settings, &repo_path.join("store"))?;
+    let root_data = RootOperationData {
+        root_commit_id: backend.root_commit_id().clone(),
+    };
+    let op_store =
+        store_factories.load_op_store(&settings, &repo_path.join("op_store"), root_data)?;
+
+    let listener = TcpListener::bind(&args.address)?;
+    eprintln!("Serving {} on {}", repo_path.display(), args.address);
+    RemoteStoreServer::new(backend, op_store)
+        .with_max_connections(args.max_connections)
+        .serve(&listener);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            let mut source = err.source();
+            while let Some(err) = source {
+                eprintln!("Caused by: {err}");
+                source = err.source();
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -79,6 +79,7 @@ use jj_lib::op_heads_store;
 use jj_lib::op_store::OpStoreError;
 use jj_lib::op_store::OperationId;
 use jj_lib::op_store::RefTarget;
+use jj_lib::op_store::UndoRelation;
 use jj_lib::op_walk;
 use jj_lib::op_walk::OpsetEvaluationError;
 use jj_lib::operation::Operation;
@@ -1865,6 +1866,42 @@ to the current parents may contain changes from multiple commits.
         Err(error)
     }
 
+    /// Starts the built-in filesystem monitor daemon in the background if it
+    /// isn't running. Snapshots crawl the whole working copy until the daemon
+    /// is ready.
+    #[cfg(all(feature = "native-fsmonitor", unix))]
+    fn start_native_fsmonitor_daemon(&self) {
+        use std::os::unix::process::CommandExt as _;
+        use std::process::Stdio;
+
+        use jj_lib::local_working_copy::LocalWorkingCopy;
+
+        let Some(wc) = self
+            .working_copy()
+            .as_any()
+            .downcast_ref::<LocalWorkingCopy>()
+        else {
+            return;
+        };
+        if jj_lib::fsmonitor::native::is_running(wc.state_path()) {
+            return;
+        }
+        let result = env::current_exe().and_then(|jj_path| {
+            std::process::Command::new(jj_path)
+                .args(["debug", "native-fsmonitor", "run"])
+                .current_dir(self.workspace_root())
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                // Don't receive signals sent to the foreground process group.
+                .process_group(0)
+                .spawn()
+        });
+        if let Err(err) = result {
+            tracing::warn!(?err, "Failed to start the filesystem monitor daemon");
+        }
+    }
+
     #[instrument(skip_all)]
     fn snapshot_working_copy(
         &mut self,
@@ -1890,6 +1927,10 @@ to the current parents may contain changes from multiple commits.
         let options = self
             .snapshot_options_with_start_tracking_matcher(&auto_tracking_matcher)
             .map_err(snapshot_command_error)?;
+        #[cfg(all(feature = "native-fsmonitor", unix))]
+        if options.fsmonitor_settings == jj_lib::fsmonitor::FsmonitorSettings::Native {
+            self.start_native_fsmonitor_daemon();
+        }
 
         // Compare working-copy tree and operation with repo's, and reload as needed.
         let mut locked_ws = self
@@ -2428,6 +2469,11 @@ impl WorkspaceCommandTransaction<'_> {
         self.tx.repo_mut()
     }
 
+    /// Records that this transaction undoes or redoes another operation.
+    pub fn set_undo_relation(&mut self, relation: UndoRelation) {
+        self.tx.set_undo_relation(relation);
+    }
+
     pub fn check_out(&mut self, commit: &Commit) -> Result<Commit, CheckOutCommitError> {
         let name = self.helper.workspace_name().to_owned();
         self.id_prefix_context.take(); // invalidate
@@ -2560,6 +2606,7 @@ jj git init --colocate",
             err @ (StoreLoadError::ReadError { .. } | StoreLoadError::Backend(_)),
         ) => internal_error_with_message("The repository appears broken or inaccessible", err),
         WorkspaceLoadError::StoreLoadError(StoreLoadError::Signing(err)) => user_error(err),
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::Config(err)) => err.into(),
         WorkspaceLoadError::WorkingCopyState(err) => internal_error(err),
         WorkspaceLoadError::NonUnicodePath | WorkspaceLoadError::Path(_) => user_error(err),
     }
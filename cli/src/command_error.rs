@@ -73,6 +73,7 @@ use crate::merge_tools::DiffEditError;
 use crate::merge_tools::MergeToolConfigError;
 use crate::merge_tools::MergeToolPartialResolutionError;
 use crate::revset_util::BookmarkNameParseError;
+use crate::revset_util::TagNameParseError;
 use crate::revset_util::UserRevsetEvaluationError;
 use crate::template_parser::TemplateParseError;
 use crate::template_parser::TemplateParseErrorKind;
@@ -356,6 +357,7 @@ impl From<WorkspaceInitError> for CommandError {
                 internal_error_with_message("Failed to access the repository", err)
             }
             WorkspaceInitError::SignInit(err) => user_error(err),
+            WorkspaceInitError::Config(err) => err.into(),
             WorkspaceInitError::TransactionCommit(err) => err.into(),
         }
     }
@@ -591,6 +593,11 @@ jj currently does not support partial clones. To use jj with this repository, tr
                 GitPushError::Git2(err) => map_git2_error(err),
                 GitPushError::Subprocess(_) => user_error(err),
                 GitPushError::UnexpectedBackend(_) => user_error(err),
+                #[cfg(feature = "git2")]
+                GitPushError::AtomicPushUnsupported => user_error_with_hint(
+                    err,
+                    "Set `git.subprocess = true` to push atomically with the `git` executable.",
+                ),
             }
         }
     }
@@ -774,6 +781,8 @@ fn find_source_parse_error_hint(err: &dyn error::Error) -> Option<String> {
         revset_resolution_error_hint(source)
     } else if let Some(source) = source.downcast_ref() {
         string_pattern_parse_error_hint(source)
+    } else if let Some(source) = source.downcast_ref() {
+        tag_name_parse_error_hint(source)
     } else if let Some(source) = source.downcast_ref() {
         template_parse_error_hint(source)
     } else {
@@ -913,6 +922,14 @@ fn string_pattern_parse_error_hint(err: &StringPatternParseError) -> Option<Stri
     }
 }
 
+fn tag_name_parse_error_hint(_: &TagNameParseError) -> Option<String> {
+    Some(
+        "See https://jj-vcs.github.io/jj/latest/revsets/ or use `jj help -k revsets` for how to \
+         quote symbols."
+            .into(),
+    )
+}
+
 fn template_parse_error_hint(err: &TemplateParseError) -> Option<String> {
     // Only for the bottom error, which is usually the root cause
     let bottom_err = iter::successors(Some(err), |e| e.origin()).last().unwrap();
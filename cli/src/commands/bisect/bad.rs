@@ -0,0 +1,41 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCandidates;
+use jj_lib::bisect::Evaluation;
+use tracing::instrument;
+
+use super::mark_and_advance;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Mark revisions as bad (with the problem)
+#[derive(clap::Args, Clone, Debug)]
+pub struct BisectBadArgs {
+    /// The revisions to mark as bad (default: the revision being evaluated)
+    #[arg(value_name = "REVSETS", add = ArgValueCandidates::new(complete::all_revisions))]
+    revisions: Vec<RevisionArg>,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_bisect_bad(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectBadArgs,
+) -> Result<(), CommandError> {
+    mark_and_advance(ui, command, &args.revisions, Evaluation::Bad)
+}
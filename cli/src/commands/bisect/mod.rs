@@ -0,0 +1,306 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod bad;
+mod good;
+mod reset;
+mod run;
+mod skip;
+mod start;
+
+use std::io;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::BisectionResult;
+use jj_lib::bisect::Bisector;
+use jj_lib::bisect::Evaluation;
+use jj_lib::bisect::NextStep;
+use jj_lib::file_util::IoResultExt as _;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo;
+use tracing::instrument;
+
+use self::bad::cmd_bisect_bad;
+use self::bad::BisectBadArgs;
+use self::good::cmd_bisect_good;
+use self::good::BisectGoodArgs;
+use self::reset::cmd_bisect_reset;
+use self::reset::BisectResetArgs;
+use self::run::cmd_bisect_run;
+use self::run::BisectRunArgs;
+use self::skip::cmd_bisect_skip;
+use self::skip::BisectSkipArgs;
+use self::start::cmd_bisect_start;
+use self::start::BisectStartArgs;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::internal_error_with_message;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Find the revision that introduced a problem by binary search
+///
+/// Start a bisection with `jj bisect start`, then mark revisions as good
+/// (without the problem) or bad (with the problem). Descendants of bad
+/// revisions are assumed to be bad, and ancestors of good revisions are
+/// assumed to be good. After each mark, the working copy is moved to a new
+/// commit on top of the next revision to evaluate, until the first bad
+/// revision is found. `jj bisect run` automates this with a command.
+///
+/// The bisection state is stored in the workspace, so it's kept across
+/// invocations until `jj bisect reset` is run. Each workspace can run its own
+/// bisection.
+#[derive(Subcommand, Clone, Debug)]
+pub(crate) enum BisectCommand {
+    Bad(BisectBadArgs),
+    Good(BisectGoodArgs),
+    Reset(BisectResetArgs),
+    Run(BisectRunArgs),
+    Skip(BisectSkipArgs),
+    Start(BisectStartArgs),
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &BisectCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        BisectCommand::Bad(args) => cmd_bisect_bad(ui, command, args),
+        BisectCommand::Good(args) => cmd_bisect_good(ui, command, args),
+        BisectCommand::Reset(args) => cmd_bisect_reset(ui, command, args),
+        BisectCommand::Run(args) => cmd_bisect_run(ui, command, args),
+        BisectCommand::Skip(args) => cmd_bisect_skip(ui, command, args),
+        BisectCommand::Start(args) => cmd_bisect_start(ui, command, args),
+    }
+}
+
+/// Bisection state stored in the workspace between invocations.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+struct BisectState {
+    good: Vec<String>,
+    bad: Vec<String>,
+    skipped: Vec<String>,
+    /// The revision the working copy was moved to for evaluation.
+    current: Option<String>,
+    /// The working-copy commit when the bisection was started.
+    original_working_copy: Option<String>,
+}
+
+impl BisectState {
+    /// The state is stored per workspace since each workspace has its own
+    /// working copy to move around.
+    fn path(workspace_command: &WorkspaceCommandHelper) -> PathBuf {
+        workspace_command
+            .workspace_root()
+            .join(".jj")
+            .join("working_copy")
+            .join("bisect_state")
+    }
+
+    fn load(workspace_command: &WorkspaceCommandHelper) -> Result<Option<Self>, CommandError> {
+        let path = Self::path(workspace_command);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).context(&path)?,
+        };
+        let state = serde_json::from_slice(&data).map_err(|err| {
+            internal_error_with_message(
+                format!("Failed to parse bisection state in {}", path.display()),
+                err,
+            )
+        })?;
+        Ok(Some(state))
+    }
+
+    fn load_started(workspace_command: &WorkspaceCommandHelper) -> Result<Self, CommandError> {
+        Self::load(workspace_command)?.ok_or_else(|| {
+            user_error_with_hint(
+                "No bisection in progress",
+                "Use `jj bisect start` to start one.",
+            )
+        })
+    }
+
+    fn save(&self, workspace_command: &WorkspaceCommandHelper) -> Result<(), CommandError> {
+        let path = Self::path(workspace_command);
+        let data = serde_json::to_vec_pretty(self).unwrap();
+        std::fs::write(&path, data).context(&path)?;
+        Ok(())
+    }
+
+    fn remove(workspace_command: &WorkspaceCommandHelper) -> Result<(), CommandError> {
+        let path = Self::path(workspace_command);
+        match std::fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err).context(&path)?,
+            _ => Ok(()),
+        }
+    }
+
+    fn mark(&mut self, id: &CommitId, evaluation: Evaluation) {
+        let hex = id.hex();
+        for list in [&mut self.good, &mut self.bad, &mut self.skipped] {
+            list.retain(|other| *other != hex);
+        }
+        let target = match evaluation {
+            Evaluation::Good => &mut self.good,
+            Evaluation::Bad => &mut self.bad,
+            Evaluation::Skip => &mut self.skipped,
+        };
+        target.push(hex);
+    }
+
+    fn current_commit_id(&self) -> Result<Option<CommitId>, CommandError> {
+        self.current.as_deref().map(parse_commit_id).transpose()
+    }
+
+    fn original_working_copy_id(&self) -> Result<Option<CommitId>, CommandError> {
+        self.original_working_copy
+            .as_deref()
+            .map(parse_commit_id)
+            .transpose()
+    }
+
+    fn to_bisector<'repo>(&self, repo: &'repo dyn Repo) -> Result<Bisector<'repo>, CommandError> {
+        let mut bisector = Bisector::new(repo);
+        for (list, evaluation) in [
+            (&self.good, Evaluation::Good),
+            (&self.bad, Evaluation::Bad),
+            (&self.skipped, Evaluation::Skip),
+        ] {
+            for hex in list {
+                bisector.mark(parse_commit_id(hex)?, evaluation);
+            }
+        }
+        Ok(bisector)
+    }
+}
+
+fn parse_commit_id(hex: &str) -> Result<CommitId, CommandError> {
+    CommitId::try_from_hex(hex).map_err(|err| {
+        user_error_with_message(format!("Invalid commit id in bisection state: {hex}"), err)
+    })
+}
+
+/// Marks the revisions (or the revision being evaluated) and moves on to the
+/// next step.
+fn mark_and_advance(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    revisions: &[RevisionArg],
+    evaluation: Evaluation,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let mut state = BisectState::load_started(&workspace_command)?;
+    let commit_ids: Vec<CommitId> = if revisions.is_empty() {
+        let current = state.current_commit_id()?.ok_or_else(|| {
+            user_error_with_hint(
+                "No revision is being evaluated",
+                "Specify the revisions to mark.",
+            )
+        })?;
+        vec![current]
+    } else {
+        workspace_command
+            .parse_union_revsets(ui, revisions)?
+            .evaluate_to_commit_ids()?
+            .try_collect()?
+    };
+    for id in &commit_ids {
+        state.mark(id, evaluation);
+    }
+    state.save(&workspace_command)?;
+    advance(ui, &mut workspace_command, &mut state)?;
+    Ok(())
+}
+
+/// Checks out the next revision to evaluate, or reports the result if the
+/// bisection is complete.
+fn advance(
+    ui: &mut Ui,
+    workspace_command: &mut WorkspaceCommandHelper,
+    state: &mut BisectState,
+) -> Result<Option<BisectionResult>, CommandError> {
+    if state.bad.is_empty() {
+        state.current = None;
+        state.save(workspace_command)?;
+        writeln!(
+            ui.status(),
+            "Mark a bad revision with `jj bisect bad` to continue."
+        )?;
+        return Ok(None);
+    }
+    let repo = workspace_command.repo().clone();
+    let bisector = state.to_bisector(repo.as_ref())?;
+    if let Some((good, bad)) = bisector.inconsistent_marks().first() {
+        return Err(user_error(format!(
+            "Revision {} is marked good but is a descendant of bad revision {}",
+            short_commit_hash(good),
+            short_commit_hash(bad)
+        )));
+    }
+    match bisector.next_step()? {
+        NextStep::Evaluate(commit_id) => {
+            let num_remaining = bisector.remaining_commits()?.len();
+            let commit = repo.store().get_commit(&commit_id)?;
+            writeln!(
+                ui.status(),
+                "Bisecting: {num_remaining} revisions left to test (roughly {} steps)",
+                num_remaining.ilog2() + 1
+            )?;
+            let mut tx = workspace_command.start_transaction();
+            if let Some(mut formatter) = ui.status_formatter() {
+                write!(formatter, "Now evaluating: ")?;
+                tx.write_commit_summary(formatter.as_mut(), &commit)?;
+                writeln!(formatter)?;
+            }
+            tx.check_out(&commit)?;
+            tx.finish(ui, format!("bisect: check out commit {}", commit_id.hex()))?;
+            state.current = Some(commit_id.hex());
+            state.save(workspace_command)?;
+            Ok(None)
+        }
+        NextStep::Done(result) => {
+            state.current = None;
+            state.save(workspace_command)?;
+            let (message, commit_ids) = match &result {
+                BisectionResult::Found(commit_ids) => ("The first bad revision is:", commit_ids),
+                BisectionResult::Indeterminate(commit_ids) => (
+                    "Some revisions were skipped. The first bad revision is one of:",
+                    commit_ids,
+                ),
+            };
+            let mut formatter = ui.stdout_formatter();
+            let template = workspace_command.commit_summary_template();
+            writeln!(formatter, "{message}")?;
+            for commit_id in commit_ids {
+                let commit = repo.store().get_commit(commit_id)?;
+                template.format(&commit, formatter.as_mut())?;
+                writeln!(formatter)?;
+            }
+            Ok(Some(result))
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::repo::Repo as _;
+use jj_lib::revset::RevsetExpression;
+use tracing::instrument;
+
+use super::BisectState;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Stop the bisection in progress
+///
+/// The working copy is moved back to the commit it was at when the bisection
+/// was started.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BisectResetArgs {}
+
+#[instrument(skip_all)]
+pub fn cmd_bisect_reset(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &BisectResetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let state = BisectState::load_started(&workspace_command)?;
+    BisectState::remove(&workspace_command)?;
+    writeln!(ui.status(), "Stopped bisection.")?;
+
+    let Some(original_id) = state.original_working_copy_id()? else {
+        return Ok(());
+    };
+    if workspace_command.get_wc_commit_id() == Some(&original_id) {
+        return Ok(());
+    }
+    let repo = workspace_command.repo().clone();
+    let original = repo.store().get_commit(&original_id)?;
+    let is_visible = RevsetExpression::commit(original_id)
+        .intersection(&RevsetExpression::visible_heads().ancestors())
+        .evaluate(repo.as_ref())?
+        .iter()
+        .next()
+        .is_some();
+    let mut tx = workspace_command.start_transaction();
+    if is_visible {
+        tx.edit(&original)?;
+    } else {
+        // The original working-copy commit was discarded when the working copy
+        // moved away from it, so create an equivalent one.
+        let new_commit = tx
+            .repo_mut()
+            .new_commit(original.parent_ids().to_vec(), original.tree_id().clone())
+            .set_description(original.description())
+            .write()?;
+        tx.edit(&new_commit)?;
+    }
+    tx.finish(ui, "bisect: reset working copy")?;
+    Ok(())
+}
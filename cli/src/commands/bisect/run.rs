@@ -0,0 +1,109 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::bisect::Evaluation;
+use tracing::instrument;
+
+use super::advance;
+use super::BisectState;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::commands::run::shell_command_builder;
+use crate::ui::Ui;
+
+/// Run a command to evaluate revisions until the bisection is complete
+///
+/// The command is run from the workspace root after the working copy is moved
+/// to each revision to evaluate. The exit status of the command determines
+/// the evaluation:
+///
+///  - 0: the revision is good
+///  - 125: the revision can't be evaluated and is skipped
+///  - 1 to 127 (except 125): the revision is bad
+///
+/// Any other exit status, or termination by a signal, stops the bisection.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BisectRunArgs {
+    /// The shell command to evaluate each revision with
+    shell_command: String,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_bisect_run(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectRunArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let mut state = BisectState::load_started(&workspace_command)?;
+    if state.bad.is_empty() {
+        return Err(user_error_with_hint(
+            "No bad revisions have been marked",
+            "Use `jj bisect bad` to mark at least one revision as bad.",
+        ));
+    }
+    if state.current.is_none() && advance(ui, &mut workspace_command, &mut state)?.is_some() {
+        return Ok(());
+    }
+    loop {
+        let current_id = state
+            .current_commit_id()?
+            .expect("the revision to evaluate should be set");
+        let status = {
+            let mut cmd = shell_command_builder(&args.shell_command);
+            cmd.current_dir(workspace_command.workspace_root());
+            tracing::debug!(?cmd, "spawning bisect command");
+            cmd.status().map_err(|err| {
+                user_error_with_message(
+                    format!("Failed to execute command '{}'", args.shell_command),
+                    err,
+                )
+            })?
+        };
+        let evaluation = match status.code() {
+            Some(0) => Evaluation::Good,
+            Some(125) => Evaluation::Skip,
+            Some(1..=127) => Evaluation::Bad,
+            Some(exit_code) => {
+                return Err(user_error(format!(
+                    "Command exited with {exit_code}, stopping the bisection"
+                )));
+            }
+            None => {
+                return Err(user_error(format!(
+                    "Command was terminated by: {status}, stopping the bisection"
+                )));
+            }
+        };
+        writeln!(
+            ui.status(),
+            "The revision is {}.",
+            match evaluation {
+                Evaluation::Good => "good",
+                Evaluation::Bad => "bad",
+                Evaluation::Skip => "skipped",
+            }
+        )?;
+        state.mark(&current_id, evaluation);
+        // The working copy may have been modified by the command, so reload
+        // the workspace to snapshot it before checking out the next revision.
+        workspace_command = command.workspace_helper(ui)?;
+        if advance(ui, &mut workspace_command, &mut state)?.is_some() {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::Evaluation;
+use jj_lib::object_id::ObjectId as _;
+use tracing::instrument;
+
+use super::advance;
+use super::BisectState;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Start a new bisection
+///
+/// Any bisection already in progress is discarded. If both good and bad
+/// revisions are given, the working copy is moved to the first revision to
+/// evaluate.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BisectStartArgs {
+    /// Revisions known to be bad (with the problem)
+    #[arg(long, value_name = "REVSETS", add = ArgValueCandidates::new(complete::all_revisions))]
+    bad: Vec<RevisionArg>,
+    /// Revisions known to be good (without the problem)
+    #[arg(long, value_name = "REVSETS", add = ArgValueCandidates::new(complete::all_revisions))]
+    good: Vec<RevisionArg>,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_bisect_start(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectStartArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let mut state = match BisectState::load(&workspace_command)? {
+        // Keep the original working copy so `jj bisect reset` can go back to it.
+        Some(old_state) => BisectState {
+            original_working_copy: old_state.original_working_copy,
+            ..Default::default()
+        },
+        None => BisectState {
+            original_working_copy: workspace_command.get_wc_commit_id().map(|id| id.hex()),
+            ..Default::default()
+        },
+    };
+    for (revisions, evaluation) in [(&args.good, Evaluation::Good), (&args.bad, Evaluation::Bad)] {
+        if revisions.is_empty() {
+            continue;
+        }
+        let commit_ids: Vec<CommitId> = workspace_command
+            .parse_union_revsets(ui, revisions)?
+            .evaluate_to_commit_ids()?
+            .try_collect()?;
+        for id in &commit_ids {
+            state.mark(id, evaluation);
+        }
+    }
+    state.save(&workspace_command)?;
+    writeln!(ui.status(), "Started bisection.")?;
+    advance(ui, &mut workspace_command, &mut state)?;
+    Ok(())
+}
@@ -471,6 +471,7 @@ mod tests {
             author,
             committer,
             secure_sig: None,
+            copies: vec![],
         })
     }
 
@@ -17,6 +17,7 @@ mod fileset;
 mod index;
 mod init_simple;
 mod local_working_copy;
+mod native_fsmonitor;
 mod operation;
 mod reindex;
 mod revset;
@@ -42,6 +43,8 @@ use self::init_simple::cmd_debug_init_simple;
 use self::init_simple::DebugInitSimpleArgs;
 use self::local_working_copy::cmd_debug_local_working_copy;
 use self::local_working_copy::DebugLocalWorkingCopyArgs;
+use self::native_fsmonitor::cmd_debug_native_fsmonitor;
+use self::native_fsmonitor::DebugNativeFsmonitorCommand;
 use self::operation::cmd_debug_operation;
 use self::operation::DebugOperationArgs;
 use self::reindex::cmd_debug_reindex;
@@ -72,6 +75,8 @@ pub enum DebugCommand {
     Index(DebugIndexArgs),
     InitSimple(DebugInitSimpleArgs),
     LocalWorkingCopy(DebugLocalWorkingCopyArgs),
+    #[command(subcommand)]
+    NativeFsmonitor(DebugNativeFsmonitorCommand),
     #[command(visible_alias = "view")]
     Operation(DebugOperationArgs),
     Reindex(DebugReindexArgs),
@@ -95,6 +100,7 @@ pub fn cmd_debug(
         DebugCommand::Index(args) => cmd_debug_index(ui, command, args),
         DebugCommand::InitSimple(args) => cmd_debug_init_simple(ui, command, args),
         DebugCommand::LocalWorkingCopy(args) => cmd_debug_local_working_copy(ui, command, args),
+        DebugCommand::NativeFsmonitor(args) => cmd_debug_native_fsmonitor(ui, command, args),
         DebugCommand::Operation(args) => cmd_debug_operation(ui, command, args),
         DebugCommand::Reindex(args) => cmd_debug_reindex(ui, command, args),
         DebugCommand::Revset(args) => cmd_debug_revset(ui, command, args),
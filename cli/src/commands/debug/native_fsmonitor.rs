@@ -0,0 +1,107 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+#[cfg(all(feature = "native-fsmonitor", unix))]
+use std::io::Write as _;
+
+use clap::Subcommand;
+#[cfg(all(feature = "native-fsmonitor", unix))]
+use jj_lib::fsmonitor::native;
+#[cfg(all(feature = "native-fsmonitor", unix))]
+use jj_lib::fsmonitor::FsmonitorSettings;
+
+#[cfg(all(feature = "native-fsmonitor", unix))]
+use super::check_local_disk_wc;
+use crate::cli_util::CommandHelper;
+#[cfg(all(feature = "native-fsmonitor", unix))]
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum DebugNativeFsmonitorCommand {
+    /// Check whether the built-in filesystem monitor is enabled and running
+    Status,
+    /// Run the filesystem monitor daemon in the foreground
+    Run,
+    /// Ask the filesystem monitor daemon to exit
+    Stop,
+    QueryChangedFiles,
+}
+
+#[cfg(all(feature = "native-fsmonitor", unix))]
+pub fn cmd_debug_native_fsmonitor(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &DebugNativeFsmonitorCommand,
+) -> Result<(), CommandError> {
+    // Don't snapshot the working copy, which could start another daemon.
+    let workspace = command.load_workspace()?;
+    let wc = check_local_disk_wc(workspace.working_copy().as_any())?;
+    match subcommand {
+        DebugNativeFsmonitorCommand::Status => {
+            if command.settings().fsmonitor_settings()? == FsmonitorSettings::Native {
+                writeln!(
+                    ui.stdout(),
+                    "The built-in filesystem monitor is enabled via `core.fsmonitor`."
+                )?;
+            } else {
+                writeln!(
+                    ui.stdout(),
+                    r#"The built-in filesystem monitor is disabled. Set `core.fsmonitor="native"` to enable."#
+                )?;
+            }
+            writeln!(
+                ui.stdout(),
+                "The daemon is {}.",
+                if native::is_running(wc.state_path()) {
+                    "running"
+                } else {
+                    "not running"
+                }
+            )?;
+        }
+        DebugNativeFsmonitorCommand::Run => {
+            native::run_daemon(workspace.workspace_root(), wc.state_path()).map_err(
+                |err| match err {
+                    native::Error::AlreadyRunning => user_error(err),
+                    err => internal_error(err),
+                },
+            )?;
+        }
+        DebugNativeFsmonitorCommand::Stop => {
+            native::stop(wc.state_path()).map_err(user_error)?;
+            writeln!(ui.status(), "Stopped the filesystem monitor daemon")?;
+        }
+        DebugNativeFsmonitorCommand::QueryChangedFiles => {
+            let (_clock, changed_files) = wc.query_native_fsmonitor()?;
+            writeln!(ui.stdout(), "Changed files: {changed_files:?}")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(all(feature = "native-fsmonitor", unix)))]
+pub fn cmd_debug_native_fsmonitor(
+    _ui: &mut Ui,
+    _command: &CommandHelper,
+    _subcommand: &DebugNativeFsmonitorCommand,
+) -> Result<(), CommandError> {
+    Err(user_error(
+        "The built-in filesystem monitor is only supported on Unix, and requires jj to be \
+         compiled with the `native-fsmonitor` feature",
+    ))
+}
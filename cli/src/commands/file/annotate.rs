@@ -12,11 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::ops::Range;
+
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
 use jj_lib::annotate::FileAnnotation;
 use jj_lib::annotate::FileAnnotator;
+use jj_lib::backend::CommitId;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
 use jj_lib::revset::RevsetExpression;
 use tracing::instrument;
 
@@ -66,6 +71,42 @@ pub(crate) struct FileAnnotateArgs {
     ///     https://jj-vcs.github.io/jj/latest/templates/
     #[arg(long, short = 'T', add = ArgValueCandidates::new(complete::template_aliases))]
     template: Option<String>,
+    /// Ignore changes made in these revisions
+    ///
+    /// Lines changed in the ignored revisions are attributed to the revisions
+    /// which previously changed them. This is useful to skip commits which
+    /// only reformat code. The revisions in the `revsets.annotate-ignore`
+    /// setting are always ignored.
+    #[arg(
+        long,
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::all_revisions)
+    )]
+    ignore_rev: Vec<RevisionArg>,
+    /// Only annotate the lines from START to END (inclusive, 1-based)
+    #[arg(
+        long,
+        short = 'L',
+        value_name = "START,END",
+        value_parser = parse_line_range
+    )]
+    line_range: Option<Range<usize>>,
+}
+
+fn parse_line_range(s: &str) -> Result<Range<usize>, String> {
+    let parse_line_number = |s: &str| match s.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("invalid line number: {s}")),
+    };
+    let (start, end) = s
+        .split_once(',')
+        .ok_or_else(|| "expected START,END".to_owned())?;
+    let start = parse_line_number(start)?;
+    let end = parse_line_number(end)?;
+    if start > end {
+        return Err(format!("start line {start} is after end line {end}"));
+    }
+    Ok(start - 1..end)
 }
 
 #[instrument(skip_all)]
@@ -104,15 +145,48 @@ pub(crate) fn cmd_file_annotate(
         CommitTemplateLanguage::wrap_annotation_line,
     )?;
 
+    let ignore_revset_text = workspace_command
+        .settings()
+        .get_string("revsets.annotate-ignore")?;
+    let ignored_commit_ids: Vec<CommitId> = workspace_command
+        .parse_union_revsets(
+            ui,
+            &itertools::chain(
+                [RevisionArg::from(ignore_revset_text)],
+                args.ignore_rev.iter().cloned(),
+            )
+            .collect_vec(),
+        )?
+        .evaluate_to_commit_ids()?
+        .try_collect()?;
+
     // TODO: Should we add an option to limit the domain to e.g. recent commits?
-    // Note that this is probably different from "--skip REVS", which won't
-    // exclude the revisions, but will ignore diffs in those revisions as if
-    // ancestor revisions had new content.
     let mut annotator = FileAnnotator::from_commit(&starting_commit, &file_path)?;
+    annotator.set_ignored_commits(ignored_commit_ids);
+    if let Some(line_range) = &args.line_range {
+        annotator.restrict_lines(line_range.clone());
+    }
     annotator.compute(repo.as_ref(), &RevsetExpression::all())?;
     let annotation = annotator.to_annotation();
 
-    render_file_annotation(repo.as_ref(), ui, &template, &annotation)?;
+    let num_lines = annotation.lines().count();
+    let line_range = match &args.line_range {
+        Some(range) if range.start >= num_lines => {
+            return Err(user_error(format!(
+                "Line range starts after the end of the file: {ui_path} has {num_lines} lines"
+            )));
+        }
+        Some(range) => range.start..range.end.min(num_lines),
+        None => 0..num_lines,
+    };
+    render_file_annotation(
+        repo.as_ref(),
+        ui,
+        &template,
+        &file_path,
+        &annotation,
+        line_range,
+    )?;
     Ok(())
 }
 
@@ -120,24 +194,31 @@ fn render_file_annotation(
     repo: &dyn Repo,
     ui: &mut Ui,
     template_render: &TemplateRenderer<AnnotationLine>,
+    file_path: &RepoPath,
     annotation: &FileAnnotation,
+    line_range: Range<usize>,
 ) -> Result<(), CommandError> {
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let mut last_id = None;
     let default_id = repo.store().root_commit_id();
-    for (line_number, (commit_id, content)) in annotation.lines().enumerate() {
+    let lines = annotation.lines().enumerate();
+    for (line_number, (line_origin, content)) in lines.take(line_range.end).skip(line_range.start) {
         /* At least in cases where the repository was jj-initialized shallowly,
         then unshallow'd with git, some changes will not have a commit id
         because jj does not import the unshallow'd commits. So we default
         to the root commit id for now. */
-        let commit_id = commit_id.unwrap_or(default_id);
+        let (commit_id, original_path) = match line_origin {
+            Ok(origin) => (&origin.commit_id, &origin.path),
+            Err(origin) => (default_id, &origin.path),
+        };
         let commit = repo.store().get_commit(commit_id)?;
         let first_line_in_hunk = last_id != Some(commit_id);
         let annotation_line = AnnotationLine {
             commit,
             content: content.to_owned(),
             line_number: line_number + 1,
+            original_path: (original_path.as_ref() != file_path).then(|| original_path.clone()),
             first_line_in_hunk,
         };
         template_render.format(&annotation_line, formatter.as_mut())?;
@@ -0,0 +1,172 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCandidates;
+use clap_complete::ArgValueCompleter;
+use jj_lib::backend::RecordedCopy;
+use jj_lib::matchers::PrefixMatcher;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTreeBuilder;
+use jj_lib::object_id::ObjectId as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Copy a file or directory and record the copy in the revision
+///
+/// Unlike copying the file in the working copy, the copy is recorded in the
+/// revision, so diffs show the new file as a copy even if it's changed
+/// heavily afterwards.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FileCopyArgs {
+    /// The revision to update
+    #[arg(
+        long, short,
+        default_value = "@",
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::mutable_revisions),
+    )]
+    revision: RevisionArg,
+    /// The file or directory to copy
+    #[arg(
+        value_hint = clap::ValueHint::AnyPath,
+        add = ArgValueCompleter::new(complete::all_revision_files),
+    )]
+    source: String,
+    /// The path to copy to, which must not exist yet
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    target: String,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_file_copy(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FileCopyArgs,
+) -> Result<(), CommandError> {
+    copy_path(
+        ui,
+        command,
+        &args.revision,
+        &args.source,
+        &args.target,
+        false,
+    )
+}
+
+/// Copies or moves (if `remove_source` is true) the `source` path to `target`
+/// in the given revision, and records the copy in it.
+pub(crate) fn copy_path(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    revision: &RevisionArg,
+    source: &str,
+    target: &str,
+    remove_source: bool,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, revision)?;
+    workspace_command.check_rewritable([commit.id()])?;
+    let source = workspace_command.parse_file_path(source)?;
+    let target = workspace_command.parse_file_path(target)?;
+    let tree = commit.tree()?;
+    let parent_tree = commit.parent_tree(workspace_command.repo().as_ref())?;
+
+    if target.starts_with(&source) {
+        return Err(user_error(format!(
+            "Cannot copy '{}' into itself.",
+            workspace_command.format_file_path(&source)
+        )));
+    }
+    if tree
+        .entries_matching(&PrefixMatcher::new([&target]))
+        .next()
+        .is_some()
+    {
+        return Err(user_error(format!(
+            "Destination path '{}' already exists.",
+            workspace_command.format_file_path(&target)
+        )));
+    }
+    let mut target_dir = target.parent();
+    while let Some(dir) = target_dir {
+        let value = tree.path_value(dir)?;
+        if value.is_present() && !value.is_tree() {
+            return Err(user_error(format!(
+                "Destination path '{}' is inside the file '{}'.",
+                workspace_command.format_file_path(&target),
+                workspace_command.format_file_path(dir)
+            )));
+        }
+        target_dir = dir.parent();
+    }
+
+    let mut copies = commit.copies().to_vec();
+    let mut tree_builder = MergedTreeBuilder::new(commit.tree_id().clone());
+    let mut num_files = 0;
+    for (path, value) in tree.entries_matching(&PrefixMatcher::new([&source])) {
+        let value = value?;
+        let relative_path = path.strip_prefix(&source).unwrap();
+        let new_path = relative_path
+            .components()
+            .fold(target.clone(), |new_path, name| new_path.join(name));
+        // Record the copy from where the file was in the parent, if it was
+        // itself copied or moved in this revision.
+        let origin = copies
+            .iter()
+            .find(|copy| copy.target == path)
+            .map_or_else(|| path.clone(), |copy| copy.source.clone());
+        if remove_source {
+            copies.retain(|copy| copy.target != path);
+            tree_builder.set_or_remove(path, Merge::absent());
+        }
+        if origin != new_path && parent_tree.path_value(&origin)?.is_present() {
+            copies.push(RecordedCopy {
+                source: origin,
+                target: new_path.clone(),
+            });
+        }
+        tree_builder.set_or_remove(new_path, value);
+        num_files += 1;
+    }
+    if num_files == 0 {
+        return Err(user_error(format!(
+            "No such path '{}'.",
+            workspace_command.format_file_path(&source)
+        )));
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let new_tree_id = tree_builder.write_tree(tree.store())?;
+    tx.repo_mut()
+        .rewrite_commit(&commit)
+        .set_tree_id(new_tree_id)
+        .set_copies(copies)
+        .write()?;
+    tx.finish(
+        ui,
+        format!(
+            "{} {} to {} in commit {}",
+            if remove_source { "move" } else { "copy" },
+            source.as_internal_file_string(),
+            target.as_internal_file_string(),
+            commit.id().hex(),
+        ),
+    )
+}
@@ -0,0 +1,67 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCandidates;
+use clap_complete::ArgValueCompleter;
+use tracing::instrument;
+
+use super::copy::copy_path;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Move or rename a file or directory and record the rename in the revision
+///
+/// Unlike renaming the file in the working copy, the rename is recorded in
+/// the revision. Diffs show the file as renamed even if it's changed heavily
+/// afterwards, and changes to the old path are moved to the new path when the
+/// revision is rebased.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FileMoveArgs {
+    /// The revision to update
+    #[arg(
+        long, short,
+        default_value = "@",
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::mutable_revisions),
+    )]
+    revision: RevisionArg,
+    /// The file or directory to move
+    #[arg(
+        value_hint = clap::ValueHint::AnyPath,
+        add = ArgValueCompleter::new(complete::all_revision_files),
+    )]
+    source: String,
+    /// The path to move to, which must not exist yet
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    target: String,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_file_move(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FileMoveArgs,
+) -> Result<(), CommandError> {
+    copy_path(
+        ui,
+        command,
+        &args.revision,
+        &args.source,
+        &args.target,
+        true,
+    )
+}
@@ -0,0 +1,144 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Read as _;
+use std::io::Write as _;
+
+use bstr::ByteSlice as _;
+use clap_complete::ArgValueCandidates;
+use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
+use jj_lib::backend::TreeValue;
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo as _;
+use jj_lib::str_util::StringPattern;
+use jj_lib::str_util::StringPatternParseError;
+use tracing::instrument;
+
+use crate::cli_util::print_unmatched_explicit_paths;
+use crate::cli_util::short_change_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Search for content in files
+///
+/// Each matching line is printed as `<path>:<line number>:<line>`. Conflicted
+/// files are searched on each side of the conflict separately, and matches
+/// are reported as `<path> (side #<n>):<line number>:<line>`.
+///
+/// If multiple revisions are searched, each line is prefixed with the change
+/// ID of the revision it was found in.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FileSearchArgs {
+    /// The revision(s) to search files in
+    #[arg(
+        long, short,
+        default_value = "@",
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revisions: Vec<RevisionArg>,
+    /// The pattern to search for
+    ///
+    /// By default, lines containing the given string are matched. Use
+    /// `glob:`, `regex:`, or another [string pattern] prefix to change how the
+    /// pattern is matched. Unlike `substring:` and `regex:` patterns, which
+    /// match any part of the line, `exact:` and `glob:` patterns have to match
+    /// the whole line. Unknown prefixes are searched for as part of the string.
+    ///
+    /// [string pattern]:
+    ///     https://jj-vcs.github.io/jj/latest/revsets/#string-patterns
+    #[arg(long, short, value_parser = parse_search_pattern)]
+    pattern: StringPattern,
+    /// Only search files matching these filesets
+    #[arg(
+        value_name = "FILESETS",
+        value_hint = clap::ValueHint::AnyPath,
+        add = ArgValueCompleter::new(complete::all_revision_files),
+    )]
+    paths: Vec<String>,
+}
+
+fn parse_search_pattern(src: &str) -> Result<StringPattern, StringPatternParseError> {
+    if let Some((kind, pat)) = src.split_once(':') {
+        match StringPattern::from_str_kind(pat, kind) {
+            // Search text may contain ":" (e.g. "std::io")
+            Err(StringPatternParseError::InvalidKind(_)) => Ok(StringPattern::substring(src)),
+            result => result,
+        }
+    } else {
+        Ok(StringPattern::substring(src))
+    }
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_file_search(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FileSearchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
+    let matcher = fileset_expression.to_matcher();
+    let store = workspace_command.repo().store();
+
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let mut trees = vec![];
+    for commit in &commits {
+        let prefix = if commits.len() > 1 {
+            format!("{}:", short_change_hash(commit.change_id()))
+        } else {
+            String::new()
+        };
+        let tree = commit.tree()?;
+        for (path, value) in tree.entries_matching(matcher.as_ref()) {
+            let value = value?;
+            let ui_path = workspace_command.format_file_path(&path);
+            for (side, term) in value.adds().enumerate() {
+                let Some(TreeValue::File { id, .. }) = term else {
+                    continue;
+                };
+                let mut content = vec![];
+                store.read_file(&path, id)?.read_to_end(&mut content)?;
+                // Skip binary files
+                if content.contains(&b'\0') {
+                    continue;
+                }
+                let location = if value.is_resolved() {
+                    ui_path.clone()
+                } else {
+                    format!("{ui_path} (side #{})", side + 1)
+                };
+                for (line_number, line) in content.lines().enumerate() {
+                    let line = line.to_str_lossy();
+                    if args.pattern.matches(&line) {
+                        writeln!(formatter, "{prefix}{location}:{}:{line}", line_number + 1)?;
+                    }
+                }
+            }
+        }
+        trees.push(tree);
+    }
+    drop(formatter);
+    print_unmatched_explicit_paths(ui, &workspace_command, &fileset_expression, &trees)?;
+    Ok(())
+}
@@ -12,12 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::hash_map;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
 use std::io;
 use std::io::Write as _;
+use std::rc::Rc;
 
+use bstr::ByteVec as _;
 use clap::ArgGroup;
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
@@ -29,8 +32,12 @@ use jj_lib::commit::CommitIteratorExt as _;
 use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::git;
 use jj_lib::git::GitBranchPushTargets;
+use jj_lib::git::GitPushOptions;
 use jj_lib::git::GitPushStats;
+use jj_lib::git::GitRefKind;
+use jj_lib::git::REMOTE_NAME_FOR_LOCAL_GIT_REPO;
 use jj_lib::op_store::RefTarget;
+use jj_lib::ref_name::GitRefNameBuf;
 use jj_lib::ref_name::RefName;
 use jj_lib::ref_name::RefNameBuf;
 use jj_lib::ref_name::RemoteName;
@@ -42,6 +49,7 @@ use jj_lib::refs::BookmarkPushUpdate;
 use jj_lib::refs::LocalAndRemoteRef;
 use jj_lib::repo::Repo;
 use jj_lib::revset::RevsetExpression;
+use jj_lib::revset::UserRevsetExpression;
 use jj_lib::settings::UserSettings;
 use jj_lib::signing::SignBehavior;
 use jj_lib::str_util::StringPattern;
@@ -58,10 +66,13 @@ use crate::command_error::cli_error;
 use crate::command_error::cli_error_with_message;
 use crate::command_error::user_error;
 use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::commands::git::get_single_remote;
+use crate::commands::tag::find_tags;
 use crate::complete;
 use crate::formatter::Formatter;
+use crate::formatter::PlainTextFormatter;
 #[cfg(feature = "git2")]
 use crate::git_util::print_git2_deprecation_warning;
 use crate::git_util::with_remote_git_callbacks;
@@ -73,7 +84,8 @@ use crate::ui::Ui;
 /// By default, pushes tracking bookmarks pointing to
 /// `remote_bookmarks(remote=<remote>)..@`. Use `--bookmark` to push specific
 /// bookmarks. Use `--all` to push all bookmarks. Use `--change` to generate
-/// bookmark names based on the change IDs of specific commits.
+/// bookmark names based on the change IDs of specific commits. Use `--stack`
+/// to do the same for every mutable commit in a stack.
 ///
 /// Unlike in Git, the remote to push to is not derived from the tracked remote
 /// bookmarks. Use `--remote` to select the remote Git repository by name. There
@@ -91,7 +103,7 @@ use crate::ui::Ui;
 ///     https://jj-vcs.github.io/jj/latest/bookmarks/#conflicts
 
 #[derive(clap::Args, Clone, Debug)]
-#[command(group(ArgGroup::new("specific").args(&["bookmark", "change", "revisions", "named"]).multiple(true)))]
+#[command(group(ArgGroup::new("specific").args(&["bookmark", "change", "stack", "revisions", "named", "tag"]).multiple(true)))]
 #[command(group(ArgGroup::new("what").args(&["all", "tracked"]).conflicts_with("specific")))]
 pub struct GitPushArgs {
     /// The remote to push to (only named remotes are supported)
@@ -167,8 +179,8 @@ pub struct GitPushArgs {
     /// repeated)
     ///
     /// The created bookmark will be tracked automatically. Use the
-    /// `git.push-bookmark-prefix` setting to change the prefix for generated
-    /// names.
+    /// `git.push-bookmark-prefix` setting and the `templates.git_push_bookmark`
+    /// template to change the generated names.
     #[arg(
         long,
         short,
@@ -180,6 +192,18 @@ pub struct GitPushArgs {
         add = ArgValueCandidates::new(complete::mutable_revisions)
     )]
     change: Vec<RevisionArg>,
+    /// Push all mutable ancestors of these commits by creating or moving a
+    /// bookmark for each of them based on its change ID (can be repeated)
+    ///
+    /// The bookmarks are named the same way as with `--change`, and are
+    /// tracked automatically. This is useful for stacked pull requests, which
+    /// need one branch per commit.
+    #[arg(
+        long,
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::mutable_revisions)
+    )]
+    stack: Vec<RevisionArg>,
     /// Specify a new bookmark name and a revision to push under that name, e.g.
     /// '--named myfeature=@'
     ///
@@ -190,6 +214,36 @@ pub struct GitPushArgs {
         add = ArgValueCompleter::new(complete::branch_name_equals_any_revision)
     )]
     named: Vec<String>,
+    /// Push this tag, or tags matching a pattern (can be repeated)
+    ///
+    /// Tags must not exist on the remote yet. Existing remote tags are never
+    /// overwritten.
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select tags by [wildcard pattern].
+    ///
+    /// [wildcard pattern]:
+    ///     https://jj-vcs.github.io/jj/latest/revsets#string-patterns
+    #[arg(
+        long,
+        value_parser = StringPattern::parse,
+        add = ArgValueCandidates::new(complete::local_tags),
+    )]
+    tag: Vec<StringPattern>,
+    /// Transmit the given string to the server as a push option (can be
+    /// repeated)
+    ///
+    /// The remote must support push options. This is equivalent to `git push
+    /// --push-option`.
+    #[arg(long = "option", short = 'o', value_name = "OPTION")]
+    options: Vec<String>,
+    /// Update either all bookmarks and tags on the remote or none of them
+    ///
+    /// Without this, the bookmarks and tags that the remote accepts are updated
+    /// even if others are rejected. This requires the remote to support atomic
+    /// pushes.
+    #[arg(long)]
+    atomic: bool,
     /// Only display what will change on the remote
     #[arg(long)]
     dry_run: bool,
@@ -202,6 +256,13 @@ fn make_bookmark_term(bookmark_names: &[impl fmt::Display]) -> String {
     }
 }
 
+fn make_tag_term(tag_names: &[impl fmt::Display]) -> String {
+    match tag_names {
+        [tag_name] => format!("tag {tag_name}"),
+        tag_names => format!("tags {}", tag_names.iter().join(", ")),
+    }
+}
+
 const DEFAULT_REMOTE: &RemoteName = RemoteName::new("origin");
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -233,6 +294,7 @@ pub fn cmd_git_push(
     let view = tx.repo().view();
     let tx_description;
     let mut bookmark_updates = vec![];
+    let mut tag_updates = vec![];
     if args.all {
         for (name, targets) in view.local_remote_bookmarks(remote) {
             let allow_new = true; // implied by --all
@@ -299,9 +361,10 @@ pub fn cmd_git_push(
 
         // --change and --named don't move existing bookmarks. If they did, be
         // careful to not select old state by -r/--revisions and bookmark names.
-        let bookmark_prefix = tx.settings().get_string("git.push-bookmark-prefix")?;
+        let change_commits = resolve_change_commits(ui, &tx, &args.change)?;
+        let stack_commits = resolve_stack_commits(ui, &tx, &args.stack)?;
         let change_bookmark_names =
-            create_change_bookmarks(ui, &mut tx, &args.change, &bookmark_prefix)?;
+            create_change_bookmarks(ui, &mut tx, &change_commits, &stack_commits)?;
         let created_bookmark_names: Vec<RefNameBuf> = args
             .named
             .iter()
@@ -323,7 +386,7 @@ pub fn cmd_git_push(
             if !seen_bookmarks.insert(name) {
                 continue;
             }
-            let allow_new = true; // --change implies creation of remote bookmark
+            let allow_new = true; // --change/--stack imply creation of remote bookmark
             let allow_delete = false; // doesn't matter
             match classify_bookmark_update(remote_symbol, targets, allow_new, allow_delete) {
                 Ok(Some(update)) => bookmark_updates.push((name.to_owned(), update)),
@@ -356,10 +419,22 @@ pub fn cmd_git_push(
             }
         }
 
+        for (name, target) in find_tags(view, &args.tag)? {
+            let Some(id) = target.as_normal() else {
+                return Err(user_error(format!(
+                    "Tag {name} is conflicted",
+                    name = name.as_symbol()
+                )));
+            };
+            tag_updates.push((name.to_owned(), id.clone()));
+        }
+
         let use_default_revset = args.bookmark.is_empty()
             && args.change.is_empty()
+            && args.stack.is_empty()
             && args.revisions.is_empty()
-            && args.named.is_empty();
+            && args.named.is_empty()
+            && args.tag.is_empty();
         let bookmarks_targeted = find_bookmarks_targeted_by_revisions(
             ui,
             tx.base_workspace_helper(),
@@ -384,18 +459,29 @@ pub fn cmd_git_push(
             }
         }
 
-        tx_description = format!(
-            "push {names} to git remote {remote}",
-            names = make_bookmark_term(
+        let bookmark_term = (!bookmark_updates.is_empty() || tag_updates.is_empty()).then(|| {
+            make_bookmark_term(
                 &bookmark_updates
                     .iter()
                     .map(|(name, _)| name.as_symbol())
-                    .collect_vec()
-            ),
+                    .collect_vec(),
+            )
+        });
+        let tag_term = (!tag_updates.is_empty()).then(|| {
+            make_tag_term(
+                &tag_updates
+                    .iter()
+                    .map(|(name, _)| name.as_symbol())
+                    .collect_vec(),
+            )
+        });
+        tx_description = format!(
+            "push {names} to git remote {remote}",
+            names = bookmark_term.into_iter().chain(tag_term).join(" and "),
             remote = remote.as_symbol()
         );
     }
-    if bookmark_updates.is_empty() {
+    if bookmark_updates.is_empty() && tag_updates.is_empty() {
         writeln!(ui.status(), "Nothing changed.")?;
         return Ok(());
     }
@@ -405,8 +491,39 @@ pub fn cmd_git_push(
     } else {
         None
     };
-    let commits_to_sign =
-        validate_commits_ready_to_push(ui, &bookmark_updates, remote, &tx, args, sign_behavior)?;
+    let new_bookmark_heads = bookmark_updates
+        .iter()
+        .filter_map(|(_, update)| update.new_target.clone())
+        .collect_vec();
+    let immutable_heads = tx
+        .base_workspace_helper()
+        .env()
+        .immutable_heads_expression()
+        .clone();
+    let commits_to_sign = validate_commits_ready_to_push(
+        ui,
+        new_bookmark_heads,
+        &immutable_heads,
+        remote,
+        &tx,
+        args,
+        sign_behavior,
+    )?;
+    // Tagged commits are checked too, but aren't signed since that would leave
+    // the tags pointing to the unsigned commits. Since tags() are immutable by
+    // default, the tagged commits themselves aren't excluded.
+    let new_tag_heads = tag_updates.iter().map(|(_, id)| id.clone()).collect_vec();
+    let tag_excluded_heads =
+        immutable_heads.minus(&RevsetExpression::commits(new_tag_heads.clone()));
+    validate_commits_ready_to_push(
+        ui,
+        new_tag_heads,
+        &tag_excluded_heads,
+        remote,
+        &tx,
+        args,
+        None,
+    )?;
     if !args.dry_run && !commits_to_sign.is_empty() {
         if let Some(sign_behavior) = sign_behavior {
             let num_updated_signatures = commits_to_sign.len();
@@ -439,6 +556,14 @@ pub fn cmd_git_push(
             remote = remote.as_symbol()
         )?;
         print_commits_ready_to_push(formatter.as_mut(), tx.repo(), &bookmark_updates)?;
+        for (name, id) in &tag_updates {
+            writeln!(
+                formatter,
+                "  Add tag {name} to {new}",
+                name = name.as_symbol(),
+                new = short_commit_hash(id)
+            )?;
+        }
     }
 
     if args.dry_run {
@@ -446,56 +571,98 @@ pub fn cmd_git_push(
         return Ok(());
     }
 
+    let git_settings = tx.settings().git_settings()?;
+    let push_options = GitPushOptions {
+        atomic: args.atomic,
+        remote_push_options: args.options.clone(),
+    };
+    let tag_names = tag_updates.into_iter().map(|(name, _)| name).collect_vec();
+    if !tag_names.is_empty() {
+        // The tags to push must exist in the underlying Git repo.
+        let export_stats = git::export_some_refs(tx.repo_mut(), |kind, symbol| {
+            kind == GitRefKind::Tag
+                && symbol.remote == REMOTE_NAME_FOR_LOCAL_GIT_REPO
+                && tag_names.iter().any(|name| **name == *symbol.name)
+        })?;
+        if let Some((symbol, reason)) = export_stats.failed_tags.into_iter().next() {
+            return Err(user_error_with_message(
+                format!(
+                    "Failed to export tag {name} to the underlying Git repo",
+                    name = symbol.name.as_symbol()
+                ),
+                reason,
+            ));
+        }
+    }
     let targets = GitBranchPushTargets {
         branch_updates: bookmark_updates,
     };
-    let git_settings = tx.settings().git_settings()?;
     let push_stats = with_remote_git_callbacks(ui, |cb| {
-        git::push_branches(tx.repo_mut(), &git_settings, remote, &targets, cb)
+        git::push_branches_and_tags(
+            tx.repo_mut(),
+            &git_settings,
+            remote,
+            &targets,
+            &tag_names,
+            &push_options,
+            cb,
+        )
     })?;
-    process_push_stats(&push_stats)?;
-    tx.finish(ui, tx_description)?;
+    // Record the refs which were pushed even if others were rejected.
+    if push_stats.all_ok() || !push_stats.pushed.is_empty() {
+        tx.finish(ui, tx_description)?;
+    }
+    // Rejected tags are reported first since they may be the reason why the
+    // atomic push failed.
+    let (tag_push_stats, bookmark_push_stats) = split_tag_push_stats(push_stats);
+    process_tag_push_stats(&tag_push_stats)?;
+    process_push_stats(&bookmark_push_stats)?;
     Ok(())
 }
 
 fn process_push_stats(push_stats: &GitPushStats) -> Result<(), CommandError> {
     if !push_stats.all_ok() {
         let mut error = user_error("Failed to push some bookmarks");
-        if !push_stats.rejected.is_empty() {
+        let (atomic_failed, rejected): (Vec<_>, Vec<_>) = push_stats
+            .rejected
+            .iter()
+            .partition(|(_, reason)| is_atomic_failure(reason));
+        let (remote_atomic_failed, remote_rejected): (Vec<_>, Vec<_>) = push_stats
+            .remote_rejected
+            .iter()
+            .partition(|(_, reason)| is_atomic_failure(reason));
+        if !rejected.is_empty() {
             error.add_formatted_hint_with(|formatter| {
                 writeln!(
                     formatter,
                     "The following references unexpectedly moved on the remote:"
                 )?;
-                for (reference, reason) in &push_stats.rejected {
-                    write!(formatter, "  ")?;
-                    write!(formatter.labeled("git_ref"), "{}", reference.as_symbol())?;
-                    if let Some(r) = reason {
-                        write!(formatter, " (reason: {r})")?;
-                    }
-                    writeln!(formatter)?;
-                }
-                Ok(())
+                write_rejected_refs(formatter, &rejected)
             });
             error.add_hint(
                 "Try fetching from the remote, then make the bookmark point to where you want it \
                  to be, and push again.",
             );
         }
-        if !push_stats.remote_rejected.is_empty() {
+        if !remote_rejected.is_empty() {
             error.add_formatted_hint_with(|formatter| {
                 writeln!(formatter, "The remote rejected the following updates:")?;
-                for (reference, reason) in &push_stats.remote_rejected {
+                write_rejected_refs(formatter, &remote_rejected)
+            });
+            error.add_hint("Try checking if you have permission to push to all the bookmarks.");
+        }
+        if !atomic_failed.is_empty() || !remote_atomic_failed.is_empty() {
+            error.add_formatted_hint_with(|formatter| {
+                writeln!(
+                    formatter,
+                    "The following references were not updated because the atomic push failed:"
+                )?;
+                for (reference, _) in itertools::chain(&atomic_failed, &remote_atomic_failed) {
                     write!(formatter, "  ")?;
-                    write!(formatter.labeled("git_ref"), "{}", reference.as_symbol())?;
-                    if let Some(r) = reason {
-                        write!(formatter, " (reason: {r})")?;
-                    }
-                    writeln!(formatter)?;
+                    writeln!(formatter.labeled("git_ref"), "{}", reference.as_symbol())?;
                 }
                 Ok(())
             });
-            error.add_hint("Try checking if you have permission to push to all the bookmarks.");
         }
         Err(error)
     } else {
@@ -503,13 +670,91 @@ fn process_push_stats(push_stats: &GitPushStats) -> Result<(), CommandError> {
     }
 }
 
+fn write_rejected_refs(
+    formatter: &mut dyn Formatter,
+    refs: &[&(GitRefNameBuf, Option<String>)],
+) -> io::Result<()> {
+    for (reference, reason) in refs {
+        write!(formatter, "  ")?;
+        write!(formatter.labeled("git_ref"), "{}", reference.as_symbol())?;
+        if let Some(r) = reason {
+            write!(formatter, " (reason: {r})")?;
+        }
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
+
+/// With `--atomic`, git reports the updates which would otherwise have
+/// succeeded as failed too.
+fn is_atomic_failure(reason: &Option<String>) -> bool {
+    matches!(
+        reason.as_deref(),
+        Some("atomic push failed" | "atomic transaction failed")
+    )
+}
+
+/// Splits the stats of the pushed tags from the stats of the other refs. Tags
+/// which were only rejected because the atomic push failed are kept with the
+/// other refs, so they are reported together.
+fn split_tag_push_stats(push_stats: GitPushStats) -> (GitPushStats, GitPushStats) {
+    let is_tag = |name: &GitRefNameBuf| name.as_str().starts_with("refs/tags/");
+    let (tag_pushed, pushed) = push_stats.pushed.into_iter().partition(is_tag);
+    let (tag_rejected, rejected) = push_stats
+        .rejected
+        .into_iter()
+        .partition(|(name, reason)| is_tag(name) && !is_atomic_failure(reason));
+    let (tag_remote_rejected, remote_rejected) = push_stats
+        .remote_rejected
+        .into_iter()
+        .partition(|(name, reason)| is_tag(name) && !is_atomic_failure(reason));
+    let tag_stats = GitPushStats {
+        pushed: tag_pushed,
+        rejected: tag_rejected,
+        remote_rejected: tag_remote_rejected,
+    };
+    let other_stats = GitPushStats {
+        pushed,
+        rejected,
+        remote_rejected,
+    };
+    (tag_stats, other_stats)
+}
+
+fn process_tag_push_stats(push_stats: &GitPushStats) -> Result<(), CommandError> {
+    if push_stats.all_ok() {
+        return Ok(());
+    }
+    let mut error = user_error("Failed to push some tags");
+    error.add_formatted_hint_with(|formatter| {
+        writeln!(formatter, "The following tags were rejected:")?;
+        for (reference, reason) in
+            itertools::chain(&push_stats.rejected, &push_stats.remote_rejected)
+        {
+            write!(formatter, "  ")?;
+            write!(formatter.labeled("git_ref"), "{}", reference.as_symbol())?;
+            if let Some(r) = reason {
+                write!(formatter, " (reason: {r})")?;
+            }
+            writeln!(formatter)?;
+        }
+        Ok(())
+    });
+    if !push_stats.rejected.is_empty() {
+        error.add_hint("Tags that already exist on the remote are never overwritten.");
+    }
+    Err(error)
+}
+
 /// Validates that the commits that will be pushed are ready (have authorship
-/// information, are not conflicted, etc.).
+/// information, are not conflicted, etc.). Ancestors of `excluded_heads` and
+/// of the remote bookmarks are excluded.
 ///
 /// Returns the list of commits which need to be signed.
 fn validate_commits_ready_to_push(
     ui: &Ui,
-    bookmark_updates: &[(RefNameBuf, BookmarkPushUpdate)],
+    new_heads: Vec<CommitId>,
+    excluded_heads: &Rc<UserRevsetExpression>,
     remote: &RemoteName,
     tx: &WorkspaceCommandTransaction,
     args: &GitPushArgs,
@@ -518,10 +763,6 @@ fn validate_commits_ready_to_push(
     let workspace_helper = tx.base_workspace_helper();
     let repo = workspace_helper.repo();
 
-    let new_heads = bookmark_updates
-        .iter()
-        .filter_map(|(_, update)| update.new_target.clone())
-        .collect_vec();
     let old_heads = repo
         .view()
         .remote_bookmarks(remote)
@@ -529,7 +770,7 @@ fn validate_commits_ready_to_push(
         .cloned()
         .collect_vec();
     let commits_to_push = RevsetExpression::commits(old_heads)
-        .union(workspace_helper.env().immutable_heads_expression())
+        .union(excluded_heads)
         .range(&RevsetExpression::commits(new_heads));
 
     let settings = workspace_helper.settings();
@@ -878,34 +1119,139 @@ fn create_explicitly_named_bookmarks(
     Ok(name)
 }
 
-/// Creates bookmarks based on the change IDs.
-fn create_change_bookmarks(
+/// Resolves the commits to push by `--change`.
+fn resolve_change_commits(
     ui: &Ui,
-    tx: &mut WorkspaceCommandTransaction,
+    tx: &WorkspaceCommandTransaction,
     changes: &[RevisionArg],
-    bookmark_prefix: &str,
-) -> Result<Vec<RefNameBuf>, CommandError> {
+) -> Result<Vec<Commit>, CommandError> {
     if changes.is_empty() {
         // NOTE: we don't want resolve_some_revsets_default_single to fail if the
         // changes argument wasn't provided, so handle that
         return Ok(vec![]);
     }
-
-    let mut bookmark_names = Vec::new();
-    let all_commits: Vec<_> = tx
+    let commits = tx
         .base_workspace_helper()
         .resolve_some_revsets_default_single(ui, changes)?
         .iter()
         .map(|id| tx.repo().store().get_commit(id))
         .try_collect()?;
+    Ok(commits)
+}
+
+/// Resolves the mutable commits in the stacks ending at `stack_heads` in
+/// topological order (parents first).
+fn resolve_stack_commits(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    stack_heads: &[RevisionArg],
+) -> Result<Vec<Commit>, CommandError> {
+    if stack_heads.is_empty() {
+        return Ok(vec![]);
+    }
+    let workspace_command = tx.base_workspace_helper();
+    let heads_expression = workspace_command
+        .parse_union_revsets(ui, stack_heads)?
+        .expression()
+        .clone();
+    let stack_expression = workspace_command
+        .env()
+        .immutable_heads_expression()
+        .range(&heads_expression);
+    let mut stack_commits: Vec<Commit> = workspace_command
+        .attach_revset_evaluator(stack_expression)
+        .evaluate_to_commits()?
+        .try_collect()?;
+    if stack_commits.is_empty() {
+        return Err(user_error(format!(
+            "No mutable revisions in the stack: {}",
+            stack_heads.iter().join(", ")
+        )));
+    }
+    stack_commits.reverse();
+    Ok(stack_commits)
+}
+
+/// Creates bookmarks based on the change IDs of `change_commits` and
+/// `stack_commits`, and returns their names.
+///
+/// Bookmarks for `change_commits` must be new or already point to the commit,
+/// whereas bookmarks for `stack_commits` are moved if needed.
+fn create_change_bookmarks(
+    ui: &Ui,
+    tx: &mut WorkspaceCommandTransaction,
+    change_commits: &[Commit],
+    stack_commits: &[Commit],
+) -> Result<Vec<RefNameBuf>, CommandError> {
+    if change_commits.is_empty() && stack_commits.is_empty() {
+        return Ok(vec![]);
+    }
 
-    for commit in all_commits {
+    let bookmark_prefix = tx.settings().get_string("git.push-bookmark-prefix")?;
+    let template_text = tx.settings().get_string("templates.git_push_bookmark")?;
+    let template = tx.parse_commit_template(ui, &template_text)?;
+    let mut named_commits: Vec<(RefNameBuf, &Commit)> = vec![];
+    let mut generated_names: HashMap<RefNameBuf, &Commit> = HashMap::new();
+    for commit in change_commits.iter().chain(stack_commits) {
+        let mut output = Vec::new();
+        template
+            .format(commit, &mut PlainTextFormatter::new(&mut output))
+            .expect("write() to vec backed formatter should never fail");
+        let name: RefNameBuf = format!("{bookmark_prefix}{}", output.into_string_lossy()).into();
+        // The name might be invalid depending on the template and the commit
+        let git_ref_name = format!("refs/heads/{}", name.as_str());
+        if let Err(err) = gix::refs::FullName::try_from(git_ref_name) {
+            return Err(user_error_with_message(
+                format!(
+                    "Generated bookmark name {name} for revision {} is not a valid Git ref name",
+                    short_commit_hash(commit.id()),
+                    name = name.as_symbol()
+                ),
+                err,
+            )
+            .hinted(
+                "Check the `templates.git_push_bookmark` and `git.push-bookmark-prefix` settings, \
+                 or use `jj git push --named` to name the bookmark explicitly",
+            ));
+        }
+        match generated_names.entry(name.clone()) {
+            hash_map::Entry::Occupied(entry) if entry.get().id() == commit.id() => {}
+            hash_map::Entry::Occupied(entry) => {
+                return Err(user_error_with_hint(
+                    format!(
+                        "Generated bookmark name {name} is the same for revisions {} and {}",
+                        short_commit_hash(entry.get().id()),
+                        short_commit_hash(commit.id()),
+                        name = name.as_symbol()
+                    ),
+                    "Use `jj git push --named` to give them distinct names, or resolve divergent \
+                     changes first",
+                ));
+            }
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(commit);
+                named_commits.push((name, commit));
+            }
+        }
+    }
+    drop(template);
+
+    let stack_commit_ids: HashSet<&CommitId> = stack_commits.iter().ids().collect();
+    let mut bookmark_names = Vec::new();
+    for (name, commit) in named_commits {
         let short_change_id = short_change_hash(commit.change_id());
-        let name: RefNameBuf = format!("{bookmark_prefix}{short_change_id}").into();
         let target = RefTarget::normal(commit.id().clone());
         let view = tx.base_repo().view();
-        if view.get_local_bookmark(&name) == &target {
+        let old_target = view.get_local_bookmark(&name);
+        if old_target == &target {
             // Existing bookmark pointing to the commit, which is allowed
+        } else if old_target.is_present() && stack_commit_ids.contains(commit.id()) {
+            writeln!(
+                ui.status(),
+                "Moving bookmark {name} to revision {short_change_id}",
+                name = name.as_symbol()
+            )?;
+            tx.repo_mut().set_local_bookmark_target(&name, target);
         } else {
             ensure_new_bookmark_name(view, &name)?;
             writeln!(
@@ -0,0 +1,210 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use chrono::FixedOffset;
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::backend::Signature;
+use jj_lib::backend::Timestamp;
+use jj_lib::commit::Commit;
+use jj_lib::commit::CommitIteratorExt as _;
+use jj_lib::object_id::ObjectId as _;
+
+use crate::cli_util::print_updated_commits;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::text_util::parse_author;
+use crate::ui::Ui;
+
+/// Modify the metadata of a revision without changing its content
+///
+/// The committer of rewritten commits is updated to the configured user and
+/// the current time, as with any other rewrite.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct MetaeditArgs {
+    /// The revision(s) to modify (default: @)
+    #[arg(
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::mutable_revisions)
+    )]
+    revisions_pos: Vec<RevisionArg>,
+    #[arg(
+        short = 'r',
+        hide = true,
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::mutable_revisions)
+    )]
+    revisions_opt: Vec<RevisionArg>,
+    /// Generate a new change ID
+    ///
+    /// Each modified revision gets a new, distinct change ID.
+    #[arg(long)]
+    update_change_id: bool,
+    /// Update the author to the configured user
+    ///
+    /// This updates the author name and email. The author timestamp is not
+    /// modified; use `--update-author-timestamp` to update it.
+    ///
+    /// You can use it in combination with the JJ_USER and JJ_EMAIL
+    /// environment variables to set a different author:
+    ///
+    /// $ JJ_USER='Foo Bar' JJ_EMAIL=foo@bar.com jj metaedit --update-author
+    #[arg(long)]
+    update_author: bool,
+    /// Set author to the provided string
+    ///
+    /// This changes author name and email while retaining author timestamp.
+    #[arg(
+        long,
+        conflicts_with = "update_author",
+        value_parser = parse_author
+    )]
+    author: Option<(String, String)>,
+    /// Update the author timestamp to the current time
+    #[arg(long)]
+    update_author_timestamp: bool,
+    /// Set the author timestamp
+    ///
+    /// The timestamp must be in RFC 3339 format, e.g.
+    /// `2001-02-03T04:05:06+07:00`.
+    #[arg(
+        long,
+        conflicts_with = "update_author_timestamp",
+        value_name = "TIMESTAMP",
+        value_parser = DateTime::parse_from_rfc3339
+    )]
+    author_timestamp: Option<DateTime<FixedOffset>>,
+    /// Rewrite the revisions even if no metadata would change
+    ///
+    /// This updates the committer name, email, and timestamp.
+    #[arg(long)]
+    force_rewrite: bool,
+}
+
+pub(crate) fn cmd_metaedit(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &MetaeditArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commits: Vec<Commit> =
+        if !args.revisions_pos.is_empty() || !args.revisions_opt.is_empty() {
+            workspace_command
+                .parse_union_revsets(ui, &[&*args.revisions_pos, &*args.revisions_opt].concat())?
+        } else {
+            workspace_command.parse_revset(ui, &RevisionArg::AT)?
+        }
+        .evaluate_to_commits()?
+        .try_collect()?; // in reverse topological order
+    if commits.is_empty() {
+        writeln!(ui.status(), "No revisions to modify.")?;
+        return Ok(());
+    }
+    workspace_command.check_rewritable(commits.iter().ids())?;
+
+    let mut tx = workspace_command.start_transaction();
+    let user_signature = tx.settings().signature();
+    // Filter out unchanged commits to avoid rebasing descendants in
+    // `transform_descendants` below unnecessarily.
+    let new_authors: HashMap<_, _> = commits
+        .iter()
+        .filter_map(|commit| {
+            let new_author = new_author(args, commit.author(), &user_signature);
+            let changed =
+                args.update_change_id || args.force_rewrite || new_author != *commit.author();
+            changed.then(|| (commit.id().clone(), new_author))
+        })
+        .collect();
+    if new_authors.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    let mut modified_commits = vec![];
+    let mut num_reparented = 0;
+    tx.repo_mut().transform_descendants(
+        new_authors.keys().cloned().collect(),
+        |mut rewriter| {
+            let old_commit = rewriter.old_commit().clone();
+            let Some(new_author) = new_authors.get(old_commit.id()) else {
+                rewriter.reparent().write()?;
+                num_reparented += 1;
+                return Ok(());
+            };
+            let new_parents = rewriter.new_parents().to_vec();
+            let mut_repo = rewriter.mut_repo();
+            let mut commit_builder = mut_repo
+                .rewrite_commit(&old_commit)
+                .set_parents(new_parents)
+                .set_author(new_author.clone());
+            if args.update_change_id {
+                commit_builder = commit_builder.generate_new_change_id();
+            }
+            let new_commit = commit_builder.write()?;
+            if args.update_change_id {
+                // The rewrite isn't recorded automatically if the change ID
+                // differs, but descendants and bookmarks should still follow.
+                mut_repo.set_rewritten_commit(old_commit.id().clone(), new_commit.id().clone());
+            }
+            modified_commits.push(new_commit);
+            Ok(())
+        },
+    )?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(formatter, "Modified {} commits:", modified_commits.len())?;
+        print_updated_commits(
+            formatter.as_mut(),
+            &tx.commit_summary_template(),
+            &modified_commits,
+        )?;
+    }
+    if num_reparented > 0 {
+        writeln!(ui.status(), "Rebased {num_reparented} descendant commits")?;
+    }
+    let tx_description = match &*commits {
+        [commit] => format!("edit commit metadata for commit {}", commit.id().hex()),
+        commits => format!(
+            "edit commit metadata for commit {} and {} more",
+            commits[0].id().hex(),
+            commits.len() - 1
+        ),
+    };
+    tx.finish(ui, tx_description)?;
+    Ok(())
+}
+
+fn new_author(args: &MetaeditArgs, old_author: &Signature, user: &Signature) -> Signature {
+    let mut author = old_author.clone();
+    if args.update_author {
+        author.name.clone_from(&user.name);
+        author.email.clone_from(&user.email);
+    }
+    if let Some((name, email)) = &args.author {
+        author.name.clone_from(name);
+        author.email.clone_from(email);
+    }
+    if args.update_author_timestamp {
+        author.timestamp = user.timestamp;
+    }
+    if let Some(timestamp) = args.author_timestamp {
+        author.timestamp = Timestamp::from_datetime(timestamp);
+    }
+    author
+}
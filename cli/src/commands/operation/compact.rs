@@ -0,0 +1,133 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+use std::iter;
+use std::slice;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::op_walk;
+
+use crate::cli_util::short_operation_hash;
+use crate::cli_util::CommandHelper;
+use crate::command_error::cli_error;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Collapse a range of operations into one
+///
+/// `jj op compact <root>..<head>` replaces the operations in the range with a
+/// single operation that has the repo state of the `<head>` operation. The
+/// descendants of the `<head>` operation will be reparented onto the new
+/// operation. The intermediate repo states can no longer be restored.
+///
+/// For example, `jj op compact ..@-` collapses all operations except for the
+/// root and the current operations.
+///
+/// The replaced operations and other unreachable objects can later be garbage
+/// collected by using `jj util gc` command.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationCompactArgs {
+    /// The operation range to compact
+    #[arg(add = ArgValueCandidates::new(complete::operations))]
+    operations: String,
+}
+
+pub fn cmd_op_compact(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationCompactArgs,
+) -> Result<(), CommandError> {
+    // Don't load the repo so that this command can be used to recover from
+    // corrupted repo state.
+    let mut workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op_store = repo_loader.op_store();
+    let op_heads_store = repo_loader.op_heads_store();
+    // It doesn't make sense to create divergent operations that will be merged
+    // with the current head.
+    if command.global_args().at_operation.is_some() {
+        return Err(cli_error("--at-op is not respected"));
+    }
+    let current_head_ops = op_walk::get_current_head_ops(op_store, op_heads_store.as_ref())?;
+    let resolve_op = |op_str| op_walk::resolve_op_at(op_store, &current_head_ops, op_str);
+    let Some((root_op_str, head_op_str)) = args.operations.split_once("..") else {
+        return Err(user_error(format!(
+            "Expected an operation range, but got {}",
+            args.operations
+        ))
+        .hinted("Use `jj op compact <operation ID>..<operation ID>` to specify the range"));
+    };
+    let root_op = if root_op_str.is_empty() {
+        repo_loader.root_operation()
+    } else {
+        resolve_op(root_op_str)?
+    };
+    let compact_op = if head_op_str.is_empty() {
+        match current_head_ops.as_slice() {
+            [op] => op.clone(),
+            _ => return Err(user_error("Cannot compact up to divergent operations")),
+        }
+    } else {
+        resolve_op(head_op_str)?
+    };
+    let is_ancestor = op_walk::walk_ancestors(slice::from_ref(&compact_op))
+        .process_results(|mut ops| ops.any(|op| op == root_op))?;
+    if !is_ancestor {
+        return Err(user_error(format!(
+            "Operation {} is not an ancestor of {}",
+            short_operation_hash(root_op.id()),
+            short_operation_hash(compact_op.id())
+        )));
+    }
+
+    let stats = op_walk::compact_range(op_store, &root_op, &compact_op, &current_head_ops)?;
+    let reparented_head_ops = || iter::zip(&current_head_ops, &stats.new_head_ids);
+    if reparented_head_ops().all(|(old, new_id)| old.id() == new_id) {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    writeln!(
+        ui.status(),
+        "Compacted {} operations and reparented {} descendant operations.",
+        stats.unreachable_count,
+        stats.rewritten_count,
+    )?;
+    for (old, new_id) in reparented_head_ops().filter(|&(old, new_id)| old.id() != new_id) {
+        op_heads_store.update_op_heads(slice::from_ref(old.id()), new_id)?;
+    }
+    // Remap the operation id of the current workspace. If there were any
+    // divergent operations, user will need to re-compact their ancestors.
+    if !command.global_args().ignore_working_copy {
+        let mut locked_ws = workspace.start_working_copy_mutation()?;
+        let old_op_id = locked_ws.locked_wc().old_operation_id();
+        if let Some((_, new_id)) = reparented_head_ops().find(|(old, _)| old.id() == old_op_id) {
+            locked_ws.finish(new_id.clone())?;
+        } else {
+            writeln!(
+                ui.warning_default(),
+                "The working copy operation {} is not updated because it differs from the repo {}.",
+                short_operation_hash(old_op_id),
+                current_head_ops
+                    .iter()
+                    .map(|op| short_operation_hash(op.id()))
+                    .join(", "),
+            )?;
+        }
+    }
+    Ok(())
+}
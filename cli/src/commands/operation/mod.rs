@@ -13,8 +13,10 @@
 // limitations under the License.
 
 mod abandon;
+mod compact;
 mod diff;
 mod log;
+pub mod redo;
 mod restore;
 mod show;
 pub mod undo;
@@ -22,10 +24,17 @@ pub mod undo;
 use abandon::cmd_op_abandon;
 use abandon::OperationAbandonArgs;
 use clap::Subcommand;
+use compact::cmd_op_compact;
+use compact::OperationCompactArgs;
 use diff::cmd_op_diff;
 use diff::OperationDiffArgs;
+use jj_lib::op_store::OpStoreError;
+use jj_lib::operation::Operation;
+use jj_lib::repo::RepoLoader;
 use log::cmd_op_log;
 use log::OperationLogArgs;
+use redo::cmd_op_redo;
+use redo::OperationRedoArgs;
 use restore::cmd_op_restore;
 use restore::OperationRestoreArgs;
 use show::cmd_op_show;
@@ -46,8 +55,10 @@ use crate::ui::Ui;
 #[derive(Subcommand, Clone, Debug)]
 pub enum OperationCommand {
     Abandon(OperationAbandonArgs),
+    Compact(OperationCompactArgs),
     Diff(OperationDiffArgs),
     Log(OperationLogArgs),
+    Redo(OperationRedoArgs),
     Restore(OperationRestoreArgs),
     Show(OperationShowArgs),
     Undo(OperationUndoArgs),
@@ -60,8 +71,10 @@ pub fn cmd_operation(
 ) -> Result<(), CommandError> {
     match subcommand {
         OperationCommand::Abandon(args) => cmd_op_abandon(ui, command, args),
+        OperationCommand::Compact(args) => cmd_op_compact(ui, command, args),
         OperationCommand::Diff(args) => cmd_op_diff(ui, command, args),
         OperationCommand::Log(args) => cmd_op_log(ui, command, args),
+        OperationCommand::Redo(args) => cmd_op_redo(ui, command, args),
         OperationCommand::Restore(args) => cmd_op_restore(ui, command, args),
         OperationCommand::Show(args) => cmd_op_show(ui, command, args),
         OperationCommand::Undo(args) => cmd_op_undo(ui, command, args),
@@ -106,3 +119,23 @@ fn view_with_desired_portions_restored(
         wc_commit_ids: repo_source.wc_commit_ids.clone(),
     }
 }
+
+/// Returns the operation the repo state corresponds to in the undo stack.
+///
+/// Undo and redo operations restore the state before the operation they
+/// reverted, so they are replaced by the parent of that operation until a
+/// regular operation is found.
+fn undo_stack_position(
+    repo_loader: &RepoLoader,
+    op: &Operation,
+) -> Result<Operation, OpStoreError> {
+    let mut op = op.clone();
+    while let Some(relation) = &op.metadata().undo_relation {
+        let reverted_op = repo_loader.load_operation(relation.target())?;
+        let Some(parent_op) = reverted_op.parents().next().transpose()? else {
+            break;
+        };
+        op = parent_op;
+    }
+    Ok(op)
+}
@@ -0,0 +1,99 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::OpStoreError;
+use jj_lib::op_store::UndoRelation;
+use jj_lib::operation::Operation;
+use jj_lib::repo::RepoLoader;
+
+use super::view_with_desired_portions_restored;
+use super::DEFAULT_UNDO_WHAT;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Redo the most recently undone operation
+///
+/// This reverts the most recent `jj undo`. Running it again reverts the undo
+/// before that, and so on. Undone operations can no longer be redone once an
+/// operation other than undo or redo has been made.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationRedoArgs {}
+
+pub fn cmd_op_redo(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &OperationRedoArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let repo_loader = workspace_command.repo().loader().clone();
+    let Some(undo_op) = find_undo_to_redo(&repo_loader, workspace_command.repo().operation())?
+    else {
+        return Err(user_error("Nothing to redo"));
+    };
+    let undone_op_id = undo_op
+        .metadata()
+        .undo_relation
+        .as_ref()
+        .map(UndoRelation::target)
+        .expect("undo operation should have an undo relation");
+    let undone_op = repo_loader.load_operation(undone_op_id)?;
+    let restored_op = undo_op
+        .parents()
+        .next()
+        .transpose()?
+        .expect("undo operation should have a parent");
+
+    let mut tx = workspace_command.start_transaction();
+    let new_view = view_with_desired_portions_restored(
+        restored_op.view()?.store_view(),
+        tx.base_repo().view().store_view(),
+        &DEFAULT_UNDO_WHAT,
+    );
+    tx.repo_mut().set_view(new_view);
+    tx.set_undo_relation(UndoRelation::Redo(undo_op.id().clone()));
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Redid operation: ")?;
+        let template = tx.base_workspace_helper().operation_summary_template();
+        template.format(&undone_op, formatter.as_mut())?;
+        writeln!(formatter)?;
+    }
+    tx.finish(ui, format!("redo operation {}", undone_op.id().hex()))?;
+    Ok(())
+}
+
+/// Finds the undo operation to be reverted by the next redo.
+///
+/// If `op` is itself a redo, the undo operation preceding the one it reverted
+/// is looked up, so that consecutive redos walk back through the undos.
+fn find_undo_to_redo(
+    repo_loader: &RepoLoader,
+    op: &Operation,
+) -> Result<Option<Operation>, OpStoreError> {
+    let mut op = op.clone();
+    loop {
+        let redone_op_id = match &op.metadata().undo_relation {
+            None => return Ok(None),
+            Some(UndoRelation::Undo(_)) => return Ok(Some(op)),
+            Some(UndoRelation::Redo(id)) => id,
+        };
+        let redone_op = repo_loader.load_operation(redone_op_id)?;
+        let Some(parent_op) = redone_op.parents().next().transpose()? else {
+            return Ok(None);
+        };
+        op = parent_op;
+    }
+}
@@ -13,16 +13,17 @@
 // limitations under the License.
 
 use clap_complete::ArgValueCandidates;
-use itertools::Itertools as _;
 use jj_lib::object_id::ObjectId as _;
-use jj_lib::op_store::OpStoreError;
+use jj_lib::op_store::UndoRelation;
 use jj_lib::operation::Operation;
 use jj_lib::repo::Repo as _;
 
+use super::undo_stack_position;
 use super::view_with_desired_portions_restored;
 use super::UndoWhatToRestore;
 use super::DEFAULT_UNDO_WHAT;
 use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::complete;
@@ -30,8 +31,12 @@ use crate::ui::Ui;
 
 /// Create a new operation that undoes an earlier operation
 ///
-/// This undoes an individual operation by applying the inverse of the
-/// operation.
+/// By default, this undoes the most recent operation. Running it again undoes
+/// the operation before that, and so on. Use `jj op redo` to redo undone
+/// operations.
+///
+/// If an operation is specified, that individual operation is undone by
+/// applying the inverse of the operation.
 #[derive(clap::Args, Clone, Debug)]
 pub struct OperationUndoArgs {
     /// The operation to undo
@@ -47,33 +52,61 @@ pub struct OperationUndoArgs {
     what: Vec<UndoWhatToRestore>,
 }
 
-fn is_undo(op: &Operation, parent_op: &Operation) -> Result<bool, OpStoreError> {
-    let grand_parents: Vec<_> = parent_op.parents().try_collect()?;
-    if let [grand_parent_op] = &grand_parents[..] {
-        Ok(op.view_id() == grand_parent_op.view_id())
+pub fn cmd_op_undo(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationUndoArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    if args.operation == "@" {
+        undo_last_operation(ui, workspace_command, args)
     } else {
-        Ok(false)
+        let bad_op = workspace_command.resolve_single_op(&args.operation)?;
+        undo_single_operation(ui, workspace_command, args, &bad_op)
     }
 }
 
-pub fn cmd_op_undo(
+/// Restores the state before the operation at the current position of the
+/// undo stack.
+fn undo_last_operation(
     ui: &mut Ui,
-    command: &CommandHelper,
+    mut workspace_command: WorkspaceCommandHelper,
     args: &OperationUndoArgs,
 ) -> Result<(), CommandError> {
-    let mut workspace_command = command.workspace_helper(ui)?;
-    let bad_op = workspace_command.resolve_single_op(&args.operation)?;
-    let mut parent_ops = bad_op.parents();
-    let Some(parent_op) = parent_ops.next().transpose()? else {
-        return Err(user_error("Cannot undo repo initialization"));
-    };
-    if parent_ops.next().is_some() {
-        return Err(user_error("Cannot undo a merge operation"));
+    let repo_loader = workspace_command.repo().loader().clone();
+    let bad_op = undo_stack_position(&repo_loader, workspace_command.repo().operation())?;
+    let parent_op = single_parent_op(&bad_op)?;
+
+    let mut tx = workspace_command.start_transaction();
+    let new_view = view_with_desired_portions_restored(
+        parent_op.view()?.store_view(),
+        tx.base_repo().view().store_view(),
+        &args.what,
+    );
+    tx.repo_mut().set_view(new_view);
+    tx.set_undo_relation(UndoRelation::Undo(bad_op.id().clone()));
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Undid operation: ")?;
+        let template = tx.base_workspace_helper().operation_summary_template();
+        template.format(&bad_op, formatter.as_mut())?;
+        writeln!(formatter)?;
     }
+    tx.finish(ui, format!("undo operation {}", bad_op.id().hex()))?;
+    Ok(())
+}
+
+/// Applies the inverse of `bad_op` on top of the current operation.
+fn undo_single_operation(
+    ui: &mut Ui,
+    mut workspace_command: WorkspaceCommandHelper,
+    args: &OperationUndoArgs,
+    bad_op: &Operation,
+) -> Result<(), CommandError> {
+    let parent_op = single_parent_op(bad_op)?;
 
     let mut tx = workspace_command.start_transaction();
     let repo_loader = tx.base_repo().loader();
-    let bad_repo = repo_loader.load_at(&bad_op)?;
+    let bad_repo = repo_loader.load_at(bad_op)?;
     let parent_repo = repo_loader.load_at(&parent_op)?;
     tx.repo_mut().merge(&bad_repo, &parent_repo)?;
     let new_view = view_with_desired_portions_restored(
@@ -85,23 +118,20 @@ pub fn cmd_op_undo(
     if let Some(mut formatter) = ui.status_formatter() {
         write!(formatter, "Undid operation: ")?;
         let template = tx.base_workspace_helper().operation_summary_template();
-        template.format(&bad_op, formatter.as_mut())?;
+        template.format(bad_op, formatter.as_mut())?;
         writeln!(formatter)?;
     }
     tx.finish(ui, format!("undo operation {}", bad_op.id().hex()))?;
+    Ok(())
+}
 
-    if args.operation == "@" && is_undo(&bad_op, &parent_op)? {
-        writeln!(
-            ui.hint_default(),
-            "This action reverted an 'undo' operation. The repository is now in the same state as \
-             it was before the original 'undo'."
-        )?;
-        writeln!(
-            ui.hint_default(),
-            "If your goal is to undo multiple operations, consider using `jj op log` to see past \
-             states, and `jj op restore` to restore one of these states."
-        )?;
+fn single_parent_op(op: &Operation) -> Result<Operation, CommandError> {
+    let mut parent_ops = op.parents();
+    let Some(parent_op) = parent_ops.next().transpose()? else {
+        return Err(user_error("Cannot undo repo initialization"));
+    };
+    if parent_ops.next().is_some() {
+        return Err(user_error("Cannot undo a merge operation"));
     }
-
-    Ok(())
+    Ok(parent_op)
 }
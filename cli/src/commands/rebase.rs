@@ -12,25 +12,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::io::Write as _;
+use std::slice;
 use std::sync::Arc;
 
 use clap::ArgGroup;
 use clap_complete::ArgValueCandidates;
+use indoc::indoc;
 use itertools::Itertools as _;
+use jj_lib::backend::BackendResult;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::commit::CommitIteratorExt as _;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::MutableRepo;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo as _;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetIteratorExt as _;
 use jj_lib::rewrite::move_commits;
+use jj_lib::rewrite::squash_commits;
+use jj_lib::rewrite::CommitWithSelection;
 use jj_lib::rewrite::EmptyBehaviour;
 use jj_lib::rewrite::MoveCommitsStats;
 use jj_lib::rewrite::MoveCommitsTarget;
 use jj_lib::rewrite::RebaseOptions;
+use jj_lib::rewrite::RebasedCommit;
 use jj_lib::rewrite::RewriteRefsOptions;
 use tracing::instrument;
 
@@ -41,8 +49,13 @@ use crate::cli_util::RevisionArg;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::cli_error;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::complete;
+use crate::description_util::combine_messages_for_editing;
+use crate::description_util::description_template;
+use crate::description_util::edit_description;
+use crate::description_util::try_combine_messages;
 use crate::ui::Ui;
 
 /// Move revisions to different parent(s)
@@ -263,9 +276,33 @@ use crate::ui::Ui;
 /// |/          |/
 /// J           J
 /// ```
+///
+/// ### Editing a rebase plan
+///
+/// With `--interactive/-i`, the revisions given by `-r` are listed in a text
+/// editor, one per line and oldest first, similar to `git rebase -i`. The
+/// revisions must form a linear stack. Each line starts with an action:
+///
+/// * `pick` keeps the revision as is
+/// * `reword` keeps the revision and opens an editor for its description
+/// * `squash` moves the changes into the revision above it and combines the
+///   descriptions
+/// * `fixup` is like `squash`, but keeps only the description of the revision
+///   above it
+/// * `drop` abandons the revision
+///
+/// Reordering the lines reorders the revisions. Descendants of the stack's
+/// head are rebased onto the new head. The whole plan is applied as a single
+/// operation.
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 #[command(group(ArgGroup::new("to_rebase").args(&["branch", "source", "revisions"])))]
+#[command(group(
+    ArgGroup::new("location")
+        .args(&["destination", "insert_after", "insert_before", "interactive"])
+        .required(true)
+        .multiple(true)
+))]
 pub(crate) struct RebaseArgs {
     /// Rebase the whole branch relative to destination's ancestors (can be
     /// repeated)
@@ -315,6 +352,27 @@ pub(crate) struct RebaseArgs {
     #[command(flatten)]
     destination: RebaseDestinationArgs,
 
+    /// Edit a plan for rewriting the revisions in a text editor
+    ///
+    /// The revisions given by `-r` must form a linear stack. Each revision is
+    /// listed on its own line, oldest first, and can be reordered, dropped,
+    /// reworded, or squashed into the revision above it. The edited plan is
+    /// applied as a single operation.
+    #[arg(
+        long,
+        short,
+        requires = "revisions",
+        conflicts_with_all = [
+            "branch",
+            "source",
+            "destination",
+            "insert_after",
+            "insert_before",
+            "skip_emptied",
+        ],
+    )]
+    interactive: bool,
+
     /// Deprecated. Use --skip-emptied instead.
     #[arg(long, conflicts_with = "revisions", hide = true)]
     skip_empty: bool,
@@ -328,7 +386,6 @@ pub(crate) struct RebaseArgs {
 }
 
 #[derive(clap::Args, Clone, Debug)]
-#[group(required = true)]
 pub struct RebaseDestinationArgs {
     /// The revision(s) to rebase onto (can be repeated to create a merge
     /// commit)
@@ -386,7 +443,9 @@ pub(crate) fn cmd_rebase(
         simplify_ancestor_merge: false,
     };
     let mut workspace_command = command.workspace_helper(ui)?;
-    if !args.revisions.is_empty() {
+    if args.interactive {
+        rebase_interactive(ui, &mut workspace_command, &args.revisions)?;
+    } else if !args.revisions.is_empty() {
         rebase_revisions(
             ui,
             &mut workspace_command,
@@ -627,6 +686,377 @@ fn rebase_revisions_transaction(
     tx.finish(ui, tx_description)
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum RebasePlanAction {
+    Pick,
+    Reword,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebasePlanAction {
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "p" | "pick" => Some(Self::Pick),
+            "r" | "reword" => Some(Self::Reword),
+            "s" | "squash" => Some(Self::Squash),
+            "f" | "fixup" => Some(Self::Fixup),
+            "d" | "drop" => Some(Self::Drop),
+            _ => None,
+        }
+    }
+
+    fn is_squash(self) -> bool {
+        matches!(self, Self::Squash | Self::Fixup)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RebasePlanStep {
+    action: RebasePlanAction,
+    commit: Commit,
+}
+
+/// A commit in the rewritten stack together with the commits to be squashed
+/// into it.
+struct RebasePlanGroup<'a> {
+    leader: &'a RebasePlanStep,
+    members: Vec<&'a RebasePlanStep>,
+}
+
+fn rebase_interactive(
+    ui: &mut Ui,
+    workspace_command: &mut WorkspaceCommandHelper,
+    revisions: &[RevisionArg],
+) -> Result<(), CommandError> {
+    let mut stack: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    stack.reverse(); // oldest first
+    if stack.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    workspace_command.check_rewritable(stack.iter().ids())?;
+    for (parent, child) in stack.iter().tuple_windows() {
+        if child.parent_ids() != [parent.id().clone()] {
+            return Err(user_error_with_hint(
+                "Revisions to rebase interactively must form a linear stack",
+                format!(
+                    "Commit {} does not have {} as its only parent",
+                    short_commit_hash(child.id()),
+                    short_commit_hash(parent.id()),
+                ),
+            ));
+        }
+    }
+
+    let text_editor = workspace_command.text_editor()?;
+    let plan = text_editor
+        .edit_str(format_rebase_plan(&stack), Some(".jjrebase"))
+        .map_err(|err| err.with_name("rebase plan"))?;
+    let steps = parse_rebase_plan(&plan, &stack)?;
+    if steps.is_empty() {
+        writeln!(ui.status(), "Rebase plan is empty, nothing changed.")?;
+        return Ok(());
+    }
+    let unchanged = steps.iter().zip(&stack).all(|(step, commit)| {
+        step.action == RebasePlanAction::Pick && step.commit.id() == commit.id()
+    });
+    if unchanged {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    let mut groups: Vec<RebasePlanGroup> = vec![];
+    for step in &steps {
+        match step.action {
+            RebasePlanAction::Drop => {}
+            RebasePlanAction::Squash | RebasePlanAction::Fixup => {
+                let Some(group) = groups.last_mut() else {
+                    return Err(user_error(format!(
+                        "Cannot squash commit {} because no commit is picked before it",
+                        short_commit_hash(step.commit.id()),
+                    )));
+                };
+                group.members.push(step);
+            }
+            RebasePlanAction::Pick | RebasePlanAction::Reword => groups.push(RebasePlanGroup {
+                leader: step,
+                members: vec![],
+            }),
+        }
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let mut current_commits: HashMap<CommitId, Commit> = steps
+        .iter()
+        .filter(|step| step.action != RebasePlanAction::Drop)
+        .map(|step| (step.commit.id().clone(), step.commit.clone()))
+        .collect();
+    for step in &steps {
+        if step.action == RebasePlanAction::Drop {
+            tx.repo_mut().record_abandoned_commit(&step.commit);
+        }
+    }
+    rebase_descendants_tracking(tx.repo_mut(), &mut current_commits)?;
+
+    // Rebuild the stack in the new order by inserting each commit between the
+    // previous one and the rest of the stack. The commits to be squashed are
+    // kept in place for now, so that their changes are rebased along with the
+    // rest of the stack.
+    let mut unplaced_ids = stack
+        .iter()
+        .ids()
+        .filter(|id| current_commits.contains_key(id))
+        .collect_vec();
+    let mut new_parent_ids = stack[0].parent_ids().to_vec();
+    for step in &steps {
+        if step.action == RebasePlanAction::Drop {
+            continue;
+        }
+        let commit = current_commits[step.commit.id()].clone();
+        let next_commit = current_commits[unplaced_ids[0]].clone();
+        move_commits(
+            tx.repo_mut(),
+            &new_parent_ids,
+            &[next_commit],
+            &MoveCommitsTarget::Commits(vec![commit]),
+            &RebaseOptions::default(),
+        )?;
+        rebase_descendants_tracking(tx.repo_mut(), &mut current_commits)?;
+        unplaced_ids.retain(|id| *id != step.commit.id());
+        new_parent_ids = vec![current_commits[step.commit.id()].id().clone()];
+    }
+
+    for group in &groups {
+        if group.leader.action != RebasePlanAction::Reword || !group.members.is_empty() {
+            continue;
+        }
+        let commit = &current_commits[group.leader.commit.id()];
+        let mut commit_builder = tx.repo_mut().rewrite_commit(commit).detach();
+        let temp_commit = commit_builder.write_hidden()?;
+        let template = description_template(ui, &tx, "", &temp_commit)?;
+        let description = edit_description(&text_editor, &template)?;
+        commit_builder.set_description(description);
+        let new_commit = commit_builder.write(tx.repo_mut())?;
+        current_commits.insert(group.leader.commit.id().clone(), new_commit);
+        rebase_descendants_tracking(tx.repo_mut(), &mut current_commits)?;
+    }
+
+    // Squash the marked commits into the commits before them.
+    for group in groups.iter().filter(|group| !group.members.is_empty()) {
+        let destination = current_commits[group.leader.commit.id()].clone();
+        let sources: Vec<_> = group
+            .members
+            .iter()
+            .map(|member| -> Result<_, CommandError> {
+                let commit = current_commits.remove(member.commit.id()).unwrap();
+                Ok(CommitWithSelection {
+                    parent_tree: commit.parent_tree(tx.repo())?,
+                    selected_tree: commit.tree()?,
+                    commit,
+                })
+            })
+            .try_collect()?;
+        let squashed = squash_commits(tx.repo_mut(), &sources, &destination, false)?
+            .expect("all changes should be selected");
+        let mut commit_builder = squashed.commit_builder.detach();
+        let squashed_sources = group
+            .members
+            .iter()
+            .zip(&sources)
+            .filter(|(member, _)| member.action == RebasePlanAction::Squash)
+            .map(|(_, source)| source.commit.clone())
+            .collect_vec();
+        let combined = match try_combine_messages(&squashed_sources, &destination) {
+            Some(description) if group.leader.action != RebasePlanAction::Reword => {
+                Some(description)
+            }
+            _ => None,
+        };
+        let description = if let Some(description) = combined {
+            description
+        } else {
+            let intro = "Enter a description for the combined commit.";
+            let combined = combine_messages_for_editing(
+                ui,
+                &tx,
+                &squashed_sources,
+                &destination,
+                &commit_builder,
+            )?;
+            commit_builder.set_description(combined);
+            let temp_commit = commit_builder.write_hidden()?;
+            let template = description_template(ui, &tx, intro, &temp_commit)?;
+            edit_description(&text_editor, &template)?
+        };
+        commit_builder.set_description(description);
+        let new_commit = commit_builder.write(tx.repo_mut())?;
+        current_commits.insert(group.leader.commit.id().clone(), new_commit);
+        rebase_descendants_tracking(tx.repo_mut(), &mut current_commits)?;
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        let num_dropped = steps
+            .iter()
+            .filter(|step| step.action == RebasePlanAction::Drop)
+            .count();
+        let num_squashed = steps.iter().filter(|step| step.action.is_squash()).count();
+        writeln!(
+            formatter,
+            "Rewrote {} commits according to the rebase plan",
+            groups.len()
+        )?;
+        if num_squashed > 0 {
+            writeln!(formatter, "Squashed {num_squashed} commits")?;
+        }
+        if num_dropped > 0 {
+            writeln!(formatter, "Dropped {num_dropped} commits")?;
+        }
+    }
+    let tx_description = if stack.len() == 1 {
+        format!("rebase commit {} interactively", stack[0].id().hex())
+    } else {
+        format!(
+            "rebase commit {} and {} more interactively",
+            stack[0].id().hex(),
+            stack.len() - 1
+        )
+    };
+    tx.finish(ui, tx_description)
+}
+
+/// Rebases descendants of the rewritten commits, keeping `current_commits` up
+/// to date with the rewritten and rebased versions.
+fn rebase_descendants_tracking(
+    mut_repo: &mut MutableRepo,
+    current_commits: &mut HashMap<CommitId, Commit>,
+) -> BackendResult<()> {
+    // Commits may have been rewritten without rebasing descendants, e.g. by
+    // move_commits().
+    for commit in current_commits.values_mut() {
+        if let [new_id] = mut_repo
+            .new_parents(slice::from_ref(commit.id()))
+            .as_slice()
+        {
+            if new_id != commit.id() {
+                *commit = mut_repo.store().get_commit(new_id)?;
+            }
+        }
+    }
+    let mut rebased_commits = HashMap::new();
+    mut_repo.rebase_descendants_with_options(
+        &RebaseOptions::default(),
+        |old_commit, rebased| {
+            if let RebasedCommit::Rewritten(new_commit) = rebased {
+                rebased_commits.insert(old_commit.id().clone(), new_commit);
+            }
+        },
+    )?;
+    for commit in current_commits.values_mut() {
+        if let Some(new_commit) = rebased_commits.remove(commit.id()) {
+            *commit = new_commit;
+        }
+    }
+    Ok(())
+}
+
+fn format_rebase_plan(stack: &[Commit]) -> String {
+    let mut plan = String::new();
+    for commit in stack {
+        let first_line = commit.description().lines().next().unwrap_or_default();
+        let line = format!("pick {} {first_line}", short_commit_hash(commit.id()));
+        plan.push_str(line.trim_end());
+        plan.push('\n');
+    }
+    plan.push_str(indoc! {"
+
+        JJ: Rebase plan for the revisions above, oldest first.
+        JJ:
+        JJ: Commands:
+        JJ: p, pick <commit> = use commit
+        JJ: r, reword <commit> = use commit, but edit the commit message
+        JJ: s, squash <commit> = meld commit into the previous commit and combine
+        JJ:                      the commit messages
+        JJ: f, fixup <commit> = like \"squash\", but keep only the previous commit's
+        JJ:                     message
+        JJ: d, drop <commit> = remove commit
+        JJ:
+        JJ: Lines can be reordered. Every commit must be listed exactly once.
+        JJ: If all lines are removed, the rebase will be aborted.
+        JJ: Lines starting with \"JJ:\" (like this one) will be removed.
+    "});
+    plan
+}
+
+fn parse_rebase_plan(plan: &str, stack: &[Commit]) -> Result<Vec<RebasePlanStep>, CommandError> {
+    let mut steps: Vec<RebasePlanStep> = vec![];
+    for line in plan.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("JJ:") {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let action_word = words.next().unwrap();
+        let action = RebasePlanAction::parse(action_word).ok_or_else(|| {
+            user_error(format!(
+                "Unknown action '{action_word}' in rebase plan line: {line}"
+            ))
+        })?;
+        let Some(prefix) = words.next() else {
+            return Err(user_error(format!(
+                "Missing commit in rebase plan line: {line}"
+            )));
+        };
+        let matching = stack
+            .iter()
+            .filter(|commit| commit.id().hex().starts_with(prefix))
+            .collect_vec();
+        let commit = match *matching.as_slice() {
+            [commit] => commit,
+            [] => {
+                return Err(user_error(format!(
+                    "Commit '{prefix}' in rebase plan is not one of the revisions to rebase"
+                )));
+            }
+            [_, _, ..] => {
+                return Err(user_error(format!(
+                    "Commit ID prefix '{prefix}' in rebase plan is ambiguous"
+                )));
+            }
+        };
+        if steps.iter().any(|step| step.commit.id() == commit.id()) {
+            return Err(user_error(format!(
+                "Commit {} is listed more than once in rebase plan",
+                short_commit_hash(commit.id())
+            )));
+        }
+        steps.push(RebasePlanStep {
+            action,
+            commit: commit.clone(),
+        });
+    }
+    if steps.is_empty() {
+        return Ok(steps);
+    }
+    if let Some(missing) = stack
+        .iter()
+        .find(|commit| steps.iter().all(|step| step.commit.id() != commit.id()))
+    {
+        return Err(user_error_with_hint(
+            format!(
+                "Commit {} is missing from rebase plan",
+                short_commit_hash(missing.id())
+            ),
+            "Use `drop` to remove a commit from the stack.",
+        ));
+    }
+    Ok(steps)
+}
+
 fn check_rebase_destinations(
     repo: &Arc<ReadonlyRepo>,
     new_parents: &[CommitId],
@@ -14,19 +14,52 @@
 
 //! This file contains the internal implementation of `run`.
 
+use std::collections::HashMap;
+use std::io::Write as _;
+use std::process::Output;
+use std::sync::Arc;
+
+use clap_complete::ArgValueCandidates;
 use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::commit::Commit;
+use jj_lib::commit::CommitIteratorExt as _;
+use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::local_working_copy::TreeState;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::store::Store;
+use jj_lib::working_copy::CheckoutOptions;
+use jj_lib::working_copy::SnapshotOptions;
+use rayon::iter::IntoParallelRefIterator as _;
+use rayon::iter::ParallelIterator as _;
+use tracing::instrument;
 
+use crate::cli_util::short_commit_hash;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::command_error::internal_error;
+use crate::command_error::internal_error_with_message;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
+use crate::complete;
 use crate::ui::Ui;
 
-/// (**Stub**, does not work yet) Run a command across a set of revisions.
+/// Run a command across a set of revisions.
+///
+/// Each selected revision is checked out into its own temporary working copy,
+/// and the command is run from the root of that working copy using the
+/// platform shell (`sh -c` on Unix, `cmd /C` on Windows). Any changes the
+/// command makes to the files are then recorded in the revision, and
+/// descendants are rebased on top of the result.
 ///
+/// The commands run in parallel, up to the number given by `--jobs`. If the
+/// command fails on any revision, no revision is rewritten.
 ///
-/// All recorded state will be persisted in the `.jj` directory, so occasionally
-/// a `jj run --clean` is needed to clean up disk space.
+/// The environment variables `JJ_COMMIT_ID` and `JJ_CHANGE_ID` are set to the
+/// full commit and change id of the revision the command runs on.
 ///
 /// # Example
 ///
@@ -40,7 +73,13 @@ pub struct RunArgs {
     /// The command to run across all selected revisions.
     shell_command: String,
     /// The revisions to change.
-    #[arg(long, short, default_value = "@", value_name = "REVSETS")]
+    #[arg(
+        long,
+        short,
+        default_value = "@",
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::mutable_revisions)
+    )]
     revisions: Vec<RevisionArg>,
     /// A no-op option to match the interface of `git rebase -x`.
     #[arg(short = 'x', hide = true)]
@@ -50,21 +89,191 @@ pub struct RunArgs {
     jobs: Option<usize>,
 }
 
+/// The outcome of running the command on a single commit.
+struct RunOutput {
+    output: Output,
+    new_tree_id: MergedTreeId,
+}
+
+#[instrument(skip_all)]
 pub fn cmd_run(ui: &mut Ui, command: &CommandHelper, args: &RunArgs) -> Result<(), CommandError> {
-    let workspace_command = command.workspace_helper(ui)?;
-    let _resolved_commits: Vec<_> = workspace_command
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let resolved_commits: Vec<Commit> = workspace_command
         .parse_union_revsets(ui, &args.revisions)?
         .evaluate_to_commits()?
         .try_collect()?;
+    workspace_command.check_rewritable(resolved_commits.iter().ids())?;
     // Jobs are resolved in this order:
     // 1. Commandline argument iff > 0.
     // 2. the amount of cores available.
     // 3. a single job, if all of the above fails.
-    let _jobs = match args.jobs {
+    let jobs = match args.jobs {
         Some(0) | None => std::thread::available_parallelism().map(|t| t.into()).ok(),
         Some(jobs) => Some(jobs),
     }
     // Fallback to a single user-visible job.
     .unwrap_or(1usize);
-    Err(user_error("This is a stub, do not use"))
+
+    let store = workspace_command.repo().store().clone();
+    let checkout_options = workspace_command.checkout_options();
+    let auto_tracking_matcher = workspace_command.auto_tracking_matcher(ui)?;
+    let snapshot_options = SnapshotOptions {
+        // The temporary working copies aren't watched.
+        fsmonitor_settings: FsmonitorSettings::None,
+        ..workspace_command
+            .snapshot_options_with_start_tracking_matcher(auto_tracking_matcher.as_ref())?
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .map_err(internal_error)?;
+    let results: Vec<Result<RunOutput, CommandError>> = pool.install(|| {
+        resolved_commits
+            .par_iter()
+            .map(|commit| {
+                run_on_commit(
+                    &store,
+                    commit,
+                    &args.shell_command,
+                    &checkout_options,
+                    &snapshot_options,
+                )
+            })
+            .collect()
+    });
+
+    // Report output in the order the commits were selected, so it doesn't
+    // depend on scheduling.
+    let mut new_tree_ids: HashMap<CommitId, MergedTreeId> = HashMap::new();
+    let mut failed_commit = None;
+    for (commit, result) in resolved_commits.iter().zip(results) {
+        let RunOutput {
+            output,
+            new_tree_id,
+        } = result?;
+        ui.stdout().write_all(&output.stdout)?;
+        ui.stderr().write_all(&output.stderr)?;
+        if !output.status.success() {
+            failed_commit.get_or_insert((commit.id().clone(), output.status));
+            continue;
+        }
+        if new_tree_id != *commit.tree_id() {
+            new_tree_ids.insert(commit.id().clone(), new_tree_id);
+        }
+    }
+    if let Some((commit_id, status)) = failed_commit {
+        let error_msg = if let Some(exit_code) = status.code() {
+            format!(
+                "Command failed on commit {} with exit code {exit_code}",
+                short_commit_hash(&commit_id)
+            )
+        } else {
+            format!(
+                "Command failed on commit {}: terminated by {status}",
+                short_commit_hash(&commit_id)
+            )
+        };
+        return Err(user_error(error_msg));
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let mut num_changed = 0;
+    let mut num_rebased = 0;
+    tx.repo_mut()
+        .transform_descendants(new_tree_ids.keys().cloned().collect(), |rewriter| {
+            let old_commit = rewriter.old_commit().clone();
+            if let Some(new_tree_id) = new_tree_ids.get(old_commit.id()) {
+                // Apply the changes made by the command on top of the rebased
+                // content, in case an ancestor was rewritten too.
+                let store = rewriter.old_commit().store().clone();
+                let builder = rewriter.rebase()?;
+                let rebased_tree = store.get_root_tree(builder.tree_id())?;
+                let new_tree = store.get_root_tree(new_tree_id)?;
+                let merged_tree = rebased_tree.merge(&old_commit.tree()?, &new_tree)?;
+                builder.set_tree_id(merged_tree.id()).write()?;
+                num_changed += 1;
+            } else if rewriter.parents_changed() {
+                rewriter.rebase()?.write()?;
+                num_rebased += 1;
+            }
+            Ok(())
+        })?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        if num_changed > 0 {
+            writeln!(formatter, "Changed {num_changed} commits")?;
+        }
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
+        }
+    }
+    tx.finish(
+        ui,
+        format!(
+            "run command '{}' on {} commits",
+            args.shell_command,
+            resolved_commits.len()
+        ),
+    )
+}
+
+/// Checks out `commit` into a temporary directory, runs `shell_command` in it,
+/// and snapshots the result.
+fn run_on_commit(
+    store: &Arc<Store>,
+    commit: &Commit,
+    shell_command: &str,
+    checkout_options: &CheckoutOptions,
+    snapshot_options: &SnapshotOptions,
+) -> Result<RunOutput, CommandError> {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("jj-run-")
+        .tempdir()
+        .map_err(|err| user_error_with_message("Failed to create temporary directory", err))?;
+    let wc_dir = temp_dir.path().join("wc");
+    let state_dir = temp_dir.path().join("state");
+    std::fs::create_dir(&wc_dir)?;
+    std::fs::create_dir(&state_dir)?;
+    let mut tree_state = TreeState::init(store.clone(), wc_dir.clone(), state_dir)
+        .map_err(|err| internal_error_with_message("Failed to set up working copy", err))?;
+    tree_state
+        .check_out(&commit.tree()?, checkout_options)
+        .map_err(|err| {
+            internal_error_with_message(
+                format!(
+                    "Failed to check out commit {}",
+                    short_commit_hash(commit.id())
+                ),
+                err,
+            )
+        })?;
+
+    let mut cmd = shell_command_builder(shell_command);
+    cmd.current_dir(&wc_dir)
+        .env("JJ_COMMIT_ID", commit.id().hex())
+        .env("JJ_CHANGE_ID", commit.change_id().reverse_hex());
+    tracing::debug!(?cmd, commit_id = ?commit.id(), "spawning run command");
+    let output = cmd.output().map_err(|err| {
+        user_error_with_message(format!("Failed to execute command '{shell_command}'"), err)
+    })?;
+    tracing::debug!(?cmd, ?output.status, "run command exited:");
+
+    tree_state.snapshot(snapshot_options)?;
+    Ok(RunOutput {
+        output,
+        new_tree_id: tree_state.current_tree_id().clone(),
+    })
+}
+
+#[cfg(unix)]
+pub(crate) fn shell_command_builder(shell_command: &str) -> std::process::Command {
+    let mut cmd = std::process::Command::new("sh");
+    cmd.arg("-c").arg(shell_command);
+    cmd
+}
+
+#[cfg(windows)]
+pub(crate) fn shell_command_builder(shell_command: &str) -> std::process::Command {
+    let mut cmd = std::process::Command::new("cmd");
+    cmd.arg("/C").arg(shell_command);
+    cmd
 }
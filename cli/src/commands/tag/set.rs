@@ -0,0 +1,136 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::git;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use jj_lib::ref_name::RefNameBuf;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::revset_util;
+use crate::text_util;
+use crate::ui::Ui;
+
+/// Create or update tags to point to a certain commit
+///
+/// With `--message`, annotated tags are created in the underlying Git
+/// repository. Otherwise, lightweight tags are created.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagSetArgs {
+    /// The tags' target revision
+    #[arg(
+        long, short,
+        default_value = "@",
+        visible_alias = "to",
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::all_revisions),
+    )]
+    revision: RevisionArg,
+
+    /// Allow moving existing tags
+    #[arg(long)]
+    allow_move: bool,
+
+    /// Create annotated tags with the given message
+    ///
+    /// This requires the Git backend.
+    #[arg(long, short, value_name = "MESSAGE")]
+    message: Option<String>,
+
+    /// The tags to create or update
+    #[arg(
+        required = true,
+        value_parser = revset_util::parse_tag_name,
+        add = ArgValueCandidates::new(complete::local_tags),
+    )]
+    names: Vec<RefNameBuf>,
+}
+
+pub fn cmd_tag_set(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagSetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let repo = workspace_command.repo().as_ref();
+    let tag_names = &args.names;
+    let mut new_tag_count = 0;
+    let mut moved_tag_count = 0;
+    for name in tag_names {
+        let old_target = repo.view().get_tag(name);
+        if old_target.is_absent() {
+            new_tag_count += 1;
+        } else if old_target.as_normal() != Some(target_commit.id()) {
+            if !args.allow_move {
+                return Err(user_error_with_hint(
+                    format!("Refusing to move tag: {name}", name = name.as_symbol()),
+                    "Use --allow-move to update existing tags.",
+                ));
+            }
+            moved_tag_count += 1;
+        }
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    for name in tag_names {
+        tx.repo_mut()
+            .set_tag_target(name, RefTarget::normal(target_commit.id().clone()));
+    }
+    if let Some(message) = &args.message {
+        // Annotated tags only exist in Git, so they have to be exported now.
+        let message = text_util::complete_newline(message);
+        let tagger = tx.settings().signature();
+        let export_stats = git::export_annotated_tags(tx.repo_mut(), tag_names, &message, &tagger)?;
+        if let Some((symbol, reason)) = export_stats.failed_tags.into_iter().next() {
+            return Err(user_error_with_message(
+                format!(
+                    "Failed to export tag {name} to the underlying Git repo",
+                    name = symbol.name.as_symbol()
+                ),
+                reason,
+            ));
+        }
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        if new_tag_count > 0 {
+            write!(formatter, "Created {new_tag_count} tags pointing to ")?;
+            tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+            writeln!(formatter)?;
+        }
+        if moved_tag_count > 0 {
+            write!(formatter, "Moved {moved_tag_count} tags to ")?;
+            tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+            writeln!(formatter)?;
+        }
+    }
+
+    tx.finish(
+        ui,
+        format!(
+            "set tag {names} to commit {id}",
+            names = tag_names.iter().map(|n| n.as_symbol()).join(", "),
+            id = target_commit.id().hex()
+        ),
+    )?;
+    Ok(())
+}
@@ -12,21 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
 use std::slice;
 use std::time::Duration;
 use std::time::SystemTime;
 
 use jj_lib::repo::Repo as _;
+use jj_lib::time_util;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 /// Run backend-dependent garbage collection.
 ///
 /// To garbage-collect old operations and the commits/objects referenced by
-/// then, run `jj op abandon ..<some old operation>` before `jj util gc`.
+/// then, run `jj op abandon ..<some old operation>` or
+/// `jj op compact ..<some old operation>` before `jj util gc`.
 ///
 /// Previous versions of a change that are reachable via the evolution log are
 /// not garbage-collected.
@@ -37,11 +41,14 @@ pub struct UtilGcArgs {
     /// By default, only obsolete objects and operations older than 2 weeks are
     /// pruned.
     ///
-    /// Only the string "now" can be passed to this parameter. Support for
-    /// arbitrary absolute and relative timestamps will come in a subsequent
-    /// release.
+    /// Accepts "now", absolute dates such as "2024-02-01", and relative dates
+    /// such as "2 weeks ago" or "2.weeks.ago".
     #[arg(long)]
     expire: Option<String>,
+
+    /// Report what would be pruned without deleting anything
+    #[arg(long)]
+    dry_run: bool,
 }
 
 pub fn cmd_util_gc(
@@ -56,14 +63,57 @@ pub fn cmd_util_gc(
     }
     let keep_newer = match args.expire.as_deref() {
         None => SystemTime::now() - Duration::from_secs(14 * 86400),
-        Some("now") => SystemTime::now() - Duration::ZERO,
-        _ => return Err(user_error("--expire only accepts 'now'")),
+        Some(expire) => parse_expire(expire)?,
     };
     let workspace_command = command.workspace_helper(ui)?;
 
     let repo = workspace_command.repo();
-    repo.op_store()
-        .gc(slice::from_ref(repo.op_id()), keep_newer)?;
-    repo.store().gc(repo.index(), keep_newer)?;
+    let op_stats =
+        repo.op_store()
+            .gc_with_stats(slice::from_ref(repo.op_id()), keep_newer, args.dry_run)?;
+    let backend_stats = repo
+        .store()
+        .gc_with_stats(repo.index(), keep_newer, args.dry_run)?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        let verb = if args.dry_run {
+            "Would prune"
+        } else {
+            "Pruned"
+        };
+        writeln!(
+            formatter,
+            "{verb} {} operations and {} views",
+            op_stats.num_operations, op_stats.num_views
+        )?;
+        writeln!(
+            formatter,
+            "{verb} {} commits and {} objects",
+            backend_stats.num_commits, backend_stats.num_objects
+        )?;
+        if backend_stats.num_packed_objects > 0 {
+            let verb = if args.dry_run { "Would pack" } else { "Packed" };
+            writeln!(
+                formatter,
+                "{verb} {} objects",
+                backend_stats.num_packed_objects
+            )?;
+        }
+    }
     Ok(())
 }
+
+/// Parses the `--expire` argument into the time before which obsolete data
+/// is pruned.
+fn parse_expire(expire: &str) -> Result<SystemTime, CommandError> {
+    if expire == "now" {
+        return Ok(SystemTime::now());
+    }
+    let now = chrono::Local::now();
+    // Git-style dates such as "2.weeks.ago" use dots as separators.
+    let millis = time_util::parse_timestamp(expire, now)
+        .or_else(|_| time_util::parse_timestamp(&expire.replace('.', " "), now))
+        .map_err(|err| user_error_with_message(format!("Invalid --expire value: {expire}"), err))?;
+    let millis = u64::try_from(millis.0)
+        .map_err(|_| user_error(format!("Invalid --expire value: {expire}")))?;
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_millis(millis))
+}
@@ -64,6 +64,8 @@ use jj_lib::signing::SignError;
 use jj_lib::signing::SignResult;
 use jj_lib::signing::Verification;
 use jj_lib::store::Store;
+use jj_lib::trailer;
+use jj_lib::trailer::Trailer;
 use once_cell::unsync::OnceCell;
 use pollster::FutureExt as _;
 
@@ -326,6 +328,23 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                     function,
                 )
             }
+            CommitTemplatePropertyKind::Trailer(property) => {
+                let table = &self.build_fn_table.trailer_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, diagnostics, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::TrailerList(property) => {
+                // TODO: migrate to table?
+                template_builder::build_formattable_list_method(
+                    self,
+                    diagnostics,
+                    build_ctx,
+                    property,
+                    function,
+                    Self::wrap_trailer,
+                    Self::wrap_trailer_list,
+                )
+            }
             CommitTemplatePropertyKind::AnnotationLine(property) => {
                 let type_name = "AnnotationLine";
                 let table = &self.build_fn_table.annotation_line_methods;
@@ -451,6 +470,18 @@ impl<'repo> CommitTemplateLanguage<'repo> {
         CommitTemplatePropertyKind::CryptographicSignatureOpt(Box::new(property))
     }
 
+    pub fn wrap_trailer(
+        property: impl TemplateProperty<Output = Trailer> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::Trailer(Box::new(property))
+    }
+
+    pub fn wrap_trailer_list(
+        property: impl TemplateProperty<Output = Vec<Trailer>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::TrailerList(Box::new(property))
+    }
+
     pub fn wrap_annotation_line(
         property: impl TemplateProperty<Output = AnnotationLine> + 'repo,
     ) -> CommitTemplatePropertyKind<'repo> {
@@ -478,6 +509,8 @@ pub enum CommitTemplatePropertyKind<'repo> {
     CryptographicSignatureOpt(
         Box<dyn TemplateProperty<Output = Option<CryptographicSignature>> + 'repo>,
     ),
+    Trailer(Box<dyn TemplateProperty<Output = Trailer> + 'repo>),
+    TrailerList(Box<dyn TemplateProperty<Output = Vec<Trailer>> + 'repo>),
     AnnotationLine(Box<dyn TemplateProperty<Output = AnnotationLine> + 'repo>),
 }
 
@@ -503,6 +536,8 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => {
                 "Option<CryptographicSignature>"
             }
+            CommitTemplatePropertyKind::Trailer(_) => "Trailer",
+            CommitTemplatePropertyKind::TrailerList(_) => "List<Trailer>",
             CommitTemplatePropertyKind::AnnotationLine(_) => "AnnotationLine",
         }
     }
@@ -542,6 +577,10 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::CryptographicSignatureOpt(property) => {
                 Some(Box::new(property.map(|sig| sig.is_some())))
             }
+            CommitTemplatePropertyKind::Trailer(_) => None,
+            CommitTemplatePropertyKind::TrailerList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
             CommitTemplatePropertyKind::AnnotationLine(_) => None,
         }
     }
@@ -586,6 +625,85 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::TreeEntry(_) => None,
             CommitTemplatePropertyKind::DiffStats(property) => Some(property.into_template()),
             CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => None,
+            CommitTemplatePropertyKind::Trailer(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::TrailerList(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::AnnotationLine(_) => None,
+        }
+    }
+
+    fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'repo>> {
+        match self {
+            CommitTemplatePropertyKind::Core(property) => property.try_into_json(),
+            CommitTemplatePropertyKind::Commit(property) => Some(Box::new(
+                property.and_then(|commit| commit_to_json(&commit)),
+            )),
+            CommitTemplatePropertyKind::CommitOpt(property) => {
+                Some(Box::new(property.and_then(|commit| {
+                    commit.map_or(Ok(serde_json::Value::Null), |commit| {
+                        commit_to_json(&commit)
+                    })
+                })))
+            }
+            CommitTemplatePropertyKind::CommitList(property) => {
+                Some(Box::new(property.and_then(|commits| {
+                    commits.iter().map(commit_to_json).try_collect()
+                })))
+            }
+            CommitTemplatePropertyKind::CommitRef(property) => Some(Box::new(
+                property.map(|commit_ref| commit_ref_to_json(&commit_ref)),
+            )),
+            CommitTemplatePropertyKind::CommitRefOpt(property) => {
+                Some(Box::new(property.map(|commit_ref| {
+                    commit_ref.map_or(serde_json::Value::Null, |commit_ref| {
+                        commit_ref_to_json(&commit_ref)
+                    })
+                })))
+            }
+            CommitTemplatePropertyKind::CommitRefList(property) => {
+                Some(Box::new(property.map(|commit_refs| {
+                    commit_refs
+                        .iter()
+                        .map(|commit_ref| commit_ref_to_json(commit_ref))
+                        .collect()
+                })))
+            }
+            CommitTemplatePropertyKind::RepoPath(property) => Some(Box::new(
+                property.map(|path| path.as_internal_file_string().into()),
+            )),
+            CommitTemplatePropertyKind::RepoPathOpt(property) => {
+                Some(Box::new(property.map(|path| {
+                    path.map(|path| path.as_internal_file_string().to_owned())
+                        .into()
+                })))
+            }
+            CommitTemplatePropertyKind::CommitOrChangeId(property) => {
+                Some(Box::new(property.map(|id| id.hex().into())))
+            }
+            CommitTemplatePropertyKind::ShortestIdPrefix(_) => None,
+            CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::TreeDiffEntry(property) => {
+                Some(Box::new(property.map(|entry| entry.to_json())))
+            }
+            CommitTemplatePropertyKind::TreeDiffEntryList(property) => {
+                Some(Box::new(property.map(|entries| {
+                    entries.iter().map(TreeDiffEntry::to_json).collect()
+                })))
+            }
+            CommitTemplatePropertyKind::TreeEntry(property) => {
+                Some(Box::new(property.map(|entry| entry.to_json())))
+            }
+            CommitTemplatePropertyKind::DiffStats(_) => None,
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => None,
+            CommitTemplatePropertyKind::Trailer(property) => {
+                Some(Box::new(property.map(|trailer| trailer_to_json(&trailer))))
+            }
+            CommitTemplatePropertyKind::TrailerList(property) => {
+                Some(Box::new(property.map(|trailers| {
+                    trailers.iter().map(trailer_to_json).collect()
+                })))
+            }
             CommitTemplatePropertyKind::AnnotationLine(_) => None,
         }
     }
@@ -612,6 +730,8 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             (CommitTemplatePropertyKind::TreeEntry(_), _) => None,
             (CommitTemplatePropertyKind::DiffStats(_), _) => None,
             (CommitTemplatePropertyKind::CryptographicSignatureOpt(_), _) => None,
+            (CommitTemplatePropertyKind::Trailer(_), _) => None,
+            (CommitTemplatePropertyKind::TrailerList(_), _) => None,
             (CommitTemplatePropertyKind::AnnotationLine(_), _) => None,
         }
     }
@@ -641,6 +761,8 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             (CommitTemplatePropertyKind::TreeEntry(_), _) => None,
             (CommitTemplatePropertyKind::DiffStats(_), _) => None,
             (CommitTemplatePropertyKind::CryptographicSignatureOpt(_), _) => None,
+            (CommitTemplatePropertyKind::Trailer(_), _) => None,
+            (CommitTemplatePropertyKind::TrailerList(_), _) => None,
             (CommitTemplatePropertyKind::AnnotationLine(_), _) => None,
         }
     }
@@ -664,6 +786,7 @@ pub struct CommitTemplateBuildFnTable<'repo> {
     pub diff_stats_methods: CommitTemplateBuildMethodFnMap<'repo, DiffStats>,
     pub cryptographic_signature_methods:
         CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature>,
+    pub trailer_methods: CommitTemplateBuildMethodFnMap<'repo, Trailer>,
     pub annotation_line_methods: CommitTemplateBuildMethodFnMap<'repo, AnnotationLine>,
 }
 
@@ -682,6 +805,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             tree_entry_methods: builtin_tree_entry_methods(),
             diff_stats_methods: builtin_diff_stats_methods(),
             cryptographic_signature_methods: builtin_cryptographic_signature_methods(),
+            trailer_methods: builtin_trailer_methods(),
             annotation_line_methods: builtin_annotation_line_methods(),
         }
     }
@@ -699,6 +823,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             tree_entry_methods: HashMap::new(),
             diff_stats_methods: HashMap::new(),
             cryptographic_signature_methods: HashMap::new(),
+            trailer_methods: HashMap::new(),
             annotation_line_methods: HashMap::new(),
         }
     }
@@ -716,6 +841,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             tree_entry_methods,
             diff_stats_methods,
             cryptographic_signature_methods,
+            trailer_methods,
             annotation_line_methods,
         } = extension;
 
@@ -739,6 +865,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             &mut self.cryptographic_signature_methods,
             cryptographic_signature_methods,
         );
+        merge_fn_map(&mut self.trailer_methods, trailer_methods);
         merge_fn_map(&mut self.annotation_line_methods, annotation_line_methods);
     }
 }
@@ -1058,9 +1185,29 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert(
+        "trailers",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property
+                .map(|commit| trailer::parse_description_trailers(commit.description()));
+            Ok(L::wrap_trailer_list(out_property))
+        },
+    );
     map
 }
 
+fn commit_to_json(commit: &Commit) -> Result<serde_json::Value, TemplatePropertyError> {
+    Ok(serde_json::json!({
+        "commit_id": commit.id().hex(),
+        "parents": commit.parent_ids().iter().map(|id| id.hex()).collect_vec(),
+        "change_id": commit.change_id().reverse_hex(),
+        "description": commit.description(),
+        "author": templater::signature_to_json(commit.author())?,
+        "committer": templater::signature_to_json(commit.committer())?,
+    }))
+}
+
 // TODO: return Vec<String>
 fn extract_working_copies(repo: &dyn Repo, commit: &Commit) -> String {
     let wc_commit_ids = repo.view().wc_commit_ids();
@@ -1338,6 +1485,28 @@ impl Template for Rc<CommitRef> {
     }
 }
 
+/// Converts ref target to list of commit ids. Conflicted target is represented
+/// as interleaved list of added and removed ids, where absent ids are `null`.
+fn ref_target_to_json(target: &RefTarget) -> serde_json::Value {
+    target
+        .as_merge()
+        .iter()
+        .map(|id| id.as_ref().map(|id| id.hex()))
+        .collect()
+}
+
+fn commit_ref_to_json(commit_ref: &CommitRef) -> serde_json::Value {
+    serde_json::json!({
+        "name": commit_ref.name,
+        "remote": commit_ref.remote,
+        "target": ref_target_to_json(&commit_ref.target),
+        "tracking_target": commit_ref
+            .tracking_ref
+            .as_ref()
+            .map(|tracking| ref_target_to_json(&tracking.target)),
+    })
+}
+
 impl Template for Vec<Rc<CommitRef>> {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         templater::format_joined(formatter, self, " ")
@@ -1978,6 +2147,15 @@ impl TreeDiffEntry {
         label
     }
 
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "path": self.path.target.as_internal_file_string(),
+            "status": self.status_label(),
+            "source": self.clone().into_source_entry().to_json(),
+            "target": self.clone().into_target_entry().to_json(),
+        })
+    }
+
     fn into_source_entry(self) -> TreeEntry {
         TreeEntry {
             path: self.path.source.map_or(self.path.target, |(path, _)| path),
@@ -2042,6 +2220,17 @@ pub struct TreeEntry {
     pub value: MergedTreeValue,
 }
 
+impl TreeEntry {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "path": self.path.as_internal_file_string(),
+            "conflict": !self.value.is_resolved(),
+            "file_type": describe_file_type(&self.value),
+            "executable": is_executable_file(&self.value).unwrap_or_default(),
+        })
+    }
+}
+
 fn builtin_tree_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, TreeEntry> {
     type L<'repo> = CommitTemplateLanguage<'repo>;
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -2217,11 +2406,56 @@ pub fn builtin_cryptographic_signature_methods<'repo>(
     map
 }
 
+impl Template for Trailer {
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        write!(formatter, "{}: {}", self.key, self.value)
+    }
+}
+
+impl Template for Vec<Trailer> {
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        templater::format_joined(formatter, self, "\n")
+    }
+}
+
+fn trailer_to_json(trailer: &Trailer) -> serde_json::Value {
+    serde_json::json!({
+        "key": trailer.key,
+        "value": trailer.value,
+    })
+}
+
+fn builtin_trailer_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Trailer> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<Trailer>::new();
+    map.insert(
+        "key",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|trailer| trailer.key);
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "value",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|trailer| trailer.value);
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map
+}
+
 #[derive(Debug, Clone)]
 pub struct AnnotationLine {
     pub commit: Commit,
     pub content: BString,
     pub line_number: usize,
+    /// Path of the file in `commit` if it differs from the annotated path.
+    pub original_path: Option<RepoPathBuf>,
     pub first_line_in_hunk: bool,
 }
 
@@ -2254,6 +2488,14 @@ pub fn builtin_annotation_line_methods<'repo>(
             Ok(L::wrap_integer(out_property))
         },
     );
+    map.insert(
+        "original_path",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|line| line.original_path);
+            Ok(L::wrap_repo_path_opt(out_property))
+        },
+    );
     map.insert(
         "first_line_in_hunk",
         |_language, _diagnostics, _build_ctx, self_property, function| {
@@ -20,6 +20,7 @@ use std::mem;
 use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use bstr::BStr;
 use bstr::BString;
@@ -486,7 +487,7 @@ impl<'a> DiffRenderer<'a> {
 }
 
 pub fn get_copy_records<'a>(
-    store: &'a Store,
+    store: &'a Arc<Store>,
     root: &CommitId,
     head: &CommitId,
     matcher: &'a dyn Matcher,
@@ -613,6 +613,22 @@ export or their "parent" bookmarks."#,
             )?;
         }
     }
+    if !stats.failed_tags.is_empty() {
+        writeln!(ui.warning_default(), "Failed to export some tags:")?;
+        let mut formatter = ui.stderr_formatter();
+        for (symbol, reason) in &stats.failed_tags {
+            write!(formatter, "  ")?;
+            write!(
+                formatter.labeled("tag"),
+                "{name}",
+                name = symbol.name.as_symbol()
+            )?;
+            for err in iter::successors(Some(reason as &dyn error::Error), |err| err.source()) {
+                write!(formatter, ": {err}")?;
+            }
+            writeln!(formatter)?;
+        }
+    }
     Ok(())
 }
 
@@ -14,6 +14,7 @@
 
 use std::any::Any;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::io;
 
@@ -37,12 +38,14 @@ use crate::template_parser;
 use crate::template_parser::FunctionCallNode;
 use crate::template_parser::TemplateDiagnostics;
 use crate::template_parser::TemplateParseResult;
+use crate::templater;
 use crate::templater::PlainTextFormattedProperty;
 use crate::templater::Template;
 use crate::templater::TemplateFormatter;
 use crate::templater::TemplateProperty;
 use crate::templater::TemplatePropertyExt as _;
 use crate::templater::TimestampRange;
+use crate::time_util::TimestampOutOfRange;
 
 pub trait OperationTemplateLanguageExtension {
     fn build_fn_table(&self) -> OperationTemplateBuildFnTable;
@@ -197,6 +200,18 @@ impl IntoTemplateProperty<'static> for OperationTemplatePropertyKind {
         }
     }
 
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value>>> {
+        match self {
+            OperationTemplatePropertyKind::Core(property) => property.try_into_json(),
+            OperationTemplatePropertyKind::Operation(property) => Some(Box::new(
+                property.and_then(|op| Ok(operation_to_json(&op)?)),
+            )),
+            OperationTemplatePropertyKind::OperationId(property) => {
+                Some(Box::new(property.map(|id| id.hex().into())))
+            }
+        }
+    }
+
     fn try_into_eq(self, other: Self) -> Option<Box<dyn TemplateProperty<Output = bool>>> {
         match (self, other) {
             (
@@ -351,6 +366,24 @@ fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
     map
 }
 
+fn operation_to_json(op: &Operation) -> Result<serde_json::Value, TimestampOutOfRange> {
+    let metadata = op.metadata();
+    let time = TimestampRange {
+        start: metadata.start_time,
+        end: metadata.end_time,
+    };
+    Ok(serde_json::json!({
+        "id": op.id().hex(),
+        "parents": op.parent_ids().iter().map(|id| id.hex()).collect_vec(),
+        "time": templater::timestamp_range_to_json(&time)?,
+        "description": metadata.description,
+        "hostname": metadata.hostname,
+        "username": metadata.username,
+        "is_snapshot": metadata.is_snapshot,
+        "tags": metadata.tags.iter().collect::<BTreeMap<_, _>>(),
+    }))
+}
+
 impl Template for OperationId {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         write!(formatter, "{}", self.hex())
@@ -42,6 +42,7 @@ use crate::template_parser::TemplateParseError;
 use crate::template_parser::TemplateParseErrorKind;
 use crate::template_parser::TemplateParseResult;
 use crate::template_parser::UnaryOp;
+use crate::templater;
 use crate::templater::CoalesceTemplate;
 use crate::templater::ConcatTemplate;
 use crate::templater::ConditionalTemplate;
@@ -180,6 +181,18 @@ pub trait IntoTemplateProperty<'a> {
     fn try_into_plain_text(self) -> Option<Box<dyn TemplateProperty<Output = String> + 'a>>;
     fn try_into_template(self) -> Option<Box<dyn Template + 'a>>;
 
+    /// Transforms into a property that will evaluate to a JSON value.
+    ///
+    /// Unlike `.escape_json()`, which only serializes a string, this builds
+    /// a structured value so that objects and lists can be nested. Types
+    /// that can't be serialized return `None`.
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
     /// Transforms into a property that will evaluate to `self == other`.
     fn try_into_eq(self, other: Self) -> Option<Box<dyn TemplateProperty<Output = bool> + 'a>>;
 
@@ -301,6 +314,44 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
         }
     }
 
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        match self {
+            CoreTemplatePropertyKind::String(property) => Some(Box::new(property.map(Into::into))),
+            CoreTemplatePropertyKind::StringList(property) => {
+                Some(Box::new(property.map(Into::into)))
+            }
+            CoreTemplatePropertyKind::Boolean(property) => Some(Box::new(property.map(Into::into))),
+            CoreTemplatePropertyKind::Integer(property) => Some(Box::new(property.map(Into::into))),
+            CoreTemplatePropertyKind::IntegerOpt(property) => {
+                Some(Box::new(property.map(Into::into)))
+            }
+            CoreTemplatePropertyKind::ConfigValue(property) => Some(Box::new(
+                property.map(|value| templater::config_value_to_json(&value)),
+            )),
+            CoreTemplatePropertyKind::Signature(property) => {
+                Some(Box::new(property.and_then(|signature| {
+                    Ok(templater::signature_to_json(&signature)?)
+                })))
+            }
+            CoreTemplatePropertyKind::Email(property) => {
+                Some(Box::new(property.map(|email| email.0.into())))
+            }
+            CoreTemplatePropertyKind::SizeHint(_) => None,
+            CoreTemplatePropertyKind::Timestamp(property) => {
+                Some(Box::new(property.and_then(|timestamp| {
+                    Ok(templater::timestamp_to_json(&timestamp)?)
+                })))
+            }
+            CoreTemplatePropertyKind::TimestampRange(property) => {
+                Some(Box::new(property.and_then(|range| {
+                    Ok(templater::timestamp_range_to_json(&range)?)
+                })))
+            }
+            CoreTemplatePropertyKind::Template(_) => None,
+            CoreTemplatePropertyKind::ListTemplate(_) => None,
+        }
+    }
+
     fn try_into_eq(self, other: Self) -> Option<Box<dyn TemplateProperty<Output = bool> + 'a>> {
         match (self, other) {
             (CoreTemplatePropertyKind::String(lhs), CoreTemplatePropertyKind::String(rhs)) => {
@@ -624,6 +675,12 @@ impl<'a, P: IntoTemplateProperty<'a>> Expression<P> {
         }
     }
 
+    pub fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        self.property.try_into_json()
+    }
+
     pub fn try_into_eq(self, other: Self) -> Option<Box<dyn TemplateProperty<Output = bool> + 'a>> {
         self.property.try_into_eq(other.property)
     }
@@ -1582,6 +1639,12 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
         let content = expect_plain_text_expression(language, diagnostics, build_ctx, content_node)?;
         Ok(L::wrap_string(content))
     });
+    map.insert("json", |language, diagnostics, build_ctx, function| {
+        let [value_node] = function.expect_exact_arguments()?;
+        let value = expect_json_expression(language, diagnostics, build_ctx, value_node)?;
+        let out_property = value.map(|value| value.to_string());
+        Ok(L::wrap_string(out_property))
+    });
     map.insert("if", |language, diagnostics, build_ctx, function| {
         let ([condition_node, true_node], [false_node]) = function.expect_arguments()?;
         let condition =
@@ -1937,6 +2000,22 @@ pub fn expect_template_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
     )
 }
 
+pub fn expect_json_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
+    language: &L,
+    diagnostics: &mut TemplateDiagnostics,
+    build_ctx: &BuildContext<L::Property>,
+    node: &ExpressionNode,
+) -> TemplateParseResult<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+    expect_expression_of_type(
+        language,
+        diagnostics,
+        build_ctx,
+        node,
+        "Serialize",
+        |expression| expression.try_into_json(),
+    )
+}
+
 fn expect_expression_of_type<'a, L: TemplateLanguage<'a> + ?Sized, T>(
     language: &L,
     diagnostics: &mut TemplateDiagnostics,
@@ -3184,6 +3263,47 @@ mod tests {
         insta::assert_snapshot!(env.render_ok("stringify(label('error', 'text'))"), @"text");
     }
 
+    #[test]
+    fn test_json_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("none_i64", || L::wrap_integer_opt(Literal(None)));
+        env.add_keyword("string_list", || {
+            L::wrap_string_list(Literal(vec!["foo".to_owned(), "\"bar\"".to_owned()]))
+        });
+        env.add_keyword("config_table", || {
+            let value: ConfigValue = "{ a = [1, true], b = 'c' }".parse().unwrap();
+            L::wrap_config_value(Literal(value))
+        });
+        env.add_keyword("author", || {
+            L::wrap_signature(Literal(Signature {
+                name: "Test User".to_owned(),
+                email: "test.user@example.com".to_owned(),
+                timestamp: new_timestamp(1_000_000_000_000, 9 * 60),
+            }))
+        });
+        env.add_keyword("bad_string", || L::wrap_string(new_error_property("Bad")));
+
+        insta::assert_snapshot!(env.render_ok(r#"json("a\nb")"#), @r#""a\nb""#);
+        // Strings are serialized in the same way as .escape_json()
+        insta::assert_snapshot!(env.render_ok(r#"json("he \n ll \n \" o")"#), @r#""he \n ll \n \" o""#);
+        insta::assert_snapshot!(env.render_ok("json(-1)"), @"-1");
+        insta::assert_snapshot!(env.render_ok("json(false)"), @"false");
+        insta::assert_snapshot!(env.render_ok("json(none_i64)"), @"null");
+        insta::assert_snapshot!(env.render_ok("json(string_list)"), @r#"["foo","\"bar\""]"#);
+        insta::assert_snapshot!(env.render_ok("json(config_table)"), @r#"{"a":[1,true],"b":"c"}"#);
+        insta::assert_snapshot!(env.render_ok("json(author)"), @r#"{"email":"test.user@example.com","name":"Test User","timestamp":"2001-09-09T10:46:40+09:00"}"#);
+        insta::assert_snapshot!(env.render_ok("json(author.email())"), @r#""test.user@example.com""#);
+        insta::assert_snapshot!(env.render_ok("json(bad_string)"), @"<Error: Bad>");
+        insta::assert_snapshot!(env.parse_err("json(label('a', 'b'))"), @r"
+         --> 1:6
+          |
+        1 | json(label('a', 'b'))
+          |      ^-------------^
+          |
+          = Expected expression of type `Serialize`, but actual type is `Template`
+        ");
+    }
+
     #[test]
     fn test_coalesce_function() {
         let mut env = TestTemplateEnv::new();
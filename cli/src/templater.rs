@@ -182,6 +182,50 @@ impl Template for TimestampRange {
     }
 }
 
+/// Converts config value to JSON value. Date-time is represented as string.
+pub fn config_value_to_json(value: &ConfigValue) -> serde_json::Value {
+    match value {
+        ConfigValue::String(v) => v.value().as_str().into(),
+        ConfigValue::Integer(v) => (*v.value()).into(),
+        ConfigValue::Float(v) => (*v.value()).into(),
+        ConfigValue::Boolean(v) => (*v.value()).into(),
+        ConfigValue::Datetime(v) => v.value().to_string().into(),
+        ConfigValue::Array(array) => array.iter().map(config_value_to_json).collect(),
+        ConfigValue::InlineTable(table) => table
+            .iter()
+            .map(|(key, value)| (key.to_owned(), config_value_to_json(value)))
+            .collect(),
+    }
+}
+
+/// Converts signature to JSON object of `name`, `email`, and `timestamp`.
+pub fn signature_to_json(
+    signature: &Signature,
+) -> Result<serde_json::Value, time_util::TimestampOutOfRange> {
+    Ok(serde_json::json!({
+        "name": signature.name,
+        "email": signature.email,
+        "timestamp": timestamp_to_json(&signature.timestamp)?,
+    }))
+}
+
+/// Converts timestamp to RFC 3339 string.
+pub fn timestamp_to_json(
+    timestamp: &Timestamp,
+) -> Result<serde_json::Value, time_util::TimestampOutOfRange> {
+    Ok(time_util::format_rfc3339_timestamp(timestamp)?.into())
+}
+
+/// Converts timestamp range to JSON object of `start` and `end`.
+pub fn timestamp_range_to_json(
+    range: &TimestampRange,
+) -> Result<serde_json::Value, time_util::TimestampOutOfRange> {
+    Ok(serde_json::json!({
+        "start": timestamp_to_json(&range.start)?,
+        "end": timestamp_to_json(&range.end)?,
+    }))
+}
+
 impl Template for Vec<String> {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         format_joined(formatter, self, " ")
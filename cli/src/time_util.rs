@@ -84,6 +84,12 @@ pub fn format_absolute_timestamp_with(
     Ok(datetime.format_with_items(format.items.iter()).to_string())
 }
 
+/// Formats the timestamp in RFC 3339 format, e.g. `2001-02-03T04:05:06+07:00`.
+pub fn format_rfc3339_timestamp(timestamp: &Timestamp) -> Result<String, TimestampOutOfRange> {
+    let datetime = datetime_from_timestamp(timestamp)?;
+    Ok(datetime.to_rfc3339())
+}
+
 pub fn format_duration(
     from: &Timestamp,
     to: &Timestamp,
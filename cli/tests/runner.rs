@@ -14,6 +14,7 @@ mod test_acls;
 mod test_advance_bookmarks;
 mod test_alias;
 mod test_backout_command;
+mod test_bisect_command;
 mod test_bookmark_command;
 mod test_builtin_aliases;
 mod test_commit_command;
@@ -33,6 +34,8 @@ mod test_edit_command;
 mod test_evolog_command;
 mod test_file_annotate_command;
 mod test_file_chmod_command;
+mod test_file_copy_move_commands;
+mod test_file_search_command;
 mod test_file_show_command;
 mod test_file_track_untrack_commands;
 mod test_fix_command;
@@ -52,6 +55,7 @@ mod test_help_command;
 mod test_immutable_commits;
 mod test_interdiff_command;
 mod test_log_command;
+mod test_metaedit_command;
 mod test_new_command;
 mod test_next_prev_commands;
 mod test_operations;
@@ -63,6 +67,7 @@ mod test_restore_command;
 mod test_revert_command;
 mod test_revset_output;
 mod test_root;
+mod test_run_command;
 mod test_shell_completion;
 mod test_show_command;
 mod test_sign_unsign_commands;
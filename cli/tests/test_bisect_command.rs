@@ -0,0 +1,270 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::create_commit;
+use crate::common::TestEnvironment;
+use crate::common::TestWorkDir;
+
+fn create_linear_history(work_dir: &TestWorkDir) {
+    create_commit(work_dir, "a", &[]);
+    create_commit(work_dir, "b", &["a"]);
+    create_commit(work_dir, "c", &["b"]);
+    create_commit(work_dir, "d", &["c"]);
+    create_commit(work_dir, "e", &["d"]);
+    create_commit(work_dir, "f", &["e"]);
+    work_dir.run_jj(["new"]).success();
+}
+
+#[test]
+fn test_bisect_not_started() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let output = work_dir.run_jj(["bisect", "good"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No bisection in progress
+    Hint: Use `jj bisect start` to start one.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["bisect", "reset"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No bisection in progress
+    Hint: Use `jj bisect start` to start one.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_bisect_manual() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    create_linear_history(&work_dir);
+
+    let output = work_dir.run_jj(["bisect", "start", "--good", "a"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Started bisection.
+    Mark a bad revision with `jj bisect bad` to continue.
+    [EOF]
+    ");
+    // Nothing to mark yet
+    let output = work_dir.run_jj(["bisect", "good"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No revision is being evaluated
+    Hint: Specify the revisions to mark.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["bisect", "bad", "f"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Bisecting: 4 revisions left to test (roughly 3 steps)
+    Now evaluating: royxmykx 7e4fbf4f c | c
+    Working copy  (@) now at: uyznsvlq aa6776d7 (empty) (no description set)
+    Parent commit (@-)      : royxmykx 7e4fbf4f c | c
+    Added 0 files, modified 0 files, removed 3 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["bisect", "good"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Bisecting: 2 revisions left to test (roughly 2 steps)
+    Now evaluating: vruxwmqv 71bd904f d | d
+    Working copy  (@) now at: xznxytkn 125aee08 (empty) (no description set)
+    Parent commit (@-)      : vruxwmqv 71bd904f d | d
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["bisect", "bad"]);
+    insta::assert_snapshot!(output, @r"
+    The first bad revision is:
+    vruxwmqv 71bd904f d | d
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["bisect", "good"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No revision is being evaluated
+    Hint: Specify the revisions to mark.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // The working copy goes back to where it was before the bisection.
+    let output = work_dir.run_jj(["bisect", "reset"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Stopped bisection.
+    Working copy  (@) now at: tlkvzzqu 30da0451 (empty) (no description set)
+    Parent commit (@-)      : kmkuslsw 2ec20500 f | f
+    Added 2 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-r", "@-", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    ○  f
+    │
+    ~
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_bisect_inconsistent() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    create_linear_history(&work_dir);
+
+    let output = work_dir.run_jj(["bisect", "start", "--good", "d", "--bad", "b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Started bisection.
+    Error: Revision 71bd904f7874 is marked good but is a descendant of bad revision 1394f625cbbd
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_bisect_per_workspace() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let secondary_dir = test_env.work_dir("secondary");
+    create_linear_history(&work_dir);
+    work_dir
+        .run_jj(["workspace", "add", "--name", "second", "../secondary"])
+        .success();
+
+    work_dir
+        .run_jj(["bisect", "start", "--good", "a", "--bad", "f"])
+        .success();
+    // The bisection in the default workspace isn't visible from the secondary
+    // workspace
+    let output = secondary_dir.run_jj(["bisect", "good"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No bisection in progress
+    Hint: Use `jj bisect start` to start one.
+    [EOF]
+    [exit status: 1]
+    ");
+    secondary_dir
+        .run_jj(["bisect", "start", "--good", "c", "--bad", "e"])
+        .success();
+
+    // Resetting the secondary workspace keeps the bisection in the default
+    // workspace
+    secondary_dir.run_jj(["bisect", "reset"]).success();
+    let output = work_dir.run_jj(["bisect", "bad"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Bisecting: 1 revisions left to test (roughly 1 steps)
+    Now evaluating: zsuskuln 1394f625 b | b
+    Working copy  (@) now at: nmzmmopx 25e8a56c (empty) (no description set)
+    Parent commit (@-)      : zsuskuln 1394f625 b | b
+    Added 0 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_bisect_run() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    create_linear_history(&work_dir);
+
+    let output = work_dir.run_jj(["bisect", "run", "true"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No bisection in progress
+    Hint: Use `jj bisect start` to start one.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    work_dir
+        .run_jj(["bisect", "start", "--good", "root()", "--bad", "f"])
+        .success();
+    // The problem was introduced by the commit that added file "d", and "c"
+    // can't be evaluated.
+    let output = work_dir.run_jj([
+        "bisect",
+        "run",
+        "if test -f d; then exit 1; elif test -f c; then exit 125; fi",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    Some revisions were skipped. The first bad revision is one of:
+    vruxwmqv 71bd904f d | d
+    royxmykx 7e4fbf4f c | c
+    [EOF]
+    ------- stderr -------
+    The revision is skipped.
+    Bisecting: 4 revisions left to test (roughly 3 steps)
+    Now evaluating: zsuskuln 1394f625 b | b
+    Working copy  (@) now at: uyznsvlq 6640ed34 (empty) (no description set)
+    Parent commit (@-)      : zsuskuln 1394f625 b | b
+    Added 0 files, modified 0 files, removed 1 files
+    The revision is good.
+    Bisecting: 2 revisions left to test (roughly 2 steps)
+    Now evaluating: vruxwmqv 71bd904f d | d
+    Working copy  (@) now at: uuqyqztp 36c29f46 (empty) (no description set)
+    Parent commit (@-)      : vruxwmqv 71bd904f d | d
+    Added 2 files, modified 0 files, removed 0 files
+    The revision is bad.
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_bisect_run_aborted() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    create_linear_history(&work_dir);
+
+    work_dir
+        .run_jj(["bisect", "start", "--good", "a", "--bad", "f"])
+        .success();
+    let output = work_dir.run_jj(["bisect", "run", "exit 128"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Command exited with 128, stopping the bisection
+    [EOF]
+    [exit status: 1]
+    ");
+    // The bisection can be resumed
+    let output = work_dir.run_jj(["bisect", "bad"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Bisecting: 1 revisions left to test (roughly 1 steps)
+    Now evaluating: zsuskuln 1394f625 b | b
+    Working copy  (@) now at: uyznsvlq 6640ed34 (empty) (no description set)
+    Parent commit (@-)      : zsuskuln 1394f625 b | b
+    Added 0 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+}
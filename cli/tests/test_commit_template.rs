@@ -1675,3 +1675,140 @@ fn test_log_format_trailers() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_log_trailers() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir
+        .run_jj([
+            "describe",
+            "-m",
+            "subject\n\nbody\n\nFixes: #123\nReviewed-by: Alice <alice@example.com>",
+        ])
+        .success();
+    work_dir.run_jj(["new", "-m", "no trailers"]).success();
+
+    let template = r#"
+    if(trailers, trailers ++ "\n", "(none)\n")
+    ++ trailers.map(|t| "[" ++ t.key() ++ "=" ++ t.value() ++ "]").join(",") ++ "\n"
+    "#;
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@|@-", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    (none)
+
+    Fixes: #123
+    Reviewed-by: Alice <alice@example.com>
+    [Fixes=#123],[Reviewed-by=Alice <alice@example.com>]
+    [EOF]
+    ");
+
+    // Filter commits by trailer
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "-r",
+        "trailer(Fixes, '#123')",
+        "-Tdescription.first_line() ++ '\n'",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    subject
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_log_json() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "foo\n");
+    work_dir
+        .run_jj(["describe", "-m", "first\n\nFixes: #1"])
+        .success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "foo"])
+        .success();
+    work_dir.run_jj(["new", "-m", "second"]).success();
+    work_dir.write_file("file2", "bar\n");
+    work_dir
+        .run_jj(["file", "move", "file1", "file3"])
+        .success();
+
+    let render = |template| work_dir.run_jj(["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(render(r#"json(self) ++ "\n""#), @r#"
+    {"author":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:10+07:00"},"change_id":"zsuskulnrvyrovkzqrwmxqlsskqntxvp","commit_id":"7c01aca2bcd07e8bd62f1efb41613b9bea3f5d24","committer":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:11+07:00"},"description":"second\n","parents":["1b46779039e06bc24338c776d36e00b33f64115b"]}
+    {"author":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08+07:00"},"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","commit_id":"1b46779039e06bc24338c776d36e00b33f64115b","committer":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08+07:00"},"description":"first\n\nFixes: #1\n","parents":["0000000000000000000000000000000000000000"]}
+    {"author":{"email":"","name":"","timestamp":"1970-01-01T00:00:00+00:00"},"change_id":"zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz","commit_id":"0000000000000000000000000000000000000000","committer":{"email":"","name":"","timestamp":"1970-01-01T00:00:00+00:00"},"description":"","parents":[]}
+    [EOF]
+    "#);
+    insta::assert_snapshot!(
+        render(r#"json(commit_id) ++ " " ++ json(bookmarks) ++ " " ++ json(trailers) ++ "\n""#),
+        @r##"
+    "7c01aca2bcd07e8bd62f1efb41613b9bea3f5d24" [] []
+    "1b46779039e06bc24338c776d36e00b33f64115b" [{"name":"foo","remote":null,"target":["1b46779039e06bc24338c776d36e00b33f64115b"],"tracking_target":null}] [{"key":"Fixes","value":"#1"}]
+    "0000000000000000000000000000000000000000" [] []
+    [EOF]
+    "##);
+    insta::assert_snapshot!(render(r#"json(diff.files()) ++ "\n""#), @r#"
+    [{"path":"file2","source":{"conflict":false,"executable":false,"file_type":"","path":"file2"},"status":"added","target":{"conflict":false,"executable":false,"file_type":"file","path":"file2"}},{"path":"file3","source":{"conflict":false,"executable":false,"file_type":"file","path":"file1"},"status":"renamed","target":{"conflict":false,"executable":false,"file_type":"file","path":"file3"}}]
+    [{"path":"file1","source":{"conflict":false,"executable":false,"file_type":"","path":"file1"},"status":"added","target":{"conflict":false,"executable":false,"file_type":"file","path":"file1"}}]
+    []
+    [EOF]
+    "#);
+    insta::assert_snapshot!(render(r#"json(parents.filter(|c| !c.root())) ++ "\n""#), @r#"
+    [{"author":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08+07:00"},"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","commit_id":"1b46779039e06bc24338c776d36e00b33f64115b","committer":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08+07:00"},"description":"first\n\nFixes: #1\n","parents":["0000000000000000000000000000000000000000"]}]
+    []
+    []
+    [EOF]
+    "#);
+
+    // Conflicted bookmark
+    work_dir
+        .run_jj(["new", "--no-edit", "root()", "-m", "third"])
+        .success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@-", "bar"])
+        .success();
+    work_dir
+        .run_jj([
+            "bookmark",
+            "set",
+            "--at-op=@-",
+            "-rdescription(third)",
+            "bar",
+        ])
+        .success();
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "-rbookmarks(bar)",
+        "-T",
+        r#"json(bookmarks.filter(|b| b.name() == "bar")) ++ "\n""#,
+    ]);
+    insta::assert_snapshot!(output, @r#"
+    [{"name":"bar","remote":null,"target":["1b46779039e06bc24338c776d36e00b33f64115b",null,"8ae6ebde5529c19afad431ed5c8c7c50aa83f182"],"tracking_target":null}]
+    [{"name":"bar","remote":null,"target":["1b46779039e06bc24338c776d36e00b33f64115b",null,"8ae6ebde5529c19afad431ed5c8c7c50aa83f182"],"tracking_target":null}]
+    [EOF]
+    ------- stderr -------
+    Concurrent modification detected, resolving automatically.
+    [EOF]
+    "#);
+
+    // Unsupported type
+    insta::assert_snapshot!(render("json(diff)"), @r"
+    ------- stderr -------
+    Error: Failed to parse template: Expected expression of type `Serialize`, but actual type is `TreeDiff`
+    Caused by:  --> 1:6
+      |
+    1 | json(diff)
+      |      ^--^
+      |
+      = Expected expression of type `Serialize`, but actual type is `TreeDiff`
+    [EOF]
+    [exit status: 1]
+    ");
+}
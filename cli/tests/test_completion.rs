@@ -682,7 +682,9 @@ fn test_config() {
 
     let output = test_env.run_jj_in(dir, ["--", "jj", "config", "get", "c"]);
     insta::assert_snapshot!(output, @r"
-    core.fsmonitor	Whether to use an external filesystem monitor, useful for large repos
+    copy-detection.max-candidates	Maximum number of added and removed files to compare by content. If there are more, only identical files are detected as copies.
+    copy-detection.similarity-threshold	Minimum similarity of file contents, greater than 0.0 and at most 1.0, for a file to be considered copied.
+    core.fsmonitor	Whether to use a filesystem monitor, useful for large repos
     core.watchman.register-snapshot-trigger	Whether to use triggers to monitor for changes in the background.
     [EOF]
     ");
@@ -690,8 +692,11 @@ fn test_config() {
     let output = test_env.run_jj_in(dir, ["--", "jj", "config", "list", "c"]);
     insta::assert_snapshot!(output, @r"
     colors	Mapping from jj formatter labels to colors
+    copy-detection	Settings for detecting copies and renames by comparing file contents. This is used by backends which don't track copies themselves.
+    copy-detection.max-candidates	Maximum number of added and removed files to compare by content. If there are more, only identical files are detected as copies.
+    copy-detection.similarity-threshold	Minimum similarity of file contents, greater than 0.0 and at most 1.0, for a file to be considered copied.
     core
-    core.fsmonitor	Whether to use an external filesystem monitor, useful for large repos
+    core.fsmonitor	Whether to use a filesystem monitor, useful for large repos
     core.watchman
     core.watchman.register-snapshot-trigger	Whether to use triggers to monitor for changes in the background.
     [EOF]
@@ -699,7 +704,9 @@ fn test_config() {
 
     let output = test_env.run_jj_in(dir, ["--", "jj", "log", "--config", "c"]);
     insta::assert_snapshot!(output, @r"
-    core.fsmonitor=	Whether to use an external filesystem monitor, useful for large repos
+    copy-detection.max-candidates=	Maximum number of added and removed files to compare by content. If there are more, only identical files are detected as copies.
+    copy-detection.similarity-threshold=	Minimum similarity of file contents, greater than 0.0 and at most 1.0, for a file to be considered copied.
+    core.fsmonitor=	Whether to use a filesystem monitor, useful for large repos
     core.watchman.register-snapshot-trigger=	Whether to use triggers to monitor for changes in the background.
     [EOF]
     ");
@@ -239,6 +239,59 @@ fn test_diff_basic() {
     insta::assert_snapshot!(output, @"");
 }
 
+#[test]
+fn test_diff_copies_simple_backend() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["debug", "init-simple", "repo"])
+        .success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "1\n2\n3\n4\n");
+    work_dir.write_file("file2", "foo\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.remove_file("file1");
+    work_dir.write_file("file3", "1\n2\n3\n5\n");
+    work_dir.write_file("file2", "bar\n");
+    work_dir.write_file("file4", "foo\n");
+
+    // Copies are detected by comparing file contents
+    let output = work_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    M file2
+    R {file1 => file3}
+    C {file2 => file4}
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj([
+        "diff",
+        "--summary",
+        "--config=copy-detection.similarity-threshold=1.0",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    D file1
+    M file2
+    A file3
+    C {file2 => file4}
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj([
+        "diff",
+        "--summary",
+        "--config=copy-detection.similarity-threshold=1.5",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Config error: Invalid type or value for copy-detection.similarity-threshold
+    Caused by: Expected a number in (0, 1], but is 1.5
+    For help, see https://jj-vcs.github.io/jj/latest/config/ or use `jj help -k config`.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_diff_empty() {
     let test_env = TestEnvironment::default();
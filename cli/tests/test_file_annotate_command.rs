@@ -50,6 +50,135 @@ fn test_annotate_linear() {
     ");
 }
 
+#[test]
+fn test_annotate_rename() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file.txt", "line1\nline2\n");
+    work_dir.run_jj(["describe", "-m=initial"]).success();
+
+    // Rename the file with heavy edits, which the Git backend doesn't detect
+    work_dir.run_jj(["new", "-m=rename"]).success();
+    work_dir
+        .run_jj(["file", "move", "file.txt", "renamed.txt"])
+        .success();
+    work_dir.write_file("renamed.txt", "line1\nnew line2\nline3\nline4\n");
+
+    work_dir.run_jj(["new", "-m=next"]).success();
+    append_to_file(
+        &work_dir.root().join("renamed.txt"),
+        "new text from new commit",
+    );
+
+    let output = work_dir.run_jj(["file", "annotate", "renamed.txt"]);
+    insta::assert_snapshot!(output, @r"
+    qpvuntsm test.use 2001-02-03 08:05:08    1 (file.txt): line1
+    kkmpptxz test.use 2001-02-03 08:05:11    2: new line2
+    kkmpptxz test.use 2001-02-03 08:05:11    3: line3
+    kkmpptxz test.use 2001-02-03 08:05:11    4: line4
+    mzvwutvl test.use 2001-02-03 08:05:12    5: new text from new commit
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_annotate_ignore_rev() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file.txt", "line1\nline2\n");
+    work_dir.run_jj(["describe", "-m=initial"]).success();
+    work_dir.run_jj(["new", "-m=reformat"]).success();
+    work_dir.write_file("file.txt", "line 1\nline 2\n");
+    work_dir.run_jj(["new", "-m=next"]).success();
+    append_to_file(&work_dir.root().join("file.txt"), "line 3");
+
+    let output = work_dir.run_jj(["file", "annotate", "file.txt"]);
+    insta::assert_snapshot!(output, @r"
+    kkmpptxz test.use 2001-02-03 08:05:10    1: line 1
+    kkmpptxz test.use 2001-02-03 08:05:10    2: line 2
+    zsuskuln test.use 2001-02-03 08:05:11    3: line 3
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "file",
+        "annotate",
+        "file.txt",
+        "--ignore-rev=description(reformat)",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    qpvuntsm test.use 2001-02-03 08:05:08    1: line 1
+    qpvuntsm test.use 2001-02-03 08:05:08    2: line 2
+    zsuskuln test.use 2001-02-03 08:05:11    3: line 3
+    [EOF]
+    ");
+
+    // Revisions can be ignored by config
+    test_env.add_config(r#"revsets.annotate-ignore = "description(reformat)""#);
+    let output = work_dir.run_jj(["file", "annotate", "file.txt"]);
+    insta::assert_snapshot!(output, @r"
+    qpvuntsm test.use 2001-02-03 08:05:08    1: line 1
+    qpvuntsm test.use 2001-02-03 08:05:08    2: line 2
+    zsuskuln test.use 2001-02-03 08:05:11    3: line 3
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_annotate_line_range() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file.txt", "line1\nline2\n");
+    work_dir.run_jj(["describe", "-m=initial"]).success();
+    work_dir.run_jj(["new", "-m=next"]).success();
+    append_to_file(&work_dir.root().join("file.txt"), "line3");
+    append_to_file(&work_dir.root().join("file.txt"), "line4");
+
+    let output = work_dir.run_jj(["file", "annotate", "file.txt", "-L", "2,3"]);
+    insta::assert_snapshot!(output, @r"
+    qpvuntsm test.use 2001-02-03 08:05:08    2: line2
+    kkmpptxz test.use 2001-02-03 08:05:10    3: line3
+    [EOF]
+    ");
+    // The end of the range is clamped to the end of the file
+    let output = work_dir.run_jj(["file", "annotate", "file.txt", "-L", "4,10"]);
+    insta::assert_snapshot!(output, @r"
+    kkmpptxz test.use 2001-02-03 08:05:10    4: line4
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["file", "annotate", "file.txt", "-L", "5,10"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Line range starts after the end of the file: file.txt has 4 lines
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["file", "annotate", "file.txt", "-L", "3,2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: invalid value '3,2' for '--line-range <START,END>': start line 3 is after end line 2
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+    let output = work_dir.run_jj(["file", "annotate", "file.txt", "-L", "0,2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: invalid value '0,2' for '--line-range <START,END>': invalid line number: 0
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_annotate_merge() {
     let test_env = TestEnvironment::default();
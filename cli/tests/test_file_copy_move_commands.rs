@@ -0,0 +1,167 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_file_copy() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\nb\nc\n");
+    work_dir.create_dir("dir");
+    work_dir.write_file("dir/file2", "d\n");
+    work_dir.run_jj(["new"]).success();
+
+    let output = work_dir.run_jj(["file", "copy", "file1", "file3"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Working copy  (@) now at: rlvkpnrz 9ec43f00 (no description set)
+    Parent commit (@-)      : qpvuntsm f120ee4d (no description set)
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "copy", "dir", "dir2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Working copy  (@) now at: rlvkpnrz fa02e329 (no description set)
+    Parent commit (@-)      : qpvuntsm f120ee4d (no description set)
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    // The copied files are added to the working copy
+    assert_eq!(work_dir.read_file("file3"), "a\nb\nc\n");
+    assert_eq!(work_dir.read_file("dir2/file2"), "d\n");
+
+    // The copies are reported even after rewriting the files
+    work_dir.write_file("file3", "rewritten\n");
+    let output = work_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    C {dir => dir2}/file2
+    C {file1 => file3}
+    [EOF]
+    ");
+
+    // Errors
+    let output = work_dir.run_jj(["file", "copy", "file1", "file3"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Destination path 'file3' already exists.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["file", "copy", "file1", "file3/file4"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Destination path 'file3/file4' is inside the file 'file3'.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["file", "copy", "nonexistent", "file4"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such path 'nonexistent'.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["file", "copy", "dir", "dir/sub"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot copy 'dir' into itself.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["file", "copy", "-r", "root()", "file1", "file4"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The root commit 000000000000 is immutable
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_file_move() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\nb\nc\n");
+    work_dir.run_jj(["describe", "-m", "base"]).success();
+    work_dir.run_jj(["new", "-m", "rename"]).success();
+
+    let output = work_dir.run_jj(["file", "move", "file1", "file2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Working copy  (@) now at: kkmpptxz 58a76f84 rename
+    Parent commit (@-)      : qpvuntsm 27f2bb6f base
+    Added 1 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    assert!(!work_dir.root().join("file1").exists());
+    work_dir.write_file("file2", "rewritten\n");
+    // Moving the file again keeps the original source
+    work_dir
+        .run_jj(["file", "move", "file2", "file3"])
+        .success();
+    let output = work_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    R {file1 => file3}
+    [EOF]
+    ");
+    // Moving the file back removes the record
+    work_dir
+        .run_jj(["file", "move", "file3", "file1"])
+        .success();
+    let output = work_dir.run_jj(["diff", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    M file1
+    [EOF]
+    ");
+    work_dir.write_file("file1", "a\nb\nc\n");
+    work_dir
+        .run_jj(["file", "move", "file1", "file2"])
+        .success();
+
+    // Changes to the old path follow the rename when rebasing
+    work_dir
+        .run_jj(["new", "description(base)", "-m", "edit"])
+        .success();
+    work_dir.write_file("file1", "a\nB\nc\n");
+    let output = work_dir.run_jj(["rebase", "-r", "description(rename)", "-d", "@"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 commits onto destination
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", "description", "--summary"]);
+    insta::assert_snapshot!(output, @r"
+    ○  rename
+    │  R {file1 => file2}
+    @  edit
+    │  M file1
+    ○  base
+    │  A file1
+    ◆
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r", "description(rename)", "file2"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    B
+    c
+    [EOF]
+    ");
+}
@@ -0,0 +1,128 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_file_search() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "foo\nbar\nfoobar\n");
+    work_dir.create_dir("dir");
+    work_dir.write_file("dir/file2", "baz\nfood\n");
+    work_dir.write_file("binary", "foo\0");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file1", "bar\n");
+
+    // Substring matching by default
+    let output = work_dir.run_jj(["file", "search", "-p", "foo", "-r", "@-"]);
+    insta::assert_snapshot!(output, @r"
+    dir/file2:2:food
+    file1:1:foo
+    file1:3:foobar
+    [EOF]
+    ");
+
+    // Limited by fileset
+    let output = work_dir.run_jj(["file", "search", "-p", "foo", "-r", "@-", "dir"]);
+    insta::assert_snapshot!(output, @r"
+    dir/file2:2:food
+    [EOF]
+    ");
+
+    // Other pattern kinds
+    let output = work_dir.run_jj(["file", "search", "-p", "glob:ba?", "-r", "@-"]);
+    insta::assert_snapshot!(output, @r"
+    dir/file2:1:baz
+    file1:2:bar
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "search", "-p", "regex:^fo+d?$", "-r", "@-"]);
+    insta::assert_snapshot!(output, @r"
+    dir/file2:2:food
+    file1:1:foo
+    [EOF]
+    ");
+
+    // Multiple revisions are prefixed with the change ID
+    let output = work_dir.run_jj(["file", "search", "-p", "bar", "-r", "@|@-", "file1"]);
+    insta::assert_snapshot!(output, @r"
+    rlvkpnrzqnoo:file1:1:bar
+    qpvuntsmwlqt:file1:2:bar
+    qpvuntsmwlqt:file1:3:foobar
+    [EOF]
+    ");
+
+    // Unmatched paths should generate warnings
+    let output = work_dir.run_jj(["file", "search", "-p", "bar", "file1", "nonexistent"]);
+    insta::assert_snapshot!(output, @r"
+    file1:1:bar
+    [EOF]
+    ------- stderr -------
+    Warning: No matching entries for paths: nonexistent
+    [EOF]
+    ");
+
+    // Unknown prefixes are part of the substring to search for
+    work_dir.write_file("notes", "TODO: fix\nuse std::io;\nstd::io\n");
+    let output = work_dir.run_jj(["file", "search", "-p", "TODO: fix"]);
+    insta::assert_snapshot!(output, @r"
+    notes:1:TODO: fix
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "search", "-p", "std::io"]);
+    insta::assert_snapshot!(output, @r"
+    notes:2:use std::io;
+    notes:3:std::io
+    [EOF]
+    ");
+
+    // Exact patterns match the whole line
+    let output = work_dir.run_jj(["file", "search", "-p", "exact:std::io"]);
+    insta::assert_snapshot!(output, @r"
+    notes:3:std::io
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_file_search_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file", "line\nbase\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file", "line\nleft\n");
+    work_dir.run_jj(["new", "@-"]).success();
+    work_dir.write_file("file", "line\nright\n");
+    work_dir.run_jj(["new", "all:@-+"]).success();
+
+    // Each side of the conflict is searched
+    let output = work_dir.run_jj(["file", "search", "-p", "regex:."]);
+    insta::assert_snapshot!(output, @r"
+    file (side #1):1:line
+    file (side #1):2:right
+    file (side #2):1:line
+    file (side #2):2:left
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "search", "-p", "right"]);
+    insta::assert_snapshot!(output, @r"
+    file (side #1):2:right
+    [EOF]
+    ");
+}
@@ -1054,6 +1054,196 @@ fn test_git_push_changes(subprocess: bool) {
     }
 }
 
+#[cfg_attr(feature = "git2", test_case(false; "use git2 for remote calls"))]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_git_push_stack(subprocess: bool) {
+    let test_env = TestEnvironment::default().with_git_subprocess(subprocess);
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+    work_dir.run_jj(["new", "root()", "-m", "a"]).success();
+    work_dir.write_file("file", "a");
+    work_dir.run_jj(["new", "-m", "b"]).success();
+    work_dir.write_file("file", "b");
+    work_dir.run_jj(["new", "-m", "c"]).success();
+    work_dir.write_file("file", "c");
+
+    // Creates a bookmark for each commit in the stack
+    let output = work_dir.run_jj(["git", "push", "--stack", "@"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Creating bookmark push-vruxwmqvtpmx for revision vruxwmqvtpmx
+    Creating bookmark push-yostqsxwqrlt for revision yostqsxwqrlt
+    Creating bookmark push-znkkpsqqskkl for revision znkkpsqqskkl
+    Changes to push to origin:
+      Add bookmark push-vruxwmqvtpmx to 468a250031fa
+      Add bookmark push-yostqsxwqrlt to 555668e59ee3
+      Add bookmark push-znkkpsqqskkl to d571a54486e0
+    [EOF]
+    ");
+    }
+
+    // Pushing again doesn't change anything
+    let output = work_dir.run_jj(["git", "push", "--stack", "@"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Bookmark push-vruxwmqvtpmx@origin already matches push-vruxwmqvtpmx
+    Bookmark push-yostqsxwqrlt@origin already matches push-yostqsxwqrlt
+    Bookmark push-znkkpsqqskkl@origin already matches push-znkkpsqqskkl
+    Nothing changed.
+    [EOF]
+    ");
+    }
+
+    // Bookmarks which were moved away are moved back
+    work_dir.run_jj(["describe", "-m", "b2", "@-"]).success();
+    work_dir
+        .run_jj([
+            "bookmark",
+            "move",
+            "--allow-backwards",
+            "--to=@--",
+            "glob:push-*",
+        ])
+        .success();
+    let output = work_dir.run_jj(["git", "push", "--stack", "@"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Moving bookmark push-yostqsxwqrlt to revision yostqsxwqrlt
+    Moving bookmark push-znkkpsqqskkl to revision znkkpsqqskkl
+    Bookmark push-vruxwmqvtpmx@origin already matches push-vruxwmqvtpmx
+    Changes to push to origin:
+      Move sideways bookmark push-yostqsxwqrlt from 555668e59ee3 to 3dc9242f39b6
+      Move sideways bookmark push-znkkpsqqskkl from d571a54486e0 to 78d27b55fde4
+    [EOF]
+    ");
+    }
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(get_bookmark_output(&work_dir), @r"
+    bookmark1: xtvrqkyv d13ecdbd (empty) description 1
+      @origin: xtvrqkyv d13ecdbd (empty) description 1
+    bookmark2: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    push-vruxwmqvtpmx: vruxwmqv 468a2500 a
+      @origin: vruxwmqv 468a2500 a
+    push-yostqsxwqrlt: yostqsxw 3dc9242f b2
+      @origin: yostqsxw 3dc9242f b2
+    push-znkkpsqqskkl: znkkpsqq 78d27b55 c
+      @origin: znkkpsqq 78d27b55 c
+    [EOF]
+    ");
+    }
+
+    // Immutable commits are not part of the stack
+    let output = work_dir.run_jj(["git", "push", "--stack", "root()"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No mutable revisions in the stack: root()
+    [EOF]
+    [exit status: 1]
+    ");
+    }
+}
+
+#[cfg_attr(feature = "git2", test_case(false; "use git2 for remote calls"))]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_git_push_changes_with_template(subprocess: bool) {
+    let test_env = TestEnvironment::default().with_git_subprocess(subprocess);
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+    work_dir.run_jj(["new", "root()", "-m", "a"]).success();
+    work_dir.write_file("file", "a");
+    work_dir.run_jj(["new", "-m", "b"]).success();
+    work_dir.write_file("file", "b");
+
+    // The template generates the name after the prefix
+    let output = work_dir.run_jj([
+        "git",
+        "push",
+        "--stack=@",
+        "--config=git.push-bookmark-prefix=test/",
+        r#"--config=templates.git_push_bookmark='"x-" ++ change_id.shortest()'"#,
+    ]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Creating bookmark test/x-v for revision vruxwmqvtpmx
+    Creating bookmark test/x-y for revision yostqsxwqrlt
+    Changes to push to origin:
+      Add bookmark test/x-v to 468a250031fa
+      Add bookmark test/x-y to 555668e59ee3
+    [EOF]
+    ");
+    }
+
+    // The generated names must be unique
+    let output = work_dir.run_jj([
+        "git",
+        "push",
+        "--stack=@",
+        r#"--config=templates.git_push_bookmark='"same"'"#,
+    ]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Generated bookmark name push-same is the same for revisions 468a250031fa and 555668e59ee3
+    Hint: Use `jj git push --named` to give them distinct names, or resolve divergent changes first
+    [EOF]
+    [exit status: 1]
+    ");
+    }
+
+    // Divergent changes generate the same name by default
+    work_dir.run_jj(["describe", "-m", "b2"]).success();
+    work_dir
+        .run_jj(["describe", "-m", "b3", "--at-op=@-"])
+        .success();
+    let output = work_dir.run_jj(["git", "push", "--change=all:description(b)"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Concurrent modification detected, resolving automatically.
+    Error: Generated bookmark name push-yostqsxwqrlt is the same for revisions 42ce64cd9219 and 2b530cb80c74
+    Hint: Use `jj git push --named` to give them distinct names, or resolve divergent changes first
+    [EOF]
+    [exit status: 1]
+    ");
+    }
+    let output = work_dir.run_jj(["git", "push", "--stack=visible_heads()"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Generated bookmark name push-yostqsxwqrlt is the same for revisions 2b530cb80c74 and 42ce64cd9219
+    Hint: Use `jj git push --named` to give them distinct names, or resolve divergent changes first
+    [EOF]
+    [exit status: 1]
+    ");
+    }
+
+    // The generated names must be valid Git ref names
+    let output = work_dir.run_jj([
+        "git",
+        "push",
+        "--stack=@",
+        r#"--config=templates.git_push_bookmark='"x.." ++ change_id.shortest()'"#,
+    ]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: Generated bookmark name "push-x..v" for revision 468a250031fa is not a valid Git ref name
+    Caused by:
+    1: A reference must be a valid tag name as well
+    2: A ref must not contain '..' as it may be mistaken for a range
+    Hint: Check the `templates.git_push_bookmark` and `git.push-bookmark-prefix` settings, or use `jj git push --named` to name the bookmark explicitly
+    [EOF]
+    [exit status: 1]
+    "#);
+    }
+}
+
 #[cfg_attr(feature = "git2", test_case(false; "use git2 for remote calls"))]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_push_changes_with_name(subprocess: bool) {
@@ -2566,6 +2756,353 @@ fn test_git_push_rejected_by_remote() {
     });
 }
 
+#[test]
+fn test_git_push_options() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+    let origin_git_dir = git_repo_dir_for_jj_repo(&test_env.work_dir("origin"));
+
+    // Print the received push options from a hook on the remote
+    let hook_path = origin_git_dir.join("hooks").join("pre-receive");
+    std::fs::write(
+        &hook_path,
+        "#!/bin/sh\necho \"options: $GIT_PUSH_OPTION_0 $GIT_PUSH_OPTION_1\"",
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o700)).unwrap();
+    }
+
+    work_dir.run_jj(["new", "bookmark1", "-m=update"]).success();
+    work_dir.run_jj(["bookmark", "move", "bookmark1"]).success();
+
+    // The remote doesn't accept push options by default
+    let output = work_dir.run_jj(["git", "push", "-o", "foo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to 186e7f3f251b
+    Error: Git process failed: External git program failed:
+    fatal: the receiving end does not support push options
+    fatal: the remote end hung up unexpectedly
+
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let mut config = std::fs::OpenOptions::new()
+        .append(true)
+        .open(origin_git_dir.join("config"))
+        .unwrap();
+    std::io::Write::write_all(&mut config, b"[receive]\n\tadvertisePushOptions = true\n").unwrap();
+    let output = work_dir.run_jj(["git", "push", "-o", "foo", "--option=bar=baz"]);
+    let mut settings = insta::Settings::clone_current();
+    settings.add_filter(r"\s*\n", "\n");
+    settings.bind(|| {
+        insta::assert_snapshot!(output, @r"
+        ------- stderr -------
+        Changes to push to origin:
+          Move forward bookmark bookmark1 from d13ecdbda2a2 to 186e7f3f251b
+        remote: options: foo bar=baz
+        [EOF]
+        ");
+    });
+}
+
+#[cfg_attr(feature = "git2", test_case(false; "use git2 for remote calls"))]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_git_push_atomic(subprocess: bool) {
+    let test_env = TestEnvironment::default().with_git_subprocess(subprocess);
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+
+    // Move bookmark1 forward on the remote
+    let origin_dir = test_env.work_dir("origin");
+    origin_dir
+        .run_jj(["new", "bookmark1", "-m=remote"])
+        .success();
+    origin_dir
+        .run_jj(["bookmark", "set", "bookmark1", "-r@"])
+        .success();
+    origin_dir.run_jj(["git", "export"]).success();
+
+    // Move both bookmarks locally. Only bookmark1 can't be pushed.
+    work_dir.run_jj(["new", "root()", "-m=local"]).success();
+    work_dir
+        .run_jj(["bookmark", "set", "bookmark1", "--allow-backwards", "-r@"])
+        .success();
+    work_dir
+        .run_jj(["new", "bookmark2", "-m=local 2"])
+        .success();
+    work_dir
+        .run_jj(["bookmark", "set", "bookmark2", "-r@"])
+        .success();
+
+    let output = work_dir.run_jj(["git", "push", "--atomic", "-bbookmark1", "-bbookmark2"]);
+    if subprocess {
+        insta::assert_snapshot!(output, @r"
+        ------- stderr -------
+        Changes to push to origin:
+          Move sideways bookmark bookmark1 from d13ecdbda2a2 to 614d1c83c492
+          Move forward bookmark bookmark2 from 8476341eb395 to 07de0089b53d
+        Error: Failed to push some bookmarks
+        Hint: The following references unexpectedly moved on the remote:
+          refs/heads/bookmark1 (reason: stale info)
+        Hint: Try fetching from the remote, then make the bookmark point to where you want it to be, and push again.
+        Hint: The following references were not updated because the atomic push failed:
+          refs/heads/bookmark2
+        [EOF]
+        [exit status: 1]
+        ");
+    } else {
+        insta::assert_snapshot!(output, @r"
+        ------- stderr -------
+        Changes to push to origin:
+          Move sideways bookmark bookmark1 from d13ecdbda2a2 to 614d1c83c492
+          Move forward bookmark bookmark2 from 8476341eb395 to 07de0089b53d
+        Error: Atomic pushes are not supported when using libgit2 for remote calls
+        Hint: Set `git.subprocess = true` to push atomically with the `git` executable.
+        [EOF]
+        [exit status: 1]
+        ");
+    }
+    // Neither bookmark was updated on the remote
+    origin_dir.run_jj(["git", "import"]).success();
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(get_bookmark_output(&origin_dir), @r"
+    bookmark1: vruxwmqv df1e3bc8 (empty) remote
+      @git: vruxwmqv df1e3bc8 (empty) remote
+    bookmark2: zsuskuln 8476341e (empty) description 2
+      @git: zsuskuln 8476341e (empty) description 2
+    [EOF]
+    ");
+    }
+}
+
+#[cfg_attr(feature = "git2", test_case(false; "use git2 for remote calls"))]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_git_push_tag(subprocess: bool) {
+    let test_env = TestEnvironment::default().with_git_subprocess(subprocess);
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+    let origin_git_repo = git::open(git_repo_dir_for_jj_repo(&test_env.work_dir("origin")));
+
+    work_dir
+        .run_jj(["tag", "set", "-rbookmark1", "v1"])
+        .success();
+    work_dir
+        .run_jj(["tag", "set", "-rbookmark2", "-m", "Release 2", "v2"])
+        .success();
+    let output = work_dir.run_jj(["git", "push", "--tag", "glob:v*", "--dry-run"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Add tag v1 to d13ecdbda2a2
+      Add tag v2 to 8476341eb395
+    Dry-run requested, not pushing.
+    [EOF]
+    ");
+    }
+    let output = work_dir.run_jj(["git", "push", "--tag", "glob:v*"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Add tag v1 to d13ecdbda2a2
+      Add tag v2 to 8476341eb395
+    [EOF]
+    ");
+    }
+    let v1 = origin_git_repo.find_reference("refs/tags/v1").unwrap();
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(v1.target().id(), @"d13ecdbda2a2e7471a29db2bed6f08ca28f91264");
+    }
+    // The annotated tag is pushed with the tag object
+    let v2 = origin_git_repo.find_reference("refs/tags/v2").unwrap();
+    let tag = origin_git_repo.find_tag(v2.target().id()).unwrap();
+    assert_eq!(tag.decode().unwrap().message, "Release 2\n");
+
+    // Existing tags on the remote aren't overwritten
+    work_dir
+        .run_jj(["tag", "set", "-rbookmark2", "--allow-move", "v1"])
+        .success();
+    let output = work_dir.run_jj(["git", "push", "--tag", "v1"]);
+    if subprocess {
+        insta::assert_snapshot!(output, @r"
+        ------- stderr -------
+        Changes to push to origin:
+          Add tag v1 to 8476341eb395
+        Error: Failed to push some tags
+        Hint: The following tags were rejected:
+          refs/tags/v1 (reason: stale info)
+        Hint: Tags that already exist on the remote are never overwritten.
+        [EOF]
+        [exit status: 1]
+        ");
+    } else {
+        insta::assert_snapshot!(output, @r"
+        ------- stderr -------
+        Changes to push to origin:
+          Add tag v1 to 8476341eb395
+        Error: Failed to push some tags
+        Hint: The following tags were rejected:
+          refs/tags/v1
+        Hint: Tags that already exist on the remote are never overwritten.
+        [EOF]
+        [exit status: 1]
+        ");
+    }
+
+    let output = work_dir.run_jj(["git", "push", "--tag", "nonexistent"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such tag: nonexistent
+    [EOF]
+    [exit status: 1]
+    ");
+    }
+}
+
+#[test]
+fn test_git_push_tag_not_ready() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+
+    // Tagged commits are checked like the commits pushed with bookmarks
+    work_dir.run_jj(["new", "bookmark1"]).success();
+    work_dir.run_jj(["tag", "set", "-r@", "v1"]).success();
+    let output = work_dir.run_jj(["git", "push", "--tag", "v1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Won't push commit 39250061290e since it has no description
+    Hint: Rejected commit: vruxwmqv 39250061 (empty) (no description set)
+    [EOF]
+    [exit status: 1]
+    ");
+    test_env.add_config(r#"git.private-commits = "description(glob:'private*')""#);
+    work_dir.run_jj(["describe", "-m=private"]).success();
+    work_dir
+        .run_jj(["tag", "set", "-r@", "--allow-move", "v1"])
+        .success();
+    let output = work_dir.run_jj(["git", "push", "--tag", "v1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Won't push commit 02ca34b1a910 since it is private
+    Hint: Rejected commit: yostqsxw 02ca34b1 (empty) private
+    Hint: Configured git.private-commits: 'description(glob:'private*')'
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Unrelated immutable history isn't checked
+    work_dir.run_jj(["new", "root()"]).success();
+    work_dir.run_jj(["new", "-m=tagged"]).success();
+    work_dir
+        .run_jj(["bookmark", "set", "-r@-", "old"])
+        .success();
+    work_dir.run_jj(["tag", "set", "-r@", "v2"]).success();
+    test_env.add_config(r#"revset-aliases."immutable_heads()" = "bookmarks(old)""#);
+    let output = work_dir.run_jj(["git", "push", "--tag", "v2", "--dry-run"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Add tag v2 to 6f7ce58c69ec
+    Dry-run requested, not pushing.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_push_tag_rejected_records_pushed_bookmarks() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+
+    work_dir
+        .run_jj(["tag", "set", "-rbookmark1", "v1"])
+        .success();
+    work_dir.run_jj(["git", "push", "--tag", "v1"]).success();
+
+    // The bookmark is pushed and recorded even though the tag is rejected
+    work_dir
+        .run_jj(["tag", "set", "-rbookmark2", "--allow-move", "v1"])
+        .success();
+    work_dir.run_jj(["new", "bookmark1", "-m=local"]).success();
+    work_dir
+        .run_jj(["bookmark", "set", "bookmark1", "-r@"])
+        .success();
+    let output = work_dir.run_jj(["git", "push", "-bbookmark1", "--tag", "v1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to d3a03346fdfa
+      Add tag v1 to 8476341eb395
+    Error: Failed to push some tags
+    Hint: The following tags were rejected:
+      refs/tags/v1 (reason: stale info)
+    Hint: Tags that already exist on the remote are never overwritten.
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(get_bookmark_output(&work_dir), @r"
+    bookmark1: kpqxywon d3a03346 (empty) local
+      @origin: kpqxywon d3a03346 (empty) local
+    bookmark2: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_push_atomic_with_tag() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+
+    work_dir
+        .run_jj(["tag", "set", "-rbookmark1", "v1"])
+        .success();
+    work_dir.run_jj(["git", "push", "--tag", "v1"]).success();
+
+    // The bookmark isn't updated if the tag is rejected
+    work_dir
+        .run_jj(["tag", "set", "-rbookmark2", "--allow-move", "v1"])
+        .success();
+    work_dir.run_jj(["new", "bookmark1", "-m=local"]).success();
+    work_dir
+        .run_jj(["bookmark", "set", "bookmark1", "-r@"])
+        .success();
+    let output = work_dir.run_jj(["git", "push", "--atomic", "-bbookmark1", "--tag", "v1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to d3a03346fdfa
+      Add tag v1 to 8476341eb395
+    Error: Failed to push some tags
+    Hint: The following tags were rejected:
+      refs/tags/v1 (reason: stale info)
+    Hint: Tags that already exist on the remote are never overwritten.
+    [EOF]
+    [exit status: 1]
+    ");
+    let origin_dir = test_env.work_dir("origin");
+    origin_dir.run_jj(["git", "import"]).success();
+    insta::assert_snapshot!(get_bookmark_output(&origin_dir), @r"
+    bookmark1: qpvuntsm d13ecdbd (empty) description 1
+      @git: qpvuntsm d13ecdbd (empty) description 1
+    bookmark2: zsuskuln 8476341e (empty) description 2
+      @git: zsuskuln 8476341e (empty) description 2
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_bookmark_output(work_dir: &TestWorkDir) -> CommandOutput {
     // --quiet to suppress deleted bookmarks hint
@@ -0,0 +1,195 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::create_commit;
+use crate::common::CommandOutput;
+use crate::common::TestEnvironment;
+use crate::common::TestWorkDir;
+
+#[must_use]
+fn get_log(work_dir: &TestWorkDir) -> CommandOutput {
+    let template = r#"
+    separate(" ",
+      change_id.short(),
+      description.first_line(),
+      author.name(),
+      author.email(),
+      author.timestamp(),
+      committer.email(),
+    ) ++ "\n"
+    "#;
+    work_dir.run_jj(["log", "-T", template])
+}
+
+#[test]
+fn test_metaedit_author() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    create_commit(&work_dir, "c", &["b"]);
+    insta::assert_snapshot!(get_log(&work_dir), @r"
+    @  royxmykxtrkr c Test User test.user@example.com 2001-02-03 04:05:12.000 +07:00 test.user@example.com
+    ○  zsuskulnrvyr b Test User test.user@example.com 2001-02-03 04:05:10.000 +07:00 test.user@example.com
+    ○  rlvkpnrzqnoo a Test User test.user@example.com 2001-02-03 04:05:08.000 +07:00 test.user@example.com
+    ◆  zzzzzzzzzzzz 1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+
+    // Fix the author email of a whole stack
+    let output = work_dir.run_jj_with(|cmd| {
+        cmd.args(["metaedit", "--update-author", "a::b"])
+            .env("JJ_EMAIL", "fixed@example.com")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Modified 2 commits:
+      rlvkpnrz 2daac72e a | a
+      zsuskuln c11b9d58 b | b
+    Rebased 1 descendant commits
+    Working copy  (@) now at: royxmykx ed9673e5 c | c
+    Parent commit (@-)      : zsuskuln c11b9d58 b | b
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(&work_dir), @r"
+    @  royxmykxtrkr c Test User test.user@example.com 2001-02-03 04:05:12.000 +07:00 fixed@example.com
+    ○  zsuskulnrvyr b Test User fixed@example.com 2001-02-03 04:05:10.000 +07:00 fixed@example.com
+    ○  rlvkpnrzqnoo a Test User fixed@example.com 2001-02-03 04:05:08.000 +07:00 fixed@example.com
+    ◆  zzzzzzzzzzzz 1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj([
+        "metaedit",
+        "--author",
+        "Other <other@example.com>",
+        "--author-timestamp",
+        "2010-01-02T03:04:05+06:00",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Modified 1 commits:
+      royxmykx 2bb5718b c | c
+    Working copy  (@) now at: royxmykx 2bb5718b c | c
+    Parent commit (@-)      : zsuskuln c11b9d58 b | b
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(&work_dir), @r"
+    @  royxmykxtrkr c Other other@example.com 2010-01-02 03:04:05.000 +06:00 test.user@example.com
+    ○  zsuskulnrvyr b Test User fixed@example.com 2001-02-03 04:05:10.000 +07:00 fixed@example.com
+    ○  rlvkpnrzqnoo a Test User fixed@example.com 2001-02-03 04:05:08.000 +07:00 fixed@example.com
+    ◆  zzzzzzzzzzzz 1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["metaedit", "--author-timestamp", "yesterday"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: invalid value 'yesterday' for '--author-timestamp <TIMESTAMP>': input contains invalid characters
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
+#[test]
+fn test_metaedit_no_changes() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    let setup_opid = work_dir.current_operation_id();
+
+    // Setting the same author doesn't rewrite anything
+    let output = work_dir.run_jj(["metaedit", "--update-author"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    assert_eq!(work_dir.current_operation_id(), setup_opid);
+
+    // Unless forced to, in which case the committer is updated
+    let output = work_dir.run_jj_with(|cmd| {
+        cmd.args(["metaedit", "--force-rewrite"])
+            .env("JJ_EMAIL", "committer@example.com")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Modified 1 commits:
+      rlvkpnrz 92ec04e6 a | a
+    Working copy  (@) now at: rlvkpnrz 92ec04e6 a | a
+    Parent commit (@-)      : zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(&work_dir), @r"
+    @  rlvkpnrzqnoo a Test User test.user@example.com 2001-02-03 04:05:08.000 +07:00 committer@example.com
+    ◆  zzzzzzzzzzzz 1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["metaedit", "-r", "none()"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    No revisions to modify.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_metaedit_update_change_id() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+
+    let output = work_dir.run_jj(["metaedit", "--update-change-id", "a"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Modified 1 commits:
+      royxmykx d244568d a | a
+    Rebased 1 descendant commits
+    Working copy  (@) now at: zsuskuln a06dcd4a b | b
+    Parent commit (@-)      : royxmykx d244568d a | a
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(&work_dir), @r"
+    @  zsuskulnrvyr b Test User test.user@example.com 2001-02-03 04:05:10.000 +07:00 test.user@example.com
+    ○  royxmykxtrkr a Test User test.user@example.com 2001-02-03 04:05:08.000 +07:00 test.user@example.com
+    ◆  zzzzzzzzzzzz 1970-01-01 00:00:00.000 +00:00
+    [EOF]
+    ");
+    // The old change ID no longer resolves
+    let output = work_dir.run_jj(["log", "-r", "rlvkpnrz"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Revision `rlvkpnrz` doesn't exist
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["metaedit", "--update-change-id", "root()"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The root commit 000000000000 is immutable
+    [EOF]
+    [exit status: 1]
+    ");
+}
@@ -364,6 +364,12 @@ fn test_op_log_template() {
     [EOF]
     ");
 
+    insta::assert_snapshot!(render(r#"json(self) ++ "\n""#), @r#"
+    @  {"description":"add workspace 'default'","hostname":"host.example.com","id":"eac759b9ab75793fd3da96e60939fb48f2cd2b2a9c1f13ffe723cf620f3005b8d3e7e923634a07ea39513e4f2f360c87b9ad5d331cf90d7a844864b83b72eba1","is_snapshot":false,"parents":["00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"],"tags":{},"time":{"end":"2001-02-03T04:05:07+07:00","start":"2001-02-03T04:05:07+07:00"},"username":"test-username"}
+    ○  {"description":"","hostname":"","id":"00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","is_snapshot":false,"parents":[],"tags":{},"time":{"end":"1970-01-01T00:00:00+00:00","start":"1970-01-01T00:00:00+00:00"},"username":""}
+    [EOF]
+    "#);
+
     // Negative length shouldn't cause panic.
     insta::assert_snapshot!(render(r#"id.short(-1) ++ "|""#), @r"
     @  <Error: out of range integral type conversion attempted>|
@@ -871,6 +877,118 @@ fn test_op_abandon_multiple_heads() {
     ");
 }
 
+#[test]
+fn test_op_compact() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.run_jj(["commit", "-m", "commit 1"]).success();
+    work_dir.run_jj(["commit", "-m", "commit 2"]).success();
+    work_dir.run_jj(["commit", "-m", "commit 3"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["op", "log"]), @r"
+    @  b0711a8ac91f test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
+    │  commit 220cb0b1b5d1c03cc0d351139d824598bb3c1967
+    │  args: jj commit -m 'commit 3'
+    ○  116edde65ded test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
+    │  commit 81a4ef3dd421f3184289df1c58bd3a16ea1e3d8e
+    │  args: jj commit -m 'commit 2'
+    ○  bee8c02a64bf test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    │  commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
+    │  args: jj commit -m 'commit 1'
+    ○  eac759b9ab75 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    │  add workspace 'default'
+    ○  000000000000 root()
+    [EOF]
+    ");
+
+    // Compact old operations. The working-copy operation id should be updated.
+    let output = work_dir.run_jj(["op", "compact", "..@-"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Compacted 3 operations and reparented 1 descendant operations.
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["debug", "local-working-copy", "--ignore-working-copy"]), @r#"
+    Current operation: OperationId("5fb029ec0a79b1e936be8e2601876760250da92fd51f10a5cabbbb65c2a6c9cfc34c465aa48f94d49041379673f02e647854229ac36d475b5fb401b63e76dd51")
+    Current tree: Merge(Resolved(TreeId("4b825dc642cb6eb9a060e54bf8d69288fbee4904")))
+    [EOF]
+    "#);
+    insta::assert_snapshot!(work_dir.run_jj(["op", "log"]), @r"
+    @  5fb029ec0a79 test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
+    │  commit 220cb0b1b5d1c03cc0d351139d824598bb3c1967
+    │  args: jj commit -m 'commit 3'
+    ○  62354a78c2c3 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
+    │  compact 3 operations
+    ○  000000000000 root()
+    [EOF]
+    ");
+    // The compacted operation has the repo state of the last operation.
+    insta::assert_snapshot!(work_dir.run_jj(["log", "--at-op=@-"]), @r"
+    @  kkmpptxz test.user@example.com 2001-02-03 08:05:09 220cb0b1
+    │  (empty) (no description set)
+    ○  rlvkpnrz test.user@example.com 2001-02-03 08:05:09 5dd2cacb
+    │  (empty) commit 2
+    ○  qpvuntsm test.user@example.com 2001-02-03 08:05:08 2a3f04e3
+    │  (empty) commit 1
+    ◆  zzzzzzzz root() 00000000
+    [EOF]
+    ");
+
+    // Compact operation range including the current operation.
+    work_dir.run_jj(["commit", "-m", "commit 4"]).success();
+    let output = work_dir.run_jj(["op", "compact", "@--.."]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Compacted 2 operations and reparented 0 descendant operations.
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["op", "log"]), @r"
+    @  e941efca6801 test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:16.000 +07:00
+    │  compact 2 operations
+    ○  62354a78c2c3 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
+    │  compact 3 operations
+    ○  000000000000 root()
+    [EOF]
+    ");
+
+    // Single operation isn't compacted.
+    let output = work_dir.run_jj(["op", "compact", "@-..@"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Range must be specified.
+    let output = work_dir.run_jj(["op", "compact", "@"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Expected an operation range, but got @
+    Hint: Use `jj op compact <operation ID>..<operation ID>` to specify the range
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Range root must be an ancestor of the head.
+    let output = work_dir.run_jj(["op", "compact", "@..@-"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Operation e941efca6801 is not an ancestor of 62354a78c2c3
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Can't create concurrent compacted operations explicitly.
+    let output = work_dir.run_jj(["op", "compact", "--at-op=@-", "..@"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: --at-op is not respected
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_op_recover_from_bad_gc() {
     let test_env = TestEnvironment::default();
@@ -1047,7 +1165,7 @@ fn test_op_summary_diff_template() {
     ]);
     insta::assert_snapshot!(output, @r"
     From operation: [38;5;4m000000000000[39m [38;5;2mroot()[39m
-      To operation: [38;5;4me3792fce5b1f[39m ([38;5;6m2001-02-03 08:05:09[39m) undo operation ac20a4ff47914da9a2e43677b94455b86383bfb9227374d6531ecee85b9ff9230eeb96416a24bb27e7477aa18d50c01810e97c6a008b5c584224650846f4c05b
+      To operation: [38;5;4m0d4b2ac18c58[39m ([38;5;6m2001-02-03 08:05:09[39m) undo operation ac20a4ff47914da9a2e43677b94455b86383bfb9227374d6531ecee85b9ff9230eeb96416a24bb27e7477aa18d50c01810e97c6a008b5c584224650846f4c05b
 
     Changed commits:
     ○  [38;5;2m+[39m [1m[38;5;5mq[0m[38;5;8mpvuntsm[39m [1m[38;5;4m2[0m[38;5;8m30dd059[39m [38;5;2m(empty)[39m [38;5;2m(no description set)[39m
@@ -1065,7 +1183,7 @@ fn test_op_summary_diff_template() {
     let output = work_dir.run_jj(["op", "undo", "--color=debug"]);
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
-    Undid operation: [38;5;4m<<operation id short::2301f6e6ec31>>[39m<<operation:: (>>[38;5;6m<<operation time end local format::2001-02-03 08:05:11>>[39m<<operation::) >><<operation description first_line::new empty commit>>
+    Undid operation: [38;5;4m<<operation id short::c5d595117b3f>>[39m<<operation:: (>>[38;5;6m<<operation time end local format::2001-02-03 08:05:11>>[39m<<operation::) >><<operation description first_line::new empty commit>>
     [EOF]
     ");
     let output = work_dir.run_jj([
@@ -1079,7 +1197,7 @@ fn test_op_summary_diff_template() {
     ]);
     insta::assert_snapshot!(output, @r"
     From operation: [38;5;4m<<operation id short::000000000000>>[39m<<operation:: >>[38;5;2m<<operation root::root()>>[39m
-      To operation: [38;5;4m<<operation id short::d208ae1b4e3c>>[39m<<operation:: (>>[38;5;6m<<operation time end local format::2001-02-03 08:05:12>>[39m<<operation::) >><<operation description first_line::undo operation 2301f6e6ec31931a9b0a594742d6035a44c05250d1707f7f8678e888b11a98773ef07bf0e8008a5bccddf7114da4a35d1a1b1f7efa37c1e6c80d6bdb8f0d7a90>>
+      To operation: [38;5;4m<<operation id short::5a2930a8a097>>[39m<<operation:: (>>[38;5;6m<<operation time end local format::2001-02-03 08:05:12>>[39m<<operation::) >><<operation description first_line::undo operation c5d595117b3f4353e7974f36083ed82d972b672d44a9497b25d4f70e401542267a16242d9332d3655c2ae15c6892dc7dc5c7d91026cc271283732ce1cd1d474e>>
 
     Changed commits:
     ○  [38;5;2m<<diff added::+>>[39m [1m[38;5;5m<<change_id shortest prefix::q>>[0m[38;5;8m<<change_id shortest rest::pvuntsm>>[39m [1m[38;5;4m<<commit_id shortest prefix::2>>[0m[38;5;8m<<commit_id shortest rest::30dd059>>[39m [38;5;2m<<empty::(empty)>>[39m [38;5;2m<<empty description placeholder::(no description set)>>[39m
@@ -31,9 +31,9 @@ fn test_rebase_invalid() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     error: the following required arguments were not provided:
-      <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+      <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--interactive>
 
-    Usage: jj rebase <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--interactive>
 
     For more information, try '--help'.
     [EOF]
@@ -46,7 +46,7 @@ fn test_rebase_invalid() {
     ------- stderr -------
     error: the argument '--revisions <REVSETS>' cannot be used with '--source <REVSETS>'
 
-    Usage: jj rebase --revisions <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase --revisions <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--interactive>
 
     For more information, try '--help'.
     [EOF]
@@ -59,7 +59,7 @@ fn test_rebase_invalid() {
     ------- stderr -------
     error: the argument '--branch <REVSETS>' cannot be used with '--source <REVSETS>'
 
-    Usage: jj rebase --branch <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase --branch <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--interactive>
 
     For more information, try '--help'.
     [EOF]
@@ -72,7 +72,7 @@ fn test_rebase_invalid() {
     ------- stderr -------
     error: the argument '--destination <REVSETS>' cannot be used with '--insert-after <REVSETS>'
 
-    Usage: jj rebase --revisions <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase --revisions <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--interactive>
 
     For more information, try '--help'.
     [EOF]
@@ -85,7 +85,7 @@ fn test_rebase_invalid() {
     ------- stderr -------
     error: the argument '--destination <REVSETS>' cannot be used with '--insert-before <REVSETS>'
 
-    Usage: jj rebase --revisions <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase --revisions <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--interactive>
 
     For more information, try '--help'.
     [EOF]
@@ -2968,6 +2968,298 @@ fn test_rebase_skip_if_on_destination() {
     ");
 }
 
+#[test]
+fn test_rebase_interactive() {
+    let mut test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let edit_script = test_env.set_up_fake_editor();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    create_commit(&work_dir, "c", &["b"]);
+    create_commit(&work_dir, "d", &["c"]);
+    work_dir.run_jj(["new"]).success();
+    let [a, b, c, d] = ["a", "b", "c", "d"].map(|rev| get_short_commit_id(&work_dir, rev));
+
+    // The plan lists the commits oldest first. Leaving it unchanged is a no-op.
+    std::fs::write(&edit_script, "dump editor").unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::d"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor")).unwrap(), @r#"
+    pick 2443ea76b0b1 a
+    pick 1394f625cbbd b
+    pick 7e4fbf4f2759 c
+    pick 71bd904f7874 d
+
+    JJ: Rebase plan for the revisions above, oldest first.
+    JJ:
+    JJ: Commands:
+    JJ: p, pick <commit> = use commit
+    JJ: r, reword <commit> = use commit, but edit the commit message
+    JJ: s, squash <commit> = meld commit into the previous commit and combine
+    JJ:                      the commit messages
+    JJ: f, fixup <commit> = like "squash", but keep only the previous commit's
+    JJ:                     message
+    JJ: d, drop <commit> = remove commit
+    JJ:
+    JJ: Lines can be reordered. Every commit must be listed exactly once.
+    JJ: If all lines are removed, the rebase will be aborted.
+    JJ: Lines starting with "JJ:" (like this one) will be removed.
+    "#);
+
+    // Reorder and drop commits. The working-copy commit follows the new top of
+    // the stack.
+    std::fs::write(
+        &edit_script,
+        format!("write\npick {d}\np {a}\ndrop {b}\npick {c}\n"),
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::d"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rewrote 3 commits according to the rebase plan
+    Dropped 1 commits
+    Working copy  (@) now at: znkkpsqq 8a0966e6 (empty) (no description set)
+    Parent commit (@-)      : royxmykx e6c513e2 c | c
+    Added 0 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_long_log_output(&work_dir), @r"
+    @    znkkpsqq  8a0966e6:  c
+    ○  c  royxmykx  e6c513e2:  a b
+    ○  a b  rlvkpnrz  76e51918:  d
+    ○  d  vruxwmqv  df32a71d
+    ◆    zzzzzzzz  00000000
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["op", "log", "-n1", "-Tdescription"]);
+    insta::assert_snapshot!(output, @r"
+    @  rebase commit 2443ea76b0b1c531326908326aab7020abab8e6c and 3 more interactively
+    [EOF]
+    ");
+
+    // Removing all lines aborts the rebase
+    work_dir.run_jj(["undo"]).success();
+    std::fs::write(&edit_script, "write\nJJ: nothing\n").unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::d"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebase plan is empty, nothing changed.
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_long_log_output(&work_dir), @r"
+    @    znkkpsqq  129e96bb:  d
+    ○  d  vruxwmqv  71bd904f:  c
+    ○  c  royxmykx  7e4fbf4f:  b
+    ○  b  zsuskuln  1394f625:  a
+    ○  a  rlvkpnrz  2443ea76
+    ◆    zzzzzzzz  00000000
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_interactive_squash_and_reword() {
+    let mut test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let edit_script = test_env.set_up_fake_editor();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    create_commit(&work_dir, "c", &["b"]);
+    create_commit(&work_dir, "d", &["c"]);
+    create_commit(&work_dir, "e", &["d"]);
+    let [a, b, c, d, e] = ["a", "b", "c", "d", "e"].map(|rev| get_short_commit_id(&work_dir, rev));
+
+    // Squash b into a (combining the descriptions in the editor), fix up e
+    // into c (keeping c's description), and reword d.
+    std::fs::write(
+        &edit_script,
+        [
+            &format!("write\npick {a}\nsquash {b}\npick {c}\nfixup {e}\nreword {d}\n"),
+            "next invocation\n",
+            "dump editor1",
+            "write\nreworded d\n",
+            "next invocation\n",
+            "dump editor2",
+            "write\na and b\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::e"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rewrote 3 commits according to the rebase plan
+    Squashed 2 commits
+    Working copy  (@) now at: uyznsvlq 2887020b (empty) (no description set)
+    Parent commit (@-)      : royxmykx 0fc158c9 c e | c
+    Added 0 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor1")).unwrap(), @r#"
+    d
+
+    JJ: This commit contains the following changes:
+    JJ:     A d
+    JJ:
+    JJ: Lines starting with "JJ:" (like this one) will be removed.
+    "#);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor2")).unwrap(), @r#"
+    JJ: Enter a description for the combined commit.
+    JJ: Description from the destination commit:
+    a
+
+    JJ: Description from source commit:
+    b
+
+    JJ: This commit contains the following changes:
+    JJ:     A a
+    JJ:     A b
+    JJ:
+    JJ: Lines starting with "JJ:" (like this one) will be removed.
+    "#);
+    let output = work_dir.run_jj([
+        "log",
+        "-T",
+        r#"separate(" ", change_id.short(), description.first_line(), bookmarks) ++ "\n""#,
+    ]);
+    insta::assert_snapshot!(output, @r"
+    @  uyznsvlquzzm
+    │ ○  vruxwmqvtpmx reworded d d
+    ├─╯
+    ○  royxmykxtrkr c c e
+    ○  rlvkpnrzqnoo a and b a b
+    ◆  zzzzzzzzzzzz
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "list", "-r", "@"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    b
+    c
+    e
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_interactive_invalid() {
+    let mut test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let edit_script = test_env.set_up_fake_editor();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    create_commit(&work_dir, "c", &["a"]);
+    let [a, b] = ["a", "b"].map(|rev| get_short_commit_id(&work_dir, rev));
+
+    // --interactive requires -r
+    let output = work_dir.run_jj(["rebase", "-i", "-s", "a"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--interactive' cannot be used with '--source <REVSETS>'
+
+    Usage: jj rebase --revisions <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--interactive>
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+
+    // --interactive conflicts with destination arguments
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a", "-d", "root()"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--interactive' cannot be used with '--destination <REVSETS>'
+
+    Usage: jj rebase --revisions <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>|--interactive>
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+
+    // The revisions must form a linear stack
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Revisions to rebase interactively must form a linear stack
+    Hint: Commit c0cb3a0b73e7 does not have 1394f625cbbd as its only parent
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Unknown action
+    std::fs::write(&edit_script, format!("write\npick {a}\nedit {b}\n")).unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Unknown action 'edit' in rebase plan line: edit 1394f625cbbd
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Commit not in the stack
+    std::fs::write(
+        &edit_script,
+        format!("write\npick {a}\npick {b}\npick 000\n"),
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Commit '000' in rebase plan is not one of the revisions to rebase
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Commit listed twice
+    std::fs::write(
+        &edit_script,
+        format!("write\npick {a}\npick {b}\ndrop {a}\n"),
+    )
+    .unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Commit 2443ea76b0b1 is listed more than once in rebase plan
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Commit missing from the plan
+    std::fs::write(&edit_script, format!("write\npick {b}\n")).unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Commit 2443ea76b0b1 is missing from rebase plan
+    Hint: Use `drop` to remove a commit from the stack.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Nothing to squash into
+    std::fs::write(&edit_script, format!("write\ndrop {a}\nsquash {b}\n")).unwrap();
+    let output = work_dir.run_jj(["rebase", "-i", "-r", "a::b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot squash commit 1394f625cbbd because no commit is picked before it
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[must_use]
 fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
     let template = "bookmarks ++ surround(': ', '', parents.map(|c| c.bookmarks()))";
@@ -2980,3 +3272,8 @@ fn get_long_log_output(work_dir: &TestWorkDir) -> CommandOutput {
                     ++ surround(':  ', '', parents.map(|c| c.bookmarks()))";
     work_dir.run_jj(["log", "-T", template])
 }
+
+fn get_short_commit_id(work_dir: &TestWorkDir, rev: &str) -> String {
+    let output = work_dir.run_jj(["log", "--no-graph", "-r", rev, "-T", "commit_id.short()"]);
+    output.success().stdout.into_raw()
+}
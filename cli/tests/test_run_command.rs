@@ -0,0 +1,189 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::create_commit;
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_run_immutable() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let output = work_dir.run_jj(["run", "true", "-r", "root()"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The root commit 000000000000 is immutable
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_rewrites_stack() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    create_commit(&work_dir, "c", &["b"]);
+
+    // Like a formatter, uppercase the contents of every file in the tree.
+    let output = work_dir.run_jj([
+        "run",
+        "for f in *; do tr a-z A-Z < $f > $f.tmp && mv $f.tmp $f; done",
+        "-r",
+        "a|b",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changed 2 commits
+    Rebased 1 descendant commits
+    Working copy  (@) now at: royxmykx ce25bf34 c | c
+    Parent commit (@-)      : zsuskuln e93e62df b | b
+    Added 0 files, modified 2 files, removed 0 files
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["log", "-T", "description", "-s"]);
+    insta::assert_snapshot!(output, @r"
+    @  c
+    │  A c
+    ○  b
+    │  A b
+    ○  a
+    │  A a
+    ◆
+    [EOF]
+    ");
+    // The descendant keeps the changes made in its ancestors.
+    let output = work_dir.run_jj(["file", "show", "-r", "c", "glob:*"]);
+    insta::assert_snapshot!(output, @r"
+    A
+    B
+    c
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_no_changes() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+
+    let output = work_dir.run_jj(["run", "cat a", "-r", "a"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    [EOF]
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["op", "log", "-n1", "--no-graph", "-T", "description"]);
+    insta::assert_snapshot!(output, @"create bookmark a pointing to commit 2443ea76b0b1c531326908326aab7020abab8e6c[EOF]");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_command_fails() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    create_commit(&work_dir, "b", &["a"]);
+    let setup_opid = work_dir.current_operation_id();
+
+    // Nothing is rewritten if the command fails on any commit.
+    let output = work_dir.run_jj(["run", "echo changed > a; test -f b", "-r", "a|b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Command failed on commit 2443ea76b0b1 with exit code 1
+    [EOF]
+    [exit status: 1]
+    ");
+    assert_eq!(work_dir.current_operation_id(), setup_opid);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_environment() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+    let output = work_dir.run_jj(["run", "echo $JJ_CHANGE_ID $JJ_COMMIT_ID", "-r", "a"]);
+    insta::assert_snapshot!(output, @r"
+    rlvkpnrzqnoowoytxnquwvuryrwnrmlp 2443ea76b0b1c531326908326aab7020abab8e6c
+    [EOF]
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_snapshot_settings() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit(&work_dir, "a", &[]);
+
+    // New files are tracked according to the snapshot settings
+    work_dir
+        .run_jj([
+            "run",
+            "echo small > small; echo large file > large",
+            "-r",
+            "a",
+            "--config=snapshot.max-new-file-size=10",
+        ])
+        .success();
+    let output = work_dir.run_jj(["file", "list", "-r", "a"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    small
+    [EOF]
+    ");
+
+    work_dir
+        .run_jj([
+            "run",
+            "echo new > new; echo modified > a",
+            "-r",
+            "a",
+            "--config=snapshot.auto-track='none()'",
+        ])
+        .success();
+    let output = work_dir.run_jj(["file", "list", "-r", "a"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    small
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r", "a", "a"]);
+    insta::assert_snapshot!(output, @r"
+    modified
+    [EOF]
+    ");
+}
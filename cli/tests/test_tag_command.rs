@@ -125,3 +125,216 @@ fn test_tag_list() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_tag_set_delete() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let git_repo = {
+        let mut git_repo_path = work_dir.root().to_owned();
+        git_repo_path.extend([".jj", "repo", "store", "git"]);
+        git::open(git_repo_path)
+    };
+
+    work_dir.run_jj(["describe", "-mcommit1"]).success();
+    work_dir.run_jj(["new", "-mcommit2"]).success();
+    work_dir.run_jj(["new"]).success();
+
+    let output = work_dir.run_jj(["tag", "set", "-r@--", "foo", "bar"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Created 2 tags pointing to qpvuntsm caf975d0 (empty) commit1
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["tag", "set", "-r@-", "baz"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Created 1 tags pointing to kkmpptxz d0a19ea4 (empty) commit2
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["tag", "list"]), @r"
+    bar: qpvuntsm caf975d0 (empty) commit1
+    baz: kkmpptxz d0a19ea4 (empty) commit2
+    foo: qpvuntsm caf975d0 (empty) commit1
+    [EOF]
+    ");
+
+    // Existing tags aren't moved by default
+    let output = work_dir.run_jj(["tag", "set", "-r@-", "foo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Refusing to move tag: foo
+    Hint: Use --allow-move to update existing tags.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["tag", "set", "-r@-", "--allow-move", "foo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Moved 1 tags to kkmpptxz d0a19ea4 (empty) commit2
+    [EOF]
+    ");
+
+    // Setting a tag to the same commit is a no-op
+    let output = work_dir.run_jj(["tag", "set", "-r@-", "foo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["tag", "set", "-r@-", "foo@origin"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: invalid value 'foo@origin' for '<NAMES>...': Failed to parse tag name: Syntax error
+
+    For more information, try '--help'.
+    Caused by:  --> 1:4
+      |
+    1 | foo@origin
+      |    ^---
+      |
+      = expected <EOI>
+    Hint: See https://jj-vcs.github.io/jj/latest/revsets/ or use `jj help -k revsets` for how to quote symbols.
+    [EOF]
+    [exit status: 2]
+    ");
+
+    let output = work_dir.run_jj(["tag", "delete", "glob:ba*"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Deleted 2 tags.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["tag", "delete", "bar"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such tag: bar
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["tag", "list"]), @r"
+    foo: kkmpptxz d0a19ea4 (empty) commit2
+    [EOF]
+    ");
+
+    // Tags are exported to the backing Git repo
+    work_dir.run_jj(["git", "export"]).success();
+    let git_tags = git_repo
+        .references()
+        .unwrap()
+        .tags()
+        .unwrap()
+        .map(|git_ref| git_ref.unwrap().name().as_bstr().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(git_tags, ["refs/tags/foo"]);
+
+    // Deleted tags aren't resurrected by import
+    work_dir.run_jj(["tag", "delete", "foo"]).success();
+    work_dir.run_jj(["git", "import"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["tag", "list"]), @"");
+    work_dir.run_jj(["git", "export"]).success();
+    assert!(git_repo
+        .try_find_reference("refs/tags/foo")
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_tag_set_annotated() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "repo"])
+        .success();
+    let work_dir = test_env.work_dir("repo");
+    let git_repo = git::open(work_dir.root());
+
+    work_dir.run_jj(["describe", "-mcommit1"]).success();
+    let output = work_dir.run_jj(["tag", "set", "-r@", "-m", "Release 1.0", "v1.0"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Created 1 tags pointing to qpvuntsm caf975d0 (empty) commit1
+    Warning: The working-copy commit in workspace 'default' became immutable, so a new commit has been created on top of it.
+    Working copy  (@) now at: kkmpptxz 72f2296f (empty) (no description set)
+    Parent commit (@-)      : qpvuntsm caf975d0 (empty) commit1
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["tag", "list"]), @r"
+    v1.0: qpvuntsm caf975d0 (empty) commit1
+    [EOF]
+    ");
+
+    let mut git_ref = git_repo.find_reference("refs/tags/v1.0").unwrap();
+    let tag = git_repo
+        .find_tag(git_ref.target().id())
+        .unwrap()
+        .decode()
+        .unwrap()
+        .into_owned();
+    assert_eq!(tag.message, "Release 1.0\n");
+    assert_eq!(tag.tagger.unwrap().name, "Test User");
+    let commit_id = git_ref.peel_to_id_in_place().unwrap();
+    insta::assert_snapshot!(commit_id, @"caf975d0989a4e84d6d2fd1047ea03663244969c");
+
+    // The annotated tag can be moved and deleted
+    work_dir.run_jj(["new", "-mcommit2"]).success();
+    let output = work_dir.run_jj(["tag", "set", "-r@", "--allow-move", "v1.0"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Moved 1 tags to mzvwutvl d2610633 (empty) commit2
+    Warning: The working-copy commit in workspace 'default' became immutable, so a new commit has been created on top of it.
+    Working copy  (@) now at: royxmykx 690e1ef2 (empty) (no description set)
+    Parent commit (@-)      : mzvwutvl d2610633 (empty) commit2
+    [EOF]
+    ");
+    let git_ref = git_repo.find_reference("refs/tags/v1.0").unwrap();
+    assert!(git_repo.find_tag(git_ref.target().id()).is_err());
+    let output = work_dir.run_jj(["tag", "delete", "v1.0"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Deleted 1 tags.
+    [EOF]
+    ");
+    assert!(git_repo
+        .try_find_reference("refs/tags/v1.0")
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_tag_set_annotated_conflict_in_git() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let git_repo = git::open(work_dir.root().join(".jj/repo/store/git"));
+
+    work_dir.run_jj(["describe", "-mcommit1"]).success();
+    work_dir.run_jj(["new", "-mcommit2"]).success();
+    let commit_id = work_dir
+        .run_jj(["log", "-Tcommit_id", "--no-graph", "-r@"])
+        .success()
+        .stdout
+        .into_raw();
+    let commit_id = gix::ObjectId::from_hex(commit_id.as_bytes()).unwrap();
+    git_repo
+        .tag_reference(
+            "v1",
+            commit_id,
+            gix::refs::transaction::PreviousValue::MustNotExist,
+        )
+        .unwrap();
+
+    // The tag created in Git isn't overwritten, and the transaction is abandoned
+    let output = work_dir.run_jj(["tag", "set", "-r@-", "-m", "Release", "v1"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to export tag v1 to the underlying Git repo
+    Caused by: Added ref had been added with a different target in Git
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["tag", "list"]), @"");
+    let git_ref = git_repo.find_reference("refs/tags/v1").unwrap();
+    assert_eq!(git_ref.target().id(), commit_id);
+}
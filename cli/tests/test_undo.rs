@@ -410,8 +410,9 @@ fn test_bookmark_track_untrack_undo() {
     work_dir.run_jj(["undo"]).success();
     insta::assert_snapshot!(get_bookmark_output(&work_dir), @r"
     feature1: qpvuntsm 8da1cfc8 (empty) commit
-    feature1@origin: qpvuntsm 8da1cfc8 (empty) commit
-    feature2@origin: qpvuntsm 8da1cfc8 (empty) commit
+      @origin: qpvuntsm 8da1cfc8 (empty) commit
+    feature2: qpvuntsm 8da1cfc8 (empty) commit
+      @origin: qpvuntsm 8da1cfc8 (empty) commit
     [EOF]
     ");
 
@@ -421,50 +422,261 @@ fn test_bookmark_track_untrack_undo() {
     insta::assert_snapshot!(get_bookmark_output(&work_dir), @r"
     feature1: qpvuntsm 8da1cfc8 (empty) commit
       @origin: qpvuntsm 8da1cfc8 (empty) commit
-    feature2@origin: qpvuntsm 8da1cfc8 (empty) commit
+    feature2: qpvuntsm 8da1cfc8 (empty) commit
+      @origin: qpvuntsm 8da1cfc8 (empty) commit
     [EOF]
     ");
 
     work_dir.run_jj(["undo"]).success();
     insta::assert_snapshot!(get_bookmark_output(&work_dir), @r"
     feature1: qpvuntsm 8da1cfc8 (empty) commit
-    feature1@origin: qpvuntsm 8da1cfc8 (empty) commit
-    feature2@origin: qpvuntsm 8da1cfc8 (empty) commit
+    feature2: qpvuntsm 8da1cfc8 (empty) commit
     [EOF]
     ");
 }
 
 #[test]
-fn test_shows_a_warning_when_undoing_an_undo_operation_as_bare_jj_undo() {
+fn test_undo_multiple_operations() {
     let test_env = TestEnvironment::default();
     test_env.run_jj_in(".", ["git", "init", "repo"]).success();
     let work_dir = test_env.work_dir("repo");
 
-    // Double-undo creation of child
-    work_dir.run_jj(["new"]).success();
-    work_dir.run_jj(["undo"]).success();
+    work_dir.run_jj(["new", "-m", "A"]).success();
+    work_dir.run_jj(["new", "-m", "B"]).success();
+    work_dir.run_jj(["new", "-m", "C"]).success();
+
+    // Repeated undo walks back through the operation log instead of undoing
+    // the previous undo
     let output = work_dir.run_jj(["undo"]);
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
-    Undid operation: 2d5b73a97567 (2001-02-03 08:05:09) undo operation 289cb69a8458456474a77cc432e8009b99f039cdcaf19ba4526753e97d70fee3fd0f410ff2b7c1d10cf0c2501702e7a85d58f9d813cdca567c377431ec4d2b97
-    Working copy  (@) now at: rlvkpnrz 65b6b74e (empty) (no description set)
+    Undid operation: 3a8df55b87ac (2001-02-03 08:05:10) new empty commit
+    Working copy  (@) now at: kkmpptxz ba31c246 (empty) B
+    Parent commit (@-)      : rlvkpnrz 9489917e (empty) A
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["undo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Undid operation: 85bbf9b99c67 (2001-02-03 08:05:09) new empty commit
+    Working copy  (@) now at: rlvkpnrz 9489917e (empty) A
     Parent commit (@-)      : qpvuntsm 230dd059 (empty) (no description set)
-    Hint: This action reverted an 'undo' operation. The repository is now in the same state as it was before the original 'undo'.
-    Hint: If your goal is to undo multiple operations, consider using `jj op log` to see past states, and `jj op restore` to restore one of these states.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @  A
+    ○
+    ◆
     [EOF]
     ");
 
-    // Double-undo creation of sibling
-    work_dir.run_jj(["new", "@-"]).success();
+    // Undo all the way to the initialization of the repo
+    work_dir.run_jj(["undo"]).success();
     work_dir.run_jj(["undo"]).success();
     let output = work_dir.run_jj(["undo"]);
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
-    Undid operation: b16799358b33 (2001-02-03 08:05:12) undo operation b14487c6d6d98f7f575ea03c48ed92d899c2a0ecbe9458221b6fc11af2bf6d918c9620cae1f8268012b0e25c7dd6f78b19ec628d0504a0830dc562d6625ba9ec
-    Working copy  (@) now at: mzvwutvl 167f90e7 (empty) (no description set)
-    Parent commit (@-)      : qpvuntsm 230dd059 (empty) (no description set)
-    Hint: This action reverted an 'undo' operation. The repository is now in the same state as it was before the original 'undo'.
-    Hint: If your goal is to undo multiple operations, consider using `jj op log` to see past states, and `jj op restore` to restore one of these states.
+    Error: Cannot undo repo initialization
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_redo() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    let output = work_dir.run_jj(["op", "redo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Nothing to redo
+    [EOF]
+    [exit status: 1]
+    ");
+
+    work_dir.run_jj(["new", "-m", "A"]).success();
+    work_dir.run_jj(["new", "-m", "B"]).success();
+    work_dir.run_jj(["new", "-m", "C"]).success();
+    work_dir.run_jj(["undo"]).success();
+    work_dir.run_jj(["undo"]).success();
+    let output = work_dir.run_jj(["log", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @  A
+    ○
+    ◆
+    [EOF]
+    ");
+
+    // Redo replays the undone operations in order
+    let output = work_dir.run_jj(["op", "redo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Redid operation: 271fa93c575b (2001-02-03 08:05:10) new empty commit
+    Working copy  (@) now at: zsuskuln d913f02f (empty) B
+    Parent commit (@-)      : kkmpptxz e5b3bc8e (empty) A
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @  B
+    ○  A
+    ○
+    ◆
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["redo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Redid operation: 6a48833fc2c0 (2001-02-03 08:05:11) new empty commit
+    Working copy  (@) now at: mzvwutvl b38a275e (empty) C
+    Parent commit (@-)      : zsuskuln d913f02f (empty) B
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @  C
+    ○  B
+    ○  A
+    ○
+    ◆
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["redo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Nothing to redo
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Undo after redo continues from the redone state
+    work_dir.run_jj(["undo"]).success();
+    work_dir.run_jj(["undo"]).success();
+    work_dir.run_jj(["redo"]).success();
+    let output = work_dir.run_jj(["log", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @  B
+    ○  A
+    ○
+    ◆
+    [EOF]
+    ");
+
+    // A new operation discards the undone operations
+    work_dir.run_jj(["new", "-m", "D"]).success();
+    let output = work_dir.run_jj(["redo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Nothing to redo
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["undo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Undid operation: dc7812ce59b5 (2001-02-03 08:05:24) new empty commit
+    Working copy  (@) now at: zsuskuln d913f02f (empty) B
+    Parent commit (@-)      : kkmpptxz e5b3bc8e (empty) A
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @  B
+    ○  A
+    ○
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_redo_colocated() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "repo"])
+        .success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.run_jj(["commit", "-m", "a"]).success();
+    work_dir
+        .run_jj(["bookmark", "create", "foo", "-r@-"])
+        .success();
+    work_dir.run_jj(["undo"]).success();
+    // The Git refs exported by the undo are kept, so the redone bookmark and
+    // commit aren't abandoned by the import.
+    let output = work_dir.run_jj(["redo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Redid operation: 082d7803a885 (2001-02-03 08:05:09) create bookmark foo pointing to commit d8d5f980a897bec1a085986377897c00e531ebce
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["bookmark", "list"]);
+    insta::assert_snapshot!(output, @r"
+    foo: qpvuntsm d8d5f980 (empty) a
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @
+    ○  a
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_undo_redo_after_op_compact() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.run_jj(["new", "-m", "A"]).success();
+    work_dir.run_jj(["new", "-m", "B"]).success();
+    work_dir.run_jj(["new", "-m", "C"]).success();
+    work_dir.run_jj(["undo"]).success();
+
+    // The reverted operation is rewritten, so the undo operation has to point
+    // to the new operation.
+    work_dir.run_jj(["op", "compact", "..@---"]).success();
+    work_dir.run_jj(["util", "gc", "--expire=now"]).success();
+    let output = work_dir.run_jj(["redo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Redid operation: 2876352559e6 (2001-02-03 08:05:10) new empty commit
+    Working copy  (@) now at: zsuskuln a9b02032 (empty) C
+    Parent commit (@-)      : kkmpptxz ba31c246 (empty) B
+    [EOF]
+    ");
+    work_dir.run_jj(["undo"]).success();
+    work_dir.run_jj(["undo"]).success();
+    let output = work_dir.run_jj(["log", "-T", "description"]);
+    insta::assert_snapshot!(output, @r"
+    @  A
+    ○
+    ◆
+    [EOF]
+    ");
+
+    // The reverted operation is compacted, so the undo operation is treated as
+    // a regular operation.
+    work_dir.run_jj(["op", "compact", "..@-"]).success();
+    work_dir.run_jj(["util", "gc", "--expire=now"]).success();
+    let output = work_dir.run_jj(["redo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Nothing to redo
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["undo"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Undid operation: 6a0d7f6fb249 (2001-02-03 08:05:16) undo operation 0c8c1d5a4c2218a08ca0d9892ef05601f551fbeca64fa1591dffe46fad621eba85334da92a671cf51fe7dde7006ab8f205f0779ee9143360ce8e98a3727130a9
+    Working copy  (@) now at: kkmpptxz ba31c246 (empty) B
+    Parent commit (@-)      : rlvkpnrz 9489917e (empty) A
     [EOF]
     ");
 }
@@ -482,7 +694,7 @@ fn test_shows_no_warning_when_undoing_a_specific_undo_change() {
     let output = work_dir.run_jj(["undo", &op_id_hex]);
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
-    Undid operation: 2d5b73a97567 (2001-02-03 08:05:09) undo operation 289cb69a8458456474a77cc432e8009b99f039cdcaf19ba4526753e97d70fee3fd0f410ff2b7c1d10cf0c2501702e7a85d58f9d813cdca567c377431ec4d2b97
+    Undid operation: efb3799092a9 (2001-02-03 08:05:09) undo operation 289cb69a8458456474a77cc432e8009b99f039cdcaf19ba4526753e97d70fee3fd0f410ff2b7c1d10cf0c2501702e7a85d58f9d813cdca567c377431ec4d2b97
     Working copy  (@) now at: rlvkpnrz 65b6b74e (empty) (no description set)
     Parent commit (@-)      : qpvuntsm 230dd059 (empty) (no description set)
     [EOF]
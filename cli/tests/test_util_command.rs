@@ -45,7 +45,12 @@ fn test_gc_args() {
     let work_dir = test_env.work_dir("repo");
 
     let output = work_dir.run_jj(["util", "gc"]);
-    insta::assert_snapshot!(output, @"");
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pruned 0 operations and 0 views
+    Pruned 0 commits and 0 objects
+    [EOF]
+    ");
 
     let output = work_dir.run_jj(["util", "gc", "--at-op=@-"]);
     insta::assert_snapshot!(output, @r"
@@ -58,10 +63,35 @@ fn test_gc_args() {
     let output = work_dir.run_jj(["util", "gc", "--expire=foobar"]);
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
-    Error: --expire only accepts 'now'
+    Error: Invalid --expire value: foobar
+    Caused by: expected unsupported identifier as position 0..6
     [EOF]
     [exit status: 1]
     ");
+
+    let output = work_dir.run_jj(["util", "gc", "--expire=2.weeks.ago"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pruned 0 operations and 0 views
+    Pruned 0 commits and 0 objects
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["util", "gc", "--expire=2 days ago", "--dry-run"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Would prune 0 operations and 0 views
+    Would prune 0 commits and 0 objects
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["util", "gc", "--expire=2024-01-01"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pruned 0 operations and 0 views
+    Pruned 0 commits and 0 objects
+    [EOF]
+    ");
 }
 
 #[test]
@@ -88,7 +118,26 @@ fn test_gc_operation_log() {
 
     // Remove some operations.
     work_dir.run_jj(["operation", "abandon", "..@-"]).success();
-    work_dir.run_jj(["util", "gc", "--expire=now"]).success();
+    let output = work_dir.run_jj(["util", "gc", "--expire=now", "--dry-run"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Would prune 5 operations and 4 views
+    Would prune 0 commits and 0 objects
+    [EOF]
+    ");
+
+    // Dry run doesn't remove anything.
+    work_dir
+        .run_jj(["debug", "operation", &op_to_remove])
+        .success();
+
+    let output = work_dir.run_jj(["util", "gc", "--expire=now"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pruned 5 operations and 4 views
+    Pruned 0 commits and 0 objects
+    [EOF]
+    ");
 
     // Now this doesn't work.
     let output = work_dir.run_jj(["debug", "operation", &op_to_remove]);
@@ -98,7 +98,7 @@ pub async fn split_hunks_to_trees(
 
     let left_tree = &source.parent_tree;
     let right_tree = source.commit.tree()?;
-    // TODO: enable copy tracking if we add support for annotate and merge
+    // TODO: enable copy tracking if we add support for merge
     let copy_records = CopyRecords::default();
     let tree_diff = left_tree.diff_stream_with_copies(&right_tree, matcher, &copy_records);
     let mut diff_stream = materialized_diff_stream(repo.store(), tree_diff);
@@ -135,7 +135,12 @@ pub async fn split_hunks_to_trees(
         let annotation = annotator.to_annotation();
         let annotation_ranges = annotation
             .compact_line_ranges()
-            .filter_map(|(commit_id, range)| Some((commit_id.ok()?, range)))
+            // Lines which originate from another path can't be absorbed
+            // into the file at left_path.
+            .filter_map(|(line_origin, range)| {
+                let line_origin = line_origin.ok().filter(|o| o.path.as_ref() == left_path)?;
+                Some((&line_origin.commit_id, range))
+            })
             .collect_vec();
         let diff = Diff::by_line([&left_text, &right_text]);
         let selected_ranges = split_file_hunks(&annotation_ranges, &diff);
@@ -20,12 +20,14 @@
 
 use std::collections::hash_map;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::iter;
 use std::ops::Range;
 use std::rc::Rc;
 
 use bstr::BStr;
 use bstr::BString;
+use futures::StreamExt as _;
 use itertools::Itertools as _;
 use pollster::FutureExt as _;
 
@@ -52,6 +54,16 @@ use crate::revset::RevsetExpression;
 use crate::revset::RevsetFilterPredicate;
 use crate::store::Store;
 
+/// Origin of a line in an annotated file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LineOrigin {
+    /// Commit where the line was introduced.
+    pub commit_id: CommitId,
+    /// Path of the file at that commit. This differs from the annotated path if
+    /// the file has been renamed or copied since.
+    pub path: RepoPathBuf,
+}
+
 /// Annotation results for a specific file
 #[derive(Clone, Debug)]
 pub struct FileAnnotation {
@@ -60,28 +72,28 @@ pub struct FileAnnotation {
 }
 
 impl FileAnnotation {
-    /// Returns iterator over `(commit_id, line)`s.
+    /// Returns iterator over `(line_origin, line)`s.
     ///
-    /// For each line, `Ok(commit_id)` points to the originator commit of the
+    /// For each line, `Ok(line_origin)` points to the originator commit of the
     /// line. If no originator commit was found within the domain,
-    /// `Err(commit_id)` should be set. It points to the root (or boundary)
+    /// `Err(line_origin)` should be set. It points to the root (or boundary)
     /// commit where the line exists.
     ///
     /// The `line` includes newline character.
-    pub fn lines(&self) -> impl Iterator<Item = (Result<&CommitId, &CommitId>, &BStr)> {
+    pub fn lines(&self) -> impl Iterator<Item = (Result<&LineOrigin, &LineOrigin>, &BStr)> {
         itertools::zip_eq(&self.line_map, self.text.split_inclusive(|b| *b == b'\n'))
-            .map(|(commit_id, line)| (commit_id.as_ref(), line.as_ref()))
+            .map(|(line_origin, line)| (line_origin.as_ref(), line.as_ref()))
     }
 
-    /// Returns iterator over `(commit_id, line_range)`s.
+    /// Returns iterator over `(line_origin, line_range)`s.
     ///
-    /// See [`Self::lines()`] for `commit_id`s.
+    /// See [`Self::lines()`] for `line_origin`s.
     ///
     /// The `line_range` is a slice range in the file `text`. Consecutive ranges
     /// having the same `commit_id` are not compacted.
     pub fn line_ranges(
         &self,
-    ) -> impl Iterator<Item = (Result<&CommitId, &CommitId>, Range<usize>)> {
+    ) -> impl Iterator<Item = (Result<&LineOrigin, &LineOrigin>, Range<usize>)> {
         let ranges = self
             .text
             .split_inclusive(|b| *b == b'\n')
@@ -91,24 +103,24 @@ impl FileAnnotation {
                 Some(start..*total)
             });
         itertools::zip_eq(&self.line_map, ranges)
-            .map(|(commit_id, range)| (commit_id.as_ref(), range))
+            .map(|(line_origin, range)| (line_origin.as_ref(), range))
     }
 
-    /// Returns iterator over compacted `(commit_id, line_range)`s.
+    /// Returns iterator over compacted `(line_origin, line_range)`s.
     ///
-    /// Consecutive ranges having the same `commit_id` are merged into one.
+    /// Consecutive ranges having the same `line_origin` are merged into one.
     pub fn compact_line_ranges(
         &self,
-    ) -> impl Iterator<Item = (Result<&CommitId, &CommitId>, Range<usize>)> {
+    ) -> impl Iterator<Item = (Result<&LineOrigin, &LineOrigin>, Range<usize>)> {
         let mut ranges = self.line_ranges();
         let mut acc = ranges.next();
         iter::from_fn(move || {
-            let (acc_commit_id, acc_range) = acc.as_mut()?;
-            for (cur_commit_id, cur_range) in ranges.by_ref() {
-                if *acc_commit_id == cur_commit_id {
+            let (acc_line_origin, acc_range) = acc.as_mut()?;
+            for (cur_line_origin, cur_range) in ranges.by_ref() {
+                if *acc_line_origin == cur_line_origin {
                     acc_range.end = cur_range.end;
                 } else {
-                    return acc.replace((cur_commit_id, cur_range));
+                    return acc.replace((cur_line_origin, cur_range));
                 }
             }
             acc.take()
@@ -122,10 +134,12 @@ impl FileAnnotation {
 }
 
 /// Annotation process for a specific file.
+///
+/// Lines are traced through renames and copies reported by
+/// [`Store::get_copy_records()`], so they are attributed to the commit that
+/// originally wrote them, possibly at a different path.
 #[derive(Clone, Debug)]
 pub struct FileAnnotator {
-    // If we add copy-tracing support, file_path might be tracked by state.
-    file_path: RepoPathBuf,
     original_text: BString,
     state: AnnotationState,
 }
@@ -136,7 +150,7 @@ impl FileAnnotator {
     /// If the file is not found, the result would be empty.
     pub fn from_commit(starting_commit: &Commit, file_path: &RepoPath) -> BackendResult<Self> {
         let source = Source::load(starting_commit, file_path)?;
-        Ok(Self::with_source(starting_commit.id(), file_path, source))
+        Ok(Self::with_source(starting_commit.id(), source))
     }
 
     /// Initializes annotator for a specific file path starting with the given
@@ -150,24 +164,24 @@ impl FileAnnotator {
         file_path: &RepoPath,
         starting_text: impl Into<Vec<u8>>,
     ) -> Self {
-        let source = Source::new(BString::new(starting_text.into()));
-        Self::with_source(starting_commit_id, file_path, source)
+        let source = Source::new(file_path.to_owned(), BString::new(starting_text.into()));
+        Self::with_source(starting_commit_id, source)
     }
 
-    fn with_source(
-        starting_commit_id: &CommitId,
-        file_path: &RepoPath,
-        mut source: Source,
-    ) -> Self {
+    fn with_source(starting_commit_id: &CommitId, mut source: Source) -> Self {
         source.fill_line_map();
         let original_text = source.text.clone();
+        let starting_origin = LineOrigin {
+            commit_id: starting_commit_id.clone(),
+            path: source.path.clone(),
+        };
         let state = AnnotationState {
-            original_line_map: vec![Err(starting_commit_id.clone()); source.line_map.len()],
+            original_line_map: vec![Err(starting_origin); source.line_map.len()],
             commit_source_map: HashMap::from([(starting_commit_id.clone(), source)]),
-            num_unresolved_roots: 0,
+            unresolved_roots: HashSet::new(),
+            ignored_commits: HashSet::new(),
         };
         FileAnnotator {
-            file_path: file_path.to_owned(),
             original_text,
             state,
         }
@@ -176,14 +190,37 @@ impl FileAnnotator {
     /// Computes line-by-line annotation within the `domain`.
     ///
     /// The `domain` expression narrows the range of ancestors to search. It
-    /// will be intersected as `domain & ::pending_commits & files(file_path)`.
-    /// The `pending_commits` is assumed to be included in the `domain`.
+    /// will be intersected as `domain & ::pending_commits & files(file_paths)`,
+    /// where `file_paths` includes the paths the file was renamed or copied
+    /// from. The `pending_commits` is assumed to be included in the `domain`.
     pub fn compute(
         &mut self,
         repo: &dyn Repo,
         domain: &Rc<ResolvedRevsetExpression>,
     ) -> Result<(), RevsetEvaluationError> {
-        process_commits(repo, &mut self.state, domain, &self.file_path)
+        process_commits(repo, &mut self.state, domain)
+    }
+
+    /// Ignores changes made in the given commits.
+    ///
+    /// Lines modified in an ignored commit are attributed to the corresponding
+    /// lines in its parents, matched by position within the changed region.
+    /// Lines added beyond that are still attributed to the ignored commit.
+    pub fn set_ignored_commits(&mut self, commit_ids: impl IntoIterator<Item = CommitId>) {
+        self.state.ignored_commits = commit_ids.into_iter().collect();
+    }
+
+    /// Restricts the annotation to the given range of 0-based line numbers in
+    /// the starting file. Lines outside the range are left unresolved.
+    ///
+    /// This should be called before [`Self::compute()`].
+    pub fn restrict_lines(&mut self, line_range: Range<usize>) {
+        self.state.commit_source_map.retain(|_, source| {
+            source
+                .line_map
+                .retain(|(_, original)| line_range.contains(original));
+            !source.line_map.is_empty()
+        });
     }
 
     /// Remaining commit ids to visit from.
@@ -209,8 +246,10 @@ struct AnnotationState {
     original_line_map: OriginalLineMap,
     /// Commits to file line mappings and contents.
     commit_source_map: HashMap<CommitId, Source>,
-    /// Number of unresolved root commits in `commit_source_map`.
-    num_unresolved_roots: usize,
+    /// Unresolved root commits in `commit_source_map`.
+    unresolved_roots: HashSet<CommitId>,
+    /// Commits whose changes are attributed to their parents where possible.
+    ignored_commits: HashSet<CommitId>,
 }
 
 /// Line mapping and file content at a certain commit.
@@ -219,14 +258,17 @@ struct Source {
     /// Mapping of line numbers in the file at the current commit to the
     /// original file, sorted by the line numbers at the current commit.
     line_map: Vec<(usize, usize)>,
+    /// Path of the file at the current commit.
+    path: RepoPathBuf,
     /// File content at the current commit.
     text: BString,
 }
 
 impl Source {
-    fn new(text: BString) -> Self {
+    fn new(path: RepoPathBuf, text: BString) -> Self {
         Source {
             line_map: Vec::new(),
+            path,
             text,
         }
     }
@@ -234,7 +276,31 @@ impl Source {
     fn load(commit: &Commit, file_path: &RepoPath) -> Result<Self, BackendError> {
         let tree = commit.tree()?;
         let text = get_file_contents(commit.store(), file_path, &tree)?;
-        Ok(Self::new(text))
+        Ok(Self::new(file_path.to_owned(), text))
+    }
+
+    /// Loads the file at `file_path` from the `parent` commit of `child`. If
+    /// the parent doesn't have the file, the file it was renamed or copied
+    /// from is loaded instead.
+    fn load_parent(
+        repo: &dyn Repo,
+        parent: &Commit,
+        child_id: &CommitId,
+        file_path: &RepoPath,
+    ) -> Result<Self, BackendError> {
+        let tree = parent.tree()?;
+        if tree.path_value(file_path)?.is_absent() {
+            let paths = [file_path.to_owned()];
+            let mut records = repo
+                .store()
+                .get_copy_records(Some(&paths), parent.id(), child_id)?;
+            if let Some(record) = records.next().block_on().transpose()? {
+                let text = get_file_contents(repo.store(), &record.source, &tree)?;
+                return Ok(Self::new(record.source, text));
+            }
+        }
+        let text = get_file_contents(repo.store(), file_path, &tree)?;
+        Ok(Self::new(file_path.to_owned(), text))
     }
 
     fn fill_line_map(&mut self) {
@@ -243,9 +309,8 @@ impl Source {
     }
 }
 
-/// List of commit IDs that originated lines, indexed by line numbers in the
-/// original file.
-type OriginalLineMap = Vec<Result<CommitId, CommitId>>;
+/// List of line origins, indexed by line numbers in the original file.
+type OriginalLineMap = Vec<Result<LineOrigin, LineOrigin>>;
 
 /// Starting from the source commits, compute changes at that commit relative to
 /// its direct parents, updating the mappings as we go.
@@ -253,29 +318,59 @@ fn process_commits(
     repo: &dyn Repo,
     state: &mut AnnotationState,
     domain: &Rc<ResolvedRevsetExpression>,
-    file_name: &RepoPath,
 ) -> Result<(), RevsetEvaluationError> {
-    let predicate = RevsetFilterPredicate::File(FilesetExpression::file_path(file_name.to_owned()));
-    // TODO: If the domain isn't a contiguous range, changes masked out by it
-    // might not be caught by the closest ancestor revision. For example,
-    // domain=merges() would pick up almost nothing because merge revisions
-    // are usually empty. Perhaps, we want to query `files(file_path,
-    // within_sub_graph=domain)`, not `domain & files(file_path)`.
-    let heads = RevsetExpression::commits(state.commit_source_map.keys().cloned().collect());
-    let revset = heads
-        .union(&domain.intersection(&heads.ancestors()).filtered(predicate))
-        .evaluate(repo)?;
-
-    state.num_unresolved_roots = 0;
-    for node in revset.iter_graph() {
-        let (commit_id, edge_list) = node?;
-        process_commit(repo, file_name, state, &commit_id, &edge_list)?;
-        if state.commit_source_map.len() == state.num_unresolved_roots {
-            // No more lines to propagate to ancestors.
-            break;
+    state.unresolved_roots.clear();
+    let mut file_paths: HashSet<RepoPathBuf> = state
+        .commit_source_map
+        .values()
+        .map(|source| source.path.clone())
+        .collect();
+    'restart: loop {
+        let predicate = RevsetFilterPredicate::File(FilesetExpression::union_all(
+            file_paths
+                .iter()
+                .map(|path| FilesetExpression::file_path(path.clone()))
+                .collect(),
+        ));
+        // TODO: If the domain isn't a contiguous range, changes masked out by it
+        // might not be caught by the closest ancestor revision. For example,
+        // domain=merges() would pick up almost nothing because merge revisions
+        // are usually empty. Perhaps, we want to query `files(file_path,
+        // within_sub_graph=domain)`, not `domain & files(file_path)`.
+        let heads = RevsetExpression::commits(
+            state
+                .commit_source_map
+                .keys()
+                .filter(|id| !state.unresolved_roots.contains(*id))
+                .cloned()
+                .collect(),
+        );
+        let revset = heads
+            .union(&domain.intersection(&heads.ancestors()).filtered(predicate))
+            .evaluate(repo)?;
+
+        for node in revset.iter_graph() {
+            let (commit_id, edge_list) = node?;
+            process_commit(repo, state, &file_paths, &commit_id, &edge_list)?;
+            // If the file was renamed or copied, search again for the commits
+            // which modified the source path.
+            let new_paths = state
+                .commit_source_map
+                .values()
+                .filter(|source| !file_paths.contains(&source.path))
+                .map(|source| source.path.clone())
+                .collect_vec();
+            if !new_paths.is_empty() {
+                file_paths.extend(new_paths);
+                continue 'restart;
+            }
+            if state.commit_source_map.len() == state.unresolved_roots.len() {
+                // No more lines to propagate to ancestors.
+                break;
+            }
         }
+        return Ok(());
     }
-    Ok(())
 }
 
 /// For a given commit, for each parent, we compare the version in the parent
@@ -283,8 +378,8 @@ fn process_commits(
 /// common. If the parent doesn't have the file, we skip it.
 fn process_commit(
     repo: &dyn Repo,
-    file_name: &RepoPath,
     state: &mut AnnotationState,
+    file_paths: &HashSet<RepoPathBuf>,
     current_commit_id: &CommitId,
     edges: &[GraphEdge<CommitId>],
 ) -> Result<(), BackendError> {
@@ -298,7 +393,12 @@ fn process_commit(
             hash_map::Entry::Occupied(entry) => entry.into_mut(),
             hash_map::Entry::Vacant(entry) => {
                 let commit = repo.store().get_commit(entry.key())?;
-                entry.insert(Source::load(&commit, file_name)?)
+                entry.insert(Source::load_parent(
+                    repo,
+                    &commit,
+                    current_commit_id,
+                    &current_source.path,
+                )?)
             }
         };
 
@@ -316,6 +416,7 @@ fn process_commit(
         copy_same_lines_with(
             &current_source.text,
             &parent_source.text,
+            state.ignored_commits.contains(current_commit_id),
             |current_start, parent_start, count| {
                 new_current_line_map
                     .extend(current_lines.peeking_take_while(|&(cur, _)| cur < current_start));
@@ -336,22 +437,34 @@ fn process_commit(
         };
         if parent_source.line_map.is_empty() {
             state.commit_source_map.remove(parent_commit_id);
-        } else if parent_edge.edge_type == GraphEdgeType::Missing {
+        } else if parent_edge.edge_type == GraphEdgeType::Missing
+            && file_paths.contains(&parent_source.path)
+        {
             // If an omitted parent had the file, leave these lines unresolved.
             // The origin of the unresolved lines is represented as
-            // Err(root_commit_id).
+            // Err(root_commit_id). If the parent had the file at another path,
+            // the parent will be visited again once commits touching that path
+            // are searched.
+            let origin = LineOrigin {
+                commit_id: current_commit_id.clone(),
+                path: current_source.path.clone(),
+            };
             for &(_, original_line_number) in &parent_source.line_map {
-                state.original_line_map[original_line_number] = Err(current_commit_id.clone());
+                state.original_line_map[original_line_number] = Err(origin.clone());
             }
-            state.num_unresolved_roots += 1;
+            state.unresolved_roots.insert(parent_commit_id.clone());
         }
     }
 
     // Once we've looked at all parents of a commit, any leftover lines must be
     // original to the current commit, so we save this information in
     // original_line_map.
+    let origin = LineOrigin {
+        commit_id: current_commit_id.clone(),
+        path: current_source.path,
+    };
     for (_, original_line_number) in current_source.line_map {
-        state.original_line_map[original_line_number] = Ok(current_commit_id.clone());
+        state.original_line_map[original_line_number] = Ok(origin.clone());
     }
 
     Ok(())
@@ -359,9 +472,13 @@ fn process_commit(
 
 /// For two files, calls `copy(current_start, parent_start, count)` for each
 /// range of contiguous lines in common (e.g. line 8-10 maps to line 9-11.)
+///
+/// If `copy_changed` is true, changed lines are also mapped to the parent
+/// lines at the same offset within each changed range.
 fn copy_same_lines_with(
     current_contents: &[u8],
     parent_contents: &[u8],
+    copy_changed: bool,
     mut copy: impl FnMut(usize, usize, usize),
 ) {
     let diff = Diff::by_line([current_contents, parent_contents]);
@@ -378,8 +495,17 @@ fn copy_same_lines_with(
             DiffHunkKind::Different => {
                 let current_output = hunk.contents[0];
                 let parent_output = hunk.contents[1];
-                current_line_counter += current_output.split_inclusive(|b| *b == b'\n').count();
-                parent_line_counter += parent_output.split_inclusive(|b| *b == b'\n').count();
+                let current_count = current_output.split_inclusive(|b| *b == b'\n').count();
+                let parent_count = parent_output.split_inclusive(|b| *b == b'\n').count();
+                if copy_changed && current_count > 0 && parent_count > 0 {
+                    copy(
+                        current_line_counter,
+                        parent_line_counter,
+                        current_count.min(parent_count),
+                    );
+                }
+                current_line_counter += current_count;
+                parent_line_counter += parent_count;
             }
         }
     }
@@ -406,6 +532,13 @@ fn get_file_contents(
 mod tests {
     use super::*;
 
+    fn make_line_origin(commit_id: &CommitId) -> LineOrigin {
+        LineOrigin {
+            commit_id: commit_id.clone(),
+            path: RepoPathBuf::from_internal_string("file").unwrap(),
+        }
+    }
+
     #[test]
     fn test_lines_iterator_empty() {
         let annotation = FileAnnotation {
@@ -419,67 +552,67 @@ mod tests {
 
     #[test]
     fn test_lines_iterator_with_content() {
-        let commit_id1 = CommitId::from_hex("111111");
-        let commit_id2 = CommitId::from_hex("222222");
-        let commit_id3 = CommitId::from_hex("333333");
+        let origin1 = make_line_origin(&CommitId::from_hex("111111"));
+        let origin2 = make_line_origin(&CommitId::from_hex("222222"));
+        let origin3 = make_line_origin(&CommitId::from_hex("333333"));
         let annotation = FileAnnotation {
             line_map: vec![
-                Ok(commit_id1.clone()),
-                Ok(commit_id2.clone()),
-                Ok(commit_id3.clone()),
+                Ok(origin1.clone()),
+                Ok(origin2.clone()),
+                Ok(origin3.clone()),
             ],
             text: "foo\n\nbar\n".into(),
         };
         assert_eq!(
             annotation.lines().collect_vec(),
             vec![
-                (Ok(&commit_id1), "foo\n".as_ref()),
-                (Ok(&commit_id2), "\n".as_ref()),
-                (Ok(&commit_id3), "bar\n".as_ref()),
+                (Ok(&origin1), "foo\n".as_ref()),
+                (Ok(&origin2), "\n".as_ref()),
+                (Ok(&origin3), "bar\n".as_ref()),
             ]
         );
         assert_eq!(
             annotation.line_ranges().collect_vec(),
             vec![
-                (Ok(&commit_id1), 0..4),
-                (Ok(&commit_id2), 4..5),
-                (Ok(&commit_id3), 5..9),
+                (Ok(&origin1), 0..4),
+                (Ok(&origin2), 4..5),
+                (Ok(&origin3), 5..9),
             ]
         );
         assert_eq!(
             annotation.compact_line_ranges().collect_vec(),
             vec![
-                (Ok(&commit_id1), 0..4),
-                (Ok(&commit_id2), 4..5),
-                (Ok(&commit_id3), 5..9),
+                (Ok(&origin1), 0..4),
+                (Ok(&origin2), 4..5),
+                (Ok(&origin3), 5..9),
             ]
         );
     }
 
     #[test]
     fn test_lines_iterator_compaction() {
-        let commit_id1 = CommitId::from_hex("111111");
-        let commit_id2 = CommitId::from_hex("222222");
-        let commit_id3 = CommitId::from_hex("333333");
+        let origin1 = make_line_origin(&CommitId::from_hex("111111"));
+        let origin2 = make_line_origin(&CommitId::from_hex("222222"));
+        let origin3 = make_line_origin(&CommitId::from_hex("333333"));
         let annotation = FileAnnotation {
             line_map: vec![
-                Ok(commit_id1.clone()),
-                Ok(commit_id1.clone()),
-                Ok(commit_id2.clone()),
-                Ok(commit_id1.clone()),
-                Ok(commit_id3.clone()),
-                Ok(commit_id3.clone()),
-                Ok(commit_id3.clone()),
+                Ok(origin1.clone()),
+                Ok(origin1.clone()),
+                Ok(origin2.clone()),
+                Ok(origin1.clone()),
+                Ok(origin3.clone()),
+                Ok(origin3.clone()),
+                Ok(origin3.clone()),
             ],
             text: "\n".repeat(7).into(),
         };
         assert_eq!(
             annotation.compact_line_ranges().collect_vec(),
             vec![
-                (Ok(&commit_id1), 0..2),
-                (Ok(&commit_id2), 2..3),
-                (Ok(&commit_id1), 3..4),
-                (Ok(&commit_id3), 4..7),
+                (Ok(&origin1), 0..2),
+                (Ok(&origin2), 2..3),
+                (Ok(&origin1), 3..4),
+                (Ok(&origin3), 4..7),
             ]
         );
     }
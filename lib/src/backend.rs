@@ -139,7 +139,7 @@ impl MergedTreeId {
     }
 }
 
-#[derive(ContentHash, Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Commit {
     pub parents: Vec<CommitId>,
     pub predecessors: Vec<CommitId>,
@@ -149,6 +149,50 @@ pub struct Commit {
     pub author: Signature,
     pub committer: Signature,
     pub secure_sig: Option<SecureSig>,
+    /// Copies and renames recorded explicitly in this commit, relative to its
+    /// parents.
+    pub copies: Vec<RecordedCopy>,
+}
+
+impl ContentHash for Commit {
+    fn hash(&self, state: &mut impl crate::content_hash::DigestUpdate) {
+        let Self {
+            parents,
+            predecessors,
+            root_tree,
+            change_id,
+            description,
+            author,
+            committer,
+            secure_sig,
+            copies,
+        } = self;
+        parents.hash(state);
+        predecessors.hash(state);
+        root_tree.hash(state);
+        change_id.hash(state);
+        description.hash(state);
+        author.hash(state);
+        committer.hash(state);
+        secure_sig.hash(state);
+        // Only hashed if present so the IDs of commits written before this
+        // field was added don't change.
+        if !copies.is_empty() {
+            copies.hash(state);
+        }
+    }
+}
+
+/// A copy or rename of a file recorded explicitly in a commit, as opposed to
+/// one detected by comparing trees.
+///
+/// If the `source` path doesn't exist in the commit, the file was renamed.
+#[derive(ContentHash, Debug, PartialEq, Eq, Clone)]
+pub struct RecordedCopy {
+    /// The path in the parent commit the file was copied from.
+    pub source: RepoPathBuf,
+    /// The path in the commit the file was copied to.
+    pub target: RepoPathBuf,
 }
 
 #[derive(ContentHash, Debug, PartialEq, Eq, Clone)]
@@ -388,9 +432,23 @@ pub fn make_root_commit(root_change_id: ChangeId, empty_tree_id: TreeId) -> Comm
         author: signature.clone(),
         committer: signature,
         secure_sig: None,
+        copies: vec![],
     }
 }
 
+/// Statistics about the objects removed by [`Backend::gc_with_stats()`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BackendGcStats {
+    /// Number of commits which are no longer kept.
+    pub num_commits: usize,
+    /// Number of other objects removed. Backends may only be able to count
+    /// some of them.
+    pub num_objects: usize,
+    /// Number of objects moved into more compact storage without being
+    /// removed.
+    pub num_packed_objects: usize,
+}
+
 /// Defines the interface for commit backends.
 #[async_trait]
 pub trait Backend: Send + Sync + Debug {
@@ -476,6 +534,10 @@ pub trait Backend: Send + Sync + Debug {
     /// large single-file histories. This also allows more iterative algorithms
     /// like blame/annotate to short-circuit after a point without wasting
     /// unnecessary resources.
+    ///
+    /// Backends which don't track copies can return
+    /// [`BackendError::Unsupported`], in which case the copies are detected by
+    /// comparing file contents.
     fn get_copy_records(
         &self,
         paths: Option<&[RepoPathBuf]>,
@@ -483,10 +545,34 @@ pub trait Backend: Send + Sync + Debug {
         head: &CommitId,
     ) -> BackendResult<BoxStream<BackendResult<CopyRecord>>>;
 
+    /// Reads the tree at `path` and all its subtrees ahead of time, so that
+    /// they can be read quickly later. The default implementation does
+    /// nothing.
+    async fn prefetch_tree(&self, _path: &RepoPath, _id: &TreeId) -> BackendResult<()> {
+        Ok(())
+    }
+
     /// Perform garbage collection.
     ///
     /// All commits found in the `index` won't be removed. In addition to that,
     /// objects created after `keep_newer` will be preserved. This mitigates a
     /// risk of deleting new commits created concurrently by another process.
     fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()>;
+
+    /// Like [`Backend::gc()`], but also reports what was removed.
+    ///
+    /// If `dry_run` is true, nothing is removed, and the returned stats
+    /// describe what would be removed. The default implementation reports no
+    /// stats.
+    fn gc_with_stats(
+        &self,
+        index: &dyn Index,
+        keep_newer: SystemTime,
+        dry_run: bool,
+    ) -> BackendResult<BackendGcStats> {
+        if !dry_run {
+            self.gc(index, keep_newer)?;
+        }
+        Ok(BackendGcStats::default())
+    }
 }
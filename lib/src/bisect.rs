@@ -0,0 +1,228 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bisect a range of commits to find the first commit with some property.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use itertools::Itertools as _;
+
+use crate::backend::CommitId;
+use crate::dag_walk;
+use crate::graph::GraphEdgeType;
+use crate::repo::Repo;
+use crate::revset::ResolvedRevsetExpression;
+use crate::revset::RevsetEvaluationError;
+use crate::revset::RevsetExpression;
+
+/// Maximum number of candidates whose ancestors are counted when picking the
+/// next commit to evaluate. Larger candidate sets are sampled.
+const MAX_SAMPLED_CANDIDATES: usize = 256;
+
+/// The outcome of evaluating a commit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Evaluation {
+    /// The commit doesn't have the property being searched for.
+    Good,
+    /// The commit has the property being searched for.
+    Bad,
+    /// The commit couldn't be evaluated.
+    Skip,
+}
+
+/// What to do next in a bisection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NextStep {
+    /// The given commit should be evaluated next.
+    Evaluate(CommitId),
+    /// There are no more commits to evaluate.
+    Done(BisectionResult),
+}
+
+/// The result of a bisection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BisectionResult {
+    /// The first bad commits were found. There's usually a single one, but
+    /// there can be several if the bad commits have more than one root.
+    Found(Vec<CommitId>),
+    /// The first bad commit is one of the given commits, but some of them were
+    /// skipped so it's unknown which.
+    Indeterminate(Vec<CommitId>),
+}
+
+/// Finds the first bad commit by binary search over the commit graph.
+///
+/// Descendants of bad commits are considered bad, and ancestors of good
+/// commits are considered good. The commits left to evaluate are the ancestors
+/// of the bad commits which aren't known to be good or bad, excluding the
+/// skipped ones.
+#[derive(Clone)]
+pub struct Bisector<'repo> {
+    repo: &'repo dyn Repo,
+    good_commits: Vec<CommitId>,
+    bad_commits: Vec<CommitId>,
+    skipped_commits: Vec<CommitId>,
+}
+
+impl<'repo> Bisector<'repo> {
+    /// Creates a bisector with no commits evaluated.
+    pub fn new(repo: &'repo dyn Repo) -> Self {
+        Self {
+            repo,
+            good_commits: vec![],
+            bad_commits: vec![],
+            skipped_commits: vec![],
+        }
+    }
+
+    /// Records the outcome of evaluating the given commit.
+    pub fn mark(&mut self, id: CommitId, evaluation: Evaluation) {
+        let target = match evaluation {
+            Evaluation::Good => &mut self.good_commits,
+            Evaluation::Bad => &mut self.bad_commits,
+            Evaluation::Skip => &mut self.skipped_commits,
+        };
+        if !target.contains(&id) {
+            target.push(id);
+        }
+    }
+
+    /// Commits marked as good.
+    pub fn good_commits(&self) -> &[CommitId] {
+        &self.good_commits
+    }
+
+    /// Commits marked as bad.
+    pub fn bad_commits(&self) -> &[CommitId] {
+        &self.bad_commits
+    }
+
+    /// Commits marked as skipped.
+    pub fn skipped_commits(&self) -> &[CommitId] {
+        &self.skipped_commits
+    }
+
+    /// Returns pairs of good and bad commits where the good commit is a
+    /// descendant of the bad commit, which means the marks are inconsistent.
+    pub fn inconsistent_marks(&self) -> Vec<(CommitId, CommitId)> {
+        let index = self.repo.index();
+        self.good_commits
+            .iter()
+            .cartesian_product(&self.bad_commits)
+            .filter(|(good, bad)| index.is_ancestor(bad, good))
+            .map(|(good, bad)| (good.clone(), bad.clone()))
+            .collect()
+    }
+
+    /// Commits which may be the first bad commit, including commits marked as
+    /// bad or skipped.
+    fn unknown_expression(&self) -> Rc<ResolvedRevsetExpression> {
+        let good = RevsetExpression::commits(self.good_commits.clone());
+        let bad = RevsetExpression::commits(self.bad_commits.clone());
+        bad.ancestors().minus(&good.ancestors())
+    }
+
+    /// Returns the commits that are left to evaluate, in topological order
+    /// with children before parents.
+    pub fn remaining_commits(&self) -> Result<Vec<CommitId>, RevsetEvaluationError> {
+        let bad = RevsetExpression::commits(self.bad_commits.clone());
+        let skipped = RevsetExpression::commits(self.skipped_commits.clone());
+        self.unknown_expression()
+            .minus(&bad.descendants())
+            .minus(&skipped)
+            .evaluate(self.repo)?
+            .iter()
+            .try_collect()
+    }
+
+    /// Determines the next commit to evaluate, or the result if the bisection
+    /// is complete.
+    pub fn next_step(&self) -> Result<NextStep, RevsetEvaluationError> {
+        let bad = RevsetExpression::commits(self.bad_commits.clone());
+        let skipped = RevsetExpression::commits(self.skipped_commits.clone());
+        let unknown = self.unknown_expression();
+        let candidates = unknown.minus(&bad.descendants()).minus(&skipped);
+        let candidates_revset = candidates.evaluate(self.repo)?;
+
+        // Collect the graph of candidates so ancestors can be counted without
+        // going through the index again.
+        let mut ids = vec![];
+        let mut parent_edges = vec![];
+        for node in candidates_revset.iter_graph() {
+            let (id, edges) = node?;
+            let parents = edges
+                .into_iter()
+                .filter(|edge| edge.edge_type != GraphEdgeType::Missing)
+                .map(|edge| edge.target)
+                .collect_vec();
+            ids.push(id);
+            parent_edges.push(parents);
+        }
+        if ids.is_empty() {
+            let first_bad: Vec<_> = unknown
+                .intersection(&bad.descendants())
+                .roots()
+                .evaluate(self.repo)?
+                .iter()
+                .try_collect()?;
+            let skipped_left: Vec<_> = unknown
+                .intersection(&skipped)
+                .evaluate(self.repo)?
+                .iter()
+                .try_collect()?;
+            let result = if skipped_left.is_empty() {
+                BisectionResult::Found(first_bad)
+            } else {
+                let commits = RevsetExpression::commits(first_bad)
+                    .union(&RevsetExpression::commits(skipped_left))
+                    .evaluate(self.repo)?
+                    .iter()
+                    .try_collect()?;
+                BisectionResult::Indeterminate(commits)
+            };
+            return Ok(NextStep::Done(result));
+        }
+        Ok(NextStep::Evaluate(pick_midpoint(&ids, &parent_edges)))
+    }
+}
+
+/// Picks the commit which splits the candidates most evenly into its
+/// ancestors and the rest.
+///
+/// `ids` must be in topological order with children before parents, and
+/// `parent_edges` must list the parents of each commit within the candidates.
+fn pick_midpoint(ids: &[CommitId], parent_edges: &[Vec<CommitId>]) -> CommitId {
+    let total = ids.len();
+    let positions: HashMap<&CommitId, usize> =
+        ids.iter().enumerate().map(|(pos, id)| (id, pos)).collect();
+    let parents_of = |pos: &usize| -> Vec<usize> {
+        parent_edges[*pos]
+            .iter()
+            .filter_map(|id| positions.get(id).copied())
+            .collect()
+    };
+    let step = total.div_ceil(MAX_SAMPLED_CANDIDATES);
+    let (best_pos, _) = (0..total)
+        .step_by(step)
+        .map(|pos| {
+            let num_ancestors = dag_walk::dfs([pos], |pos| *pos, parents_of).count();
+            (pos, num_ancestors.min(total - num_ancestors))
+        })
+        // Prefer the commit closest to the heads among equally good ones.
+        .rev()
+        .max_by_key(|(_, score)| *score)
+        .unwrap();
+    ids[best_pos].clone()
+}
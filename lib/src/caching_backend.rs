@@ -0,0 +1,477 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Backend wrapper that caches objects on local disk.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use futures::stream;
+use futures::stream::BoxStream;
+use futures::StreamExt as _;
+use futures::TryStreamExt as _;
+use prost::Message as _;
+use tempfile::NamedTempFile;
+
+use crate::backend::Backend;
+use crate::backend::BackendError;
+use crate::backend::BackendGcStats;
+use crate::backend::BackendLoadError;
+use crate::backend::BackendResult;
+use crate::backend::ChangeId;
+use crate::backend::Commit;
+use crate::backend::CommitId;
+use crate::backend::Conflict;
+use crate::backend::ConflictId;
+use crate::backend::CopyRecord;
+use crate::backend::FileId;
+use crate::backend::SigningFn;
+use crate::backend::SymlinkId;
+use crate::backend::Tree;
+use crate::backend::TreeId;
+use crate::backend::TreeValue;
+use crate::config::ConfigGetResultExt as _;
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::index::Index;
+use crate::object_id::ObjectId;
+use crate::repo::StoreLoadError;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::settings::HumanByteSize;
+use crate::settings::UserSettings;
+use crate::simple_backend::decode_commit_with_signature;
+use crate::simple_backend::encode_commit_with_signature;
+use crate::simple_backend::tree_from_proto;
+use crate::simple_backend::tree_to_proto;
+
+/// Type of object stored in the cache.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum CacheKind {
+    File,
+    Tree,
+    Commit,
+}
+
+impl CacheKind {
+    const ALL: [Self; 3] = [Self::File, Self::Tree, Self::Commit];
+
+    fn dir_name(self) -> &'static str {
+        match self {
+            Self::File => "files",
+            Self::Tree => "trees",
+            Self::Commit => "commits",
+        }
+    }
+}
+
+type CacheKey = (CacheKind, String);
+
+#[derive(Debug, Default)]
+struct CacheState {
+    /// Map of cached object to its size and last access time.
+    entries: HashMap<CacheKey, (u64, u64)>,
+    /// Cached objects ordered by last access time.
+    lru: BTreeMap<u64, CacheKey>,
+    total_size: u64,
+    clock: u64,
+}
+
+impl CacheState {
+    fn touch(&mut self, key: &CacheKey) -> bool {
+        let Some((_, last_used)) = self.entries.get_mut(key) else {
+            return false;
+        };
+        self.lru.remove(last_used);
+        self.clock += 1;
+        *last_used = self.clock;
+        self.lru.insert(self.clock, key.clone());
+        true
+    }
+
+    fn insert(&mut self, key: CacheKey, size: u64) {
+        self.remove(&key);
+        self.clock += 1;
+        self.entries.insert(key.clone(), (size, self.clock));
+        self.lru.insert(self.clock, key);
+        self.total_size += size;
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some((size, last_used)) = self.entries.remove(key) {
+            self.lru.remove(&last_used);
+            self.total_size -= size;
+        }
+    }
+
+    fn pop_least_recently_used(&mut self) -> Option<CacheKey> {
+        let (_, key) = self.lru.pop_first()?;
+        let (size, _) = self.entries.remove(&key).unwrap();
+        self.total_size -= size;
+        Some(key)
+    }
+}
+
+/// Size-bounded cache of serialized objects stored in a directory.
+#[derive(Debug)]
+struct ObjectCache {
+    dir: PathBuf,
+    max_size: u64,
+    state: Mutex<CacheState>,
+}
+
+impl ObjectCache {
+    fn load(dir: &Path, max_size: u64) -> io::Result<Self> {
+        let mut found_entries = vec![];
+        for kind in CacheKind::ALL {
+            let kind_dir = dir.join(kind.dir_name());
+            fs::create_dir_all(&kind_dir)?;
+            for entry in fs::read_dir(&kind_dir)? {
+                let entry = entry?;
+                let Ok(name) = entry.file_name().into_string() else {
+                    continue;
+                };
+                // Skip temporary files
+                if hex::decode(&name).is_err() {
+                    continue;
+                }
+                let metadata = entry.metadata()?;
+                let mtime = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                found_entries.push((mtime, (kind, name), metadata.len()));
+            }
+        }
+        // Restore the access order from the file modification times.
+        found_entries.sort_unstable_by_key(|(mtime, ..)| *mtime);
+        let mut state = CacheState::default();
+        for (_, key, size) in found_entries {
+            state.insert(key, size);
+        }
+        let cache = ObjectCache {
+            dir: dir.to_owned(),
+            max_size,
+            state: Mutex::new(state),
+        };
+        cache.evict()?;
+        Ok(cache)
+    }
+
+    fn path(&self, (kind, name): &CacheKey) -> PathBuf {
+        self.dir.join(kind.dir_name()).join(name)
+    }
+
+    fn get(&self, kind: CacheKind, id: &impl ObjectId) -> Option<Vec<u8>> {
+        let key = (kind, id.hex());
+        let path = self.path(&key);
+        match fs::read(&path) {
+            Ok(data) => {
+                let mut state = self.state.lock().unwrap();
+                if !state.touch(&key) {
+                    // Cached by another process
+                    state.insert(key, data.len() as u64);
+                }
+                drop(state);
+                // Update mtime so the access order is preserved across
+                // processes. This is best effort.
+                if let Ok(file) = File::options().append(true).open(&path) {
+                    file.set_modified(SystemTime::now()).ok();
+                }
+                Some(data)
+            }
+            Err(_) => {
+                // Evicted by another process, or corrupted
+                self.state.lock().unwrap().remove(&key);
+                None
+            }
+        }
+    }
+
+    /// Adds the object to the cache. Failure to write to the cache doesn't
+    /// affect the result of the backend operation, so it's only logged.
+    fn insert(&self, kind: CacheKind, id: &impl ObjectId, data: &[u8]) {
+        if let Err(err) = self.try_insert(kind, id, data) {
+            tracing::warn!(?err, ?kind, "Failed to write object to cache");
+        }
+    }
+
+    fn try_insert(&self, kind: CacheKind, id: &impl ObjectId, data: &[u8]) -> io::Result<()> {
+        let size = data.len() as u64;
+        if size > self.max_size {
+            return Ok(());
+        }
+        let key = (kind, id.hex());
+        let mut temp_file = NamedTempFile::new_in(self.dir.join(kind.dir_name()))?;
+        temp_file.write_all(data)?;
+        persist_content_addressed_temp_file(temp_file, self.path(&key))?;
+        self.state.lock().unwrap().insert(key, size);
+        self.evict()
+    }
+
+    fn evict(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        while state.total_size > self.max_size {
+            let key = state.pop_least_recently_used().unwrap();
+            match fs::remove_file(self.path(&key)) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_other_err(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> BackendError {
+    BackendError::Other(err.into())
+}
+
+/// Wraps the `backend` in a `CachingBackend` if `object-cache.max-size` is
+/// set. The cache is stored in `object-cache.dir`, which defaults to the
+/// `cache` directory in the `store_path`.
+pub fn wrap_backend_from_settings(
+    settings: &UserSettings,
+    store_path: &Path,
+    backend: Box<dyn Backend>,
+) -> Result<Box<dyn Backend>, StoreLoadError> {
+    let Some(max_size) = settings
+        .get_value_with("object-cache.max-size", HumanByteSize::try_from)
+        .optional()?
+    else {
+        return Ok(backend);
+    };
+    let cache_dir = match settings.get::<PathBuf>("object-cache.dir").optional()? {
+        Some(dir) => store_path.join(dir),
+        None => store_path.join("cache"),
+    };
+    let backend = CachingBackend::new(backend, &cache_dir, max_size.0)
+        .map_err(|err| BackendLoadError(err.into()))?;
+    Ok(Box::new(backend))
+}
+
+/// Backend that wraps another backend, and keeps an on-disk cache of files,
+/// trees, and commits read from or written to the inner backend.
+///
+/// The total size of the cached objects is bounded. The least recently used
+/// objects are evicted first. Since objects are immutable, cached objects
+/// never have to be invalidated.
+#[derive(Debug)]
+pub struct CachingBackend {
+    inner: Box<dyn Backend>,
+    cache: ObjectCache,
+}
+
+impl CachingBackend {
+    /// Wraps the `inner` backend, storing up to `max_size` bytes of objects in
+    /// the `cache_dir`. The directory will be created if it doesn't exist.
+    pub fn new(inner: Box<dyn Backend>, cache_dir: &Path, max_size: u64) -> BackendResult<Self> {
+        let cache = ObjectCache::load(cache_dir, max_size).map_err(to_other_err)?;
+        Ok(CachingBackend { inner, cache })
+    }
+
+    /// Returns the wrapped backend.
+    pub fn inner(&self) -> &dyn Backend {
+        self.inner.as_ref()
+    }
+}
+
+#[async_trait]
+impl Backend for CachingBackend {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    /// Returns the name of the inner backend so the repository can also be
+    /// loaded without the cache.
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn commit_id_length(&self) -> usize {
+        self.inner.commit_id_length()
+    }
+
+    fn change_id_length(&self) -> usize {
+        self.inner.change_id_length()
+    }
+
+    fn root_commit_id(&self) -> &CommitId {
+        self.inner.root_commit_id()
+    }
+
+    fn root_change_id(&self) -> &ChangeId {
+        self.inner.root_change_id()
+    }
+
+    fn empty_tree_id(&self) -> &TreeId {
+        self.inner.empty_tree_id()
+    }
+
+    fn concurrency(&self) -> usize {
+        self.inner.concurrency()
+    }
+
+    async fn read_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
+        if let Some(data) = self.cache.get(CacheKind::File, id) {
+            return Ok(Box::new(Cursor::new(data)));
+        }
+        let mut reader = self.inner.read_file(path, id).await?;
+        let mut data = vec![];
+        reader
+            .read_to_end(&mut data)
+            .map_err(|err| BackendError::ReadFile {
+                path: path.to_owned(),
+                id: id.clone(),
+                source: err.into(),
+            })?;
+        self.cache.insert(CacheKind::File, id, &data);
+        Ok(Box::new(Cursor::new(data)))
+    }
+
+    async fn write_file(
+        &self,
+        path: &RepoPath,
+        contents: &mut (dyn Read + Send),
+    ) -> BackendResult<FileId> {
+        self.inner.write_file(path, contents).await
+    }
+
+    async fn read_symlink(&self, path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
+        self.inner.read_symlink(path, id).await
+    }
+
+    async fn write_symlink(&self, path: &RepoPath, target: &str) -> BackendResult<SymlinkId> {
+        self.inner.write_symlink(path, target).await
+    }
+
+    async fn read_tree(&self, path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
+        if let Some(data) = self.cache.get(CacheKind::Tree, id) {
+            if let Ok(proto) = crate::protos::simple_store::Tree::decode(&*data) {
+                return Ok(tree_from_proto(proto));
+            }
+        }
+        let tree = self.inner.read_tree(path, id).await?;
+        self.cache
+            .insert(CacheKind::Tree, id, &tree_to_proto(&tree).encode_to_vec());
+        Ok(tree)
+    }
+
+    async fn write_tree(&self, path: &RepoPath, contents: &Tree) -> BackendResult<TreeId> {
+        let id = self.inner.write_tree(path, contents).await?;
+        self.cache.insert(
+            CacheKind::Tree,
+            &id,
+            &tree_to_proto(contents).encode_to_vec(),
+        );
+        Ok(id)
+    }
+
+    fn read_conflict(&self, path: &RepoPath, id: &ConflictId) -> BackendResult<Conflict> {
+        self.inner.read_conflict(path, id)
+    }
+
+    fn write_conflict(&self, path: &RepoPath, contents: &Conflict) -> BackendResult<ConflictId> {
+        self.inner.write_conflict(path, contents)
+    }
+
+    async fn read_commit(&self, id: &CommitId) -> BackendResult<Commit> {
+        if let Some(data) = self.cache.get(CacheKind::Commit, id) {
+            if let Some(commit) = decode_commit_with_signature(&data) {
+                return Ok(commit);
+            }
+        }
+        let commit = self.inner.read_commit(id).await?;
+        self.cache.insert(
+            CacheKind::Commit,
+            id,
+            &encode_commit_with_signature(&commit),
+        );
+        Ok(commit)
+    }
+
+    async fn write_commit(
+        &self,
+        contents: Commit,
+        sign_with: Option<&mut SigningFn>,
+    ) -> BackendResult<(CommitId, Commit)> {
+        let (id, commit) = self.inner.write_commit(contents, sign_with).await?;
+        self.cache.insert(
+            CacheKind::Commit,
+            &id,
+            &encode_commit_with_signature(&commit),
+        );
+        Ok((id, commit))
+    }
+
+    fn get_copy_records(
+        &self,
+        paths: Option<&[RepoPathBuf]>,
+        root: &CommitId,
+        head: &CommitId,
+    ) -> BackendResult<BoxStream<'_, BackendResult<CopyRecord>>> {
+        self.inner.get_copy_records(paths, root, head)
+    }
+
+    /// Reads the tree and all its subtrees into the cache. Uncached trees are
+    /// fetched concurrently up to the concurrency of the inner backend.
+    async fn prefetch_tree(&self, path: &RepoPath, id: &TreeId) -> BackendResult<()> {
+        let mut visited = HashSet::new();
+        let mut pending: Vec<(RepoPathBuf, TreeId)> = vec![(path.to_owned(), id.clone())];
+        while !pending.is_empty() {
+            let trees: Vec<(RepoPathBuf, Tree)> = stream::iter(pending)
+                .filter(|(_, id)| futures::future::ready(visited.insert(id.clone())))
+                .map(|(dir, id)| async move {
+                    let tree = self.read_tree(&dir, &id).await?;
+                    Ok::<_, BackendError>((dir, tree))
+                })
+                .buffer_unordered(self.concurrency().max(1))
+                .try_collect()
+                .await?;
+            pending = trees
+                .iter()
+                .flat_map(|(dir, tree)| {
+                    tree.entries().filter_map(|entry| match entry.value() {
+                        TreeValue::Tree(id) => Some((dir.join(entry.name()), id.clone())),
+                        _ => None,
+                    })
+                })
+                .collect();
+        }
+        Ok(())
+    }
+
+    fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.inner.gc(index, keep_newer)
+    }
+
+    fn gc_with_stats(
+        &self,
+        index: &dyn Index,
+        keep_newer: SystemTime,
+        dry_run: bool,
+    ) -> BackendResult<BackendGcStats> {
+        self.inner.gc_with_stats(index, keep_newer, dry_run)
+    }
+}
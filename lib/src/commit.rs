@@ -29,6 +29,7 @@ use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
+use crate::backend::RecordedCopy;
 use crate::backend::Signature;
 use crate::merged_tree::MergedTree;
 use crate::repo::Repo;
@@ -157,6 +158,11 @@ impl Commit {
         &self.data.committer
     }
 
+    /// Copies and renames recorded explicitly in this commit.
+    pub fn copies(&self) -> &[RecordedCopy] {
+        &self.data.copies
+    }
+
     ///  A commit is hidden if its commit id is not in the change id index.
     pub fn is_hidden(&self, repo: &dyn Repo) -> bool {
         let maybe_entries = repo.resolve_change_id(self.change_id());
@@ -23,6 +23,7 @@ use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
+use crate::backend::RecordedCopy;
 use crate::backend::Signature;
 use crate::commit::is_backend_commit_empty;
 use crate::commit::Commit;
@@ -120,6 +121,15 @@ impl CommitBuilder<'_> {
         self
     }
 
+    pub fn copies(&self) -> &[RecordedCopy] {
+        self.inner.copies()
+    }
+
+    pub fn set_copies(mut self, copies: Vec<RecordedCopy>) -> Self {
+        self.inner.set_copies(copies);
+        self
+    }
+
     /// [`Commit::is_discardable()`] for the new commit.
     pub fn is_discardable(&self) -> BackendResult<bool> {
         self.inner.is_discardable(self.mut_repo)
@@ -187,6 +197,7 @@ impl DetachedCommitBuilder {
             author: signature.clone(),
             committer: signature,
             secure_sig: None,
+            copies: vec![],
         };
         DetachedCommitBuilder {
             store,
@@ -322,6 +333,15 @@ impl DetachedCommitBuilder {
         self
     }
 
+    pub fn copies(&self) -> &[RecordedCopy] {
+        &self.commit.copies
+    }
+
+    pub fn set_copies(&mut self, copies: Vec<RecordedCopy>) -> &mut Self {
+        self.commit.copies = copies;
+        self
+    }
+
     /// [`Commit::is_discardable()`] for the new commit.
     pub fn is_discardable(&self, repo: &dyn Repo) -> BackendResult<bool> {
         Ok(self.description().is_empty() && self.is_empty(repo)?)
@@ -15,20 +15,35 @@
 //! Code for working with copies and renames.
 
 use std::collections::HashMap;
+use std::io::Read as _;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
 
+use bstr::ByteSlice as _;
 use futures::Stream;
+use futures::StreamExt as _;
+use serde::de::IntoDeserializer as _;
+use serde::Deserialize as _;
 
+use crate::backend::BackendError;
 use crate::backend::BackendResult;
+use crate::backend::CommitId;
 use crate::backend::CopyRecord;
+use crate::backend::FileId;
+use crate::backend::TreeValue;
+use crate::commit::Commit;
+use crate::config::ConfigGetError;
+use crate::matchers::EverythingMatcher;
 use crate::merge::MergedTreeValue;
 use crate::merged_tree::MergedTree;
 use crate::merged_tree::TreeDiffStream;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
+use crate::settings::UserSettings;
+use crate::store::Store;
 
 /// A collection of CopyRecords.
 #[derive(Default, Debug)]
@@ -215,3 +230,299 @@ impl Stream for CopiesTreeDiffStream<'_> {
         Poll::Ready(None)
     }
 }
+
+/// Options for detecting copies and renames by comparing file contents.
+#[derive(Clone, Debug)]
+pub struct CopyDetectionOptions {
+    /// Minimum similarity of the source and target contents, greater than 0.0
+    /// and at most 1.0, for a file to be considered copied. Files with
+    /// identical contents are always matched.
+    pub similarity_threshold: f32,
+    /// Maximum number of source and target files to compare by content. Must
+    /// be positive. If there are more candidates, only identical files are
+    /// matched.
+    pub max_candidates: usize,
+}
+
+impl CopyDetectionOptions {
+    /// Loads the options from the `copy-detection` settings.
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, ConfigGetError> {
+        type ConvertError = Box<dyn std::error::Error + Send + Sync>;
+        let similarity_threshold =
+            settings.get_value_with("copy-detection.similarity-threshold", |value| {
+                let threshold = f32::deserialize(value.into_deserializer())?;
+                if threshold > 0.0 && threshold <= 1.0 {
+                    Ok::<_, ConvertError>(threshold)
+                } else {
+                    Err(format!("Expected a number in (0, 1], but is {threshold}").into())
+                }
+            })?;
+        let max_candidates = settings.get_value_with("copy-detection.max-candidates", |value| {
+            let max_candidates = usize::deserialize(value.into_deserializer())?;
+            if max_candidates > 0 {
+                Ok::<_, ConvertError>(max_candidates)
+            } else {
+                Err("Expected a positive number".into())
+            }
+        })?;
+        Ok(CopyDetectionOptions {
+            similarity_threshold,
+            max_candidates,
+        })
+    }
+}
+
+impl Default for CopyDetectionOptions {
+    fn default() -> Self {
+        CopyDetectionOptions {
+            similarity_threshold: 0.5,
+            max_candidates: 1000,
+        }
+    }
+}
+
+/// A file which may have been the source of a copy or rename.
+struct SourceCandidate {
+    path: RepoPathBuf,
+    id: FileId,
+    /// Whether the path was deleted in the head tree.
+    deleted: bool,
+}
+
+/// A file which may have been created by a copy or rename.
+struct TargetCandidate {
+    path: RepoPathBuf,
+    id: FileId,
+}
+
+/// Detects copies and renames between the trees of the `root` and `head`
+/// commits by comparing file contents. This works with any backend.
+///
+/// Files added in `head` are first matched against deleted or modified files
+/// with identical contents. The remaining added files are then paired with the
+/// most similar deleted or modified file, if the similarity reaches
+/// [`CopyDetectionOptions::similarity_threshold`]. Each source file is used at
+/// most once, and deleted files are preferred over modified ones.
+///
+/// If `paths` is `Some`, only copies to these paths are returned.
+pub async fn detect_copy_records(
+    store: &Arc<Store>,
+    paths: Option<&[RepoPathBuf]>,
+    root: &CommitId,
+    head: &CommitId,
+    options: &CopyDetectionOptions,
+) -> BackendResult<Vec<CopyRecord>> {
+    let root_tree = store.get_commit_async(root).await?.tree()?;
+    let head_tree = store.get_commit_async(head).await?.tree()?;
+
+    let mut sources = vec![];
+    let mut targets = vec![];
+    let mut diff_stream = root_tree.diff_stream(&head_tree, &EverythingMatcher);
+    while let Some(entry) = diff_stream.next().await {
+        let (before, after) = entry.values?;
+        let before_id = match before.as_resolved() {
+            Some(Some(TreeValue::File { id, .. })) => Some(id),
+            _ => None,
+        };
+        let after_id = match after.as_resolved() {
+            Some(Some(TreeValue::File { id, .. })) => Some(id),
+            _ => None,
+        };
+        match (before_id, after_id) {
+            (Some(id), None) => sources.push(SourceCandidate {
+                path: entry.path,
+                id: id.clone(),
+                deleted: after.is_absent() || after.is_tree(),
+            }),
+            (Some(before_id), Some(_)) => sources.push(SourceCandidate {
+                path: entry.path,
+                id: before_id.clone(),
+                deleted: false,
+            }),
+            (None, Some(id))
+                if before.is_absent() && paths.is_none_or(|paths| paths.contains(&entry.path)) =>
+            {
+                targets.push(TargetCandidate {
+                    path: entry.path,
+                    id: id.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+    // Try deleted files first so renames win over copies.
+    sources.sort_by_key(|source| !source.deleted);
+
+    let mut source_used = vec![false; sources.len()];
+    let mut target_sources: Vec<Option<usize>> = vec![None; targets.len()];
+
+    // Match identical contents.
+    let mut sources_by_id: HashMap<&FileId, Vec<usize>> = HashMap::new();
+    for (i, source) in sources.iter().enumerate() {
+        sources_by_id.entry(&source.id).or_default().push(i);
+    }
+    for (target, target_source) in targets.iter().zip(&mut target_sources) {
+        let Some(candidates) = sources_by_id.get(&target.id) else {
+            continue;
+        };
+        if let Some(&i) = candidates.iter().find(|&&i| !source_used[i]) {
+            source_used[i] = true;
+            *target_source = Some(i);
+        }
+    }
+
+    // Match similar contents.
+    let remaining_sources = (0..sources.len())
+        .filter(|&i| !source_used[i])
+        .collect::<Vec<_>>();
+    let remaining_targets = (0..targets.len())
+        .filter(|&i| target_sources[i].is_none())
+        .collect::<Vec<_>>();
+    if !remaining_sources.is_empty()
+        && !remaining_targets.is_empty()
+        && options.similarity_threshold <= 1.0
+        && remaining_sources.len() <= options.max_candidates
+        && remaining_targets.len() <= options.max_candidates
+    {
+        let mut source_contents = vec![];
+        for &i in &remaining_sources {
+            let source = &sources[i];
+            source_contents.push(read_file(store, &source.path, &source.id).await?);
+        }
+        let mut target_contents = vec![];
+        for &i in &remaining_targets {
+            let target = &targets[i];
+            target_contents.push(read_file(store, &target.path, &target.id).await?);
+        }
+        let source_lines = source_contents
+            .iter()
+            .map(|c| count_lines(c))
+            .collect::<Vec<_>>();
+        let target_lines = target_contents
+            .iter()
+            .map(|c| count_lines(c))
+            .collect::<Vec<_>>();
+
+        let mut scored_pairs = vec![];
+        for (ti, target_content) in target_contents.iter().enumerate() {
+            for (si, source_content) in source_contents.iter().enumerate() {
+                let max_len = source_content.len().max(target_content.len());
+                let min_len = source_content.len().min(target_content.len());
+                // Empty files are too common to be meaningfully matched.
+                if min_len == 0 || (min_len as f32) < options.similarity_threshold * max_len as f32
+                {
+                    continue;
+                }
+                let common_len = common_lines_len(&source_lines[si], &target_lines[ti]);
+                let score = common_len as f32 / max_len as f32;
+                if score >= options.similarity_threshold {
+                    scored_pairs.push((score, ti, si));
+                }
+            }
+        }
+        // Pick the best matches first. Ties are broken by the order of the
+        // targets and sources to make the result deterministic.
+        scored_pairs.sort_by(|(score1, ti1, si1), (score2, ti2, si2)| {
+            score2
+                .total_cmp(score1)
+                .then(ti1.cmp(ti2))
+                .then(si1.cmp(si2))
+        });
+        for (_, ti, si) in scored_pairs {
+            let (ti, si) = (remaining_targets[ti], remaining_sources[si]);
+            if target_sources[ti].is_some() || source_used[si] {
+                continue;
+            }
+            source_used[si] = true;
+            target_sources[ti] = Some(si);
+        }
+    }
+
+    let records = targets
+        .into_iter()
+        .zip(target_sources)
+        .filter_map(|(target, source_index)| {
+            let source = &sources[source_index?];
+            Some(CopyRecord {
+                target: target.path,
+                target_commit: head.clone(),
+                source: source.path.clone(),
+                source_file: source.id.clone(),
+                source_commit: root.clone(),
+            })
+        })
+        .collect();
+    Ok(records)
+}
+
+/// Returns the copies recorded explicitly in the `head` commit, relative to its
+/// parent `root`. Returns nothing if `root` isn't a parent of `head`.
+///
+/// Records whose source doesn't exist as a file in `root`, or whose target
+/// doesn't exist in `head`, are skipped. If `paths` is `Some`, only copies to
+/// these paths are returned.
+pub async fn recorded_copy_records(
+    store: &Arc<Store>,
+    paths: Option<&[RepoPathBuf]>,
+    root: &CommitId,
+    head: &Commit,
+) -> BackendResult<Vec<CopyRecord>> {
+    if head.copies().is_empty() || !head.parent_ids().contains(root) {
+        return Ok(vec![]);
+    }
+    let root_tree = store.get_commit_async(root).await?.tree()?;
+    let head_tree = head.tree()?;
+    let mut records = vec![];
+    for copy in head.copies() {
+        if paths.is_some_and(|paths| !paths.contains(&copy.target)) {
+            continue;
+        }
+        let Some(Some(TreeValue::File { id, .. })) =
+            root_tree.path_value(&copy.source)?.into_resolved().ok()
+        else {
+            continue;
+        };
+        if head_tree.path_value(&copy.target)?.is_absent() {
+            continue;
+        }
+        records.push(CopyRecord {
+            target: copy.target.clone(),
+            target_commit: head.id().clone(),
+            source: copy.source.clone(),
+            source_file: id,
+            source_commit: root.clone(),
+        });
+    }
+    Ok(records)
+}
+
+async fn read_file(store: &Store, path: &RepoPath, id: &FileId) -> BackendResult<Vec<u8>> {
+    let mut content = vec![];
+    store
+        .read_file_async(path, id)
+        .await?
+        .read_to_end(&mut content)
+        .map_err(|err| BackendError::ReadFile {
+            path: path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })?;
+    Ok(content)
+}
+
+/// Counts the total length of each distinct line in the `content`.
+fn count_lines(content: &[u8]) -> HashMap<&[u8], usize> {
+    let mut lines: HashMap<&[u8], usize> = HashMap::new();
+    for line in content.lines_with_terminator() {
+        *lines.entry(line).or_default() += line.len();
+    }
+    lines
+}
+
+/// Returns the number of bytes in lines shared by both sides.
+fn common_lines_len(lines1: &HashMap<&[u8], usize>, lines2: &HashMap<&[u8], usize>) -> usize {
+    lines1
+        .iter()
+        .filter_map(|(line, len1)| Some(*len1.min(lines2.get(line)?)))
+        .sum()
+}
@@ -0,0 +1,266 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-commit Bloom filters of changed paths.
+//!
+//! Each filter is encoded as follows:
+//! ```text
+//! (empty): changed paths are unknown; the commit may touch any path
+//! u8: number of hash functions (0 means no paths changed)
+//! <remaining bytes>: filter bits
+//! ```
+//!
+//! A changed file path is recorded along with all its ancestor directories,
+//! so a query for a directory matches commits that changed files under it.
+
+#![allow(missing_docs)]
+
+use std::collections::HashSet;
+use std::iter;
+use std::sync::Arc;
+
+use blake2::Blake2b512;
+use digest::Digest as _;
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+
+use super::composite::CompositeIndex;
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::commit::Commit;
+use crate::fileset::FilePattern;
+use crate::fileset::FilesetExpression;
+use crate::matchers::EverythingMatcher;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::rewrite;
+use crate::store::Store;
+
+const NUM_HASH_FUNCTIONS: u8 = 7;
+const BITS_PER_PATH: usize = 10;
+/// Commits changing more paths than this are recorded as unknown.
+const MAX_PATHS_PER_FILTER: usize = 1024;
+
+/// Pair of hashes of a path, combined to derive bit positions.
+#[derive(Clone, Copy, Debug)]
+struct PathKey(u64, u64);
+
+impl PathKey {
+    fn new(path: &RepoPath) -> Self {
+        let hash = Blake2b512::digest(path.as_internal_file_string().as_bytes());
+        let h1 = u64::from_le_bytes(hash[0..8].try_into().unwrap());
+        // Make the step odd so it doesn't degenerate to a single bit.
+        let h2 = u64::from_le_bytes(hash[8..16].try_into().unwrap()) | 1;
+        PathKey(h1, h2)
+    }
+
+    fn bit_positions(self, num_hashes: u8, num_bits: u64) -> impl Iterator<Item = u64> {
+        let PathKey(h1, h2) = self;
+        (0..u64::from(num_hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+/// Builds encoded filter from the given changed `paths`.
+pub(super) fn build_filter<'a>(paths: impl IntoIterator<Item = &'a RepoPath>) -> Vec<u8> {
+    let mut all_paths = HashSet::new();
+    for path in paths {
+        let ancestors = iter::successors(Some(path), |path| path.parent());
+        for path in ancestors.take_while(|path| !path.is_root()) {
+            if !all_paths.insert(path) {
+                break; // ancestors must have been inserted
+            }
+        }
+        if all_paths.len() > MAX_PATHS_PER_FILTER {
+            return vec![];
+        }
+    }
+    if all_paths.is_empty() {
+        return vec![0];
+    }
+    let num_bytes = (all_paths.len() * BITS_PER_PATH).div_ceil(8);
+    let num_bits = u64::try_from(num_bytes * 8).unwrap();
+    let mut buf = vec![0; 1 + num_bytes];
+    buf[0] = NUM_HASH_FUNCTIONS;
+    let bits = &mut buf[1..];
+    for path in all_paths {
+        for bit in PathKey::new(path).bit_positions(NUM_HASH_FUNCTIONS, num_bits) {
+            bits[(bit / 8) as usize] |= 1 << (bit % 8);
+        }
+    }
+    buf
+}
+
+/// Computes encoded filter of the paths changed by the `commit` relative to
+/// its parents.
+///
+/// Conflicts between the parents aren't resolved, so the recorded paths are a
+/// superset of the paths `files()` would match.
+pub(super) fn compute_filter(
+    store: &Arc<Store>,
+    index: &CompositeIndex,
+    commit: &Commit,
+) -> BackendResult<Vec<u8>> {
+    let parents: Vec<_> = commit.parents().try_collect()?;
+    if let [parent] = parents.as_slice() {
+        if parent.tree_id() == commit.tree_id() {
+            return Ok(build_filter([]));
+        }
+    }
+    let from_tree = rewrite::merge_commit_trees_no_resolve_without_repo(store, &index, &parents)?;
+    let to_tree = commit.tree()?;
+    let mut tree_diff = from_tree.diff_stream(&to_tree, &EverythingMatcher);
+    let mut paths = vec![];
+    async {
+        while let Some(entry) = tree_diff.next().await {
+            entry.values?;
+            paths.push(entry.path);
+        }
+        Ok::<_, BackendError>(())
+    }
+    .block_on()?;
+    Ok(build_filter(paths.iter().map(AsRef::as_ref)))
+}
+
+/// Paths to look up in changed-path filters.
+///
+/// A commit can only match the fileset if its filter may contain any of the
+/// paths.
+#[derive(Clone, Debug)]
+pub(super) struct ChangedPathQuery {
+    keys: Vec<PathKey>,
+}
+
+impl ChangedPathQuery {
+    /// Extracts paths from the given fileset `expression`. Returns `None` if
+    /// the fileset may match arbitrary paths.
+    pub(super) fn from_fileset(expression: &FilesetExpression) -> Option<Self> {
+        let paths = collect_prefix_paths(expression)?;
+        let keys = paths.iter().map(|path| PathKey::new(path)).collect();
+        Some(ChangedPathQuery { keys })
+    }
+
+    /// Returns false if the commit of the encoded `filter` can't have changed
+    /// any of the queried paths.
+    pub(super) fn may_match(&self, filter: &[u8]) -> bool {
+        let Some((&num_hashes, bits)) = filter.split_first() else {
+            return true;
+        };
+        if num_hashes == 0 || bits.is_empty() {
+            return false;
+        }
+        let num_bits = u64::try_from(bits.len() * 8).unwrap();
+        self.keys.iter().any(|&key| {
+            key.bit_positions(num_hashes, num_bits)
+                .all(|bit| bits[(bit / 8) as usize] & (1 << (bit % 8)) != 0)
+        })
+    }
+}
+
+/// Returns paths one of which must be an ancestor of (or equal to) any path
+/// matched by the `expression`.
+fn collect_prefix_paths(expression: &FilesetExpression) -> Option<Vec<&RepoPathBuf>> {
+    let path = match expression {
+        FilesetExpression::None => return Some(vec![]),
+        FilesetExpression::All => return None,
+        FilesetExpression::Pattern(pattern) => match pattern {
+            FilePattern::FilePath(path) | FilePattern::PrefixPath(path) => path,
+            FilePattern::FileGlob { dir, .. } => dir,
+        },
+        FilesetExpression::UnionAll(expressions) => {
+            let mut paths = vec![];
+            for expression in expressions {
+                paths.extend(collect_prefix_paths(expression)?);
+            }
+            return Some(paths);
+        }
+        FilesetExpression::Intersection(expression1, expression2) => {
+            return collect_prefix_paths(expression1).or_else(|| collect_prefix_paths(expression2));
+        }
+        FilesetExpression::Difference(expression1, _) => {
+            return collect_prefix_paths(expression1);
+        }
+    };
+    // Root path would match any non-empty commit.
+    (!path.is_root()).then(|| vec![path])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_path(value: &str) -> &RepoPath {
+        RepoPath::from_internal_string(value).unwrap()
+    }
+
+    fn query(paths: &[&str]) -> ChangedPathQuery {
+        let expression = FilesetExpression::union_all(
+            paths
+                .iter()
+                .map(|path| FilesetExpression::prefix_path(repo_path(path).to_owned()))
+                .collect(),
+        );
+        ChangedPathQuery::from_fileset(&expression).unwrap()
+    }
+
+    #[test]
+    fn test_changed_path_filter() {
+        let filter = build_filter([repo_path("dir/sub/file"), repo_path("top")]);
+        assert!(query(&["dir/sub/file"]).may_match(&filter));
+        assert!(query(&["dir/sub"]).may_match(&filter));
+        assert!(query(&["dir"]).may_match(&filter));
+        assert!(query(&["top"]).may_match(&filter));
+        assert!(query(&["nonexistent", "top"]).may_match(&filter));
+        assert!(!query(&[]).may_match(&filter));
+
+        // Unchanged commit can be filtered out
+        let filter = build_filter([]);
+        assert_eq!(filter, [0]);
+        assert!(!query(&["dir"]).may_match(&filter));
+
+        // Unknown changes must be examined
+        assert!(query(&["dir"]).may_match(&[]));
+        let paths: Vec<_> = (0..=MAX_PATHS_PER_FILTER)
+            .map(|i| RepoPathBuf::from_internal_string(format!("file{i}")).unwrap())
+            .collect();
+        let filter = build_filter(paths.iter().map(AsRef::as_ref));
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_changed_path_query_from_fileset() {
+        let file = || FilesetExpression::file_path(repo_path("file").to_owned());
+        let root = FilesetExpression::prefix_path(RepoPathBuf::root());
+        assert!(ChangedPathQuery::from_fileset(&file()).is_some());
+        assert!(ChangedPathQuery::from_fileset(&FilesetExpression::all()).is_none());
+        assert!(ChangedPathQuery::from_fileset(&root).is_none());
+        assert!(
+            ChangedPathQuery::from_fileset(&FilesetExpression::union_all(vec![
+                file(),
+                FilesetExpression::all(),
+            ]))
+            .is_none()
+        );
+        assert!(
+            ChangedPathQuery::from_fileset(&FilesetExpression::all().intersection(file()))
+                .is_some()
+        );
+        assert!(
+            ChangedPathQuery::from_fileset(&file().difference(FilesetExpression::all())).is_some()
+        );
+        assert!(
+            ChangedPathQuery::from_fileset(&FilesetExpression::all().difference(file())).is_none()
+        );
+    }
+}
@@ -91,6 +91,10 @@ pub(super) trait IndexSegment: Send + Sync {
     fn num_parents(&self, local_pos: LocalPosition) -> u32;
 
     fn parent_positions(&self, local_pos: LocalPosition) -> SmallIndexPositionsVec;
+
+    /// Returns encoded filter of the paths changed by the commit, or `None` if
+    /// this segment doesn't record changed paths.
+    fn changed_path_filter(&self, local_pos: LocalPosition) -> Option<&[u8]>;
 }
 
 pub(super) type DynIndexSegment = dyn IndexSegment;
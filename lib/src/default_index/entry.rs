@@ -114,6 +114,10 @@ impl<'a> IndexEntry<'a> {
         self.source.parent_positions(self.local_pos)
     }
 
+    pub(super) fn changed_path_filter(&self) -> Option<&'a [u8]> {
+        self.source.changed_path_filter(self.local_pos)
+    }
+
     pub fn parents(&self) -> impl ExactSizeIterator<Item = IndexEntry<'a>> + use<'a> {
         let composite = CompositeIndex::new(self.source);
         self.parent_positions()
@@ -21,6 +21,7 @@
 
 #![allow(missing_docs)]
 
+mod changed_path;
 mod composite;
 mod entry;
 mod mutable;
@@ -45,16 +46,19 @@ pub use self::store::DefaultIndexStoreInitError;
 
 #[cfg(test)]
 mod tests {
+    use std::slice;
     use std::sync::Arc;
 
     use itertools::Itertools as _;
     use smallvec::smallvec_inline;
     use test_case::test_case;
 
+    use super::changed_path;
     use super::composite::DynIndexSegment;
     use super::composite::IndexSegment as _;
     use super::entry::SmallIndexPositionsVec;
     use super::mutable::MutableIndexSegment;
+    use super::readonly::ReadonlyIndexSegment;
     use super::*;
     use crate::backend::ChangeId;
     use crate::backend::CommitId;
@@ -64,6 +68,7 @@ mod tests {
     use crate::object_id::HexPrefix;
     use crate::object_id::ObjectId as _;
     use crate::object_id::PrefixResolution;
+    use crate::repo_path::RepoPath;
     use crate::tests::new_temp_dir;
 
     /// Generator of unique 16-byte CommitId excluding root id
@@ -1222,4 +1227,87 @@ mod tests {
             vec![id_3.clone(), id_5.clone()]
         );
     }
+
+    #[test]
+    fn index_changed_path_filters() {
+        let temp_dir = new_temp_dir();
+        let mut new_change_id = change_id_generator();
+        let id_0 = CommitId::from_hex("000000");
+        let id_1 = CommitId::from_hex("111111");
+        let id_2 = CommitId::from_hex("222222");
+        let filter_0 = changed_path::build_filter([]);
+        let filter_1 =
+            changed_path::build_filter([RepoPath::from_internal_string("dir/file").unwrap()]);
+
+        // Segment without filters
+        let mut mutable_segment = MutableIndexSegment::full(3, 16);
+        mutable_segment.add_commit_data(id_0.clone(), new_change_id(), &[]);
+        let saved_index = mutable_segment.save_in(temp_dir.path()).unwrap();
+        assert!(!saved_index.has_changed_path_filters());
+        let entry = saved_index.as_composite().entry_by_id(&id_0).unwrap();
+        assert_eq!(entry.changed_path_filter(), None);
+
+        let mut mutable_segment = MutableIndexSegment::full(3, 16);
+        mutable_segment.enable_changed_path_filters();
+        mutable_segment
+            .add_commit_data_with_filter(id_0.clone(), new_change_id(), &[], |_| filter_0.clone());
+        mutable_segment.add_commit_data_with_filter(
+            id_1.clone(),
+            new_change_id(),
+            slice::from_ref(&id_0),
+            |_| filter_1.clone(),
+        );
+        let initial_file = mutable_segment.save_in(temp_dir.path()).unwrap();
+        assert!(initial_file.has_changed_path_filters());
+
+        // Filters should be preserved when squashing segments. Commits added
+        // without filter are recorded as unknown.
+        let mut index = DefaultMutableIndex::incremental(initial_file);
+        index.add_commit_data(id_2.clone(), new_change_id(), slice::from_ref(&id_1));
+        let saved_index = index.squash_and_save_in(temp_dir.path()).unwrap();
+        assert!(saved_index.parent_file().is_none());
+        assert!(saved_index.has_changed_path_filters());
+        let index = saved_index.as_composite();
+        let filter = |id| index.entry_by_id(id).unwrap().changed_path_filter();
+        assert_eq!(filter(&id_0), Some(filter_0.as_slice()));
+        assert_eq!(filter(&id_1), Some(filter_1.as_slice()));
+        assert_eq!(filter(&id_2), Some([].as_slice()));
+    }
+
+    #[test]
+    fn index_changed_path_filters_corrupt_offsets() {
+        let temp_dir = new_temp_dir();
+        let mut new_change_id = change_id_generator();
+        let id_0 = CommitId::from_hex("000000");
+        let id_1 = CommitId::from_hex("111111");
+        let filter =
+            changed_path::build_filter([RepoPath::from_internal_string("dir/file").unwrap()]);
+
+        let mut mutable_segment = MutableIndexSegment::full(3, 16);
+        mutable_segment.enable_changed_path_filters();
+        mutable_segment
+            .add_commit_data_with_filter(id_0.clone(), new_change_id(), &[], |_| filter.clone());
+        mutable_segment
+            .add_commit_data_with_filter(id_1, new_change_id(), &[id_0], |_| filter.clone());
+        let saved_index = mutable_segment.save_in(temp_dir.path()).unwrap();
+        let name = saved_index.name().to_owned();
+
+        // Make the first filter end past the end of the second filter
+        let path = temp_dir.path().join(&name);
+        let mut data = std::fs::read(&path).unwrap();
+        let offsets_end = data.len() - filter.len() * 2;
+        let bad_offset = u32::try_from(filter.len() * 2 + 1).unwrap();
+        data[offsets_end - 8..offsets_end - 4].copy_from_slice(&bad_offset.to_le_bytes());
+        std::fs::write(&path, data).unwrap();
+
+        let err = ReadonlyIndexSegment::load(temp_dir.path(), name, 3, 16).unwrap_err();
+        assert!(err.is_corrupt_or_not_found());
+        let ReadonlyIndexLoadError::Other { error, .. } = err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(
+            error.to_string(),
+            "changed-path filter offsets are out of order"
+        );
+    }
 }
@@ -31,6 +31,7 @@ use smallvec::smallvec;
 use smallvec::SmallVec;
 use tempfile::NamedTempFile;
 
+use super::changed_path;
 use super::composite::AsCompositeIndex;
 use super::composite::ChangeIdIndexImpl;
 use super::composite::CompositeIndex;
@@ -78,6 +79,8 @@ pub(super) struct MutableIndexSegment {
     graph: Vec<MutableGraphEntry>,
     commit_lookup: BTreeMap<CommitId, LocalPosition>,
     change_lookup: BTreeMap<ChangeId, SmallLocalPositionsVec>,
+    /// Encoded changed-path filter per entry if enabled.
+    changed_path_filters: Option<Vec<Vec<u8>>>,
 }
 
 impl MutableIndexSegment {
@@ -90,6 +93,7 @@ impl MutableIndexSegment {
             graph: vec![],
             commit_lookup: BTreeMap::new(),
             change_lookup: BTreeMap::new(),
+            changed_path_filters: None,
         }
     }
 
@@ -97,6 +101,7 @@ impl MutableIndexSegment {
         let num_parent_commits = parent_file.as_composite().num_commits();
         let commit_id_length = parent_file.commit_id_length();
         let change_id_length = parent_file.change_id_length();
+        let changed_path_filters = parent_file.has_changed_path_filters().then(Vec::new);
         Self {
             parent_file: Some(parent_file),
             num_parent_commits,
@@ -105,18 +110,32 @@ impl MutableIndexSegment {
             graph: vec![],
             commit_lookup: BTreeMap::new(),
             change_lookup: BTreeMap::new(),
+            changed_path_filters,
         }
     }
 
+    /// Enables recording of changed-path filters for commits to be added.
+    pub(super) fn enable_changed_path_filters(&mut self) {
+        assert!(self.graph.is_empty());
+        self.changed_path_filters = Some(vec![]);
+    }
+
     pub(super) fn as_composite(&self) -> &CompositeIndex {
         CompositeIndex::new(self)
     }
 
     pub(super) fn add_commit(&mut self, commit: &Commit) {
-        self.add_commit_data(
+        if self.as_composite().has_id(commit.id()) {
+            return;
+        }
+        self.add_commit_data_with_filter(
             commit.id().clone(),
             commit.change_id().clone(),
             commit.parent_ids(),
+            |index| {
+                // If the trees can't be read, the changes are recorded as unknown.
+                changed_path::compute_filter(commit.store(), index, commit).unwrap_or_default()
+            },
         );
     }
 
@@ -125,6 +144,16 @@ impl MutableIndexSegment {
         commit_id: CommitId,
         change_id: ChangeId,
         parent_ids: &[CommitId],
+    ) {
+        self.add_commit_data_with_filter(commit_id, change_id, parent_ids, |_| vec![]);
+    }
+
+    pub(super) fn add_commit_data_with_filter(
+        &mut self,
+        commit_id: CommitId,
+        change_id: ChangeId,
+        parent_ids: &[CommitId],
+        compute_changed_path_filter: impl FnOnce(&CompositeIndex) -> Vec<u8>,
     ) {
         if self.as_composite().has_id(&commit_id) {
             return;
@@ -146,6 +175,10 @@ impl MutableIndexSegment {
             );
             entry.parent_positions.push(parent_entry.position());
         }
+        let changed_path_filter = self
+            .changed_path_filters
+            .is_some()
+            .then(|| compute_changed_path_filter(self.as_composite()));
         let local_pos = LocalPosition(u32::try_from(self.graph.len()).unwrap());
         self.commit_lookup
             .insert(entry.commit_id.clone(), local_pos);
@@ -155,6 +188,9 @@ impl MutableIndexSegment {
             .and_modify(|positions| positions.push(local_pos))
             .or_insert(smallvec![local_pos]);
         self.graph.push(entry);
+        if let Some(filters) = &mut self.changed_path_filters {
+            filters.extend(changed_path_filter);
+        }
     }
 
     pub(super) fn add_commits_from(&mut self, other_segment: &DynIndexSegment) {
@@ -162,7 +198,13 @@ impl MutableIndexSegment {
         for pos in other_segment.num_parent_commits()..other.num_commits() {
             let entry = other.entry_by_pos(IndexPosition(pos));
             let parent_ids = entry.parents().map(|entry| entry.commit_id()).collect_vec();
-            self.add_commit_data(entry.commit_id(), entry.change_id(), &parent_ids);
+            let filter = entry.changed_path_filter().unwrap_or_default();
+            self.add_commit_data_with_filter(
+                entry.commit_id(),
+                entry.change_id(),
+                &parent_ids,
+                |_| filter.to_vec(),
+            );
         }
     }
 
@@ -228,6 +270,9 @@ impl MutableIndexSegment {
         buf.extend(0_u32.to_le_bytes());
         let change_overflow_offset = buf.len();
         buf.extend(0_u32.to_le_bytes());
+        let changed_path_filters = self.changed_path_filters.as_deref().unwrap_or_default();
+        let num_changed_path_filters = u32::try_from(changed_path_filters.len()).unwrap();
+        buf.extend(num_changed_path_filters.to_le_bytes());
 
         // Positions of change ids in the sorted table
         let change_id_pos_map: HashMap<&ChangeId, u32> = self
@@ -312,6 +357,15 @@ impl MutableIndexSegment {
         for LocalPosition(pos) in change_overflow {
             buf.extend(pos.to_le_bytes());
         }
+
+        let mut filter_end = 0;
+        for filter in changed_path_filters {
+            filter_end += u32::try_from(filter.len()).unwrap();
+            buf.extend(filter_end.to_le_bytes());
+        }
+        for filter in changed_path_filters {
+            buf.extend_from_slice(filter);
+        }
     }
 
     /// If the MutableIndex has more than half the commits of its parent
@@ -341,6 +395,7 @@ impl MutableIndexSegment {
         } else {
             MutableIndexSegment::full(self.commit_id_length, self.change_id_length)
         };
+        squashed.changed_path_filters = self.changed_path_filters.as_ref().map(|_| vec![]);
         for parent_file in files_to_squash.iter().rev() {
             squashed.add_commits_from(parent_file.as_ref());
         }
@@ -453,6 +508,11 @@ impl IndexSegment for MutableIndexSegment {
     fn parent_positions(&self, local_pos: LocalPosition) -> SmallIndexPositionsVec {
         self.graph[local_pos.0 as usize].parent_positions.clone()
     }
+
+    fn changed_path_filter(&self, local_pos: LocalPosition) -> Option<&[u8]> {
+        let filters = self.changed_path_filters.as_ref()?;
+        Some(&filters[local_pos.0 as usize])
+    }
 }
 
 /// In-memory mutable records for the on-disk commit index backend.
@@ -469,7 +529,10 @@ impl DefaultMutableIndex {
         DefaultMutableIndex(mutable_segment)
     }
 
-    #[cfg(test)]
+    pub(super) fn enable_changed_path_filters(&mut self) {
+        self.0.enable_changed_path_filters();
+    }
+
     pub(crate) fn add_commit_data(
         &mut self,
         commit_id: CommitId,
@@ -104,7 +104,7 @@ impl ReadonlyIndexLoadError {
 }
 
 /// Current format version of the index segment file.
-pub(crate) const INDEX_SEGMENT_FILE_FORMAT_VERSION: u32 = 6;
+pub(crate) const INDEX_SEGMENT_FILE_FORMAT_VERSION: u32 = 7;
 
 /// If set, the value is stored in the overflow table.
 pub(crate) const OVERFLOW_FLAG: u32 = 0x8000_0000;
@@ -186,6 +186,7 @@ impl CommitGraphEntry<'_> {
 /// u32: number of local change ids
 /// u32: number of overflow parent entries
 /// u32: number of overflow change id positions
+/// u32: number of changed-path filters (0 or number of local commit entries)
 /// for each entry, in some topological order with parents first:
 ///   u32: generation number
 ///   if number of parents <= 2:
@@ -211,8 +212,13 @@ impl CommitGraphEntry<'_> {
 ///   u32: global index position
 /// for each overflow change id entry:
 ///   u32: local position in the graph entries table
+/// for each changed-path filter, in the graph entries order:
+///   u32: end offset of the filter in the filter data
+/// <end offset of the last filter number of bytes>: changed-path filter data
 /// ```
 ///
+/// See the `changed_path` module for the encoding of changed-path filters.
+///
 /// Note that u32 fields are 4-byte aligned so long as the parent file name
 /// (which is hexadecimal hash) and commit/change ids aren't of exotic length.
 // TODO: replace the table by a trie so we don't have to repeat the full commit
@@ -228,12 +234,15 @@ pub(super) struct ReadonlyIndexSegment {
     num_local_commits: u32,
     num_local_change_ids: u32,
     num_change_overflow_entries: u32,
+    num_changed_path_filters: u32,
     // Base data offsets in bytes:
     commit_lookup_base: usize,
     change_id_table_base: usize,
     change_pos_table_base: usize,
     parent_overflow_base: usize,
     change_overflow_base: usize,
+    changed_path_offsets_base: usize,
+    changed_path_data_base: usize,
     data: Vec<u8>,
 }
 
@@ -329,6 +338,7 @@ impl ReadonlyIndexSegment {
         let num_local_change_ids = read_u32(file)?;
         let num_parent_overflow_entries = read_u32(file)?;
         let num_change_overflow_entries = read_u32(file)?;
+        let num_changed_path_filters = read_u32(file)?;
         let mut data = vec![];
         file.read_to_end(&mut data).map_err(from_io_err)?;
 
@@ -339,6 +349,7 @@ impl ReadonlyIndexSegment {
         let change_pos_table_size = (num_local_change_ids as usize) * 4;
         let parent_overflow_size = (num_parent_overflow_entries as usize) * 4;
         let change_overflow_size = (num_change_overflow_entries as usize) * 4;
+        let changed_path_offsets_size = (num_changed_path_filters as usize) * 4;
 
         let graph_base = 0;
         let commit_lookup_base = graph_base + graph_size;
@@ -346,7 +357,24 @@ impl ReadonlyIndexSegment {
         let change_pos_table_base = change_id_table_base + change_id_table_size;
         let parent_overflow_base = change_pos_table_base + change_pos_table_size;
         let change_overflow_base = parent_overflow_base + parent_overflow_size;
-        let expected_size = change_overflow_base + change_overflow_size;
+        let changed_path_offsets_base = change_overflow_base + change_overflow_size;
+        let changed_path_data_base = changed_path_offsets_base + changed_path_offsets_size;
+
+        if num_changed_path_filters != 0 && num_changed_path_filters != num_local_commits {
+            return Err(ReadonlyIndexLoadError::invalid_data(
+                name,
+                "unexpected number of changed-path filters",
+            ));
+        }
+        let changed_path_data_size = if num_changed_path_filters > 0 {
+            let offset = changed_path_data_base - 4;
+            data.get(offset..changed_path_data_base).map_or(0, |bytes| {
+                u32::from_le_bytes(bytes.try_into().unwrap()) as usize
+            })
+        } else {
+            0
+        };
+        let expected_size = changed_path_data_base + changed_path_data_size;
 
         if data.len() != expected_size {
             return Err(ReadonlyIndexLoadError::invalid_data(
@@ -354,6 +382,17 @@ impl ReadonlyIndexSegment {
                 "unexpected data length",
             ));
         }
+        // The last offset is the data size, so the filters stay within the data
+        // if the offsets are in order.
+        let changed_path_offsets = data[changed_path_offsets_base..changed_path_data_base]
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()));
+        if !changed_path_offsets.is_sorted() {
+            return Err(ReadonlyIndexLoadError::invalid_data(
+                name,
+                "changed-path filter offsets are out of order",
+            ));
+        }
 
         Ok(Arc::new(ReadonlyIndexSegment {
             parent_file,
@@ -364,11 +403,14 @@ impl ReadonlyIndexSegment {
             num_local_commits,
             num_local_change_ids,
             num_change_overflow_entries,
+            num_changed_path_filters,
             commit_lookup_base,
             change_id_table_base,
             change_pos_table_base,
             parent_overflow_base,
             change_overflow_base,
+            changed_path_offsets_base,
+            changed_path_data_base,
             data,
         }))
     }
@@ -389,6 +431,10 @@ impl ReadonlyIndexSegment {
         self.change_id_length
     }
 
+    pub(super) fn has_changed_path_filters(&self) -> bool {
+        self.num_changed_path_filters > 0
+    }
+
     fn graph_entry(&self, local_pos: LocalPosition) -> CommitGraphEntry {
         let table = &self.data[..self.commit_lookup_base];
         let entry_size = CommitGraphEntry::size(self.commit_id_length);
@@ -436,13 +482,19 @@ impl ReadonlyIndexSegment {
         &self,
         overflow_pos: u32,
     ) -> impl Iterator<Item = LocalPosition> + use<'_> {
-        let table = &self.data[self.change_overflow_base..];
+        let table = &self.data[self.change_overflow_base..self.changed_path_offsets_base];
         let offset = (overflow_pos as usize) * 4;
         table[offset..]
             .chunks_exact(4)
             .map(|chunk| LocalPosition(u32::from_le_bytes(chunk.try_into().unwrap())))
     }
 
+    fn changed_path_filter_end(&self, local_pos: LocalPosition) -> usize {
+        let table = &self.data[self.changed_path_offsets_base..self.changed_path_data_base];
+        let offset = (local_pos.0 as usize) * 4;
+        u32::from_le_bytes(table[offset..][..4].try_into().unwrap()) as usize
+    }
+
     /// Binary searches commit id by `prefix`. Returns the lookup position.
     fn commit_id_byte_prefix_to_lookup_pos(&self, prefix: &[u8]) -> PositionLookupResult {
         binary_search_pos_by(self.num_local_commits, |pos| {
@@ -587,6 +639,18 @@ impl IndexSegment for ReadonlyIndexSegment {
             self.overflow_parents(overflow_pos, num_parents)
         }
     }
+
+    fn changed_path_filter(&self, local_pos: LocalPosition) -> Option<&[u8]> {
+        if !self.has_changed_path_filters() {
+            return None;
+        }
+        let start = match local_pos.0.checked_sub(1) {
+            Some(prev_pos) => self.changed_path_filter_end(LocalPosition(prev_pos)),
+            None => 0,
+        };
+        let end = self.changed_path_filter_end(local_pos);
+        Some(&self.data[self.changed_path_data_base..][start..end])
+    }
 }
 
 /// Commit index backend which stores data on local disk.
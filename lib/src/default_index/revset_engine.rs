@@ -32,6 +32,7 @@ use futures::StreamExt as _;
 use itertools::Itertools as _;
 use pollster::FutureExt as _;
 
+use super::changed_path::ChangedPathQuery;
 use super::rev_walk::EagerRevWalk;
 use super::rev_walk::PeekableRevWalk;
 use super::rev_walk::RevWalk;
@@ -46,6 +47,7 @@ use crate::conflicts::materialize_tree_value;
 use crate::conflicts::MaterializedTreeValue;
 use crate::default_index::AsCompositeIndex;
 use crate::default_index::CompositeIndex;
+use crate::default_index::IndexEntry;
 use crate::default_index::IndexPosition;
 use crate::diff::Diff;
 use crate::diff::DiffHunkKind;
@@ -65,8 +67,12 @@ use crate::revset::RevsetEvaluationError;
 use crate::revset::RevsetFilterPredicate;
 use crate::revset::GENERATION_RANGE_FULL;
 use crate::rewrite;
+use crate::signing::SigStatus;
+use crate::signing::SignError;
+use crate::signing::Verification;
 use crate::store::Store;
 use crate::str_util::StringPattern;
+use crate::trailer::parse_description_trailers;
 use crate::union_find;
 
 type BoxedPredicateFn<'a> =
@@ -783,6 +789,18 @@ struct EvaluationContext<'index> {
     index: &'index CompositeIndex,
 }
 
+/// Verifies the commit signature. Signature that can't be parsed is reported as
+/// bad. Results are cached by the signer.
+fn verify_commit_signature(commit: &Commit) -> Result<Option<Verification>, RevsetEvaluationError> {
+    match commit.verification() {
+        Ok(verification) => Ok(verification),
+        Err(SignError::InvalidSignatureFormat) => {
+            Ok(Some(Verification::new(SigStatus::Bad, None, None)))
+        }
+        Err(err) => Err(RevsetEvaluationError::Other(err.into())),
+    }
+}
+
 fn to_u32_generation_range(range: &Range<u64>) -> Result<Range<u32>, RevsetEvaluationError> {
     let start = range.start.try_into().map_err(|_| {
         RevsetEvaluationError::Other(
@@ -1173,6 +1191,18 @@ fn build_predicate_fn(
                 Ok(pattern.matches(commit.description().lines().next().unwrap_or_default()))
             })
         }
+        RevsetFilterPredicate::Trailer { key, value } => {
+            let key_pattern = key.clone();
+            let value_pattern = value.clone();
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id())?;
+                let trailers = parse_description_trailers(commit.description());
+                Ok(trailers.iter().any(|trailer| {
+                    key_pattern.matches(&trailer.key) && value_pattern.matches(&trailer.value)
+                }))
+            })
+        }
         RevsetFilterPredicate::AuthorName(pattern) => {
             let pattern = pattern.clone();
             box_pure_predicate_fn(move |index, pos| {
@@ -1225,8 +1255,12 @@ fn build_predicate_fn(
         }
         RevsetFilterPredicate::File(expr) => {
             let matcher: Rc<dyn Matcher> = expr.to_matcher().into();
+            let query = ChangedPathQuery::from_fileset(expr);
             box_pure_predicate_fn(move |index, pos| {
                 let entry = index.entry_by_pos(pos);
+                if !may_have_changed_paths(&entry, query.as_ref()) {
+                    return Ok(false);
+                }
                 let commit = store.get_commit(&entry.commit_id())?;
                 Ok(has_diff_from_parent(&store, index, &commit, &*matcher)?)
             })
@@ -1234,8 +1268,12 @@ fn build_predicate_fn(
         RevsetFilterPredicate::DiffContains { text, files } => {
             let text_pattern = text.clone();
             let files_matcher: Rc<dyn Matcher> = files.to_matcher().into();
+            let query = ChangedPathQuery::from_fileset(files);
             box_pure_predicate_fn(move |index, pos| {
                 let entry = index.entry_by_pos(pos);
+                if !may_have_changed_paths(&entry, query.as_ref()) {
+                    return Ok(false);
+                }
                 let commit = store.get_commit(&entry.commit_id())?;
                 Ok(matches_diff_from_parent(
                     &store,
@@ -1256,6 +1294,30 @@ fn build_predicate_fn(
             let commit = store.get_commit(&entry.commit_id())?;
             Ok(commit.is_signed())
         }),
+        RevsetFilterPredicate::SignedBy(pattern) => {
+            let pattern = pattern.clone();
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id())?;
+                let Some(verification) = verify_commit_signature(&commit)? else {
+                    return Ok(false);
+                };
+                Ok(verification.status == SigStatus::Good
+                    && [&verification.key, &verification.display]
+                        .into_iter()
+                        .flatten()
+                        .any(|s| pattern.matches(s)))
+            })
+        }
+        RevsetFilterPredicate::SignatureStatus(status) => {
+            let status = *status;
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id())?;
+                let verification = verify_commit_signature(&commit)?;
+                Ok(verification.is_some_and(|verification| verification.status == status))
+            })
+        }
         RevsetFilterPredicate::Extension(ext) => {
             let ext = ext.clone();
             box_pure_predicate_fn(move |index, pos| {
@@ -1267,6 +1329,15 @@ fn build_predicate_fn(
     }
 }
 
+/// Returns false if the changed-path filter tells that the commit doesn't
+/// touch any of the queried paths.
+fn may_have_changed_paths(entry: &IndexEntry, query: Option<&ChangedPathQuery>) -> bool {
+    match (query, entry.changed_path_filter()) {
+        (Some(query), Some(filter)) => query.may_match(filter),
+        _ => true,
+    }
+}
+
 fn has_diff_from_parent(
     store: &Arc<Store>,
     index: &CompositeIndex,
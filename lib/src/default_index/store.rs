@@ -27,12 +27,14 @@ use itertools::Itertools as _;
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
+use super::composite::AsCompositeIndex as _;
 use super::mutable::DefaultMutableIndex;
 use super::readonly::DefaultReadonlyIndex;
 use super::readonly::ReadonlyIndexLoadError;
 use super::readonly::ReadonlyIndexSegment;
 use crate::backend::BackendError;
 use crate::backend::BackendInitError;
+use crate::backend::BackendResult;
 use crate::backend::CommitId;
 use crate::commit::CommitByCommitterTimestamp;
 use crate::dag_walk;
@@ -91,6 +93,7 @@ pub enum DefaultIndexStoreError {
 #[derive(Debug)]
 pub struct DefaultIndexStore {
     dir: PathBuf,
+    changed_path_filters: bool,
 }
 
 impl DefaultIndexStore {
@@ -101,6 +104,7 @@ impl DefaultIndexStore {
     pub fn init(dir: &Path) -> Result<Self, DefaultIndexStoreInitError> {
         let store = DefaultIndexStore {
             dir: dir.to_owned(),
+            changed_path_filters: false,
         };
         store.ensure_base_dirs()?;
         Ok(store)
@@ -109,9 +113,20 @@ impl DefaultIndexStore {
     pub fn load(dir: &Path) -> DefaultIndexStore {
         DefaultIndexStore {
             dir: dir.to_owned(),
+            changed_path_filters: false,
         }
     }
 
+    /// Enables recording of paths changed by each commit when the index is
+    /// built from scratch.
+    ///
+    /// Incrementally-built index segments inherit the setting from the parent
+    /// segment, so the index has to be rebuilt for the change to take effect.
+    pub fn with_changed_path_filters(mut self, enabled: bool) -> Self {
+        self.changed_path_filters = enabled;
+        self
+    }
+
     pub fn reinit(&self) -> Result<(), DefaultIndexStoreInitError> {
         // Create base directories in case the store was initialized by old jj.
         self.ensure_base_dirs()?;
@@ -222,6 +237,23 @@ impl DefaultIndexStore {
             None => {
                 maybe_parent_file = None;
                 mutable_index = DefaultMutableIndex::full(commit_id_length, change_id_length);
+                if self.changed_path_filters {
+                    mutable_index.enable_changed_path_filters();
+                } else {
+                    // Changed-path filters can't be computed without reading
+                    // commits, so the commit-graph is of no use in that case.
+                    seed_index_from_git_commit_graph(
+                        &mut mutable_index,
+                        store,
+                        historical_heads.iter().map(|(commit_id, _)| commit_id),
+                    )
+                    .map_err(|source| {
+                        DefaultIndexStoreError::IndexCommits {
+                            op_id: operation.id().clone(),
+                            source,
+                        }
+                    })?;
+                }
             }
             Some(parent_op_id) => {
                 let parent_file = self.load_index_segments_at_operation(
@@ -241,11 +273,7 @@ impl DefaultIndexStore {
         );
         // Build a list of ancestors of heads where parents and predecessors come after
         // the commit itself.
-        let parent_file_has_id = |id: &CommitId| {
-            maybe_parent_file
-                .as_ref()
-                .is_some_and(|segment| segment.as_composite().has_id(id))
-        };
+        let index_has_id = |id: &CommitId| mutable_index.as_composite().has_id(id);
         let get_commit_with_op = |commit_id: &CommitId, op_id: &OperationId| {
             let op_id = op_id.clone();
             match store.get_commit(commit_id) {
@@ -259,12 +287,12 @@ impl DefaultIndexStore {
         let commits = dag_walk::topo_order_reverse_ord_ok(
             historical_heads
                 .iter()
-                .filter(|&(commit_id, _)| !parent_file_has_id(commit_id))
+                .filter(|&(commit_id, _)| !index_has_id(commit_id))
                 .map(|(commit_id, op_id)| get_commit_with_op(commit_id, op_id)),
             |(CommitByCommitterTimestamp(commit), _)| commit.id().clone(),
             |(CommitByCommitterTimestamp(commit), op_id)| {
                 itertools::chain(commit.parent_ids(), commit.predecessor_ids())
-                    .filter(|&id| !parent_file_has_id(id))
+                    .filter(|&id| !index_has_id(id))
                     .map(|commit_id| get_commit_with_op(commit_id, op_id))
                     .collect_vec()
             },
@@ -379,3 +407,41 @@ impl IndexStore for DefaultIndexStore {
         Ok(Box::new(DefaultReadonlyIndex::from_segment(index_segment)))
     }
 }
+
+/// Adds commits which can be read from Git's commit-graph file to the index.
+#[cfg(feature = "git")]
+fn seed_index_from_git_commit_graph<'a>(
+    mutable_index: &mut DefaultMutableIndex,
+    store: &Store,
+    head_ids: impl IntoIterator<Item = &'a CommitId>,
+) -> BackendResult<()> {
+    let Ok(git_backend) = crate::git::get_git_backend(store) else {
+        return Ok(());
+    };
+    let entries = git_backend.read_commit_graph(head_ids)?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+    tracing::info!(
+        commits_count = entries.len(),
+        "indexing commits from Git commit-graph"
+    );
+    mutable_index.add_commit_data(
+        store.root_commit_id().clone(),
+        store.root_change_id().clone(),
+        &[],
+    );
+    for entry in entries {
+        mutable_index.add_commit_data(entry.commit_id, entry.change_id, &entry.parent_ids);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "git"))]
+fn seed_index_from_git_commit_graph<'a>(
+    _mutable_index: &mut DefaultMutableIndex,
+    _store: &Store,
+    _head_ids: impl IntoIterator<Item = &'a CommitId>,
+) -> BackendResult<()> {
+    Ok(())
+}
@@ -40,6 +40,10 @@ pub enum FsmonitorSettings {
     /// The Watchman filesystem monitor (<https://facebook.github.io/watchman/>).
     Watchman(WatchmanConfig),
 
+    /// The built-in filesystem monitor, which records changes in a background
+    /// daemon. Only supported on Unix.
+    Native,
+
     /// Only used in tests.
     Test {
         /// The set of changed files to pretend that the filesystem monitor is
@@ -62,6 +66,7 @@ impl FsmonitorSettings {
             "watchman" => Ok(Self::Watchman(WatchmanConfig {
                 register_trigger: settings.get_bool("core.watchman.register-snapshot-trigger")?,
             })),
+            "native" => Ok(Self::Native),
             "test" => Err(ConfigGetError::Type {
                 name: name.to_owned(),
                 error: "Cannot use test fsmonitor in real repository".into(),
@@ -333,3 +338,441 @@ pub mod watchman {
         }
     }
 }
+
+/// Built-in filesystem monitor. A daemon process watches the working copy
+/// using the `notify` crate (inotify on Linux) and records changed paths. It
+/// serves queries over a Unix socket in the working-copy state directory.
+#[cfg(all(feature = "native-fsmonitor", unix))]
+pub mod native {
+    use std::collections::BTreeMap;
+    use std::ffi::OsString;
+    use std::fs;
+    use std::io;
+    use std::io::BufRead as _;
+    use std::io::BufReader;
+    use std::io::Write as _;
+    use std::os::unix::ffi::OsStrExt as _;
+    use std::os::unix::ffi::OsStringExt as _;
+    use std::os::unix::fs::MetadataExt as _;
+    use std::os::unix::net::UnixListener;
+    use std::os::unix::net::UnixStream;
+    use std::path::Component;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use itertools::Itertools as _;
+    use notify::event::EventKind;
+    use notify::RecursiveMode;
+    use notify::Watcher as _;
+    use thiserror::Error;
+    use tracing::info;
+    use tracing::instrument;
+
+    const SOCKET_FILE_NAME: &str = "fsmonitor.sock";
+    const COOKIE_FILE_PREFIX: &str = "fsmonitor-cookie-";
+    /// How long to wait for the cookie file event before giving up on
+    /// synchronizing a query with the event stream.
+    const SYNC_TIMEOUT: Duration = Duration::from_secs(10);
+    const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
+    const TICK_INTERVAL: Duration = Duration::from_secs(1);
+    /// Number of recorded paths after which the history is dropped. Clients
+    /// will crawl the entire working copy on their next query.
+    const MAX_RECORDED_PATHS: usize = 1_000_000;
+
+    /// Represents an instance in time from the perspective of the daemon.
+    ///
+    /// Clocks from a different daemon instance (e.g. before the daemon was
+    /// restarted) can't be compared, so querying with such a clock returns no
+    /// list of changed files.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct Clock {
+        instance_id: u64,
+        sequence: u64,
+    }
+
+    impl From<crate::protos::working_copy::NativeFsmonitorClock> for Clock {
+        fn from(clock: crate::protos::working_copy::NativeFsmonitorClock) -> Self {
+            Self {
+                instance_id: clock.instance_id,
+                sequence: clock.sequence,
+            }
+        }
+    }
+
+    impl From<Clock> for crate::protos::working_copy::NativeFsmonitorClock {
+        fn from(clock: Clock) -> Self {
+            Self {
+                instance_id: clock.instance_id,
+                sequence: clock.sequence,
+            }
+        }
+    }
+
+    #[expect(missing_docs)]
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("The filesystem monitor daemon is not running")]
+        NotRunning(#[source] io::Error),
+
+        #[error("The filesystem monitor daemon is already running")]
+        AlreadyRunning,
+
+        #[error("Failed to communicate with the filesystem monitor daemon")]
+        Io(#[from] io::Error),
+
+        #[error("Invalid response from the filesystem monitor daemon")]
+        InvalidResponse,
+
+        #[error("Failed to watch the working copy")]
+        Watch(#[from] notify::Error),
+    }
+
+    /// Returns the path to the socket of the daemon for the working copy
+    /// whose state is stored in `state_path`.
+    pub fn socket_path(state_path: &Path) -> PathBuf {
+        state_path.join(SOCKET_FILE_NAME)
+    }
+
+    /// Returns whether a daemon is accepting queries for the working copy.
+    pub fn is_running(state_path: &Path) -> bool {
+        UnixStream::connect(socket_path(state_path)).is_ok()
+    }
+
+    /// Query the daemon for changed files since the previous point in time.
+    ///
+    /// The returned list of paths is relative to the working copy root. A
+    /// changed directory means that anything under it may have changed. If
+    /// the list is `None`, then the caller must crawl the entire working copy
+    /// themselves.
+    #[instrument]
+    pub fn query_changed_files(
+        state_path: &Path,
+        previous_clock: Option<Clock>,
+    ) -> Result<(Clock, Option<Vec<PathBuf>>), Error> {
+        info!("Querying the filesystem monitor daemon for changed files...");
+        let mut stream = UnixStream::connect(socket_path(state_path)).map_err(Error::NotRunning)?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        match previous_clock {
+            Some(clock) => writeln!(stream, "query {} {}", clock.instance_id, clock.sequence)?,
+            None => writeln!(stream, "query")?,
+        }
+        let mut reader = BufReader::new(stream);
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let (instance_id, sequence, count) = header
+            .trim_end()
+            .split(' ')
+            .collect_tuple()
+            .ok_or(Error::InvalidResponse)?;
+        let clock = Clock {
+            instance_id: instance_id.parse().map_err(|_| Error::InvalidResponse)?,
+            sequence: sequence.parse().map_err(|_| Error::InvalidResponse)?,
+        };
+        if count == "fresh" {
+            return Ok((clock, None));
+        }
+        let count: usize = count.parse().map_err(|_| Error::InvalidResponse)?;
+        let mut paths = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut buf = vec![];
+            reader.read_until(b'\0', &mut buf)?;
+            if buf.pop() != Some(b'\0') {
+                return Err(Error::InvalidResponse);
+            }
+            paths.push(PathBuf::from(OsString::from_vec(buf)));
+        }
+        Ok((clock, Some(paths)))
+    }
+
+    /// Ask the daemon for the working copy to exit.
+    pub fn stop(state_path: &Path) -> Result<(), Error> {
+        let mut stream = UnixStream::connect(socket_path(state_path)).map_err(Error::NotRunning)?;
+        writeln!(stream, "stop")?;
+        Ok(())
+    }
+
+    enum Message {
+        Event(notify::Result<notify::Event>),
+        Query {
+            stream: UnixStream,
+            previous_clock: Option<Clock>,
+        },
+        Stop,
+    }
+
+    struct PendingQuery {
+        cookie_path: PathBuf,
+        stream: UnixStream,
+        previous_clock: Option<Clock>,
+        deadline: Instant,
+    }
+
+    struct DaemonState {
+        working_copy_path: PathBuf,
+        state_path: PathBuf,
+        instance_id: u64,
+        sequence: u64,
+        /// Changed paths relative to the working copy root, mapped to the
+        /// sequence number of their last change.
+        changed_paths: BTreeMap<PathBuf, u64>,
+        pending_queries: Vec<PendingQuery>,
+        next_cookie: u64,
+    }
+
+    /// Run the daemon for the working copy at `working_copy_path` until it's
+    /// stopped, its socket is removed, or the working copy is deleted.
+    #[instrument]
+    pub fn run_daemon(working_copy_path: &Path, state_path: &Path) -> Result<(), Error> {
+        let working_copy_path = working_copy_path.canonicalize()?;
+        let state_path = state_path.canonicalize()?;
+        let socket_path = socket_path(&state_path);
+        if is_running(&state_path) {
+            return Err(Error::AlreadyRunning);
+        }
+        match fs::remove_file(&socket_path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+        let listener = UnixListener::bind(&socket_path)?;
+        let socket_ino = fs::metadata(&socket_path)?.ino();
+
+        let (tx, rx) = mpsc::channel();
+        let watcher = {
+            let tx = tx.clone();
+            info!(?working_copy_path, "Watching working copy...");
+            notify::recommended_watcher(move |event| {
+                tx.send(Message::Event(event)).ok();
+            })
+            .and_then(|mut watcher| {
+                watcher.watch(&working_copy_path, RecursiveMode::Recursive)?;
+                Ok(watcher)
+            })
+        };
+        let _watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                fs::remove_file(&socket_path).ok();
+                return Err(err.into());
+            }
+        };
+        thread::spawn(move || accept_requests(&listener, &tx));
+
+        let mut state = DaemonState {
+            working_copy_path,
+            state_path,
+            instance_id: rand::random(),
+            sequence: 0,
+            changed_paths: BTreeMap::new(),
+            pending_queries: vec![],
+            next_cookie: 0,
+        };
+        loop {
+            match rx.recv_timeout(TICK_INTERVAL) {
+                Ok(Message::Event(event)) => {
+                    if !state.handle_event(event) {
+                        break;
+                    }
+                }
+                Ok(Message::Query {
+                    stream,
+                    previous_clock,
+                }) => state.start_query(stream, previous_clock),
+                Ok(Message::Stop) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+            }
+            state.expire_pending_queries();
+            // Another daemon may have replaced the socket.
+            match fs::metadata(&socket_path) {
+                Ok(metadata) if metadata.ino() == socket_ino => {}
+                _ => {
+                    info!("Socket was removed, exiting");
+                    return Ok(());
+                }
+            }
+        }
+        fs::remove_file(&socket_path).ok();
+        Ok(())
+    }
+
+    fn accept_requests(listener: &UnixListener, tx: &mpsc::Sender<Message>) {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok();
+            stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok();
+            let mut line = String::new();
+            if BufReader::new(&stream).read_line(&mut line).is_err() {
+                continue;
+            }
+            let words = line.split_whitespace().collect_vec();
+            let message = match words.as_slice() {
+                ["query"] => Message::Query {
+                    stream,
+                    previous_clock: None,
+                },
+                ["query", instance_id, sequence] => {
+                    let (Ok(instance_id), Ok(sequence)) = (instance_id.parse(), sequence.parse())
+                    else {
+                        continue;
+                    };
+                    Message::Query {
+                        stream,
+                        previous_clock: Some(Clock {
+                            instance_id,
+                            sequence,
+                        }),
+                    }
+                }
+                ["stop"] => Message::Stop,
+                _ => continue,
+            };
+            if tx.send(message).is_err() {
+                return;
+            }
+        }
+    }
+
+    impl DaemonState {
+        /// Records the paths of the event. Returns `false` if the working copy
+        /// was deleted.
+        fn handle_event(&mut self, event: notify::Result<notify::Event>) -> bool {
+            let event = match event {
+                Ok(event) if !event.need_rescan() => event,
+                Ok(_) => {
+                    info!("Events were dropped, resetting the change history");
+                    self.reset();
+                    return true;
+                }
+                Err(err) => {
+                    tracing::warn!(?err, "Error while watching, resetting the change history");
+                    self.reset();
+                    return true;
+                }
+            };
+            // Reading files doesn't change them.
+            if matches!(event.kind, EventKind::Access(_)) {
+                return true;
+            }
+            self.sequence += 1;
+            for path in event.paths {
+                if let Some(index) = self
+                    .pending_queries
+                    .iter()
+                    .position(|query| query.cookie_path == path)
+                {
+                    let query = self.pending_queries.swap_remove(index);
+                    fs::remove_file(&query.cookie_path).ok();
+                    self.finish_query(query.stream, query.previous_clock, true);
+                    continue;
+                }
+                let Ok(relative_path) = path.strip_prefix(&self.working_copy_path) else {
+                    continue;
+                };
+                match relative_path.components().next() {
+                    None if matches!(event.kind, EventKind::Remove(_)) => {
+                        info!("Working copy was removed, exiting");
+                        return false;
+                    }
+                    // The working copy snapshot never looks into these.
+                    Some(Component::Normal(name)) if name == ".jj" || name == ".git" => {}
+                    _ => {
+                        self.changed_paths
+                            .insert(relative_path.to_owned(), self.sequence);
+                    }
+                }
+            }
+            if self.changed_paths.len() > MAX_RECORDED_PATHS {
+                info!("Too many changed paths, resetting the change history");
+                self.reset();
+            }
+            true
+        }
+
+        /// Forgets the recorded changes. Clocks handed out before can no longer
+        /// be used for incremental queries.
+        fn reset(&mut self) {
+            self.instance_id = rand::random();
+            self.sequence = 0;
+            self.changed_paths.clear();
+        }
+
+        /// Creates a cookie file, and responds to the query once its event is
+        /// received. Since events are delivered in order, this ensures that any
+        /// change made before the query is reported.
+        fn start_query(&mut self, stream: UnixStream, previous_clock: Option<Clock>) {
+            let cookie_path = self.state_path.join(format!(
+                "{COOKIE_FILE_PREFIX}{}-{}",
+                std::process::id(),
+                self.next_cookie
+            ));
+            self.next_cookie += 1;
+            if let Err(err) = fs::write(&cookie_path, b"") {
+                tracing::warn!(?err, "Failed to create cookie file");
+                self.finish_query(stream, previous_clock, false);
+                return;
+            }
+            self.pending_queries.push(PendingQuery {
+                cookie_path,
+                stream,
+                previous_clock,
+                deadline: Instant::now() + SYNC_TIMEOUT,
+            });
+        }
+
+        fn expire_pending_queries(&mut self) {
+            let now = Instant::now();
+            let (expired, pending) = std::mem::take(&mut self.pending_queries)
+                .into_iter()
+                .partition(|query| query.deadline <= now);
+            self.pending_queries = pending;
+            for query in expired {
+                tracing::warn!(?query.cookie_path, "Timed out waiting for cookie file");
+                fs::remove_file(&query.cookie_path).ok();
+                self.finish_query(query.stream, query.previous_clock, false);
+            }
+        }
+
+        /// Responds with the paths changed since `previous_clock`. If the query
+        /// couldn't be synchronized with the event stream, or the clock came
+        /// from another instance, the client will have to crawl everything.
+        fn finish_query(
+            &self,
+            mut stream: UnixStream,
+            previous_clock: Option<Clock>,
+            is_synced: bool,
+        ) {
+            let paths = previous_clock
+                .filter(|clock| {
+                    is_synced
+                        && clock.instance_id == self.instance_id
+                        && clock.sequence <= self.sequence
+                })
+                .map(|clock| {
+                    self.changed_paths
+                        .iter()
+                        .filter(|(_, &sequence)| sequence > clock.sequence)
+                        .map(|(path, _)| path)
+                        .collect_vec()
+                });
+            let mut response = format!("{} {} ", self.instance_id, self.sequence).into_bytes();
+            match paths {
+                None => response.extend_from_slice(b"fresh\n"),
+                Some(paths) => {
+                    response.extend_from_slice(format!("{}\n", paths.len()).as_bytes());
+                    for path in paths {
+                        response.extend_from_slice(path.as_os_str().as_bytes());
+                        response.push(b'\0');
+                    }
+                }
+            }
+            if let Err(err) = stream.write_all(&response) {
+                tracing::warn!(?err, "Failed to respond to query");
+            }
+        }
+    }
+}
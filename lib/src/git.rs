@@ -35,10 +35,13 @@ use thiserror::Error;
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::CommitId;
+use crate::backend::Signature;
 use crate::backend::TreeValue;
+use crate::caching_backend::CachingBackend;
 use crate::commit::Commit;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
+use crate::git_backend::signature_to_git;
 use crate::git_backend::GitBackend;
 use crate::git_subprocess::GitSubprocessContext;
 use crate::git_subprocess::GitSubprocessError;
@@ -289,10 +292,12 @@ pub struct UnexpectedGitBackendError;
 
 /// Returns the underlying `GitBackend` implementation.
 pub fn get_git_backend(store: &Store) -> Result<&GitBackend, UnexpectedGitBackendError> {
-    store
-        .backend_impl()
-        .downcast_ref()
-        .ok_or(UnexpectedGitBackendError)
+    let mut backend = store.backend_impl();
+    // The Git backend might be wrapped by the object cache
+    if let Some(caching_backend) = backend.downcast_ref::<CachingBackend>() {
+        backend = caching_backend.inner().as_any();
+    }
+    backend.downcast_ref().ok_or(UnexpectedGitBackendError)
 }
 
 /// Returns new thread-local instance to access to the underlying Git repo.
@@ -597,18 +602,19 @@ fn diff_refs_to_import(
         .filter(|&(symbol, _)| git_ref_filter(GitRefKind::Bookmark, symbol))
         .map(|(symbol, remote_ref)| (RemoteRefKey(symbol), (&remote_ref.target, remote_ref.state)))
         .collect();
-    // TODO: compare to tags stored in the "git" remote view. Since tags should
-    // never be moved locally in jj, we can consider local tags as merge base.
+    // Local tags can be moved in jj, so the last imported or exported Git tags
+    // are used as merge base.
+    // TODO: compare to tags stored in the "git" remote view instead.
     let mut known_remote_tags = view
-        .tags()
+        .git_refs()
         .iter()
-        .map(|(name, target)| {
-            let symbol = name.to_remote_symbol(REMOTE_NAME_FOR_LOCAL_GIT_REPO);
-            let state = RemoteRefState::Tracked;
-            (symbol, (target, state))
+        .filter_map(|(full_name, target)| {
+            let (kind, symbol) =
+                parse_git_ref(full_name).expect("stored git ref should be parsable");
+            (kind == GitRefKind::Tag).then_some((symbol, target))
         })
         .filter(|&(symbol, _)| git_ref_filter(GitRefKind::Tag, symbol))
-        .map(|(symbol, remote_ref)| (RemoteRefKey(symbol), remote_ref))
+        .map(|(symbol, target)| (RemoteRefKey(symbol), (target, RemoteRefState::Tracked)))
         .collect();
 
     let mut changed_git_refs = Vec::new();
@@ -863,6 +869,9 @@ pub enum FailedRefExportReason {
     /// Failed to set the ref in the Git repo
     #[error("Failed to set")]
     FailedToSet(#[source] Box<gix::reference::edit::Error>),
+    /// Failed to write the annotated tag object to the Git repo
+    #[error("Failed to write tag object")]
+    FailedToWriteTag(#[source] Box<gix::object::write::Error>),
 }
 
 /// Describes changes made by [`export_refs()`].
@@ -870,6 +879,8 @@ pub enum FailedRefExportReason {
 pub struct GitExportStats {
     /// Remote bookmarks that couldn't be exported, sorted by `symbol`.
     pub failed_bookmarks: Vec<(RemoteRefSymbolBuf, FailedRefExportReason)>,
+    /// Tags that couldn't be exported, sorted by `symbol`.
+    pub failed_tags: Vec<(RemoteRefSymbolBuf, FailedRefExportReason)>,
 }
 
 #[derive(Debug)]
@@ -884,18 +895,23 @@ struct RefsToExport {
     bookmarks_to_delete: Vec<(RemoteRefSymbolBuf, gix::ObjectId)>,
     /// Remote bookmarks that couldn't be exported, sorted by `symbol`.
     failed_bookmarks: Vec<(RemoteRefSymbolBuf, FailedRefExportReason)>,
+    /// Tag `(symbol, (old_oid, new_oid))`s to update, sorted by `symbol`.
+    tags_to_update: Vec<(RemoteRefSymbolBuf, (Option<gix::ObjectId>, gix::ObjectId))>,
+    /// Tag `(symbol, old_oid)`s to delete, sorted by `symbol`.
+    tags_to_delete: Vec<(RemoteRefSymbolBuf, gix::ObjectId)>,
+    /// Tags that couldn't be exported, sorted by `symbol`.
+    failed_tags: Vec<(RemoteRefSymbolBuf, FailedRefExportReason)>,
 }
 
-/// Export changes to bookmarks made in the Jujutsu repo compared to our last
-/// seen view of the Git repo in `mut_repo.view().git_refs()`.
+/// Export changes to bookmarks and tags made in the Jujutsu repo compared to
+/// our last seen view of the Git repo in `mut_repo.view().git_refs()`.
 ///
-/// We ignore changed bookmarks that are conflicted (were also changed in the
-/// Git repo compared to our last remembered view of the Git repo). These will
-/// be marked conflicted by the next `jj git import`.
+/// We ignore changed bookmarks and tags that are conflicted (were also changed
+/// in the Git repo compared to our last remembered view of the Git repo). These
+/// will be marked conflicted by the next `jj git import`.
 ///
-/// We do not export tags and other refs at the moment, since these aren't
-/// supposed to be modified by JJ. For them, the Git state is considered
-/// authoritative.
+/// Tags are exported as lightweight tags. Annotated tags can be exported by
+/// [`export_annotated_tags()`].
 pub fn export_refs(mut_repo: &mut MutableRepo) -> Result<GitExportStats, GitExportError> {
     export_some_refs(mut_repo, |_, _| true)
 }
@@ -903,6 +919,44 @@ pub fn export_refs(mut_repo: &mut MutableRepo) -> Result<GitExportStats, GitExpo
 pub fn export_some_refs(
     mut_repo: &mut MutableRepo,
     git_ref_filter: impl Fn(GitRefKind, RemoteRefSymbol<'_>) -> bool,
+) -> Result<GitExportStats, GitExportError> {
+    export_refs_impl(mut_repo, git_ref_filter, None)
+}
+
+/// Exports the local tags `names` as annotated tags with the given `message`
+/// and `tagger`.
+///
+/// The local tags should have been set in the view. Like [`export_refs()`],
+/// tags which are unchanged since the last import or export are left as is,
+/// and tags which were changed in the Git repo are reported as failed.
+pub fn export_annotated_tags(
+    mut_repo: &mut MutableRepo,
+    names: &[RefNameBuf],
+    message: &str,
+    tagger: &Signature,
+) -> Result<GitExportStats, GitExportError> {
+    let annotation = TagAnnotation { message, tagger };
+    export_refs_impl(
+        mut_repo,
+        |kind, symbol| {
+            kind == GitRefKind::Tag
+                && symbol.remote == REMOTE_NAME_FOR_LOCAL_GIT_REPO
+                && names.iter().any(|name| **name == *symbol.name)
+        },
+        Some(&annotation),
+    )
+}
+
+/// Message and tagger of the annotated tags to be exported.
+struct TagAnnotation<'a> {
+    message: &'a str,
+    tagger: &'a Signature,
+}
+
+fn export_refs_impl(
+    mut_repo: &mut MutableRepo,
+    git_ref_filter: impl Fn(GitRefKind, RemoteRefSymbol<'_>) -> bool,
+    tag_annotation: Option<&TagAnnotation>,
 ) -> Result<GitExportStats, GitExportError> {
     fn get<'a, V>(map: &'a [(RemoteRefSymbolBuf, V)], key: RemoteRefSymbol<'_>) -> Option<&'a V> {
         debug_assert!(map.is_sorted_by_key(|(k, _)| k));
@@ -917,6 +971,9 @@ pub fn export_some_refs(
         bookmarks_to_update,
         bookmarks_to_delete,
         mut failed_bookmarks,
+        tags_to_update,
+        tags_to_delete,
+        mut failed_tags,
     } = diff_refs_to_export(
         mut_repo.view(),
         mut_repo.store().root_commit_id(),
@@ -960,33 +1017,66 @@ pub fn export_some_refs(
             }
         }
     }
-    for (symbol, old_oid) in bookmarks_to_delete {
-        let Some(git_ref_name) = to_git_ref_name(GitRefKind::Bookmark, symbol.as_ref()) else {
-            failed_bookmarks.push((symbol, FailedRefExportReason::InvalidGitName));
-            continue;
-        };
-        if let Err(reason) = delete_git_ref(&git_repo, &git_ref_name, &old_oid) {
-            failed_bookmarks.push((symbol, reason));
-        } else {
-            let new_target = RefTarget::absent();
-            mut_repo.set_git_ref_target(&git_ref_name, new_target);
+    for (kind, refs_to_delete, failed_refs) in [
+        (
+            GitRefKind::Bookmark,
+            bookmarks_to_delete,
+            &mut failed_bookmarks,
+        ),
+        (GitRefKind::Tag, tags_to_delete, &mut failed_tags),
+    ] {
+        for (symbol, old_oid) in refs_to_delete {
+            let Some(git_ref_name) = to_git_ref_name(kind, symbol.as_ref()) else {
+                failed_refs.push((symbol, FailedRefExportReason::InvalidGitName));
+                continue;
+            };
+            if let Err(reason) = delete_git_ref(&git_repo, &git_ref_name, &old_oid) {
+                failed_refs.push((symbol, reason));
+            } else {
+                let new_target = RefTarget::absent();
+                mut_repo.set_git_ref_target(&git_ref_name, new_target);
+            }
         }
     }
-    for (symbol, (old_oid, new_oid)) in bookmarks_to_update {
-        let Some(git_ref_name) = to_git_ref_name(GitRefKind::Bookmark, symbol.as_ref()) else {
-            failed_bookmarks.push((symbol, FailedRefExportReason::InvalidGitName));
-            continue;
-        };
-        if let Err(reason) = update_git_ref(&git_repo, &git_ref_name, old_oid, new_oid) {
-            failed_bookmarks.push((symbol, reason));
-        } else {
-            let new_target = RefTarget::normal(CommitId::from_bytes(new_oid.as_bytes()));
-            mut_repo.set_git_ref_target(&git_ref_name, new_target);
+    for (kind, refs_to_update, failed_refs) in [
+        (
+            GitRefKind::Bookmark,
+            bookmarks_to_update,
+            &mut failed_bookmarks,
+        ),
+        (GitRefKind::Tag, tags_to_update, &mut failed_tags),
+    ] {
+        for (symbol, (old_oid, new_oid)) in refs_to_update {
+            let Some(git_ref_name) = to_git_ref_name(kind, symbol.as_ref()) else {
+                failed_refs.push((symbol, FailedRefExportReason::InvalidGitName));
+                continue;
+            };
+            let new_ref_oid = match (kind, tag_annotation) {
+                (GitRefKind::Tag, Some(annotation)) => {
+                    match write_tag_object(&git_repo, symbol.name.as_str(), new_oid, annotation) {
+                        Ok(tag_oid) => tag_oid,
+                        Err(reason) => {
+                            failed_refs.push((symbol, reason));
+                            continue;
+                        }
+                    }
+                }
+                _ => new_oid,
+            };
+            if let Err(reason) =
+                update_git_ref(&git_repo, &git_ref_name, old_oid, new_oid, new_ref_oid)
+            {
+                failed_refs.push((symbol, reason));
+            } else {
+                let new_target = RefTarget::normal(CommitId::from_bytes(new_oid.as_bytes()));
+                mut_repo.set_git_ref_target(&git_ref_name, new_target);
+            }
         }
     }
 
     // Stabilize output, allow binary search.
     failed_bookmarks.sort_unstable_by(|(name1, _), (name2, _)| name1.cmp(name2));
+    failed_tags.sort_unstable_by(|(name1, _), (name2, _)| name1.cmp(name2));
 
     copy_exportable_local_bookmarks_to_remote_view(
         mut_repo,
@@ -997,7 +1087,32 @@ pub fn export_some_refs(
         },
     );
 
-    Ok(GitExportStats { failed_bookmarks })
+    Ok(GitExportStats {
+        failed_bookmarks,
+        failed_tags,
+    })
+}
+
+/// Writes an annotated tag object pointing to the commit `target`, and returns
+/// its id.
+fn write_tag_object(
+    git_repo: &gix::Repository,
+    name: &str,
+    target: gix::ObjectId,
+    annotation: &TagAnnotation,
+) -> Result<gix::ObjectId, FailedRefExportReason> {
+    let tag = gix::objs::Tag {
+        target,
+        target_kind: gix::object::Kind::Commit,
+        name: name.into(),
+        tagger: Some(signature_to_git(annotation.tagger).to_owned()),
+        message: annotation.message.into(),
+        pgp_signature: None,
+    };
+    let tag_id = git_repo
+        .write_object(&tag)
+        .map_err(|err| FailedRefExportReason::FailedToWriteTag(err.into()))?;
+    Ok(tag_id.detach())
 }
 
 fn copy_exportable_local_bookmarks_to_remote_view(
@@ -1027,7 +1142,7 @@ fn copy_exportable_local_bookmarks_to_remote_view(
     }
 }
 
-/// Calculates diff of bookmarks to be exported.
+/// Calculates diff of bookmarks and tags to be exported.
 fn diff_refs_to_export(
     view: &View,
     root_commit_id: &CommitId,
@@ -1048,6 +1163,17 @@ fn diff_refs_to_export(
         .filter(|&(symbol, _)| git_ref_filter(GitRefKind::Bookmark, symbol))
         .map(|(symbol, new_target)| (symbol, (RefTarget::absent_ref(), new_target)))
         .collect();
+    // Local tags are considered to be the new Git tags.
+    let mut all_tag_targets: HashMap<RemoteRefSymbol, (&RefTarget, &RefTarget)> = view
+        .tags()
+        .iter()
+        .map(|(name, target)| {
+            let symbol = name.to_remote_symbol(REMOTE_NAME_FOR_LOCAL_GIT_REPO);
+            (symbol, target)
+        })
+        .filter(|&(symbol, _)| git_ref_filter(GitRefKind::Tag, symbol))
+        .map(|(symbol, new_target)| (symbol, (RefTarget::absent_ref(), new_target)))
+        .collect();
     let known_git_refs = view
         .git_refs()
         .iter()
@@ -1056,30 +1182,57 @@ fn diff_refs_to_export(
                 parse_git_ref(full_name).expect("stored git ref should be parsable");
             ((kind, symbol), target)
         })
-        .filter(|&((kind, symbol), _)| {
-            // There are two situations where remote bookmarks get out of sync:
-            // 1. `jj bookmark forget --include-remotes`
-            // 2. `jj op undo`/`restore` in colocated repo
-            kind == GitRefKind::Bookmark && git_ref_filter(kind, symbol)
-        });
-    for ((_kind, symbol), target) in known_git_refs {
-        all_bookmark_targets
+        // There are two situations where remote bookmarks get out of sync:
+        // 1. `jj bookmark forget --include-remotes`
+        // 2. `jj op undo`/`restore` in colocated repo
+        .filter(|&((kind, symbol), _)| git_ref_filter(kind, symbol));
+    for ((kind, symbol), target) in known_git_refs {
+        let all_targets = match kind {
+            GitRefKind::Bookmark => &mut all_bookmark_targets,
+            GitRefKind::Tag => &mut all_tag_targets,
+        };
+        all_targets
             .entry(symbol)
             .and_modify(|(old_target, _)| *old_target = target)
             .or_insert((target, RefTarget::absent_ref()));
     }
 
-    let mut bookmarks_to_update = Vec::new();
-    let mut bookmarks_to_delete = Vec::new();
-    let mut failed_bookmarks = Vec::new();
     let root_commit_target = RefTarget::normal(root_commit_id.clone());
-    for (symbol, (old_target, new_target)) in all_bookmark_targets {
+    let (bookmarks_to_update, bookmarks_to_delete, failed_bookmarks) =
+        classify_refs_to_export(all_bookmark_targets, &root_commit_target);
+    let (tags_to_update, tags_to_delete, failed_tags) =
+        classify_refs_to_export(all_tag_targets, &root_commit_target);
+    RefsToExport {
+        bookmarks_to_update,
+        bookmarks_to_delete,
+        failed_bookmarks,
+        tags_to_update,
+        tags_to_delete,
+        failed_tags,
+    }
+}
+
+/// Splits `(old_target, new_target)`s into refs to update, refs to delete, and
+/// refs that can't be exported. Each list is sorted by `symbol`.
+#[expect(clippy::type_complexity)]
+fn classify_refs_to_export(
+    all_targets: HashMap<RemoteRefSymbol, (&RefTarget, &RefTarget)>,
+    root_commit_target: &RefTarget,
+) -> (
+    Vec<(RemoteRefSymbolBuf, (Option<gix::ObjectId>, gix::ObjectId))>,
+    Vec<(RemoteRefSymbolBuf, gix::ObjectId)>,
+    Vec<(RemoteRefSymbolBuf, FailedRefExportReason)>,
+) {
+    let mut refs_to_update = Vec::new();
+    let mut refs_to_delete = Vec::new();
+    let mut failed_refs = Vec::new();
+    for (symbol, (old_target, new_target)) in all_targets {
         if new_target == old_target {
             continue;
         }
-        if *new_target == root_commit_target {
+        if new_target == root_commit_target {
             // Git doesn't have a root commit
-            failed_bookmarks.push((symbol.to_owned(), FailedRefExportReason::OnRootCommit));
+            failed_refs.push((symbol.to_owned(), FailedRefExportReason::OnRootCommit));
             continue;
         }
         let old_oid = if let Some(id) = old_target.as_normal() {
@@ -1087,7 +1240,7 @@ fn diff_refs_to_export(
         } else if old_target.has_conflict() {
             // The old git ref should only be a conflict if there were concurrent import
             // operations while the value changed. Don't overwrite these values.
-            failed_bookmarks.push((symbol.to_owned(), FailedRefExportReason::ConflictedOldState));
+            failed_refs.push((symbol.to_owned(), FailedRefExportReason::ConflictedOldState));
             continue;
         } else {
             assert!(old_target.is_absent());
@@ -1095,25 +1248,21 @@ fn diff_refs_to_export(
         };
         if let Some(id) = new_target.as_normal() {
             let new_oid = gix::ObjectId::from_bytes_or_panic(id.as_bytes());
-            bookmarks_to_update.push((symbol.to_owned(), (old_oid, new_oid)));
+            refs_to_update.push((symbol.to_owned(), (old_oid, new_oid)));
         } else if new_target.has_conflict() {
             // Skip conflicts and leave the old value in git_refs
             continue;
         } else {
             assert!(new_target.is_absent());
-            bookmarks_to_delete.push((symbol.to_owned(), old_oid.unwrap()));
+            refs_to_delete.push((symbol.to_owned(), old_oid.unwrap()));
         }
     }
 
     // Stabilize export order and output, allow binary search.
-    bookmarks_to_update.sort_unstable_by(|(sym1, _), (sym2, _)| sym1.cmp(sym2));
-    bookmarks_to_delete.sort_unstable_by(|(sym1, _), (sym2, _)| sym1.cmp(sym2));
-    failed_bookmarks.sort_unstable_by(|(sym1, _), (sym2, _)| sym1.cmp(sym2));
-    RefsToExport {
-        bookmarks_to_update,
-        bookmarks_to_delete,
-        failed_bookmarks,
-    }
+    refs_to_update.sort_unstable_by(|(sym1, _), (sym2, _)| sym1.cmp(sym2));
+    refs_to_delete.sort_unstable_by(|(sym1, _), (sym2, _)| sym1.cmp(sym2));
+    failed_refs.sort_unstable_by(|(sym1, _), (sym2, _)| sym1.cmp(sym2));
+    (refs_to_update, refs_to_delete, failed_refs)
 }
 
 fn delete_git_ref(
@@ -1122,7 +1271,7 @@ fn delete_git_ref(
     old_oid: &gix::oid,
 ) -> Result<(), FailedRefExportReason> {
     if let Ok(git_ref) = git_repo.find_reference(git_ref_name.as_str()) {
-        if git_ref.inner.target.try_id() == Some(old_oid) {
+        if git_ref_points_to(&git_ref, old_oid) {
             // The ref has not been updated by git, so go ahead and delete it
             git_ref
                 .delete()
@@ -1137,18 +1286,21 @@ fn delete_git_ref(
     Ok(())
 }
 
+/// Updates the ref to point to `new_ref_oid`, which is either the commit
+/// `new_oid` or an annotated tag pointing to it.
 fn update_git_ref(
     git_repo: &gix::Repository,
     git_ref_name: &GitRefName,
     old_oid: Option<gix::ObjectId>,
     new_oid: gix::ObjectId,
+    new_ref_oid: gix::ObjectId,
 ) -> Result<(), FailedRefExportReason> {
     match old_oid {
         None => {
             if let Ok(git_repo_ref) = git_repo.find_reference(git_ref_name.as_str()) {
                 // The ref was added in jj and in git. We're good if and only if git
                 // pointed it to our desired target.
-                if git_repo_ref.inner.target.try_id() != Some(&new_oid) {
+                if !git_ref_points_to(&git_repo_ref, &new_oid) {
                     return Err(FailedRefExportReason::AddedInJjAddedInGit);
                 }
             } else {
@@ -1156,7 +1308,7 @@ fn update_git_ref(
                 git_repo
                     .reference(
                         git_ref_name.as_str(),
-                        new_oid,
+                        new_ref_oid,
                         gix::refs::transaction::PreviousValue::MustNotExist,
                         "export from jj",
                     )
@@ -1164,17 +1316,25 @@ fn update_git_ref(
             }
         }
         Some(old_oid) => {
+            // An annotated tag points to the commit through the tag object, which
+            // we should expect instead.
+            let old_target = match git_repo.find_reference(git_ref_name.as_str()) {
+                Ok(git_repo_ref) if git_ref_points_to(&git_repo_ref, &old_oid) => {
+                    git_repo_ref.inner.target
+                }
+                _ => old_oid.into(),
+            };
             // The ref was modified in jj. We can use gix API for updating under a lock.
             if let Err(err) = git_repo.reference(
                 git_ref_name.as_str(),
-                new_oid,
-                gix::refs::transaction::PreviousValue::MustExistAndMatch(old_oid.into()),
+                new_ref_oid,
+                gix::refs::transaction::PreviousValue::MustExistAndMatch(old_target),
                 "export from jj",
             ) {
                 // The reference was probably updated in git
                 if let Ok(git_repo_ref) = git_repo.find_reference(git_ref_name.as_str()) {
                     // We still consider this a success if it was updated to our desired target
-                    if git_repo_ref.inner.target.try_id() != Some(&new_oid) {
+                    if !git_ref_points_to(&git_repo_ref, &new_oid) {
                         return Err(FailedRefExportReason::FailedToSet(err.into()));
                     }
                 } else {
@@ -1190,6 +1350,18 @@ fn update_git_ref(
     Ok(())
 }
 
+/// Returns true if the ref points to the given object directly, or through an
+/// annotated tag.
+fn git_ref_points_to(git_ref: &gix::Reference, oid: &gix::oid) -> bool {
+    if git_ref.inner.target.try_id() == Some(oid) {
+        return true;
+    }
+    git_ref
+        .clone()
+        .peel_to_id_in_place()
+        .is_ok_and(|id| id.as_ref() == oid)
+}
+
 /// Ensures Git HEAD is detached and pointing to the `new_oid`. If `new_oid`
 /// is `None` (meaning absent), dummy placeholder ref will be set.
 fn update_git_head(
@@ -2492,6 +2664,18 @@ pub enum GitPushError {
     Subprocess(#[from] GitSubprocessError),
     #[error(transparent)]
     UnexpectedBackend(#[from] UnexpectedGitBackendError),
+    #[cfg(feature = "git2")]
+    #[error("Atomic pushes are not supported when using libgit2 for remote calls")]
+    AtomicPushUnsupported,
+}
+
+/// Options for pushing to a Git remote.
+#[derive(Clone, Debug, Default)]
+pub struct GitPushOptions {
+    /// Update either all refs on the remote or none of them.
+    pub atomic: bool,
+    /// Push options to transmit to the server, like `git push --push-option`.
+    pub remote_push_options: Vec<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -2509,12 +2693,41 @@ pub struct GitRefUpdate {
     pub new_target: Option<CommitId>,
 }
 
-/// Pushes the specified branches and updates the repo view accordingly.
+/// Pushes the specified branches and updates the repo view for the branches
+/// that were pushed.
 pub fn push_branches(
     mut_repo: &mut MutableRepo,
     git_settings: &GitSettings,
     remote: &RemoteName,
     targets: &GitBranchPushTargets,
+    options: &GitPushOptions,
+    callbacks: RemoteCallbacks<'_>,
+) -> Result<GitPushStats, GitPushError> {
+    push_branches_and_tags(
+        mut_repo,
+        git_settings,
+        remote,
+        targets,
+        &[],
+        options,
+        callbacks,
+    )
+}
+
+/// Pushes the specified branches and tags with a single `git push`, and
+/// updates the repo view for the branches that were pushed, even if other refs
+/// were rejected.
+///
+/// The tags must have been exported to the underlying Git repo, and are
+/// expected to not exist on the remote. Annotated tags are pushed with the tag
+/// objects.
+pub fn push_branches_and_tags(
+    mut_repo: &mut MutableRepo,
+    git_settings: &GitSettings,
+    remote: &RemoteName,
+    targets: &GitBranchPushTargets,
+    tag_names: &[RefNameBuf],
+    options: &GitPushOptions,
     callbacks: RemoteCallbacks<'_>,
 ) -> Result<GitPushStats, GitPushError> {
     validate_remote_name(remote)?;
@@ -2528,28 +2741,48 @@ pub fn push_branches(
             new_target: update.new_target.clone(),
         })
         .collect_vec();
+    let qualified_tag_names: Vec<GitRefNameBuf> = tag_names
+        .iter()
+        .map(|name| format!("refs/tags/{name}", name = name.as_str()).into())
+        .collect();
 
-    let push_stats = push_updates(mut_repo, git_settings, remote, &ref_updates, callbacks)?;
+    let (mut qualified_remote_refs_expected_locations, mut refspecs) =
+        to_push_refspecs(&ref_updates);
+    for name in &qualified_tag_names {
+        qualified_remote_refs_expected_locations.insert(name.as_ref(), None);
+        // Push the local refs rather than the commits so the tag objects of
+        // annotated tags are sent.
+        refspecs.push(RefSpec::forced(name.as_str(), name.as_str()));
+    }
+    let push_stats = push_refspecs(
+        mut_repo,
+        git_settings,
+        remote,
+        &qualified_remote_refs_expected_locations,
+        &refspecs,
+        options,
+        callbacks,
+    )?;
     tracing::debug!(?push_stats);
 
-    // TODO: add support for partially pushed refs? we could update the view
-    // excluding rejected refs, but the transaction would be aborted anyway
-    // if we returned an Err.
-    if push_stats.all_ok() {
-        for (name, update) in &targets.branch_updates {
-            let git_ref_name: GitRefNameBuf = format!(
-                "refs/remotes/{remote}/{name}",
-                remote = remote.as_str(),
-                name = name.as_str()
-            )
-            .into();
-            let new_remote_ref = RemoteRef {
-                target: RefTarget::resolved(update.new_target.clone()),
-                state: RemoteRefState::Tracked,
-            };
-            mut_repo.set_git_ref_target(&git_ref_name, new_remote_ref.target.clone());
-            mut_repo.set_remote_bookmark(name.to_remote_symbol(remote), new_remote_ref);
+    // Other refs may have been rejected, but the pushed refs have already been
+    // updated on the remote.
+    for ((name, update), ref_update) in targets.branch_updates.iter().zip(&ref_updates) {
+        if !push_stats.pushed.contains(&ref_update.qualified_name) {
+            continue;
         }
+        let git_ref_name: GitRefNameBuf = format!(
+            "refs/remotes/{remote}/{name}",
+            remote = remote.as_str(),
+            name = name.as_str()
+        )
+        .into();
+        let new_remote_ref = RemoteRef {
+            target: RefTarget::resolved(update.new_target.clone()),
+            state: RemoteRefState::Tracked,
+        };
+        mut_repo.set_git_ref_target(&git_ref_name, new_remote_ref.target.clone());
+        mut_repo.set_remote_bookmark(name.to_remote_symbol(remote), new_remote_ref);
     }
 
     Ok(push_stats)
@@ -2561,8 +2794,26 @@ pub fn push_updates(
     git_settings: &GitSettings,
     remote_name: &RemoteName,
     updates: &[GitRefUpdate],
+    options: &GitPushOptions,
     callbacks: RemoteCallbacks<'_>,
 ) -> Result<GitPushStats, GitPushError> {
+    let (qualified_remote_refs_expected_locations, refspecs) = to_push_refspecs(updates);
+    push_refspecs(
+        repo,
+        git_settings,
+        remote_name,
+        &qualified_remote_refs_expected_locations,
+        &refspecs,
+        options,
+        callbacks,
+    )
+}
+
+/// Returns the expected remote locations and the refspecs to push the
+/// `updates`.
+fn to_push_refspecs(
+    updates: &[GitRefUpdate],
+) -> (HashMap<&GitRefName, Option<&CommitId>>, Vec<RefSpec>) {
     let mut qualified_remote_refs_expected_locations = HashMap::new();
     let mut refspecs = vec![];
     for update in updates {
@@ -2582,6 +2833,18 @@ pub fn push_updates(
             refspecs.push(RefSpec::delete(&update.qualified_name));
         }
     }
+    (qualified_remote_refs_expected_locations, refspecs)
+}
+
+fn push_refspecs(
+    repo: &dyn Repo,
+    git_settings: &GitSettings,
+    remote_name: &RemoteName,
+    qualified_remote_refs_expected_locations: &HashMap<&GitRefName, Option<&CommitId>>,
+    refspecs: &[RefSpec],
+    options: &GitPushOptions,
+    callbacks: RemoteCallbacks<'_>,
+) -> Result<GitPushStats, GitPushError> {
     // TODO(ilyagr): `push_refs`, or parts of it, should probably be inlined. This
     // requires adjusting some tests.
 
@@ -2594,8 +2857,9 @@ pub fn push_updates(
             repo,
             &git_repo,
             remote_name,
-            &qualified_remote_refs_expected_locations,
+            qualified_remote_refs_expected_locations,
             &refspecs,
+            options,
             callbacks,
         );
     }
@@ -2606,8 +2870,9 @@ pub fn push_updates(
         &git_repo,
         &git_ctx,
         remote_name,
-        &qualified_remote_refs_expected_locations,
-        &refspecs,
+        qualified_remote_refs_expected_locations,
+        refspecs,
+        options,
         callbacks,
     )
 }
@@ -2619,8 +2884,12 @@ fn git2_push_refs(
     remote_name: &RemoteName,
     qualified_remote_refs_expected_locations: &HashMap<&GitRefName, Option<&CommitId>>,
     refspecs: &[String],
+    options: &GitPushOptions,
     callbacks: RemoteCallbacks<'_>,
 ) -> Result<GitPushStats, GitPushError> {
+    if options.atomic {
+        return Err(GitPushError::AtomicPushUnsupported);
+    }
     let mut remote = git_repo.find_remote(remote_name.as_str()).map_err(|err| {
         if is_remote_not_found_err(&err) {
             GitPushError::NoSuchRemote(remote_name.to_owned())
@@ -2641,6 +2910,12 @@ fn git2_push_refs(
         let mut proxy_options = git2::ProxyOptions::new();
         proxy_options.auto();
         push_options.proxy_options(proxy_options);
+        let remote_push_options = options
+            .remote_push_options
+            .iter()
+            .map(String::as_str)
+            .collect_vec();
+        push_options.remote_push_options(&remote_push_options);
         let mut callbacks = callbacks.into_git();
         callbacks.push_negotiation(|updates| {
             for update in updates {
@@ -2763,6 +3038,7 @@ fn subprocess_push_refs(
     remote_name: &RemoteName,
     qualified_remote_refs_expected_locations: &HashMap<&GitRefName, Option<&CommitId>>,
     refspecs: &[RefSpec],
+    options: &GitPushOptions,
     mut callbacks: RemoteCallbacks<'_>,
 ) -> Result<GitPushStats, GitPushError> {
     // check the remote exists
@@ -2775,7 +3051,7 @@ fn subprocess_push_refs(
         .map(|full_refspec| RefToPush::new(full_refspec, qualified_remote_refs_expected_locations))
         .collect();
 
-    let mut push_stats = git_ctx.spawn_push(remote_name, &refs_to_push, &mut callbacks)?;
+    let mut push_stats = git_ctx.spawn_push(remote_name, &refs_to_push, options, &mut callbacks)?;
     push_stats.pushed.sort();
     push_stats.rejected.sort();
     push_stats.remote_rejected.sort();
@@ -49,6 +49,7 @@ use thiserror::Error;
 use crate::backend::make_root_commit;
 use crate::backend::Backend;
 use crate::backend::BackendError;
+use crate::backend::BackendGcStats;
 use crate::backend::BackendInitError;
 use crate::backend::BackendLoadError;
 use crate::backend::BackendResult;
@@ -62,6 +63,7 @@ use crate::backend::CopyRecord;
 use crate::backend::FileId;
 use crate::backend::MergedTreeId;
 use crate::backend::MillisSinceEpoch;
+use crate::backend::RecordedCopy;
 use crate::backend::SecureSig;
 use crate::backend::Signature;
 use crate::backend::SigningFn;
@@ -71,6 +73,7 @@ use crate::backend::Tree;
 use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::config::ConfigGetError;
+use crate::dag_walk;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
 use crate::hex_util::to_forward_hex;
@@ -79,6 +82,7 @@ use crate::lock::FileLock;
 use crate::merge::Merge;
 use crate::merge::MergeBuilder;
 use crate::object_id::ObjectId;
+use crate::repo_path::InvalidNewRepoPathError;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponentBuf;
@@ -156,6 +160,14 @@ pub enum GitGcError {
     GcCommandErrorStatus(ExitStatus),
 }
 
+/// Commit read from Git's commit-graph file.
+#[derive(Clone, Debug)]
+pub struct GitCommitGraphEntry {
+    pub commit_id: CommitId,
+    pub change_id: ChangeId,
+    pub parent_ids: Vec<CommitId>,
+}
+
 pub struct GitBackend {
     // While gix::Repository can be created from gix::ThreadSafeRepository, it's
     // cheaper to cache the thread-local instance behind a mutex than creating
@@ -425,6 +437,102 @@ impl GitBackend {
         self.save_extra_metadata_table(mut_table, &table_lock)
     }
 
+    /// Reads the given commits and ancestors from Git's commit-graph file
+    /// without inflating the commit objects.
+    ///
+    /// Parents precede their children in the returned list. Commits that
+    /// haven't been imported, commits that have predecessors, and descendants
+    /// of such commits are omitted. Returns an empty list if the commit-graph
+    /// file isn't available.
+    #[tracing::instrument(skip(self, head_ids))]
+    pub fn read_commit_graph<'a>(
+        &self,
+        head_ids: impl IntoIterator<Item = &'a CommitId>,
+    ) -> BackendResult<Vec<GitCommitGraphEntry>> {
+        let graph = {
+            let locked_repo = self.lock_git_repo();
+            // Parents of shallow commits would have to be discarded.
+            let shallow_commits = locked_repo
+                .shallow_commits()
+                .map_err(|err| BackendError::Other(Box::new(err)))?;
+            if shallow_commits.is_some() {
+                return Ok(vec![]);
+            }
+            match locked_repo.commit_graph_if_enabled() {
+                Ok(Some(graph)) => graph,
+                Ok(None) => return Ok(vec![]),
+                Err(err) => {
+                    tracing::warn!(?err, "failed to load commit-graph file");
+                    return Ok(vec![]);
+                }
+            }
+        };
+        let table = self.cached_extra_metadata_table()?;
+
+        // Sort commits in the same order as the index would be built from
+        // commit objects.
+        let to_node = |pos: gix::commitgraph::Position| {
+            let commit = graph.commit_at(pos);
+            (commit.committer_timestamp(), commit.id(), pos)
+        };
+        let sorted_positions = dag_walk::topo_order_reverse_ord(
+            head_ids
+                .into_iter()
+                .filter_map(|id| graph.lookup(validate_git_object_id(id).ok()?))
+                .map(to_node),
+            |&(_, _, pos)| pos,
+            |&(_, _, pos)| {
+                let parents = graph.commit_at(pos).iter_parents();
+                parents.filter_map(Result::ok).map(to_node).collect_vec()
+            },
+        )
+        .into_iter()
+        .rev()
+        .map(|(_, _, pos)| pos)
+        .collect_vec();
+
+        let mut included_positions = HashSet::new();
+        let mut entries = vec![];
+        for pos in sorted_positions {
+            let Ok(parent_positions) = graph
+                .commit_at(pos)
+                .iter_parents()
+                .collect::<Result<Vec<_>, _>>()
+            else {
+                continue;
+            };
+            if !parent_positions
+                .iter()
+                .all(|pos| included_positions.contains(pos))
+            {
+                continue;
+            }
+            let commit_id = CommitId::from_bytes(graph.id_at(pos).as_bytes());
+            let Some(extras) = table.get_value(commit_id.as_bytes()) else {
+                continue;
+            };
+            let proto = crate::protos::git_store::Commit::decode(extras).unwrap();
+            if proto.change_id.is_empty() || !proto.predecessors.is_empty() {
+                continue;
+            }
+            let parent_ids = if parent_positions.is_empty() {
+                vec![self.root_commit_id.clone()]
+            } else {
+                parent_positions
+                    .iter()
+                    .map(|&pos| CommitId::from_bytes(graph.id_at(pos).as_bytes()))
+                    .collect()
+            };
+            included_positions.insert(pos);
+            entries.push(GitCommitGraphEntry {
+                commit_id,
+                change_id: ChangeId::new(proto.change_id),
+                parent_ids,
+            });
+        }
+        Ok(entries)
+    }
+
     fn read_file_sync(&self, id: &FileId) -> BackendResult<Box<dyn Read>> {
         let git_blob_id = validate_git_object_id(id)?;
         let locked_repo = self.lock_git_repo();
@@ -630,6 +738,7 @@ fn commit_from_git_without_root_parent(
         author,
         committer,
         secure_sig,
+        copies: vec![],
     })
 }
 
@@ -660,7 +769,7 @@ fn signature_from_git(signature: gix::actor::SignatureRef) -> Signature {
     }
 }
 
-fn signature_to_git(signature: &Signature) -> gix::actor::SignatureRef<'_> {
+pub(crate) fn signature_to_git(signature: &Signature) -> gix::actor::SignatureRef<'_> {
     // git does not support empty names or emails
     let name = if !signature.name.is_empty() {
         &signature.name
@@ -700,10 +809,18 @@ fn serialize_extras(commit: &Commit) -> Vec<u8> {
     for predecessor in &commit.predecessors {
         proto.predecessors.push(predecessor.to_bytes());
     }
+    proto.copies = commit
+        .copies
+        .iter()
+        .map(|copy| crate::protos::git_store::commit::Copy {
+            source: copy.source.as_internal_file_string().to_owned(),
+            target: copy.target.as_internal_file_string().to_owned(),
+        })
+        .collect();
     proto.encode_to_vec()
 }
 
-fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) {
+fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) -> Result<(), InvalidNewRepoPathError> {
     let proto = crate::protos::git_store::Commit::decode(bytes).unwrap();
     if !proto.change_id.is_empty() {
         commit.change_id = ChangeId::new(proto.change_id);
@@ -728,6 +845,17 @@ fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) {
     for predecessor in &proto.predecessors {
         commit.predecessors.push(CommitId::from_bytes(predecessor));
     }
+    commit.copies = proto
+        .copies
+        .into_iter()
+        .map(|copy| {
+            Ok(RecordedCopy {
+                source: RepoPathBuf::from_internal_string(copy.source)?,
+                target: RepoPathBuf::from_internal_string(copy.target)?,
+            })
+        })
+        .collect::<Result<_, InvalidNewRepoPathError>>()?;
+    Ok(())
 }
 
 /// Returns `RefEdit` that will create a ref in `refs/jj/keep` if not exist.
@@ -763,12 +891,16 @@ fn to_ref_deletion(git_ref: gix::refs::Reference) -> gix::refs::transaction::Ref
 }
 
 /// Recreates `refs/jj/keep` refs for the `new_heads`, and removes the other
-/// unreachable and non-head refs.
+/// unreachable and non-head refs. Returns the commits which are no longer
+/// referenced by no-gc refs.
+///
+/// If `dry_run` is true, the refs are left unchanged.
 fn recreate_no_gc_refs(
     git_repo: &gix::Repository,
     new_heads: impl IntoIterator<Item = CommitId>,
     keep_newer: SystemTime,
-) -> BackendResult<()> {
+    dry_run: bool,
+) -> BackendResult<Vec<CommitId>> {
     // Calculate diff between existing no-gc refs and new heads.
     let new_heads: HashSet<CommitId> = new_heads.into_iter().collect();
     let mut no_gc_refs_to_keep_count: usize = 0;
@@ -820,6 +952,17 @@ fn recreate_no_gc_refs(
         "collected reachable refs"
     );
 
+    let unreferenced_ids = no_gc_refs_to_delete
+        .iter()
+        .filter_map(|git_ref| git_ref.target.try_id())
+        .map(|oid| CommitId::from_bytes(oid.as_bytes()))
+        .filter(|id| !new_heads.contains(id))
+        .unique()
+        .collect();
+    if dry_run {
+        return Ok(unreferenced_ids);
+    }
+
     // It's slow to delete packed refs one by one, so update refs all at once.
     let ref_edits = itertools::chain(
         no_gc_refs_to_delete.into_iter().map(to_ref_deletion),
@@ -829,13 +972,69 @@ fn recreate_no_gc_refs(
         .edit_references(ref_edits)
         .map_err(|err| BackendError::Other(err.into()))?;
 
-    Ok(())
+    Ok(unreferenced_ids)
+}
+
+/// Counts the commits reachable from `heads` which aren't in the `index`.
+fn count_unindexed_ancestors(
+    git_repo: &gix::Repository,
+    index: &dyn Index,
+    heads: Vec<CommitId>,
+) -> BackendResult<usize> {
+    let mut visited = HashSet::new();
+    let mut to_visit = heads;
+    while let Some(id) = to_visit.pop() {
+        if index.has_id(&id) || !visited.insert(id.clone()) {
+            continue;
+        }
+        let oid = validate_git_object_id(&id)?;
+        // The commit may have been removed by "git gc" already.
+        let Ok(commit) = git_repo.find_commit(oid) else {
+            continue;
+        };
+        to_visit.extend(
+            commit
+                .parent_ids()
+                .map(|parent_id| CommitId::from_bytes(parent_id.as_bytes())),
+        );
+    }
+    Ok(visited.len())
 }
 
-fn run_git_gc(program: &OsStr, git_dir: &Path) -> Result<(), GitGcError> {
+/// Formats the time in a way that git accepts as `--prune` or `--expire`
+/// argument.
+fn to_git_expiry_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    format!("@{secs} +0000")
+}
+
+/// Prunes unreachable loose objects, and returns the number of pruned objects.
+/// If `dry_run` is set, objects are only counted.
+fn prune_git_objects(
+    program: &OsStr,
+    git_dir: &Path,
+    keep_newer: SystemTime,
+    dry_run: bool,
+) -> Result<usize, GitGcError> {
+    let mut git = Command::new(program);
+    git.arg("--git-dir=."); // turn off discovery
+    git.args(["prune", if dry_run { "--dry-run" } else { "--verbose" }]);
+    git.arg(format!("--expire={}", to_git_expiry_date(keep_newer)));
+    git.current_dir(git_dir);
+    let output = git.output().map_err(GitGcError::GcCommand)?;
+    if !output.status.success() {
+        return Err(GitGcError::GcCommandErrorStatus(output.status));
+    }
+    Ok(output.stdout.iter().filter(|&&b| b == b'\n').count())
+}
+
+fn run_git_gc(program: &OsStr, git_dir: &Path, keep_newer: SystemTime) -> Result<(), GitGcError> {
     let mut git = Command::new(program);
     git.arg("--git-dir=."); // turn off discovery
     git.arg("gc");
+    git.arg(format!("--prune={}", to_git_expiry_date(keep_newer)));
     // Don't specify it by GIT_DIR/--git-dir. On Windows, the path could be
     // canonicalized as UNC path, which wouldn't be supported by git.
     git.current_dir(git_dir);
@@ -1196,8 +1395,13 @@ impl Backend for GitBackend {
         };
 
         let table = self.cached_extra_metadata_table()?;
+        let to_read_err = |err: InvalidNewRepoPathError| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err.into(),
+        };
         if let Some(extras) = table.get_value(id.as_bytes()) {
-            deserialize_extras(&mut commit, extras);
+            deserialize_extras(&mut commit, extras).map_err(to_read_err)?;
         } else {
             // TODO: Remove this hack and map to ObjectNotFound error if we're sure that
             // there are no reachable ancestor commits without extras metadata. Git commits
@@ -1207,7 +1411,7 @@ impl Backend for GitBackend {
             self.import_head_commits([id])?;
             let table = self.cached_extra_metadata_table()?;
             let extras = table.get_value(id.as_bytes()).unwrap();
-            deserialize_extras(&mut commit, extras);
+            deserialize_extras(&mut commit, extras).map_err(to_read_err)?;
         }
         Ok(commit)
     }
@@ -1417,25 +1621,56 @@ impl Backend for GitBackend {
         Ok(Box::pin(futures::stream::iter(records)))
     }
 
-    #[tracing::instrument(skip(self, index))]
     fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.gc_with_stats(index, keep_newer, false)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, index))]
+    fn gc_with_stats(
+        &self,
+        index: &dyn Index,
+        keep_newer: SystemTime,
+        dry_run: bool,
+    ) -> BackendResult<BackendGcStats> {
         let git_repo = self.lock_git_repo();
         let new_heads = index
             .all_heads_for_gc()
             .map_err(|err| BackendError::Other(err.into()))?
             .filter(|id| *id != self.root_commit_id);
-        recreate_no_gc_refs(&git_repo, new_heads, keep_newer)?;
-        // TODO: remove unreachable entries from extras table if segment file
-        // mtime <= keep_newer? (it won't be consistent with no-gc refs
-        // preserved by the keep_newer timestamp though)
-        // TODO: remove unreachable extras table segments
-        // TODO: pass in keep_newer to "git gc" command
-        run_git_gc(self.git_executable.as_ref(), self.git_repo_path())
+        let unreferenced_ids = recreate_no_gc_refs(&git_repo, new_heads, keep_newer, dry_run)?;
+        let num_commits = count_unindexed_ancestors(&git_repo, index, unreferenced_ids)?;
+        // Loose objects are pruned first to count them. "git gc" will prune
+        // the remainder, but doesn't report the number of removed objects. In
+        // dry-run mode, this doesn't include the objects which are only
+        // reachable from the unreferenced commits.
+        let num_objects = prune_git_objects(
+            self.git_executable.as_ref(),
+            self.git_repo_path(),
+            keep_newer,
+            dry_run,
+        )
+        .map_err(|err| BackendError::Other(err.into()))?;
+        if !dry_run {
+            // TODO: remove unreachable entries from extras table if segment file
+            // mtime <= keep_newer? (it won't be consistent with no-gc refs
+            // preserved by the keep_newer timestamp though)
+            // TODO: remove unreachable extras table segments
+            run_git_gc(
+                self.git_executable.as_ref(),
+                self.git_repo_path(),
+                keep_newer,
+            )
             .map_err(|err| BackendError::Other(err.into()))?;
-        // Since "git gc" will move loose refs into packed refs, in-memory
-        // packed-refs cache should be invalidated without relying on mtime.
-        git_repo.refs.force_refresh_packed_buffer().ok();
-        Ok(())
+            // Since "git gc" will move loose refs into packed refs, in-memory
+            // packed-refs cache should be invalidated without relying on mtime.
+            git_repo.refs.force_refresh_packed_buffer().ok();
+        }
+        Ok(BackendGcStats {
+            num_commits,
+            num_objects,
+            ..Default::default()
+        })
     }
 }
 
@@ -1898,6 +2133,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let (initial_commit_id, _init_commit) =
@@ -1989,6 +2225,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
@@ -2075,6 +2312,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
@@ -2173,6 +2411,7 @@ mod tests {
             author: signature.clone(),
             committer: signature,
             secure_sig: None,
+            copies: vec![],
         };
         let commit_id = backend.write_commit(commit, None).block_on().unwrap().0;
         let git_refs = git_repo.references().unwrap();
@@ -2251,6 +2490,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
@@ -2293,6 +2533,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let mut signer = |data: &_| {
@@ -28,6 +28,7 @@ use bstr::ByteSlice as _;
 use itertools::Itertools as _;
 use thiserror::Error;
 
+use crate::git::GitPushOptions;
 use crate::git::GitPushStats;
 use crate::git::Progress;
 use crate::git::RefSpec;
@@ -229,6 +230,7 @@ impl<'a> GitSubprocessContext<'a> {
         &self,
         remote_name: &RemoteName,
         references: &[RefToPush],
+        options: &GitPushOptions,
         callbacks: &mut RemoteCallbacks<'_>,
     ) -> Result<GitPushStats, GitSubprocessError> {
         let mut command = self.create_command();
@@ -242,6 +244,15 @@ impl<'a> GitSubprocessContext<'a> {
         if callbacks.progress.is_some() {
             command.arg("--progress");
         }
+        if options.atomic {
+            command.arg("--atomic");
+        }
+        command.args(
+            options
+                .remote_push_options
+                .iter()
+                .map(|option| format!("--push-option={option}")),
+        );
         command.args(
             references
                 .iter()
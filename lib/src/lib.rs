@@ -31,6 +31,8 @@ pub mod content_hash;
 pub mod absorb;
 pub mod annotate;
 pub mod backend;
+pub mod bisect;
+pub mod caching_backend;
 pub mod commit;
 pub mod commit_builder;
 pub mod config;
@@ -89,6 +91,8 @@ pub mod operation;
 pub mod protos;
 pub mod ref_name;
 pub mod refs;
+#[cfg(feature = "remote-store")]
+pub mod remote_store;
 pub mod repo;
 pub mod repo_path;
 pub mod revset;
@@ -104,6 +108,7 @@ pub mod signing;
 pub mod simple_backend;
 pub mod simple_op_heads_store;
 pub mod simple_op_store;
+mod simple_pack;
 pub mod ssh_signing;
 pub mod stacked_table;
 pub mod store;
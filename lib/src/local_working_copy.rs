@@ -41,6 +41,7 @@ use std::sync::Arc;
 use std::sync::OnceLock;
 use std::time::UNIX_EPOCH;
 
+use bstr::BString;
 use either::Either;
 use futures::StreamExt as _;
 use itertools::EitherOrBoth;
@@ -70,10 +71,13 @@ use crate::conflicts::choose_materialized_conflict_marker_len;
 use crate::conflicts::materialize_merge_result_to_bytes_with_marker_len;
 use crate::conflicts::materialize_tree_value;
 use crate::conflicts::ConflictMarkerStyle;
+use crate::conflicts::MaterializedFileValue;
 use crate::conflicts::MaterializedTreeValue;
 use crate::conflicts::MIN_CONFLICT_MARKER_LEN;
 use crate::file_util::check_symlink_support;
 use crate::file_util::try_symlink;
+#[cfg(all(feature = "native-fsmonitor", unix))]
+use crate::fsmonitor::native;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::watchman;
 use crate::fsmonitor::FsmonitorSettings;
@@ -87,6 +91,7 @@ use crate::matchers::FilesMatcher;
 use crate::matchers::IntersectionMatcher;
 use crate::matchers::Matcher;
 use crate::matchers::PrefixMatcher;
+use crate::matchers::Visit;
 use crate::merge::Merge;
 use crate::merge::MergeBuilder;
 use crate::merge::MergedTreeValue;
@@ -432,6 +437,9 @@ pub struct TreeState {
     /// the repo is configured to use the Watchman filesystem monitor and
     /// Watchman has been queried at least once.
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
+    /// The most recent clock value returned by the built-in filesystem
+    /// monitor daemon.
+    native_fsmonitor_clock: Option<crate::protos::working_copy::NativeFsmonitorClock>,
 }
 
 fn file_state_from_proto(proto: &crate::protos::working_copy::FileState) -> FileState {
@@ -653,6 +661,154 @@ fn can_create_new_file(disk_path: &Path) -> Result<bool, CheckoutError> {
     Ok(new_file_created)
 }
 
+/// Creates new file named `disk_path` to be written.
+fn create_new_file(disk_path: &Path) -> Result<File, CheckoutError> {
+    OpenOptions::new()
+        .write(true)
+        .create_new(true) // Don't overwrite un-ignored file. Don't follow symlink.
+        .open(disk_path)
+        .map_err(|err| CheckoutError::Other {
+            message: format!("Failed to open file {} for writing", disk_path.display()),
+            err: err.into(),
+        })
+}
+
+#[cfg_attr(windows, allow(unused_variables))]
+fn set_executable(disk_path: &Path, executable: bool) -> Result<(), CheckoutError> {
+    #[cfg(unix)]
+    {
+        let mode = if executable { 0o755 } else { 0o644 };
+        fs::set_permissions(disk_path, fs::Permissions::from_mode(mode))
+            .map_err(|err| checkout_error_for_stat_error(err, disk_path))?;
+    }
+    Ok(())
+}
+
+/// Maximum number of files to create before writing their contents in
+/// parallel. Each pending file keeps its file descriptor open.
+const MAX_PENDING_FILES: usize = 128;
+/// Maximum size of file contents to keep in memory before writing them.
+const MAX_PENDING_SIZE: usize = 16 << 20;
+/// Files larger than this are streamed to disk instead of being batched.
+const MAX_PENDING_FILE_SIZE: u64 = 1 << 20;
+
+/// New file whose contents will be written by `TreeState::update()`.
+///
+/// The file is created upfront so that checking and removal of the following
+/// paths observe it as if it had been written.
+struct PendingFile {
+    path: RepoPathBuf,
+    disk_path: PathBuf,
+    file: File,
+    contents: PendingFileContents,
+}
+
+enum PendingFileContents {
+    Data {
+        data: Vec<u8>,
+        executable: bool,
+    },
+    Conflict {
+        contents: Merge<BString>,
+        executable: bool,
+    },
+}
+
+impl PendingFileContents {
+    fn size(&self) -> usize {
+        match self {
+            Self::Data { data, .. } => data.len(),
+            Self::Conflict { contents, .. } => contents.iter().map(|content| content.len()).sum(),
+        }
+    }
+}
+
+impl PendingFile {
+    fn write(
+        self,
+        conflict_marker_style: ConflictMarkerStyle,
+    ) -> Result<(RepoPathBuf, FileState), CheckoutError> {
+        let PendingFile {
+            path,
+            disk_path,
+            mut file,
+            contents,
+        } = self;
+        let (data, executable, materialized_conflict_data) = match contents {
+            PendingFileContents::Data { data, executable } => (data, executable, None),
+            PendingFileContents::Conflict {
+                contents,
+                executable,
+            } => {
+                let conflict_marker_len = choose_materialized_conflict_marker_len(&contents);
+                let data = materialize_merge_result_to_bytes_with_marker_len(
+                    &contents,
+                    conflict_marker_style,
+                    conflict_marker_len,
+                )
+                .into();
+                let materialized_conflict_data = MaterializedConflictData {
+                    conflict_marker_len: conflict_marker_len.try_into().unwrap_or(u32::MAX),
+                };
+                (data, executable, Some(materialized_conflict_data))
+            }
+        };
+        file.write_all(&data).map_err(|err| CheckoutError::Other {
+            message: format!("Failed to write file {}", disk_path.display()),
+            err: err.into(),
+        })?;
+        set_executable(&disk_path, executable)?;
+        // Read the file state from the file descriptor. That way, know that the file
+        // exists and is of the expected type, and the stat information is most likely
+        // accurate, except for other processes modifying the file concurrently (The
+        // mtime is set at write time and won't change when we close the file.)
+        let metadata = file
+            .metadata()
+            .map_err(|err| checkout_error_for_stat_error(err, &disk_path))?;
+        let file_state = FileState::for_file(
+            executable,
+            data.len() as u64,
+            &metadata,
+            materialized_conflict_data,
+        );
+        Ok((path, file_state))
+    }
+}
+
+/// Writes a file by streaming its contents to disk without buffering them in
+/// memory.
+fn write_file(
+    disk_path: &Path,
+    contents: &mut dyn Read,
+    executable: bool,
+) -> Result<FileState, CheckoutError> {
+    let mut file = create_new_file(disk_path)?;
+    let size = io::copy(contents, &mut file).map_err(|err| CheckoutError::Other {
+        message: format!("Failed to write file {}", disk_path.display()),
+        err: err.into(),
+    })?;
+    set_executable(disk_path, executable)?;
+    // Read the file state from the file descriptor. That way, know that the file
+    // exists and is of the expected type, and the stat information is most likely
+    // accurate, except for other processes modifying the file concurrently (The
+    // mtime is set at write time and won't change when we close the file.)
+    let metadata = file
+        .metadata()
+        .map_err(|err| checkout_error_for_stat_error(err, disk_path))?;
+    Ok(FileState::for_file(executable, size, &metadata, None))
+}
+
+/// Writes the contents of the pending files in parallel.
+fn write_pending_files(
+    pending_files: Vec<PendingFile>,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<Vec<(RepoPathBuf, FileState)>, CheckoutError> {
+    pending_files
+        .into_par_iter()
+        .map(|pending_file| pending_file.write(conflict_marker_style))
+        .collect()
+}
+
 const RESERVED_DIR_NAMES: &[&str] = &[".git", ".jj"];
 
 /// Suppose the `disk_path` exists, checks if the last component points to
@@ -730,6 +886,7 @@ fn file_state(metadata: &Metadata) -> Option<FileState> {
 struct FsmonitorMatcher {
     matcher: Option<Box<dyn Matcher>>,
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
+    native_fsmonitor_clock: Option<crate::protos::working_copy::NativeFsmonitorClock>,
 }
 
 #[derive(Debug, Error)]
@@ -792,6 +949,7 @@ impl TreeState {
             own_mtime: MillisSinceEpoch(0),
             symlink_support: check_symlink_support().unwrap_or(false),
             watchman_clock: None,
+            native_fsmonitor_clock: None,
         }
     }
 
@@ -855,6 +1013,7 @@ impl TreeState {
             FileStatesMap::from_proto(proto.file_states, proto.is_file_states_sorted);
         self.sparse_patterns = sparse_patterns_from_proto(proto.sparse_patterns.as_ref());
         self.watchman_clock = proto.watchman_clock;
+        self.native_fsmonitor_clock = proto.native_fsmonitor_clock;
         Ok(())
     }
 
@@ -881,6 +1040,7 @@ impl TreeState {
         }
         proto.sparse_patterns = Some(sparse_patterns);
         proto.watchman_clock = self.watchman_clock.clone();
+        proto.native_fsmonitor_clock = self.native_fsmonitor_clock;
 
         let mut temp_file = NamedTempFile::new_in(&self.state_path).unwrap();
         temp_file
@@ -948,6 +1108,16 @@ impl TreeState {
             .await
             .map_err(|err| TreeStateError::Fsmonitor(Box::new(err)))
     }
+
+    #[cfg(all(feature = "native-fsmonitor", unix))]
+    #[instrument(skip(self))]
+    pub fn query_native_fsmonitor(
+        &self,
+    ) -> Result<(native::Clock, Option<Vec<PathBuf>>), TreeStateError> {
+        let previous_clock = self.native_fsmonitor_clock.map(native::Clock::from);
+        native::query_changed_files(&self.state_path, previous_clock)
+            .map_err(|err| TreeStateError::Fsmonitor(Box::new(err)))
+    }
 }
 
 /// Functions to snapshot local-disk files to the store.
@@ -975,6 +1145,7 @@ impl TreeState {
         let FsmonitorMatcher {
             matcher: fsmonitor_matcher,
             watchman_clock,
+            native_fsmonitor_clock,
         } = self.make_fsmonitor_matcher(fsmonitor_settings)?;
         let fsmonitor_matcher = match fsmonitor_matcher.as_ref() {
             None => &EverythingMatcher,
@@ -985,6 +1156,7 @@ impl TreeState {
         if matcher.visit(RepoPath::root()).is_nothing() {
             // No need to load the current tree, set up channels, etc.
             self.watchman_clock = watchman_clock;
+            self.native_fsmonitor_clock = native_fsmonitor_clock;
             return Ok((is_dirty, SnapshotStats::default()));
         }
 
@@ -1073,6 +1245,11 @@ impl TreeState {
         } else {
             tracing::info!("not updating watchman clock because there are untracked files");
         }
+        if stats.untracked_paths.is_empty() || native_fsmonitor_clock.is_none() {
+            self.native_fsmonitor_clock = native_fsmonitor_clock;
+        } else {
+            tracing::info!("not updating fsmonitor clock because there are untracked files");
+        }
         Ok((is_dirty, stats))
     }
 
@@ -1081,15 +1258,35 @@ impl TreeState {
         &self,
         fsmonitor_settings: &FsmonitorSettings,
     ) -> Result<FsmonitorMatcher, SnapshotError> {
-        let (watchman_clock, changed_files) = match fsmonitor_settings {
-            FsmonitorSettings::None => (None, None),
-            FsmonitorSettings::Test { changed_files } => (None, Some(changed_files.clone())),
+        let (watchman_clock, native_fsmonitor_clock, changed_files) = match fsmonitor_settings {
+            FsmonitorSettings::None => (None, None, None),
+            FsmonitorSettings::Test { changed_files } => (None, None, Some(changed_files.clone())),
+            #[cfg(all(feature = "native-fsmonitor", unix))]
+            FsmonitorSettings::Native => match self.query_native_fsmonitor() {
+                Ok((clock, changed_files)) => (None, Some(clock.into()), changed_files),
+                Err(err) => {
+                    tracing::warn!(?err, "Failed to query filesystem monitor");
+                    (None, None, None)
+                }
+            },
+            #[cfg(not(all(feature = "native-fsmonitor", unix)))]
+            FsmonitorSettings::Native => {
+                return Err(SnapshotError::Other {
+                    message: "Failed to query the filesystem monitor".to_string(),
+                    err: "The built-in filesystem monitor is only supported on Unix, and requires \
+                          jj to be compiled with the `native-fsmonitor` feature (consider \
+                          disabling `core.fsmonitor`)"
+                        .into(),
+                });
+            }
             #[cfg(feature = "watchman")]
             FsmonitorSettings::Watchman(config) => match self.query_watchman(config) {
-                Ok((watchman_clock, changed_files)) => (Some(watchman_clock.into()), changed_files),
+                Ok((watchman_clock, changed_files)) => {
+                    (Some(watchman_clock.into()), None, changed_files)
+                }
                 Err(err) => {
                     tracing::warn!(?err, "Failed to query filesystem monitor");
-                    (None, None)
+                    (None, None, None)
                 }
             },
             #[cfg(not(feature = "watchman"))]
@@ -1112,12 +1309,19 @@ impl TreeState {
                         .collect_vec()
                 });
 
-                Some(Box::new(FilesMatcher::new(repo_paths)))
+                if *fsmonitor_settings == FsmonitorSettings::Native {
+                    // A changed directory may contain any number of changed
+                    // files.
+                    Some(Box::new(PrefixMatcher::new(repo_paths)))
+                } else {
+                    Some(Box::new(FilesMatcher::new(repo_paths)))
+                }
             }
         };
         Ok(FsmonitorMatcher {
             matcher,
             watchman_clock,
+            native_fsmonitor_clock,
         })
     }
 }
@@ -1599,35 +1803,6 @@ impl FileSnapshotter<'_> {
 
 /// Functions to update local-disk files from the store.
 impl TreeState {
-    fn write_file(
-        &self,
-        disk_path: &Path,
-        contents: &mut dyn Read,
-        executable: bool,
-    ) -> Result<FileState, CheckoutError> {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create_new(true) // Don't overwrite un-ignored file. Don't follow symlink.
-            .open(disk_path)
-            .map_err(|err| CheckoutError::Other {
-                message: format!("Failed to open file {} for writing", disk_path.display()),
-                err: err.into(),
-            })?;
-        let size = io::copy(contents, &mut file).map_err(|err| CheckoutError::Other {
-            message: format!("Failed to write file {}", disk_path.display()),
-            err: err.into(),
-        })?;
-        self.set_executable(disk_path, executable)?;
-        // Read the file state from the file descriptor. That way, know that the file
-        // exists and is of the expected type, and the stat information is most likely
-        // accurate, except for other processes modifying the file concurrently (The
-        // mtime is set at write time and won't change when we close the file.)
-        let metadata = file
-            .metadata()
-            .map_err(|err| checkout_error_for_stat_error(err, disk_path))?;
-        Ok(FileState::for_file(executable, size, &metadata, None))
-    }
-
     fn write_symlink(&self, disk_path: &Path, target: String) -> Result<FileState, CheckoutError> {
         let target = PathBuf::from(&target);
         try_symlink(&target, disk_path).map_err(|err| CheckoutError::Other {
@@ -1644,50 +1819,6 @@ impl TreeState {
         Ok(FileState::for_symlink(&metadata))
     }
 
-    fn write_conflict(
-        &self,
-        disk_path: &Path,
-        conflict_data: Vec<u8>,
-        executable: bool,
-        materialized_conflict_data: Option<MaterializedConflictData>,
-    ) -> Result<FileState, CheckoutError> {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create_new(true) // Don't overwrite un-ignored file. Don't follow symlink.
-            .open(disk_path)
-            .map_err(|err| CheckoutError::Other {
-                message: format!("Failed to open file {} for writing", disk_path.display()),
-                err: err.into(),
-            })?;
-        file.write_all(&conflict_data)
-            .map_err(|err| CheckoutError::Other {
-                message: format!("Failed to write conflict to file {}", disk_path.display()),
-                err: err.into(),
-            })?;
-        let size = conflict_data.len() as u64;
-        self.set_executable(disk_path, executable)?;
-        let metadata = file
-            .metadata()
-            .map_err(|err| checkout_error_for_stat_error(err, disk_path))?;
-        Ok(FileState::for_file(
-            executable,
-            size,
-            &metadata,
-            materialized_conflict_data,
-        ))
-    }
-
-    #[cfg_attr(windows, allow(unused_variables))]
-    fn set_executable(&self, disk_path: &Path, executable: bool) -> Result<(), CheckoutError> {
-        #[cfg(unix)]
-        {
-            let mode = if executable { 0o755 } else { 0o644 };
-            fs::set_permissions(disk_path, fs::Permissions::from_mode(mode))
-                .map_err(|err| checkout_error_for_stat_error(err, disk_path))?;
-        }
-        Ok(())
-    }
-
     pub fn check_out(
         &mut self,
         new_tree: &MergedTree,
@@ -1774,6 +1905,19 @@ impl TreeState {
         };
         let mut changed_file_states = Vec::new();
         let mut deleted_files = HashSet::new();
+        // Files are created in diff order, but their contents are written in
+        // batches.
+        let mut pending_files = Vec::new();
+        let mut pending_size = 0;
+        // All trees will be read if the working copy is populated from scratch,
+        // so let the backend fetch them ahead.
+        if old_tree.id() == self.store.empty_merged_tree_id()
+            && matcher.visit(RepoPath::root()) == Visit::AllRecursively
+        {
+            for tree in new_tree.as_merge().iter() {
+                self.store.prefetch_tree(tree.dir(), tree.id()).await?;
+            }
+        }
         let mut diff_stream = old_tree
             .diff_stream(new_tree, matcher)
             .map(|TreeDiffEntry { path, values }| async {
@@ -1829,7 +1973,7 @@ impl TreeState {
             }
 
             // TODO: Check that the file has not changed before overwriting/removing it.
-            let file_state = match after {
+            let contents = match after {
                 MaterializedTreeValue::Absent | MaterializedTreeValue::AccessDenied(_) => {
                     let mut parent_dir = disk_path.parent().unwrap();
                     loop {
@@ -1841,52 +1985,81 @@ impl TreeState {
                     deleted_files.insert(path);
                     continue;
                 }
-                MaterializedTreeValue::File(mut file) => {
-                    self.write_file(&disk_path, &mut file.reader, file.executable)?
+                MaterializedTreeValue::File(file) => {
+                    let MaterializedFileValue {
+                        id,
+                        executable,
+                        mut reader,
+                    } = file;
+                    // Read up to the size limit to tell small files, which
+                    // are batched, from large ones, which are streamed.
+                    let mut data = Vec::new();
+                    (&mut reader)
+                        .take(MAX_PENDING_FILE_SIZE + 1)
+                        .read_to_end(&mut data)
+                        .map_err(|err| BackendError::ReadFile {
+                            path: path.clone(),
+                            id,
+                            source: err.into(),
+                        })?;
+                    if data.len() as u64 > MAX_PENDING_FILE_SIZE {
+                        let mut contents = io::Cursor::new(data).chain(reader);
+                        let file_state = write_file(&disk_path, &mut contents, executable)?;
+                        changed_file_states.push((path, file_state));
+                        continue;
+                    }
+                    PendingFileContents::Data { data, executable }
                 }
                 MaterializedTreeValue::Symlink { id: _, target } => {
                     if self.symlink_support {
-                        self.write_symlink(&disk_path, target)?
-                    } else {
-                        self.write_file(&disk_path, &mut target.as_bytes(), false)?
+                        let file_state = self.write_symlink(&disk_path, target)?;
+                        changed_file_states.push((path, file_state));
+                        continue;
+                    }
+                    PendingFileContents::Data {
+                        data: target.into_bytes(),
+                        executable: false,
                     }
                 }
                 MaterializedTreeValue::GitSubmodule(_) => {
                     eprintln!("ignoring git submodule at {path:?}");
-                    FileState::for_gitsubmodule()
+                    changed_file_states.push((path, FileState::for_gitsubmodule()));
+                    continue;
                 }
                 MaterializedTreeValue::Tree(_) => {
                     panic!("unexpected tree entry in diff at {path:?}");
                 }
-                MaterializedTreeValue::FileConflict(file) => {
-                    let conflict_marker_len =
-                        choose_materialized_conflict_marker_len(&file.contents);
-                    let data = materialize_merge_result_to_bytes_with_marker_len(
-                        &file.contents,
-                        conflict_marker_style,
-                        conflict_marker_len,
-                    )
-                    .into();
-                    let materialized_conflict_data = MaterializedConflictData {
-                        conflict_marker_len: conflict_marker_len.try_into().unwrap_or(u32::MAX),
-                    };
-                    self.write_conflict(
-                        &disk_path,
-                        data,
-                        file.executable.unwrap_or(false),
-                        Some(materialized_conflict_data),
-                    )?
-                }
+                MaterializedTreeValue::FileConflict(file) => PendingFileContents::Conflict {
+                    contents: file.contents,
+                    executable: file.executable.unwrap_or(false),
+                },
                 MaterializedTreeValue::OtherConflict { id } => {
                     // Unless all terms are regular files, we can't do much
                     // better than trying to describe the merge.
-                    let data = id.describe().into_bytes();
-                    let executable = false;
-                    self.write_conflict(&disk_path, data, executable, None)?
+                    PendingFileContents::Data {
+                        data: id.describe().into_bytes(),
+                        executable: false,
+                    }
                 }
             };
-            changed_file_states.push((path, file_state));
+            let file = create_new_file(&disk_path)?;
+            pending_size += contents.size();
+            pending_files.push(PendingFile {
+                path,
+                disk_path,
+                file,
+                contents,
+            });
+            if pending_files.len() >= MAX_PENDING_FILES || pending_size >= MAX_PENDING_SIZE {
+                let written_file_states =
+                    write_pending_files(mem::take(&mut pending_files), conflict_marker_style)?;
+                changed_file_states.extend(written_file_states);
+                pending_size = 0;
+            }
         }
+        let written_file_states = write_pending_files(pending_files, conflict_marker_style)?;
+        changed_file_states.extend(written_file_states);
+        changed_file_states.sort_unstable_by(|(path1, _), (path2, _)| path1.cmp(path2));
         self.file_states
             .merge_in(changed_file_states, &deleted_files);
         Ok(stats)
@@ -2183,6 +2356,18 @@ impl LocalWorkingCopy {
                 err: err.into(),
             })
     }
+
+    #[cfg(all(feature = "native-fsmonitor", unix))]
+    pub fn query_native_fsmonitor(
+        &self,
+    ) -> Result<(native::Clock, Option<Vec<PathBuf>>), WorkingCopyStateError> {
+        self.tree_state()?
+            .query_native_fsmonitor()
+            .map_err(|err| WorkingCopyStateError {
+                message: "Failed to query the filesystem monitor".to_string(),
+                err: err.into(),
+            })
+    }
 }
 
 pub struct LocalWorkingCopyFactory {}
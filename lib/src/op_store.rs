@@ -385,6 +385,7 @@ impl Operation {
             username: "".to_string(),
             is_snapshot: false,
             tags: HashMap::new(),
+            undo_relation: None,
         };
         Operation {
             view_id: root_view_id,
@@ -394,7 +395,7 @@ impl Operation {
     }
 }
 
-#[derive(ContentHash, PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct OperationMetadata {
     pub start_time: Timestamp,
     pub end_time: Timestamp,
@@ -406,6 +407,57 @@ pub struct OperationMetadata {
     /// copy.
     pub is_snapshot: bool,
     pub tags: HashMap<String, String>,
+    /// Set if this operation was created by `undo` or `redo`.
+    pub undo_relation: Option<UndoRelation>,
+}
+
+impl ContentHash for OperationMetadata {
+    fn hash(&self, state: &mut impl crate::content_hash::DigestUpdate) {
+        let Self {
+            start_time,
+            end_time,
+            description,
+            hostname,
+            username,
+            is_snapshot,
+            tags,
+            undo_relation,
+        } = self;
+        start_time.hash(state);
+        end_time.hash(state);
+        description.hash(state);
+        hostname.hash(state);
+        username.hash(state);
+        is_snapshot.hash(state);
+        tags.hash(state);
+        // Only hashed if present so the IDs of operations written before this
+        // field was added don't change.
+        if let Some(relation) = undo_relation {
+            relation.hash(state);
+        }
+    }
+}
+
+/// How an undo/redo operation relates to the operation it reverted.
+///
+/// The undo stack is reconstructed from these relations: undoing an operation
+/// moves the current position to its parent, and redoing an undo operation
+/// moves the position back to where it was before the undo.
+#[derive(ContentHash, PartialEq, Eq, Clone, Debug)]
+pub enum UndoRelation {
+    /// The operation restored the state before the given operation.
+    Undo(OperationId),
+    /// The operation reverted the given undo operation.
+    Redo(OperationId),
+}
+
+impl UndoRelation {
+    /// The operation that was undone or redone.
+    pub fn target(&self) -> &OperationId {
+        match self {
+            UndoRelation::Undo(id) | UndoRelation::Redo(id) => id,
+        }
+    }
 }
 
 /// Data to be loaded into the root operation/view.
@@ -467,8 +519,33 @@ pub trait OpStore: Send + Sync + Debug {
     /// removed. In addition to that, objects created after `keep_newer` will be
     /// preserved. This mitigates a risk of deleting new heads created
     /// concurrently by another process.
-    // TODO: return stats?
     fn gc(&self, head_ids: &[OperationId], keep_newer: SystemTime) -> OpStoreResult<()>;
+
+    /// Like [`OpStore::gc()`], but also reports what was removed.
+    ///
+    /// If `dry_run` is true, nothing is removed, and the returned stats
+    /// describe what would be removed. The default implementation reports no
+    /// stats.
+    fn gc_with_stats(
+        &self,
+        head_ids: &[OperationId],
+        keep_newer: SystemTime,
+        dry_run: bool,
+    ) -> OpStoreResult<OpStoreGcStats> {
+        if !dry_run {
+            self.gc(head_ids, keep_newer)?;
+        }
+        Ok(OpStoreGcStats::default())
+    }
+}
+
+/// Statistics about the objects removed by [`OpStore::gc_with_stats()`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OpStoreGcStats {
+    /// Number of operations removed.
+    pub num_operations: usize,
+    /// Number of views removed.
+    pub num_views: usize,
 }
 
 #[cfg(test)]
@@ -30,10 +30,13 @@ use crate::op_heads_store;
 use crate::op_heads_store::OpHeadResolutionError;
 use crate::op_heads_store::OpHeadsStore;
 use crate::op_heads_store::OpHeadsStoreError;
+use crate::op_store;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OpStoreResult;
 use crate::op_store::OperationId;
+use crate::op_store::OperationMetadata;
+use crate::op_store::UndoRelation;
 use crate::operation::Operation;
 use crate::repo::ReadonlyRepo;
 use crate::repo::Repo as _;
@@ -318,6 +321,15 @@ pub fn reparent_range(
             .filter_map(|id| rewritten_ids.get(id).or_else(|| dest_once.take()))
             .cloned()
             .collect();
+        // Undo/redo relations point to ancestor operations, which may have
+        // been rewritten or become unreachable.
+        let remap = |id: OperationId| rewritten_ids.get(&id).cloned().unwrap_or(id);
+        data.metadata.undo_relation = match data.metadata.undo_relation.take() {
+            Some(relation) if unreachable_ids.contains(relation.target()) => None,
+            Some(UndoRelation::Undo(id)) => Some(UndoRelation::Undo(remap(id))),
+            Some(UndoRelation::Redo(id)) => Some(UndoRelation::Redo(remap(id))),
+            None => None,
+        };
         let new_id = op_store.write_operation(&data)?;
         rewritten_ids.insert(old_op.id().clone(), new_id);
     }
@@ -334,3 +346,61 @@ pub fn reparent_range(
         unreachable_count: unreachable_ids.len(),
     })
 }
+
+/// Collapses the operation range `root_op..compact_op` into a single operation
+/// which has the view of the `compact_op`, and reparents the range
+/// `compact_op..head_ops` onto it.
+///
+/// Returns the new head operation ids as well as some stats. The
+/// `unreachable_count` is the number of the collapsed operations. If the range
+/// `root_op..compact_op` contains less than two operations, nothing is changed
+/// and the `new_head_ids` will be the ids of the `head_ops`.
+pub fn compact_range(
+    op_store: &Arc<dyn OpStore>,
+    root_op: &Operation,
+    compact_op: &Operation,
+    head_ops: &[Operation],
+) -> OpStoreResult<ReparentStats> {
+    let root_ids: HashSet<_> = walk_ancestors(slice::from_ref(root_op))
+        .map_ok(|op| op.id().clone())
+        .try_collect()?;
+    let ops_to_compact: Vec<_> = walk_ancestors(slice::from_ref(compact_op))
+        .filter_ok(|op| !root_ids.contains(op.id()))
+        .try_collect()?;
+    if ops_to_compact.len() < 2 {
+        return Ok(ReparentStats {
+            new_head_ids: head_ops.iter().map(|op| op.id().clone()).collect(),
+            rewritten_count: 0,
+            unreachable_count: 0,
+        });
+    }
+
+    let compact_metadata = compact_op.metadata();
+    let metadata = OperationMetadata {
+        start_time: ops_to_compact
+            .iter()
+            .map(|op| op.metadata().start_time)
+            .min_by_key(|timestamp| timestamp.timestamp)
+            .unwrap(),
+        end_time: compact_metadata.end_time,
+        description: format!("compact {} operations", ops_to_compact.len()),
+        hostname: compact_metadata.hostname.clone(),
+        username: compact_metadata.username.clone(),
+        is_snapshot: ops_to_compact.iter().all(|op| op.metadata().is_snapshot),
+        tags: HashMap::new(),
+        undo_relation: None,
+    };
+    let data = op_store::Operation {
+        view_id: compact_op.view_id().clone(),
+        parents: vec![root_op.id().clone()],
+        metadata,
+    };
+    let new_id = op_store.write_operation(&data)?;
+    let new_op = Operation::new(op_store.clone(), new_id, data);
+    reparent_range(
+        op_store.as_ref(),
+        slice::from_ref(compact_op),
+        head_ops,
+        &new_op,
+    )
+}
@@ -12,6 +12,8 @@ pub struct Commit {
     /// TODO(#1624): delete when we assume that all commits use this format
     #[prost(bool, tag = "10")]
     pub uses_tree_conflict_format: bool,
+    #[prost(message, repeated, tag = "11")]
+    pub copies: ::prost::alloc::vec::Vec<commit::Copy>,
     #[deprecated]
     #[prost(bool, tag = "8")]
     pub is_open: bool,
@@ -19,3 +21,14 @@ pub struct Commit {
     #[prost(bool, tag = "9")]
     pub is_pruned: bool,
 }
+/// Nested message and enum types in `Commit`.
+pub mod commit {
+    /// A copy or rename recorded by the user.
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Copy {
+        #[prost(string, tag = "1")]
+        pub source: ::prost::alloc::string::String,
+        #[prost(string, tag = "2")]
+        pub target: ::prost::alloc::string::String,
+    }
+}
@@ -149,6 +149,20 @@ pub struct OperationMetadata {
         ::prost::alloc::string::String,
         ::prost::alloc::string::String,
     >,
+    #[prost(oneof = "operation_metadata::UndoRelation", tags = "8, 9")]
+    pub undo_relation: ::core::option::Option<operation_metadata::UndoRelation>,
+}
+/// Nested message and enum types in `OperationMetadata`.
+pub mod operation_metadata {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum UndoRelation {
+        /// The operation restores the state before this operation.
+        #[prost(bytes, tag = "8")]
+        UndoOf(::prost::alloc::vec::Vec<u8>),
+        /// The operation reverts this undo operation.
+        #[prost(bytes, tag = "9")]
+        RedoOf(::prost::alloc::vec::Vec<u8>),
+    }
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
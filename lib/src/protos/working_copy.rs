@@ -48,6 +48,8 @@ pub struct TreeState {
     pub sparse_patterns: ::core::option::Option<SparsePatterns>,
     #[prost(message, optional, tag = "4")]
     pub watchman_clock: ::core::option::Option<WatchmanClock>,
+    #[prost(message, optional, tag = "7")]
+    pub native_fsmonitor_clock: ::core::option::Option<NativeFsmonitorClock>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WatchmanClock {
@@ -64,6 +66,15 @@ pub mod watchman_clock {
         UnixTimestamp(i64),
     }
 }
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct NativeFsmonitorClock {
+    /// Identifies the daemon instance. The sequence number is meaningless to
+    /// other instances.
+    #[prost(uint64, tag = "1")]
+    pub instance_id: u64,
+    #[prost(uint64, tag = "2")]
+    pub sequence: u64,
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Checkout {
     /// The operation at which the working copy was updated.
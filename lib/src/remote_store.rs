@@ -0,0 +1,1194 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commit backend and operation store which talk to a remote server.
+//!
+//! The client and the server communicate over HTTP/1.1. Objects are encoded
+//! in the same protobuf messages as the simple backend and the simple
+//! operation store. See `docs/technical/remote-store.md` for the protocol.
+
+#![allow(missing_docs)]
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::io::BufRead as _;
+use std::io::BufReader;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Write as _;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs as _;
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use pollster::FutureExt as _;
+use prost::Message as _;
+use thiserror::Error;
+
+use crate::backend::Backend;
+use crate::backend::BackendError;
+use crate::backend::BackendInitError;
+use crate::backend::BackendLoadError;
+use crate::backend::BackendResult;
+use crate::backend::ChangeId;
+use crate::backend::Commit;
+use crate::backend::CommitId;
+use crate::backend::Conflict;
+use crate::backend::ConflictId;
+use crate::backend::CopyRecord;
+use crate::backend::FileId;
+use crate::backend::SigningFn;
+use crate::backend::SymlinkId;
+use crate::backend::Tree;
+use crate::backend::TreeId;
+use crate::index::Index;
+use crate::object_id::HexPrefix;
+use crate::object_id::ObjectId;
+use crate::object_id::PrefixResolution;
+use crate::op_store::OpStore;
+use crate::op_store::OpStoreError;
+use crate::op_store::OpStoreResult;
+use crate::op_store::Operation;
+use crate::op_store::OperationId;
+use crate::op_store::View;
+use crate::op_store::ViewId;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::simple_backend::conflict_from_proto;
+use crate::simple_backend::conflict_to_proto;
+use crate::simple_backend::decode_commit_with_signature;
+use crate::simple_backend::encode_commit_with_signature;
+use crate::simple_backend::tree_from_proto;
+use crate::simple_backend::tree_to_proto;
+use crate::simple_op_store::operation_from_proto;
+use crate::simple_op_store::operation_to_proto;
+use crate::simple_op_store::view_from_proto;
+use crate::simple_op_store::view_to_proto;
+
+/// Name of the file storing the server address in the store directory.
+const ADDRESS_FILE_NAME: &str = "address";
+
+/// Maximum size of request and response bodies.
+const MAX_BODY_SIZE: usize = 256 * 1024 * 1024;
+/// Maximum length of the start line and each header line.
+const MAX_LINE_LENGTH: u64 = 8 * 1024;
+/// Timeout for connecting to the server, and for each read or write.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Timeout for each read or write on the server. Idle connections are closed
+/// after this duration.
+const SERVER_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_CONNECTIONS: usize = 16;
+/// Maximum number of connections kept open by the client for reuse.
+const MAX_IDLE_CONNECTIONS: usize = 8;
+/// Delay before accepting connections again after a failure, which is usually
+/// caused by running out of resources.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+const STATUS_OK: u16 = 200;
+const STATUS_BAD_REQUEST: u16 = 400;
+const STATUS_NOT_FOUND: u16 = 404;
+const STATUS_CONFLICT: u16 = 409;
+const STATUS_PAYLOAD_TOO_LARGE: u16 = 413;
+const STATUS_INTERNAL_SERVER_ERROR: u16 = 500;
+
+/// Error returned by the remote server or the transport.
+#[derive(Debug, Error)]
+pub enum RemoteStoreError {
+    #[error("Failed to communicate with remote store at {address}")]
+    Io {
+        address: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Remote store responded with status {status}: {message}")]
+    Status { status: u16, message: String },
+    #[error("Unexpected response from remote store: {0}")]
+    InvalidResponse(String),
+}
+
+impl RemoteStoreError {
+    fn is_not_found(&self) -> bool {
+        matches!(self, Self::Status { status, .. } if *status == STATUS_NOT_FOUND)
+    }
+}
+
+#[derive(Debug)]
+struct HttpResponse {
+    status: u16,
+    object_id: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Start line and headers of HTTP message.
+#[derive(Debug)]
+struct HttpHead {
+    start_line: String,
+    object_id: Option<String>,
+    content_length: usize,
+    /// Whether the connection will be closed after this message.
+    close: bool,
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Reads a line of at most `MAX_LINE_LENGTH` bytes. Returns `None` at the end
+/// of the stream.
+fn read_http_line(reader: &mut impl io::BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    reader.take(MAX_LINE_LENGTH).read_line(&mut line)?;
+    if line.is_empty() {
+        Ok(None)
+    } else if line.ends_with('\n') {
+        Ok(Some(line.trim_end().to_owned()))
+    } else {
+        Err(invalid_data("line too long or truncated"))
+    }
+}
+
+/// Reads the start line and the headers of HTTP message. Returns `None` if the
+/// stream ended before the message started.
+fn read_http_head(reader: &mut impl io::BufRead) -> io::Result<Option<HttpHead>> {
+    let Some(start_line) = read_http_line(reader)? else {
+        return Ok(None);
+    };
+    let mut head = HttpHead {
+        start_line,
+        object_id: None,
+        content_length: 0,
+        close: false,
+    };
+    loop {
+        let line =
+            read_http_line(reader)?.ok_or_else(|| invalid_data("unexpected end of headers"))?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| invalid_data("invalid header"))?;
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            head.content_length = value.parse().map_err(|_| invalid_data("invalid length"))?;
+        } else if name.eq_ignore_ascii_case("object-id") {
+            head.object_id = Some(value.to_owned());
+        } else if name.eq_ignore_ascii_case("connection") {
+            head.close = value.eq_ignore_ascii_case("close");
+        }
+    }
+    Ok(Some(head))
+}
+
+fn read_http_body(reader: &mut impl io::Read, content_length: usize) -> io::Result<Vec<u8>> {
+    if content_length > MAX_BODY_SIZE {
+        return Err(invalid_data("body too large"));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(body)
+}
+
+/// Percent-encodes `text` for use in the query string.
+fn encode_query_value(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for &b in text.as_bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) {
+            encoded.push(char::from(b));
+        } else {
+            encoded.push_str(&format!("%{b:02X}"));
+        }
+    }
+    encoded
+}
+
+fn decode_query_value(text: &str) -> Option<String> {
+    let mut decoded = Vec::with_capacity(text.len());
+    let mut bytes = text.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            decoded.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            decoded.push(b);
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Client of the remote store server.
+#[derive(Debug)]
+struct RemoteStoreClient {
+    address: String,
+    /// Connections which can be reused for subsequent requests. At most
+    /// `MAX_IDLE_CONNECTIONS` are kept.
+    idle_connections: Mutex<Vec<BufReader<TcpStream>>>,
+}
+
+impl RemoteStoreClient {
+    fn new(address: String) -> Self {
+        RemoteStoreClient {
+            address,
+            idle_connections: Mutex::new(vec![]),
+        }
+    }
+
+    fn connect(&self) -> io::Result<BufReader<TcpStream>> {
+        let mut last_err = None;
+        for addr in self.address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, CLIENT_TIMEOUT) {
+                Ok(stream) => {
+                    // Requests are written in multiple chunks, which shouldn't
+                    // be delayed on the reused connection.
+                    stream.set_nodelay(true)?;
+                    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+                    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+                    return Ok(BufReader::new(stream));
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to")
+        }))
+    }
+
+    fn send_request(
+        &self,
+        connection: &mut BufReader<TcpStream>,
+        method: &str,
+        target: &str,
+        body: &[u8],
+    ) -> io::Result<(HttpHead, Vec<u8>)> {
+        let stream = connection.get_mut();
+        write!(
+            stream,
+            "{method} {target} HTTP/1.1\r\nHost: {address}\r\nContent-Length: {len}\r\n\r\n",
+            address = self.address,
+            len = body.len()
+        )?;
+        stream.write_all(body)?;
+        stream.flush()?;
+        let head = read_http_head(connection)?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        let body = read_http_body(connection, head.content_length)?;
+        Ok((head, body))
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        target: &str,
+        body: &[u8],
+    ) -> Result<HttpResponse, RemoteStoreError> {
+        let to_io_err = |source| RemoteStoreError::Io {
+            address: self.address.clone(),
+            source,
+        };
+        let idle_connection = self.idle_connections.lock().unwrap().pop();
+        let reused = idle_connection.and_then(|mut connection| {
+            // The server may have closed the idle connection. Since objects
+            // are content-addressed, the request can be safely sent again.
+            let response = self
+                .send_request(&mut connection, method, target, body)
+                .ok()?;
+            Some((connection, response))
+        });
+        let (connection, (head, body)) = match reused {
+            Some(reused) => reused,
+            None => {
+                let mut connection = self.connect().map_err(to_io_err)?;
+                let response = self
+                    .send_request(&mut connection, method, target, body)
+                    .map_err(to_io_err)?;
+                (connection, response)
+            }
+        };
+        if !head.close {
+            let mut idle_connections = self.idle_connections.lock().unwrap();
+            if idle_connections.len() < MAX_IDLE_CONNECTIONS {
+                idle_connections.push(connection);
+            }
+        }
+        let status = head
+            .start_line
+            .split(' ')
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(|| RemoteStoreError::InvalidResponse(head.start_line.clone()))?;
+        if status != STATUS_OK {
+            return Err(RemoteStoreError::Status {
+                status,
+                message: String::from_utf8_lossy(&body).into_owned(),
+            });
+        }
+        Ok(HttpResponse {
+            status,
+            object_id: head.object_id,
+            body,
+        })
+    }
+
+    fn get(&self, target: &str) -> Result<Vec<u8>, RemoteStoreError> {
+        Ok(self.request("GET", target, &[])?.body)
+    }
+
+    /// Posts new object, and returns the id bytes and the response body.
+    fn post(&self, target: &str, body: &[u8]) -> Result<(Vec<u8>, Vec<u8>), RemoteStoreError> {
+        let response = self.request("POST", target, body)?;
+        let id = response
+            .object_id
+            .as_deref()
+            .and_then(|hex| hex::decode(hex).ok())
+            .ok_or_else(|| {
+                RemoteStoreError::InvalidResponse(format!(
+                    "invalid object id in response with status {}",
+                    response.status
+                ))
+            })?;
+        Ok((id, response.body))
+    }
+}
+
+/// Properties of the stores served by the remote server.
+#[derive(Debug)]
+struct ServerInfo {
+    root_commit_id: CommitId,
+    root_change_id: ChangeId,
+    empty_tree_id: TreeId,
+    root_operation_id: OperationId,
+    concurrency: usize,
+}
+
+impl ServerInfo {
+    fn fetch(client: &RemoteStoreClient) -> Result<Self, RemoteStoreError> {
+        let body = client.get("/info")?;
+        let text = String::from_utf8(body)
+            .map_err(|_| RemoteStoreError::InvalidResponse("non-UTF-8 info".to_owned()))?;
+        let field = |name: &str| {
+            text.lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(' '))
+                .ok_or_else(|| RemoteStoreError::InvalidResponse(format!("missing {name}")))
+        };
+        let hex_field = |name: &str| {
+            let value = field(name)?;
+            hex::decode(value)
+                .map_err(|_| RemoteStoreError::InvalidResponse(format!("invalid {name}")))
+        };
+        Ok(ServerInfo {
+            root_commit_id: CommitId::new(hex_field("root-commit-id")?),
+            root_change_id: ChangeId::new(hex_field("root-change-id")?),
+            empty_tree_id: TreeId::new(hex_field("empty-tree-id")?),
+            root_operation_id: OperationId::new(hex_field("root-operation-id")?),
+            concurrency: field("concurrency")?
+                .parse()
+                .map_err(|_| RemoteStoreError::InvalidResponse("invalid concurrency".to_owned()))?,
+        })
+    }
+}
+
+fn connect(store_path: &Path) -> Result<(RemoteStoreClient, ServerInfo), BackendLoadError> {
+    let address = fs::read_to_string(store_path.join(ADDRESS_FILE_NAME))
+        .map_err(|err| BackendLoadError(err.into()))?;
+    let client = RemoteStoreClient::new(address.trim().to_owned());
+    let info = ServerInfo::fetch(&client).map_err(|err| BackendLoadError(err.into()))?;
+    Ok((client, info))
+}
+
+fn write_address(store_path: &Path, address: &str) -> Result<(), BackendInitError> {
+    fs::write(store_path.join(ADDRESS_FILE_NAME), address)
+        .map_err(|err| BackendInitError(err.into()))
+}
+
+fn path_query(path: &RepoPath) -> String {
+    format!(
+        "?path={}",
+        encode_query_value(path.as_internal_file_string())
+    )
+}
+
+fn to_read_err(err: RemoteStoreError, id: &impl ObjectId) -> BackendError {
+    if err.is_not_found() {
+        BackendError::ObjectNotFound {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err.into(),
+        }
+    } else {
+        BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err.into(),
+        }
+    }
+}
+
+fn to_write_err(err: RemoteStoreError, object_type: &'static str) -> BackendError {
+    BackendError::WriteObject {
+        object_type,
+        source: err.into(),
+    }
+}
+
+fn decode_err(id: &impl ObjectId) -> impl FnOnce(prost::DecodeError) -> BackendError + '_ {
+    |err| BackendError::ReadObject {
+        object_type: id.object_type(),
+        hash: id.hex(),
+        source: err.into(),
+    }
+}
+
+/// Backend which reads and writes objects through remote server.
+#[derive(Debug)]
+pub struct RemoteBackend {
+    client: RemoteStoreClient,
+    info: ServerInfo,
+}
+
+impl RemoteBackend {
+    pub fn name() -> &'static str {
+        "remote"
+    }
+
+    /// Initializes the backend to connect to the server at `address` (in
+    /// `host:port` form.)
+    pub fn init(store_path: &Path, address: &str) -> Result<Self, BackendInitError> {
+        write_address(store_path, address)?;
+        Self::load(store_path).map_err(|BackendLoadError(err)| BackendInitError(err))
+    }
+
+    pub fn load(store_path: &Path) -> Result<Self, BackendLoadError> {
+        let (client, info) = connect(store_path)?;
+        Ok(RemoteBackend { client, info })
+    }
+}
+
+#[async_trait]
+impl Backend for RemoteBackend {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        Self::name()
+    }
+
+    fn commit_id_length(&self) -> usize {
+        self.info.root_commit_id.as_bytes().len()
+    }
+
+    fn change_id_length(&self) -> usize {
+        self.info.root_change_id.as_bytes().len()
+    }
+
+    fn root_commit_id(&self) -> &CommitId {
+        &self.info.root_commit_id
+    }
+
+    fn root_change_id(&self) -> &ChangeId {
+        &self.info.root_change_id
+    }
+
+    fn empty_tree_id(&self) -> &TreeId {
+        &self.info.empty_tree_id
+    }
+
+    fn concurrency(&self) -> usize {
+        self.info.concurrency
+    }
+
+    async fn read_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
+        let target = format!("/files/{}{}", id.hex(), path_query(path));
+        let body = self
+            .client
+            .get(&target)
+            .map_err(|err| to_read_err(err, id))?;
+        Ok(Box::new(Cursor::new(body)))
+    }
+
+    async fn write_file(
+        &self,
+        path: &RepoPath,
+        contents: &mut (dyn Read + Send),
+    ) -> BackendResult<FileId> {
+        let mut body = vec![];
+        contents
+            .read_to_end(&mut body)
+            .map_err(|err| BackendError::WriteObject {
+                object_type: "file",
+                source: err.into(),
+            })?;
+        let target = format!("/files{}", path_query(path));
+        let (id, _) = self
+            .client
+            .post(&target, &body)
+            .map_err(|err| to_write_err(err, "file"))?;
+        Ok(FileId::new(id))
+    }
+
+    async fn read_symlink(&self, path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
+        let target = format!("/symlinks/{}{}", id.hex(), path_query(path));
+        let body = self
+            .client
+            .get(&target)
+            .map_err(|err| to_read_err(err, id))?;
+        String::from_utf8(body).map_err(|err| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err.into(),
+        })
+    }
+
+    async fn write_symlink(&self, path: &RepoPath, target: &str) -> BackendResult<SymlinkId> {
+        let request_target = format!("/symlinks{}", path_query(path));
+        let (id, _) = self
+            .client
+            .post(&request_target, target.as_bytes())
+            .map_err(|err| to_write_err(err, "symlink"))?;
+        Ok(SymlinkId::new(id))
+    }
+
+    async fn read_tree(&self, path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
+        let target = format!("/trees/{}{}", id.hex(), path_query(path));
+        let body = self
+            .client
+            .get(&target)
+            .map_err(|err| to_read_err(err, id))?;
+        let proto = crate::protos::simple_store::Tree::decode(&*body).map_err(decode_err(id))?;
+        Ok(tree_from_proto(proto))
+    }
+
+    async fn write_tree(&self, path: &RepoPath, contents: &Tree) -> BackendResult<TreeId> {
+        let target = format!("/trees{}", path_query(path));
+        let body = tree_to_proto(contents).encode_to_vec();
+        let (id, _) = self
+            .client
+            .post(&target, &body)
+            .map_err(|err| to_write_err(err, "tree"))?;
+        Ok(TreeId::new(id))
+    }
+
+    fn read_conflict(&self, path: &RepoPath, id: &ConflictId) -> BackendResult<Conflict> {
+        let target = format!("/conflicts/{}{}", id.hex(), path_query(path));
+        let body = self
+            .client
+            .get(&target)
+            .map_err(|err| to_read_err(err, id))?;
+        let proto =
+            crate::protos::simple_store::Conflict::decode(&*body).map_err(decode_err(id))?;
+        Ok(conflict_from_proto(proto))
+    }
+
+    fn write_conflict(&self, path: &RepoPath, contents: &Conflict) -> BackendResult<ConflictId> {
+        let target = format!("/conflicts{}", path_query(path));
+        let body = conflict_to_proto(contents).encode_to_vec();
+        let (id, _) = self
+            .client
+            .post(&target, &body)
+            .map_err(|err| to_write_err(err, "conflict"))?;
+        Ok(ConflictId::new(id))
+    }
+
+    async fn read_commit(&self, id: &CommitId) -> BackendResult<Commit> {
+        let target = format!("/commits/{}", id.hex());
+        let body = self
+            .client
+            .get(&target)
+            .map_err(|err| to_read_err(err, id))?;
+        decode_commit_with_signature(&body).ok_or_else(|| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: "invalid commit data".into(),
+        })
+    }
+
+    async fn write_commit(
+        &self,
+        contents: Commit,
+        sign_with: Option<&mut SigningFn>,
+    ) -> BackendResult<(CommitId, Commit)> {
+        // The signed data would depend on the server backend.
+        if sign_with.is_some() {
+            return Err(BackendError::Unsupported(
+                "The remote backend doesn't support signing commits".to_owned(),
+            ));
+        }
+        let body = encode_commit_with_signature(&contents);
+        let (id, body) = self
+            .client
+            .post("/commits", &body)
+            .map_err(|err| to_write_err(err, "commit"))?;
+        let commit = decode_commit_with_signature(&body).ok_or_else(|| {
+            to_write_err(
+                RemoteStoreError::InvalidResponse("invalid commit data".to_owned()),
+                "commit",
+            )
+        })?;
+        Ok((CommitId::new(id), commit))
+    }
+
+    fn get_copy_records(
+        &self,
+        _paths: Option<&[RepoPathBuf]>,
+        _root: &CommitId,
+        _head: &CommitId,
+    ) -> BackendResult<BoxStream<'_, BackendResult<CopyRecord>>> {
+        Err(BackendError::Unsupported(
+            "The remote backend doesn't track copies".to_owned(),
+        ))
+    }
+
+    fn gc(&self, _index: &dyn Index, _keep_newer: SystemTime) -> BackendResult<()> {
+        // Garbage collection is up to the server.
+        Ok(())
+    }
+}
+
+fn to_op_read_err(err: RemoteStoreError, id: &impl ObjectId) -> OpStoreError {
+    if err.is_not_found() {
+        OpStoreError::ObjectNotFound {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err.into(),
+        }
+    } else {
+        OpStoreError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: err.into(),
+        }
+    }
+}
+
+fn to_op_write_err(err: RemoteStoreError, object_type: &'static str) -> OpStoreError {
+    OpStoreError::WriteObject {
+        object_type,
+        source: err.into(),
+    }
+}
+
+/// Operation store which reads and writes objects through remote server.
+///
+/// Operation heads are not managed by this store.
+#[derive(Debug)]
+pub struct RemoteOpStore {
+    client: RemoteStoreClient,
+    info: ServerInfo,
+}
+
+impl RemoteOpStore {
+    pub fn name() -> &'static str {
+        "remote_op_store"
+    }
+
+    /// Initializes the store to connect to the server at `address` (in
+    /// `host:port` form.)
+    pub fn init(store_path: &Path, address: &str) -> Result<Self, BackendInitError> {
+        write_address(store_path, address)?;
+        Self::load(store_path).map_err(|BackendLoadError(err)| BackendInitError(err))
+    }
+
+    pub fn load(store_path: &Path) -> Result<Self, BackendLoadError> {
+        let (client, info) = connect(store_path)?;
+        Ok(RemoteOpStore { client, info })
+    }
+}
+
+impl OpStore for RemoteOpStore {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        Self::name()
+    }
+
+    fn root_operation_id(&self) -> &OperationId {
+        &self.info.root_operation_id
+    }
+
+    fn read_view(&self, id: &ViewId) -> OpStoreResult<View> {
+        let target = format!("/views/{}", id.hex());
+        let body = self
+            .client
+            .get(&target)
+            .map_err(|err| to_op_read_err(err, id))?;
+        let proto = crate::protos::op_store::View::decode(&*body).map_err(|err| {
+            OpStoreError::ReadObject {
+                object_type: id.object_type(),
+                hash: id.hex(),
+                source: err.into(),
+            }
+        })?;
+        Ok(view_from_proto(proto))
+    }
+
+    fn write_view(&self, contents: &View) -> OpStoreResult<ViewId> {
+        let body = view_to_proto(contents).encode_to_vec();
+        let (id, _) = self
+            .client
+            .post("/views", &body)
+            .map_err(|err| to_op_write_err(err, "view"))?;
+        Ok(ViewId::new(id))
+    }
+
+    fn read_operation(&self, id: &OperationId) -> OpStoreResult<Operation> {
+        let target = format!("/operations/{}", id.hex());
+        let body = self
+            .client
+            .get(&target)
+            .map_err(|err| to_op_read_err(err, id))?;
+        let to_read_err =
+            |err: Box<dyn std::error::Error + Send + Sync>| OpStoreError::ReadObject {
+                object_type: id.object_type(),
+                hash: id.hex(),
+                source: err,
+            };
+        let proto = crate::protos::op_store::Operation::decode(&*body)
+            .map_err(|err| to_read_err(err.into()))?;
+        operation_from_proto(proto).map_err(|err| to_read_err(err.into()))
+    }
+
+    fn write_operation(&self, contents: &Operation) -> OpStoreResult<OperationId> {
+        let body = operation_to_proto(contents).encode_to_vec();
+        let (id, _) = self
+            .client
+            .post("/operations", &body)
+            .map_err(|err| to_op_write_err(err, "operation"))?;
+        Ok(OperationId::new(id))
+    }
+
+    fn resolve_operation_id_prefix(
+        &self,
+        prefix: &HexPrefix,
+    ) -> OpStoreResult<PrefixResolution<OperationId>> {
+        let target = format!("/operations?prefix={}", prefix.hex());
+        match self.client.get(&target) {
+            Ok(body) => {
+                let id = std::str::from_utf8(&body)
+                    .ok()
+                    .and_then(|hex| OperationId::try_from_hex(hex).ok())
+                    .ok_or_else(|| {
+                        OpStoreError::Other(
+                            RemoteStoreError::InvalidResponse("invalid operation id".to_owned())
+                                .into(),
+                        )
+                    })?;
+                Ok(PrefixResolution::SingleMatch(id))
+            }
+            Err(err) if err.is_not_found() => Ok(PrefixResolution::NoMatch),
+            Err(RemoteStoreError::Status {
+                status: STATUS_CONFLICT,
+                ..
+            }) => Ok(PrefixResolution::AmbiguousMatch),
+            Err(err) => Err(OpStoreError::Other(err.into())),
+        }
+    }
+
+    fn gc(&self, _head_ids: &[OperationId], _keep_newer: SystemTime) -> OpStoreResult<()> {
+        // Garbage collection is up to the server.
+        Ok(())
+    }
+}
+
+/// Server which serves commit backend and operation store to remote clients.
+#[derive(Debug)]
+pub struct RemoteStoreServer {
+    backend: Box<dyn Backend>,
+    op_store: Box<dyn OpStore>,
+    max_connections: usize,
+}
+
+impl RemoteStoreServer {
+    pub fn new(backend: Box<dyn Backend>, op_store: Box<dyn OpStore>) -> Self {
+        RemoteStoreServer {
+            backend,
+            op_store,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+        }
+    }
+
+    /// Sets the maximum number of connections served concurrently. New
+    /// connections wait until one of the served connections is closed.
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections.max(1);
+        self
+    }
+
+    /// Accepts connections on the `listener` and serves requests. Each
+    /// connection is handled by one of `max_connections` worker threads. This
+    /// function never returns.
+    pub fn serve(&self, listener: &TcpListener) {
+        // Accepted connections are handed over only when a worker is free.
+        let (sender, receiver) = mpsc::sync_channel::<TcpStream>(0);
+        let receiver = Mutex::new(receiver);
+        thread::scope(|scope| {
+            for _ in 0..self.max_connections {
+                scope.spawn(|| loop {
+                    // The lock has to be released before handling the connection.
+                    let Ok(stream) = receiver.lock().unwrap().recv() else {
+                        break;
+                    };
+                    if let Err(err) = self.handle_connection(stream) {
+                        tracing::warn!(?err, "failed to handle remote store request");
+                    }
+                });
+            }
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => sender.send(stream).expect("workers should be alive"),
+                    Err(err) => {
+                        tracing::warn!(?err, "failed to accept remote store connection");
+                        thread::sleep(ACCEPT_RETRY_DELAY);
+                    }
+                }
+            }
+        });
+    }
+
+    fn handle_connection(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(SERVER_TIMEOUT))?;
+        stream.set_write_timeout(Some(SERVER_TIMEOUT))?;
+        let mut reader = BufReader::new(stream);
+        loop {
+            let head = match read_http_head(&mut reader) {
+                Ok(Some(head)) => head,
+                Ok(None) => return Ok(()),
+                // Idle connection timed out
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(());
+                }
+                Err(err) => return Err(err),
+            };
+            if head.content_length > MAX_BODY_SIZE {
+                let message = b"request body too large";
+                write_http_response(
+                    reader.get_mut(),
+                    STATUS_PAYLOAD_TOO_LARGE,
+                    None,
+                    message,
+                    true,
+                )?;
+                return Ok(());
+            }
+            let body = read_http_body(&mut reader, head.content_length)?;
+            let (status, object_id, body) = match head.start_line.split(' ').collect::<Vec<_>>()[..]
+            {
+                [method, target, _] => match self.handle_request(method, target, body) {
+                    Ok((object_id, body)) => (STATUS_OK, object_id, body),
+                    Err((status, message)) => (status, None, message.into_bytes()),
+                },
+                _ => (STATUS_BAD_REQUEST, None, b"invalid request line".to_vec()),
+            };
+            write_http_response(
+                reader.get_mut(),
+                status,
+                object_id.as_deref(),
+                &body,
+                head.close,
+            )?;
+            if head.close {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Handles request, and returns the id of the written object (if any) and
+    /// the response body, or the error status and message.
+    fn handle_request(
+        &self,
+        method: &str,
+        target: &str,
+        body: Vec<u8>,
+    ) -> Result<(Option<String>, Vec<u8>), (u16, String)> {
+        let bad_request = |message: &str| (STATUS_BAD_REQUEST, message.to_owned());
+        let (target_path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query_value = |name: &str| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+                .map(|value| decode_query_value(value).ok_or_else(|| bad_request("invalid query")))
+                .transpose()
+        };
+        let repo_path = || -> Result<RepoPathBuf, (u16, String)> {
+            let path = query_value("path")?.unwrap_or_default();
+            RepoPathBuf::from_internal_string(path).map_err(|_| bad_request("invalid path"))
+        };
+        let segments: Vec<&str> = target_path
+            .strip_prefix('/')
+            .ok_or_else(|| bad_request("invalid target"))?
+            .split('/')
+            .collect();
+        let parse_id = |hex: &str| hex::decode(hex).map_err(|_| bad_request("invalid object id"));
+        let decode_body_err = |err: prost::DecodeError| bad_request(&err.to_string());
+        match (method, &segments[..]) {
+            ("GET", ["info"]) => {
+                let info = format!(
+                    "root-commit-id {}\nroot-change-id {}\nempty-tree-id {}\nroot-operation-id \
+                     {}\nconcurrency {}\n",
+                    self.backend.root_commit_id().hex(),
+                    self.backend.root_change_id().hex(),
+                    self.backend.empty_tree_id().hex(),
+                    self.op_store.root_operation_id().hex(),
+                    self.backend.concurrency(),
+                );
+                Ok((None, info.into_bytes()))
+            }
+            ("GET", ["files", hex]) => {
+                let id = FileId::new(parse_id(hex)?);
+                let mut reader = self
+                    .backend
+                    .read_file(&repo_path()?, &id)
+                    .block_on()
+                    .map_err(backend_error_response)?;
+                let mut content = vec![];
+                reader
+                    .read_to_end(&mut content)
+                    .map_err(|err| (STATUS_INTERNAL_SERVER_ERROR, err.to_string()))?;
+                Ok((None, content))
+            }
+            ("POST", ["files"]) => {
+                let id = self
+                    .backend
+                    .write_file(&repo_path()?, &mut body.as_slice())
+                    .block_on()
+                    .map_err(backend_error_response)?;
+                Ok((Some(id.hex()), vec![]))
+            }
+            ("GET", ["symlinks", hex]) => {
+                let id = SymlinkId::new(parse_id(hex)?);
+                let target = self
+                    .backend
+                    .read_symlink(&repo_path()?, &id)
+                    .block_on()
+                    .map_err(backend_error_response)?;
+                Ok((None, target.into_bytes()))
+            }
+            ("POST", ["symlinks"]) => {
+                let target =
+                    String::from_utf8(body).map_err(|_| bad_request("non-UTF-8 target"))?;
+                let id = self
+                    .backend
+                    .write_symlink(&repo_path()?, &target)
+                    .block_on()
+                    .map_err(backend_error_response)?;
+                Ok((Some(id.hex()), vec![]))
+            }
+            ("GET", ["trees", hex]) => {
+                let id = TreeId::new(parse_id(hex)?);
+                let tree = self
+                    .backend
+                    .read_tree(&repo_path()?, &id)
+                    .block_on()
+                    .map_err(backend_error_response)?;
+                Ok((None, tree_to_proto(&tree).encode_to_vec()))
+            }
+            ("POST", ["trees"]) => {
+                let proto =
+                    crate::protos::simple_store::Tree::decode(&*body).map_err(decode_body_err)?;
+                let id = self
+                    .backend
+                    .write_tree(&repo_path()?, &tree_from_proto(proto))
+                    .block_on()
+                    .map_err(backend_error_response)?;
+                Ok((Some(id.hex()), vec![]))
+            }
+            ("GET", ["conflicts", hex]) => {
+                let id = ConflictId::new(parse_id(hex)?);
+                let conflict = self
+                    .backend
+                    .read_conflict(&repo_path()?, &id)
+                    .map_err(backend_error_response)?;
+                Ok((None, conflict_to_proto(&conflict).encode_to_vec()))
+            }
+            ("POST", ["conflicts"]) => {
+                let proto = crate::protos::simple_store::Conflict::decode(&*body)
+                    .map_err(decode_body_err)?;
+                let id = self
+                    .backend
+                    .write_conflict(&repo_path()?, &conflict_from_proto(proto))
+                    .map_err(backend_error_response)?;
+                Ok((Some(id.hex()), vec![]))
+            }
+            ("GET", ["commits", hex]) => {
+                let id = CommitId::new(parse_id(hex)?);
+                let commit = self
+                    .backend
+                    .read_commit(&id)
+                    .block_on()
+                    .map_err(backend_error_response)?;
+                Ok((None, encode_commit_with_signature(&commit)))
+            }
+            ("POST", ["commits"]) => {
+                let mut commit = decode_commit_with_signature(&body)
+                    .ok_or_else(|| bad_request("invalid commit"))?;
+                // Signatures can only be created by the backend.
+                commit.secure_sig = None;
+                let (id, commit) = self
+                    .backend
+                    .write_commit(commit, None)
+                    .block_on()
+                    .map_err(backend_error_response)?;
+                Ok((Some(id.hex()), encode_commit_with_signature(&commit)))
+            }
+            ("GET", ["views", hex]) => {
+                let id = ViewId::new(parse_id(hex)?);
+                let view = self
+                    .op_store
+                    .read_view(&id)
+                    .map_err(op_store_error_response)?;
+                Ok((None, view_to_proto(&view).encode_to_vec()))
+            }
+            ("POST", ["views"]) => {
+                let proto =
+                    crate::protos::op_store::View::decode(&*body).map_err(decode_body_err)?;
+                let id = self
+                    .op_store
+                    .write_view(&view_from_proto(proto))
+                    .map_err(op_store_error_response)?;
+                Ok((Some(id.hex()), vec![]))
+            }
+            ("GET", ["operations"]) => {
+                let prefix = query_value("prefix")?
+                    .as_deref()
+                    .and_then(HexPrefix::new)
+                    .ok_or_else(|| bad_request("invalid prefix"))?;
+                match self
+                    .op_store
+                    .resolve_operation_id_prefix(&prefix)
+                    .map_err(op_store_error_response)?
+                {
+                    PrefixResolution::SingleMatch(id) => Ok((None, id.hex().into_bytes())),
+                    PrefixResolution::NoMatch => {
+                        Err((STATUS_NOT_FOUND, "no matching operation".to_owned()))
+                    }
+                    PrefixResolution::AmbiguousMatch => {
+                        Err((STATUS_CONFLICT, "ambiguous operation id prefix".to_owned()))
+                    }
+                }
+            }
+            ("GET", ["operations", hex]) => {
+                let id = OperationId::new(parse_id(hex)?);
+                let operation = self
+                    .op_store
+                    .read_operation(&id)
+                    .map_err(op_store_error_response)?;
+                Ok((None, operation_to_proto(&operation).encode_to_vec()))
+            }
+            ("POST", ["operations"]) => {
+                let proto =
+                    crate::protos::op_store::Operation::decode(&*body).map_err(decode_body_err)?;
+                let operation =
+                    operation_from_proto(proto).map_err(|err| bad_request(&err.to_string()))?;
+                if operation.parents.is_empty() {
+                    return Err(bad_request("operation has no parents"));
+                }
+                let id = self
+                    .op_store
+                    .write_operation(&operation)
+                    .map_err(op_store_error_response)?;
+                Ok((Some(id.hex()), vec![]))
+            }
+            _ => Err((
+                STATUS_NOT_FOUND,
+                format!("unknown request {method} {target_path}"),
+            )),
+        }
+    }
+}
+
+fn write_http_response(
+    stream: &mut TcpStream,
+    status: u16,
+    object_id: Option<&str>,
+    body: &[u8],
+    close: bool,
+) -> io::Result<()> {
+    let reason = match status {
+        STATUS_OK => "OK",
+        STATUS_BAD_REQUEST => "Bad Request",
+        STATUS_NOT_FOUND => "Not Found",
+        STATUS_CONFLICT => "Conflict",
+        STATUS_PAYLOAD_TOO_LARGE => "Payload Too Large",
+        _ => "Internal Server Error",
+    };
+    write!(stream, "HTTP/1.1 {status} {reason}\r\n")?;
+    if close {
+        write!(stream, "Connection: close\r\n")?;
+    }
+    if let Some(id) = object_id {
+        write!(stream, "Object-Id: {id}\r\n")?;
+    }
+    write!(stream, "Content-Length: {}\r\n\r\n", body.len())?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+fn backend_error_response(err: BackendError) -> (u16, String) {
+    let status = match &err {
+        BackendError::ObjectNotFound { .. } => STATUS_NOT_FOUND,
+        _ => STATUS_INTERNAL_SERVER_ERROR,
+    };
+    (status, format_error_chain(&err))
+}
+
+fn op_store_error_response(err: OpStoreError) -> (u16, String) {
+    let status = match &err {
+        OpStoreError::ObjectNotFound { .. } => STATUS_NOT_FOUND,
+        _ => STATUS_INTERNAL_SERVER_ERROR,
+    };
+    (status, format_error_chain(&err))
+}
+
+fn format_error_chain(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        message.push_str(": ");
+        message.push_str(&err.to_string());
+        source = err.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_value_round_trip() {
+        for text in ["", "dir/file", "a b&c=d", "%", "\u{1F600}"] {
+            let encoded = encode_query_value(text);
+            assert!(!encoded.contains([' ', '&', '=', '?']));
+            assert_eq!(decode_query_value(&encoded).as_deref(), Some(text));
+        }
+        assert_eq!(decode_query_value("%"), None);
+        assert_eq!(decode_query_value("%zz"), None);
+    }
+}
@@ -38,10 +38,13 @@ use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
+use crate::caching_backend::wrap_backend_from_settings;
 use crate::commit::Commit;
 use crate::commit::CommitByCommitterTimestamp;
 use crate::commit_builder::CommitBuilder;
 use crate::commit_builder::DetachedCommitBuilder;
+use crate::config::ConfigGetError;
+use crate::copies::CopyDetectionOptions;
 use crate::dag_walk;
 use crate::default_index::DefaultIndexStore;
 use crate::default_index::DefaultMutableIndex;
@@ -159,6 +162,8 @@ pub enum RepoInitError {
     #[error(transparent)]
     Backend(#[from] BackendInitError),
     #[error(transparent)]
+    Config(#[from] ConfigGetError),
+    #[error(transparent)]
     OpHeadsStore(#[from] OpHeadsStoreError),
     #[error(transparent)]
     Path(#[from] PathError),
@@ -176,7 +181,15 @@ impl ReadonlyRepo {
     }
 
     pub fn default_index_store_initializer() -> &'static IndexStoreInitializer<'static> {
-        &|_settings, store_path| Ok(Box::new(DefaultIndexStore::init(store_path)?))
+        &|settings, store_path| {
+            let changed_path_filters = settings
+                .get_bool("index.changed-path-filters")
+                .map_err(|err| BackendInitError(err.into()))?;
+            let store = DefaultIndexStore::init(store_path)?;
+            Ok(Box::new(
+                store.with_changed_path_filters(changed_path_filters),
+            ))
+        }
     }
 
     pub fn default_submodule_store_initializer() -> &'static SubmoduleStoreInitializer<'static> {
@@ -201,7 +214,8 @@ impl ReadonlyRepo {
         let backend = backend_initializer(settings, &store_path)?;
         let backend_path = store_path.join("type");
         fs::write(&backend_path, backend.name()).context(&backend_path)?;
-        let store = Store::new(backend, signer);
+        let copy_detection = CopyDetectionOptions::from_settings(settings)?;
+        let store = Store::new(backend, signer, copy_detection);
 
         let op_store_path = repo_path.join("op_store");
         fs::create_dir(&op_store_path).context(&op_store_path)?;
@@ -417,6 +431,15 @@ impl Default for StoreFactories {
                 )?))
             }),
         );
+        #[cfg(feature = "remote-store")]
+        factories.add_backend(
+            crate::remote_store::RemoteBackend::name(),
+            Box::new(|_settings, store_path| {
+                Ok(Box::new(crate::remote_store::RemoteBackend::load(
+                    store_path,
+                )?))
+            }),
+        );
         #[cfg(feature = "testing")]
         factories.add_backend(
             crate::secret_backend::SecretBackend::name(),
@@ -434,6 +457,15 @@ impl Default for StoreFactories {
                 Ok(Box::new(SimpleOpStore::load(store_path, root_data)))
             }),
         );
+        #[cfg(feature = "remote-store")]
+        factories.add_op_store(
+            crate::remote_store::RemoteOpStore::name(),
+            Box::new(|_settings, store_path, _root_data| {
+                Ok(Box::new(crate::remote_store::RemoteOpStore::load(
+                    store_path,
+                )?))
+            }),
+        );
 
         // OpHeadsStores
         factories.add_op_heads_store(
@@ -444,7 +476,15 @@ impl Default for StoreFactories {
         // Index
         factories.add_index_store(
             DefaultIndexStore::name(),
-            Box::new(|_settings, store_path| Ok(Box::new(DefaultIndexStore::load(store_path)))),
+            Box::new(|settings, store_path| {
+                let changed_path_filters = settings
+                    .get_bool("index.changed-path-filters")
+                    .map_err(|err| BackendLoadError(err.into()))?;
+                let store = DefaultIndexStore::load(store_path);
+                Ok(Box::new(
+                    store.with_changed_path_filters(changed_path_filters),
+                ))
+            }),
         );
 
         // SubmoduleStores
@@ -473,6 +513,8 @@ pub enum StoreLoadError {
     Backend(#[from] BackendLoadError),
     #[error(transparent)]
     Signing(#[from] SignInitError),
+    #[error(transparent)]
+    Config(#[from] ConfigGetError),
 }
 
 impl StoreFactories {
@@ -521,7 +563,8 @@ impl StoreFactories {
                 store_type: backend_type.to_string(),
             }
         })?;
-        Ok(backend_factory(settings, store_path)?)
+        let backend = backend_factory(settings, store_path)?;
+        wrap_backend_from_settings(settings, store_path, backend)
     }
 
     pub fn add_op_store(&mut self, name: &str, factory: OpStoreFactory) {
@@ -676,6 +719,7 @@ impl RepoLoader {
         let store = Store::new(
             store_factories.load_backend(settings, &repo_path.join("store"))?,
             Signer::from_settings(settings)?,
+            CopyDetectionOptions::from_settings(settings)?,
         );
         let root_op_data = RootOperationData {
             root_commit_id: store.root_commit_id().clone(),
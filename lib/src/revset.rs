@@ -66,6 +66,7 @@ pub use crate::revset_parser::RevsetDiagnostics;
 pub use crate::revset_parser::RevsetParseError;
 pub use crate::revset_parser::RevsetParseErrorKind;
 pub use crate::revset_parser::UnaryOp;
+use crate::signing::SigStatus;
 use crate::store::Store;
 use crate::str_util::StringPattern;
 use crate::time_util::DatePattern;
@@ -162,6 +163,11 @@ pub enum RevsetFilterPredicate {
     Description(StringPattern),
     /// Commits with first line of the description matching the pattern.
     Subject(StringPattern),
+    /// Commits with a trailer whose key and value match the patterns.
+    Trailer {
+        key: StringPattern,
+        value: StringPattern,
+    },
     /// Commits with author name matching the pattern.
     AuthorName(StringPattern),
     /// Commits with author email matching the pattern.
@@ -185,6 +191,11 @@ pub enum RevsetFilterPredicate {
     HasConflict,
     /// Commits that are cryptographically signed.
     Signed,
+    /// Commits with a good signature whose key or display string matches the
+    /// pattern.
+    SignedBy(StringPattern),
+    /// Commits with a signature of the given verification status.
+    SignatureStatus(SigStatus),
     /// Custom predicates provided by extensions
     Extension(Rc<dyn RevsetFilterExtension>),
 }
@@ -808,6 +819,17 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         let predicate = RevsetFilterPredicate::Subject(pattern);
         Ok(RevsetExpression::filter(predicate))
     });
+    map.insert("trailer", |diagnostics, function, _context| {
+        let ([key_arg], [value_opt_arg]) = function.expect_arguments()?;
+        let key = expect_trailer_key_pattern(diagnostics, key_arg)?;
+        let value = if let Some(value_arg) = value_opt_arg {
+            expect_string_pattern(diagnostics, value_arg)?
+        } else {
+            StringPattern::everything()
+        };
+        let predicate = RevsetFilterPredicate::Trailer { key, value };
+        Ok(RevsetExpression::filter(predicate))
+    });
     map.insert("author", |diagnostics, function, _context| {
         let [arg] = function.expect_exact_arguments()?;
         let pattern = expect_string_pattern(diagnostics, arg)?;
@@ -840,6 +862,18 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         let predicate = RevsetFilterPredicate::Signed;
         Ok(RevsetExpression::filter(predicate))
     });
+    map.insert("signed_by", |diagnostics, function, _context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let pattern = expect_string_pattern(diagnostics, arg)?;
+        let predicate = RevsetFilterPredicate::SignedBy(pattern);
+        Ok(RevsetExpression::filter(predicate))
+    });
+    map.insert("signature_status", |diagnostics, function, _context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let status = expect_literal(diagnostics, "signature status", arg)?;
+        let predicate = RevsetFilterPredicate::SignatureStatus(status);
+        Ok(RevsetExpression::filter(predicate))
+    });
     map.insert("mine", |_diagnostics, function, context| {
         function.expect_no_arguments()?;
         // Email address domains are inherently case‐insensitive, and the local‐parts
@@ -981,6 +1015,22 @@ pub fn expect_string_pattern(
     )
 }
 
+/// Parses a trailer key pattern, which matches the key exactly by default.
+fn expect_trailer_key_pattern(
+    diagnostics: &mut RevsetDiagnostics,
+    node: &ExpressionNode,
+) -> Result<StringPattern, RevsetParseError> {
+    revset_parser::expect_pattern_with(
+        diagnostics,
+        "string pattern",
+        node,
+        |_diagnostics, value, kind| match kind {
+            Some(kind) => StringPattern::from_str_kind(value, kind),
+            None => Ok(StringPattern::exact(value)),
+        },
+    )
+}
+
 pub fn expect_date_pattern(
     diagnostics: &mut RevsetDiagnostics,
     node: &ExpressionNode,
@@ -3256,6 +3306,37 @@ mod tests {
         )
         "#);
         insta::assert_debug_snapshot!(parse("signed()").unwrap(), @"Filter(Signed)");
+        insta::assert_debug_snapshot!(
+            parse("signed_by(foo)").unwrap(),
+            @r#"Filter(SignedBy(Substring("foo")))"#);
+        insta::assert_debug_snapshot!(
+            parse("signature_status(good)").unwrap(),
+            @"Filter(SignatureStatus(Good))");
+        insta::assert_debug_snapshot!(
+            parse("signature_status(invalid)").unwrap_err().kind(),
+            @r#"Expression("Expected expression of type signature status")"#);
+        insta::assert_debug_snapshot!(parse("trailer(Fixes)").unwrap(), @r#"
+        Filter(
+            Trailer {
+                key: Exact("Fixes"),
+                value: Substring(""),
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(parse("trailer(Fixes, foo)").unwrap(), @r#"
+        Filter(
+            Trailer {
+                key: Exact("Fixes"),
+                value: Substring("foo"),
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(parse("trailer()").unwrap_err().kind(), @r#"
+        InvalidFunctionArguments {
+            name: "trailer",
+            message: "Expected 1 to 2 arguments",
+        }
+        "#);
     }
 
     #[test]
@@ -36,12 +36,14 @@ use crate::index::Index;
 use crate::index::IndexError;
 use crate::matchers::Matcher;
 use crate::matchers::Visit;
+use crate::merge::Merge;
 use crate::merged_tree::MergedTree;
 use crate::merged_tree::MergedTreeBuilder;
 use crate::merged_tree::TreeDiffEntry;
 use crate::repo::MutableRepo;
 use crate::repo::Repo;
 use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
 use crate::revset::RevsetExpression;
 use crate::revset::RevsetIteratorExt as _;
 use crate::store::Store;
@@ -117,6 +119,94 @@ pub fn restore_tree(
     }
 }
 
+/// Returns the renames recorded in `commit` which can be followed when merging
+/// `from_tree` into `to_tree`: the source exists only in `from_tree` and the
+/// target exists only in `to_tree`. The file must also still be at the source
+/// path in `other_tree`, which the rename will be applied to.
+fn find_recorded_renames(
+    commit: &Commit,
+    from_tree: &MergedTree,
+    to_tree: &MergedTree,
+    other_tree: &MergedTree,
+) -> BackendResult<Vec<(RepoPathBuf, RepoPathBuf)>> {
+    let has_only = |tree: &MergedTree, present: &RepoPath, absent: &RepoPath| {
+        Ok::<_, BackendError>(
+            tree.path_value(present)?.is_present() && tree.path_value(absent)?.is_absent(),
+        )
+    };
+    let mut renames = vec![];
+    for copy in commit.copies() {
+        if has_only(from_tree, &copy.source, &copy.target)?
+            && has_only(to_tree, &copy.target, &copy.source)?
+            && has_only(other_tree, &copy.source, &copy.target)?
+        {
+            renames.push((copy.source.clone(), copy.target.clone()));
+        }
+    }
+    Ok(renames)
+}
+
+/// Moves the files at the source paths of `renames` in `tree` to the target
+/// paths. Renames whose source is absent or whose target already exists are
+/// skipped.
+fn apply_renames(
+    tree: &MergedTree,
+    renames: &[(RepoPathBuf, RepoPathBuf)],
+) -> BackendResult<MergedTree> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for (source, target) in renames {
+        let value = tree.path_value(source)?;
+        if value.is_absent() || tree.path_value(target)?.is_present() {
+            continue;
+        }
+        tree_builder.set_or_remove(target.clone(), value);
+        tree_builder.set_or_remove(source.clone(), Merge::absent());
+    }
+    let store = tree.store();
+    store.get_root_tree(&tree_builder.write_tree(store)?)
+}
+
+/// Merges the changes from `old_base_tree` to `old_tree` into
+/// `new_base_tree`, following the renames recorded in `old_commit` and in the
+/// new parents.
+fn merge_following_renames(
+    old_commit: &Commit,
+    old_parents: &[Commit],
+    new_parents: &[Commit],
+    old_base_tree: &MergedTree,
+    new_base_tree: &MergedTree,
+    old_tree: &MergedTree,
+) -> BackendResult<MergedTree> {
+    let mut old_base_tree = old_base_tree.clone();
+    let mut new_base_tree = new_base_tree.clone();
+    let mut old_tree = old_tree.clone();
+    // The rebased commit renamed a file which still exists at the old path in
+    // the new base. Move it there too, so changes to it follow the rename.
+    let renames = find_recorded_renames(old_commit, &old_base_tree, &old_tree, &new_base_tree)?;
+    if !renames.is_empty() {
+        old_base_tree = apply_renames(&old_base_tree, &renames)?;
+        new_base_tree = apply_renames(&new_base_tree, &renames)?;
+    }
+    // A new parent, which is a child of an old parent, renamed a file which
+    // the rebased commit still has at the old path.
+    let old_parent_ids = old_parents.iter().ids().collect::<HashSet<_>>();
+    for new_parent in new_parents {
+        if !new_parent
+            .parent_ids()
+            .iter()
+            .any(|id| old_parent_ids.contains(id))
+        {
+            continue;
+        }
+        let renames = find_recorded_renames(new_parent, &old_base_tree, &new_base_tree, &old_tree)?;
+        if !renames.is_empty() {
+            old_base_tree = apply_renames(&old_base_tree, &renames)?;
+            old_tree = apply_renames(&old_tree, &renames)?;
+        }
+    }
+    new_base_tree.merge(&old_base_tree, &old_tree)
+}
+
 pub fn rebase_commit(
     mut_repo: &mut MutableRepo,
     old_commit: Commit,
@@ -251,9 +341,17 @@ impl<'repo> CommitRewriter<'repo> {
             let old_base_tree = merge_commit_trees(self.mut_repo, &old_parents)?;
             let new_base_tree = merge_commit_trees(self.mut_repo, &new_parents)?;
             let old_tree = self.old_commit.tree()?;
+            let new_tree = merge_following_renames(
+                &self.old_commit,
+                &old_parents,
+                &new_parents,
+                &old_base_tree,
+                &new_base_tree,
+                &old_tree,
+            )?;
             (
                 old_base_tree.id() == *self.old_commit.tree_id(),
-                new_base_tree.merge(&old_base_tree, &old_tree)?.id(),
+                new_tree.id(),
             )
         };
         // Ensure we don't abandon commits with multiple parents (merge commits), even
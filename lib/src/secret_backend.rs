@@ -24,6 +24,7 @@ use futures::stream::BoxStream;
 
 use crate::backend::Backend;
 use crate::backend::BackendError;
+use crate::backend::BackendGcStats;
 use crate::backend::BackendLoadError;
 use crate::backend::BackendResult;
 use crate::backend::ChangeId;
@@ -196,4 +197,13 @@ impl Backend for SecretBackend {
     fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
         self.inner.gc(index, keep_newer)
     }
+
+    fn gc_with_stats(
+        &self,
+        index: &dyn Index,
+        keep_newer: SystemTime,
+        dry_run: bool,
+    ) -> BackendResult<BackendGcStats> {
+        self.inner.gc_with_stats(index, keep_newer, dry_run)
+    }
 }
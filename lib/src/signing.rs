@@ -17,6 +17,7 @@
 
 use std::fmt::Debug;
 use std::fmt::Display;
+use std::str::FromStr;
 use std::sync::Mutex;
 
 use clru::CLruCache;
@@ -54,6 +55,19 @@ impl Display for SigStatus {
     }
 }
 
+impl FromStr for SigStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "good" => Ok(SigStatus::Good),
+            "unknown" => Ok(SigStatus::Unknown),
+            "bad" => Ok(SigStatus::Bad),
+            _ => Err(()),
+        }
+    }
+}
+
 /// The result of a signature verification.
 /// Key and display are optional additional info that backends can or can not
 /// provide to add additional information for the templater to potentially show.
@@ -15,9 +15,13 @@
 #![allow(missing_docs)]
 
 use std::any::Any;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fs;
 use std::fs::File;
+use std::io;
+use std::io::Cursor;
 use std::io::Read;
 use std::io::Write as _;
 use std::path::Path;
@@ -27,7 +31,6 @@ use std::time::SystemTime;
 use async_trait::async_trait;
 use blake2::Blake2b512;
 use blake2::Digest as _;
-use futures::stream;
 use futures::stream::BoxStream;
 use pollster::FutureExt as _;
 use prost::Message as _;
@@ -36,6 +39,7 @@ use tempfile::NamedTempFile;
 use crate::backend::make_root_commit;
 use crate::backend::Backend;
 use crate::backend::BackendError;
+use crate::backend::BackendGcStats;
 use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::Commit;
@@ -47,6 +51,7 @@ use crate::backend::CopyRecord;
 use crate::backend::FileId;
 use crate::backend::MergedTreeId;
 use crate::backend::MillisSinceEpoch;
+use crate::backend::RecordedCopy;
 use crate::backend::SecureSig;
 use crate::backend::Signature;
 use crate::backend::SigningFn;
@@ -58,11 +63,17 @@ use crate::backend::TreeValue;
 use crate::content_hash::blake2b_hash;
 use crate::file_util::persist_content_addressed_temp_file;
 use crate::index::Index;
+use crate::lock::FileLock;
 use crate::merge::MergeBuilder;
 use crate::object_id::ObjectId;
+use crate::repo_path::InvalidNewRepoPathError;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponentBuf;
+use crate::simple_pack::PackObjectKind;
+use crate::simple_pack::PackSet;
+use crate::simple_pack::PackWriter;
+use crate::simple_pack::OBJECT_ID_LENGTH;
 
 const COMMIT_ID_LENGTH: usize = 64;
 const CHANGE_ID_LENGTH: usize = 16;
@@ -87,12 +98,23 @@ fn to_other_err(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Bac
     BackendError::Other(err.into())
 }
 
+fn object_dir_name(kind: PackObjectKind) -> &'static str {
+    match kind {
+        PackObjectKind::Commit => "commits",
+        PackObjectKind::Tree => "trees",
+        PackObjectKind::File => "files",
+        PackObjectKind::Symlink => "symlinks",
+        PackObjectKind::Conflict => "conflicts",
+    }
+}
+
 #[derive(Debug)]
 pub struct SimpleBackend {
     path: PathBuf,
     root_commit_id: CommitId,
     root_change_id: ChangeId,
     empty_tree_id: TreeId,
+    packs: PackSet,
 }
 
 impl SimpleBackend {
@@ -106,6 +128,7 @@ impl SimpleBackend {
         fs::create_dir(store_path.join("files")).unwrap();
         fs::create_dir(store_path.join("symlinks")).unwrap();
         fs::create_dir(store_path.join("conflicts")).unwrap();
+        fs::create_dir(store_path.join("packs")).unwrap();
         let backend = Self::load(store_path);
         let empty_tree_id = backend
             .write_tree(RepoPath::root(), &Tree::default())
@@ -126,6 +149,7 @@ impl SimpleBackend {
             root_commit_id,
             root_change_id,
             empty_tree_id,
+            packs: PackSet::new(store_path.join("packs")),
         }
     }
 
@@ -148,6 +172,189 @@ impl SimpleBackend {
     fn conflict_path(&self, id: &ConflictId) -> PathBuf {
         self.path.join("conflicts").join(id.hex())
     }
+
+    /// Reads loose object file at `path`, or packed object if the loose file
+    /// doesn't exist.
+    fn read_object_data(
+        &self,
+        kind: PackObjectKind,
+        path: &Path,
+        id: &impl ObjectId,
+    ) -> BackendResult<Vec<u8>> {
+        match fs::read(path) {
+            Ok(buf) => Ok(buf),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                match self.packs.read_object(kind, id.as_bytes()) {
+                    Ok(Some(buf)) => Ok(buf),
+                    Ok(None) => Err(map_not_found_err(err, id)),
+                    Err(err) => Err(map_not_found_err(err, id)),
+                }
+            }
+            Err(err) => Err(map_not_found_err(err, id)),
+        }
+    }
+
+    /// Returns the ids of the loose objects of the given `kind`.
+    fn list_loose_objects(&self, kind: PackObjectKind) -> io::Result<Vec<Vec<u8>>> {
+        let mut ids = vec![];
+        for entry in fs::read_dir(self.path.join(object_dir_name(kind)))? {
+            let entry = entry?;
+            let Some(id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| hex::decode(name).ok())
+            else {
+                continue;
+            };
+            if id.len() == OBJECT_ID_LENGTH {
+                ids.push(id);
+            }
+        }
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    /// Collects the paths of the file objects reachable from the `heads`. The
+    /// files are ordered by the traversal order, so consecutive versions of
+    /// the same path are likely to be similar.
+    fn collect_file_paths(
+        &self,
+        heads: impl IntoIterator<Item = CommitId>,
+    ) -> BackendResult<Vec<(FileId, RepoPathBuf)>> {
+        let mut file_paths = vec![];
+        let mut visited_files = HashSet::new();
+        let mut visited_trees = HashSet::new();
+        let mut visited_commits = HashSet::new();
+        let mut commit_ids: Vec<CommitId> = heads.into_iter().collect();
+        while let Some(commit_id) = commit_ids.pop() {
+            if commit_id == self.root_commit_id || !visited_commits.insert(commit_id.clone()) {
+                continue;
+            }
+            let commit = self.read_commit(&commit_id).block_on()?;
+            let mut trees: Vec<(RepoPathBuf, TreeId)> = commit
+                .root_tree
+                .to_merge()
+                .into_iter()
+                .map(|id| (RepoPathBuf::root(), id))
+                .collect();
+            while let Some((dir, tree_id)) = trees.pop() {
+                if !visited_trees.insert(tree_id.clone()) {
+                    continue;
+                }
+                let tree = self.read_tree(&dir, &tree_id).block_on()?;
+                for entry in tree.entries() {
+                    let path = dir.join(entry.name());
+                    match entry.value() {
+                        TreeValue::File { id, .. } if visited_files.insert(id.clone()) => {
+                            file_paths.push((id.clone(), path));
+                        }
+                        TreeValue::Tree(id) => trees.push((path, id.clone())),
+                        _ => {}
+                    }
+                }
+            }
+            commit_ids.extend(commit.parents.into_iter().rev());
+        }
+        Ok(file_paths)
+    }
+
+    /// Packs all loose objects and existing pack files into a new pack file.
+    /// Returns the number of objects in the new pack file.
+    fn repack(&self, index: &dyn Index, dry_run: bool) -> BackendResult<usize> {
+        // Concurrent repacks would remove each other's input files.
+        let _lock = if dry_run {
+            None
+        } else {
+            // Repos created before pack files were introduced have no packs
+            // directory.
+            fs::create_dir_all(self.packs.dir()).map_err(to_other_err)?;
+            let lock = FileLock::lock(self.packs.dir().join("lock")).map_err(to_other_err)?;
+            // The packs might have been replaced while waiting for the lock.
+            self.packs.invalidate();
+            Some(lock)
+        };
+        let loose_ids: Vec<(PackObjectKind, Vec<Vec<u8>>)> = PackObjectKind::ALL
+            .into_iter()
+            .map(|kind| Ok((kind, self.list_loose_objects(kind)?)))
+            .collect::<io::Result<_>>()
+            .map_err(to_other_err)?;
+        let num_loose_objects: usize = loose_ids.iter().map(|(_, ids)| ids.len()).sum();
+        let old_packs = self.packs.packs().map_err(to_other_err)?;
+        if num_loose_objects == 0 && old_packs.len() <= 1 {
+            return Ok(0);
+        }
+        let num_objects = loose_ids
+            .iter()
+            .flat_map(|(kind, ids)| ids.iter().map(|id| (*kind, id.as_slice())))
+            .chain(old_packs.iter().flat_map(|pack| pack.object_keys()))
+            .collect::<HashSet<_>>()
+            .len();
+        if dry_run {
+            return Ok(num_objects);
+        }
+
+        let heads = index.all_heads_for_gc().map_err(to_other_err)?;
+        let file_paths = self.collect_file_paths(heads)?;
+        let mut writer = PackWriter::new(self.packs.dir()).map_err(to_other_err)?;
+        // File contents are delta-encoded against the previous version at the
+        // same path.
+        let mut last_versions: HashMap<RepoPathBuf, (usize, Vec<u8>)> = HashMap::new();
+        let mut packed_files = HashSet::new();
+        for (id, path) in file_paths {
+            let data = match self.read_object_data(PackObjectKind::File, &self.file_path(&id), &id)
+            {
+                Ok(data) => data,
+                // Unreachable objects might have been removed
+                Err(BackendError::ObjectNotFound { .. }) => continue,
+                Err(err) => return Err(err),
+            };
+            let base = last_versions
+                .get(&path)
+                .map(|(pos, base_data)| (*pos, base_data.as_slice()));
+            let pos = writer
+                .add(PackObjectKind::File, id.as_bytes(), &data, base)
+                .map_err(to_other_err)?;
+            last_versions.insert(path, (pos, data));
+            packed_files.insert(id.to_bytes());
+        }
+        drop(last_versions);
+
+        let mut add_object = |kind: PackObjectKind, id: &[u8], data: &[u8]| {
+            if kind == PackObjectKind::File && packed_files.contains(id) {
+                return Ok(());
+            }
+            writer.add(kind, id, data, None).map(|_| ())
+        };
+        for (kind, ids) in &loose_ids {
+            for id in ids {
+                let path = self.path.join(object_dir_name(*kind)).join(hex::encode(id));
+                let data = fs::read(path).map_err(to_other_err)?;
+                add_object(*kind, id, &data).map_err(to_other_err)?;
+            }
+        }
+        for pack in old_packs.iter() {
+            for (kind, id, data) in pack.objects() {
+                let data = data.map_err(to_other_err)?;
+                add_object(kind, id, &data).map_err(to_other_err)?;
+            }
+        }
+        let new_pack_path = writer.finish().map_err(to_other_err)?;
+
+        // Readers will look up the new pack file if an object is missing.
+        for (kind, ids) in &loose_ids {
+            for id in ids {
+                let path = self.path.join(object_dir_name(*kind)).join(hex::encode(id));
+                fs::remove_file(path).map_err(to_other_err)?;
+            }
+        }
+        for pack in old_packs.iter() {
+            if pack.path() != new_pack_path {
+                fs::remove_file(pack.path()).map_err(to_other_err)?;
+            }
+        }
+        self.packs.invalidate();
+        Ok(num_objects)
+    }
 }
 
 #[async_trait]
@@ -186,8 +393,14 @@ impl Backend for SimpleBackend {
 
     async fn read_file(&self, _path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
         let path = self.file_path(id);
-        let file = File::open(path).map_err(|err| map_not_found_err(err, id))?;
-        Ok(Box::new(file))
+        match File::open(&path) {
+            Ok(file) => Ok(Box::new(file)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let buf = self.read_object_data(PackObjectKind::File, &path, id)?;
+                Ok(Box::new(Cursor::new(buf)))
+            }
+            Err(err) => Err(map_not_found_err(err, id)),
+        }
     }
 
     async fn write_file(
@@ -219,7 +432,12 @@ impl Backend for SimpleBackend {
 
     async fn read_symlink(&self, _path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
         let path = self.symlink_path(id);
-        let target = fs::read_to_string(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object_data(PackObjectKind::Symlink, &path, id)?;
+        let target = String::from_utf8(buf).map_err(|err| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: Box::new(err),
+        })?;
         Ok(target)
     }
 
@@ -240,7 +458,7 @@ impl Backend for SimpleBackend {
 
     async fn read_tree(&self, _path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
         let path = self.tree_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object_data(PackObjectKind::Tree, &path, id)?;
 
         let proto = crate::protos::simple_store::Tree::decode(&*buf).map_err(to_other_err)?;
         Ok(tree_from_proto(proto))
@@ -265,7 +483,7 @@ impl Backend for SimpleBackend {
 
     fn read_conflict(&self, _path: &RepoPath, id: &ConflictId) -> BackendResult<Conflict> {
         let path = self.conflict_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object_data(PackObjectKind::Conflict, &path, id)?;
 
         let proto = crate::protos::simple_store::Conflict::decode(&*buf).map_err(to_other_err)?;
         Ok(conflict_from_proto(proto))
@@ -297,10 +515,10 @@ impl Backend for SimpleBackend {
         }
 
         let path = self.commit_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object_data(PackObjectKind::Commit, &path, id)?;
 
         let proto = crate::protos::simple_store::Commit::decode(&*buf).map_err(to_other_err)?;
-        Ok(commit_from_proto(proto))
+        commit_from_proto(proto).map_err(to_other_err)
     }
 
     async fn write_commit(
@@ -344,12 +562,30 @@ impl Backend for SimpleBackend {
         _root: &CommitId,
         _head: &CommitId,
     ) -> BackendResult<BoxStream<BackendResult<CopyRecord>>> {
-        Ok(Box::pin(stream::empty()))
+        // Copies are detected by the store instead.
+        Err(BackendError::Unsupported(
+            "The simple backend doesn't track copies".to_owned(),
+        ))
     }
 
-    fn gc(&self, _index: &dyn Index, _keep_newer: SystemTime) -> BackendResult<()> {
+    fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        self.gc_with_stats(index, keep_newer, false)?;
         Ok(())
     }
+
+    fn gc_with_stats(
+        &self,
+        index: &dyn Index,
+        _keep_newer: SystemTime,
+        dry_run: bool,
+    ) -> BackendResult<BackendGcStats> {
+        // Unreachable objects aren't removed, but loose objects are packed.
+        let num_packed_objects = self.repack(index, dry_run)?;
+        Ok(BackendGcStats {
+            num_packed_objects,
+            ..Default::default()
+        })
+    }
 }
 
 #[allow(clippy::assigning_clones)]
@@ -374,10 +610,20 @@ pub fn commit_to_proto(commit: &Commit) -> crate::protos::simple_store::Commit {
     proto.description = commit.description.clone();
     proto.author = Some(signature_to_proto(&commit.author));
     proto.committer = Some(signature_to_proto(&commit.committer));
+    proto.copies = commit
+        .copies
+        .iter()
+        .map(|copy| crate::protos::simple_store::commit::Copy {
+            source: copy.source.as_internal_file_string().to_owned(),
+            target: copy.target.as_internal_file_string().to_owned(),
+        })
+        .collect();
     proto
 }
 
-fn commit_from_proto(mut proto: crate::protos::simple_store::Commit) -> Commit {
+fn commit_from_proto(
+    mut proto: crate::protos::simple_store::Commit,
+) -> Result<Commit, InvalidNewRepoPathError> {
     // Note how .take() sets the secure_sig field to None before we encode the data.
     // Needs to be done first since proto is partially moved a bunch below
     let secure_sig = proto.secure_sig.take().map(|sig| SecureSig {
@@ -395,7 +641,17 @@ fn commit_from_proto(mut proto: crate::protos::simple_store::Commit) -> Commit {
         MergedTreeId::Legacy(TreeId::new(proto.root_tree[0].clone()))
     };
     let change_id = ChangeId::new(proto.change_id);
-    Commit {
+    let copies = proto
+        .copies
+        .into_iter()
+        .map(|copy| {
+            Ok(RecordedCopy {
+                source: RepoPathBuf::from_internal_string(copy.source)?,
+                target: RepoPathBuf::from_internal_string(copy.target)?,
+            })
+        })
+        .collect::<Result<_, InvalidNewRepoPathError>>()?;
+    Ok(Commit {
         parents,
         predecessors,
         root_tree,
@@ -404,10 +660,45 @@ fn commit_from_proto(mut proto: crate::protos::simple_store::Commit) -> Commit {
         author: signature_from_proto(proto.author.unwrap_or_default()),
         committer: signature_from_proto(proto.committer.unwrap_or_default()),
         secure_sig,
+        copies,
+    })
+}
+
+/// Serializes commit including the data signed by the `secure_sig`, which
+/// can't be reconstructed from the other fields if the commit was created by
+/// another backend.
+pub(crate) fn encode_commit_with_signature(commit: &Commit) -> Vec<u8> {
+    let mut proto = commit_to_proto(commit);
+    let mut signed_data: &[u8] = &[];
+    if let Some(secure_sig) = &commit.secure_sig {
+        proto.secure_sig = Some(secure_sig.sig.clone());
+        signed_data = &secure_sig.data;
+    }
+    let proto_data = proto.encode_to_vec();
+    let mut buf = vec![];
+    buf.extend(u32::try_from(proto_data.len()).unwrap().to_le_bytes());
+    buf.extend(proto_data);
+    buf.extend(signed_data);
+    buf
+}
+
+pub(crate) fn decode_commit_with_signature(buf: &[u8]) -> Option<Commit> {
+    let (len, buf) = buf.split_first_chunk::<4>()?;
+    let len = usize::try_from(u32::from_le_bytes(*len)).unwrap();
+    let proto_data = buf.get(..len)?;
+    let signed_data = &buf[len..];
+    let proto = crate::protos::simple_store::Commit::decode(proto_data).ok()?;
+    let mut commit = commit_from_proto(proto).ok()?;
+    if let Some(secure_sig) = &mut commit.secure_sig {
+        *secure_sig = SecureSig {
+            data: signed_data.to_vec(),
+            sig: secure_sig.sig.clone(),
+        };
     }
+    Some(commit)
 }
 
-fn tree_to_proto(tree: &Tree) -> crate::protos::simple_store::Tree {
+pub(crate) fn tree_to_proto(tree: &Tree) -> crate::protos::simple_store::Tree {
     let mut proto = crate::protos::simple_store::Tree::default();
     for entry in tree.entries() {
         proto
@@ -420,7 +711,7 @@ fn tree_to_proto(tree: &Tree) -> crate::protos::simple_store::Tree {
     proto
 }
 
-fn tree_from_proto(proto: crate::protos::simple_store::Tree) -> Tree {
+pub(crate) fn tree_from_proto(proto: crate::protos::simple_store::Tree) -> Tree {
     let mut tree = Tree::default();
     for proto_entry in proto.entries {
         let value = tree_value_from_proto(proto_entry.value.unwrap());
@@ -505,7 +796,7 @@ fn signature_from_proto(proto: crate::protos::simple_store::commit::Signature) -
     }
 }
 
-fn conflict_to_proto(conflict: &Conflict) -> crate::protos::simple_store::Conflict {
+pub(crate) fn conflict_to_proto(conflict: &Conflict) -> crate::protos::simple_store::Conflict {
     let mut proto = crate::protos::simple_store::Conflict::default();
     for term in &conflict.removes {
         proto.removes.push(conflict_term_to_proto(term));
@@ -516,7 +807,7 @@ fn conflict_to_proto(conflict: &Conflict) -> crate::protos::simple_store::Confli
     proto
 }
 
-fn conflict_from_proto(proto: crate::protos::simple_store::Conflict) -> Conflict {
+pub(crate) fn conflict_from_proto(proto: crate::protos::simple_store::Conflict) -> Conflict {
     let removes = proto
         .removes
         .into_iter()
@@ -566,6 +857,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
@@ -604,6 +896,60 @@ mod tests {
         assert_eq!(root_merge_commit, commit);
     }
 
+    #[test]
+    fn test_encode_decode_commit_with_signature() {
+        let mut commit = Commit {
+            parents: vec![CommitId::from_hex("123456")],
+            predecessors: vec![],
+            root_tree: MergedTreeId::resolved(TreeId::from_hex("abcdef")),
+            change_id: ChangeId::from_hex("abc123"),
+            description: "description".to_string(),
+            author: create_signature(),
+            committer: create_signature(),
+            secure_sig: None,
+            copies: vec![],
+        };
+        let round_trip =
+            |commit: &Commit| decode_commit_with_signature(&encode_commit_with_signature(commit));
+        assert_eq!(round_trip(&commit), Some(commit.clone()));
+
+        // The signed data doesn't have to be the simple backend format
+        commit.secure_sig = Some(SecureSig {
+            data: b"signed data".to_vec(),
+            sig: b"signature".to_vec(),
+        });
+        assert_eq!(round_trip(&commit), Some(commit.clone()));
+
+        assert_eq!(decode_commit_with_signature(b""), None);
+        assert_eq!(decode_commit_with_signature(&[0xff, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn test_commit_from_proto_invalid_copy_path() {
+        let commit = Commit {
+            parents: vec![CommitId::from_hex("123456")],
+            predecessors: vec![],
+            root_tree: MergedTreeId::resolved(TreeId::from_hex("abcdef")),
+            change_id: ChangeId::from_hex("abc123"),
+            description: "description".to_string(),
+            author: create_signature(),
+            committer: create_signature(),
+            secure_sig: None,
+            copies: vec![],
+        };
+        let mut proto = commit_to_proto(&commit);
+        proto
+            .copies
+            .push(crate::protos::simple_store::commit::Copy {
+                source: "/absolute".to_owned(),
+                target: "file".to_owned(),
+            });
+        assert_matches!(
+            commit_from_proto(proto),
+            Err(InvalidNewRepoPathError { .. })
+        );
+    }
+
     fn create_signature() -> Signature {
         Signature {
             name: "Someone".to_string(),
@@ -48,6 +48,7 @@ use crate::object_id::PrefixResolution;
 use crate::op_store;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
+use crate::op_store::OpStoreGcStats;
 use crate::op_store::OpStoreResult;
 use crate::op_store::Operation;
 use crate::op_store::OperationId;
@@ -57,6 +58,7 @@ use crate::op_store::RemoteRef;
 use crate::op_store::RemoteRefState;
 use crate::op_store::RemoteView;
 use crate::op_store::RootOperationData;
+use crate::op_store::UndoRelation;
 use crate::op_store::View;
 use crate::op_store::ViewId;
 use crate::ref_name::GitRefNameBuf;
@@ -273,8 +275,18 @@ impl OpStore for SimpleOpStore {
             .map_err(|err| OpStoreError::Other(err.into()))
     }
 
-    #[tracing::instrument(skip(self))]
     fn gc(&self, head_ids: &[OperationId], keep_newer: SystemTime) -> OpStoreResult<()> {
+        self.gc_with_stats(head_ids, keep_newer, false)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn gc_with_stats(
+        &self,
+        head_ids: &[OperationId],
+        keep_newer: SystemTime,
+        dry_run: bool,
+    ) -> OpStoreResult<OpStoreGcStats> {
         let to_op_id = |entry: &fs::DirEntry| -> Option<OperationId> {
             let name = entry.file_name().into_string().ok()?;
             OperationId::try_from_hex(&name).ok()
@@ -283,7 +295,8 @@ impl OpStore for SimpleOpStore {
             let name = entry.file_name().into_string().ok()?;
             ViewId::try_from_hex(&name).ok()
         };
-        let remove_file_if_not_new = |entry: &fs::DirEntry| -> Result<(), PathError> {
+        // Returns true if the file was (or would be) removed.
+        let remove_file_if_not_new = |entry: &fs::DirEntry| -> Result<bool, PathError> {
             let path = entry.path();
             // Check timestamp, but there's still TOCTOU problem if an existing
             // file is renewed.
@@ -291,10 +304,14 @@ impl OpStore for SimpleOpStore {
             let mtime = metadata.modified().expect("unsupported platform?");
             if mtime > keep_newer {
                 tracing::trace!(?path, "not removing");
-                Ok(())
+                Ok(false)
+            } else if dry_run {
+                tracing::trace!(?path, "would remove");
+                Ok(true)
             } else {
                 tracing::trace!(?path, "removing");
-                fs::remove_file(&path).context(&path)
+                fs::remove_file(&path).context(&path)?;
+                Ok(true)
             }
         };
 
@@ -316,7 +333,8 @@ impl OpStore for SimpleOpStore {
             "collected reachable objects"
         );
 
-        let prune_ops = || -> Result<(), PathError> {
+        let prune_ops = || -> Result<usize, PathError> {
+            let mut num_removed = 0;
             let op_dir = self.operations_dir();
             for entry in op_dir.read_dir().context(&op_dir)? {
                 let entry = entry.context(&op_dir)?;
@@ -330,13 +348,16 @@ impl OpStore for SimpleOpStore {
                 // If the operation was added after collecting reachable_views,
                 // its view mtime would also be renewed. So there's no need to
                 // update the reachable_views set to preserve the view.
-                remove_file_if_not_new(&entry)?;
+                if remove_file_if_not_new(&entry)? {
+                    num_removed += 1;
+                }
             }
-            Ok(())
+            Ok(num_removed)
         };
-        prune_ops().map_err(|err| OpStoreError::Other(err.into()))?;
+        let num_operations = prune_ops().map_err(|err| OpStoreError::Other(err.into()))?;
 
-        let prune_views = || -> Result<(), PathError> {
+        let prune_views = || -> Result<usize, PathError> {
+            let mut num_removed = 0;
             let view_dir = self.views_dir();
             for entry in view_dir.read_dir().context(&view_dir)? {
                 let entry = entry.context(&view_dir)?;
@@ -347,13 +368,18 @@ impl OpStore for SimpleOpStore {
                 if reachable_views.contains(&id) {
                     continue;
                 }
-                remove_file_if_not_new(&entry)?;
+                if remove_file_if_not_new(&entry)? {
+                    num_removed += 1;
+                }
             }
-            Ok(())
+            Ok(num_removed)
         };
-        prune_views().map_err(|err| OpStoreError::Other(err.into()))?;
+        let num_views = prune_views().map_err(|err| OpStoreError::Other(err.into()))?;
 
-        Ok(())
+        Ok(OpStoreGcStats {
+            num_operations,
+            num_views,
+        })
     }
 }
 
@@ -388,7 +414,7 @@ fn io_to_write_error(err: PathError, object_type: &'static str) -> OpStoreError
 }
 
 #[derive(Debug, Error)]
-enum PostDecodeError {
+pub(crate) enum PostDecodeError {
     #[error("Invalid hash length (expected {expected} bytes, got {actual} bytes)")]
     InvalidHashLength { expected: usize, actual: usize },
 }
@@ -440,6 +466,7 @@ fn operation_metadata_to_proto(
         username: metadata.username.clone(),
         is_snapshot: metadata.is_snapshot,
         tags: metadata.tags.clone(),
+        undo_relation: metadata.undo_relation.as_ref().map(undo_relation_to_proto),
     }
 }
 
@@ -456,10 +483,31 @@ fn operation_metadata_from_proto(
         username: proto.username,
         is_snapshot: proto.is_snapshot,
         tags: proto.tags,
+        undo_relation: proto.undo_relation.map(undo_relation_from_proto),
+    }
+}
+
+fn undo_relation_to_proto(
+    relation: &UndoRelation,
+) -> crate::protos::op_store::operation_metadata::UndoRelation {
+    use crate::protos::op_store::operation_metadata::UndoRelation as Proto;
+    match relation {
+        UndoRelation::Undo(id) => Proto::UndoOf(id.to_bytes()),
+        UndoRelation::Redo(id) => Proto::RedoOf(id.to_bytes()),
+    }
+}
+
+fn undo_relation_from_proto(
+    proto: crate::protos::op_store::operation_metadata::UndoRelation,
+) -> UndoRelation {
+    use crate::protos::op_store::operation_metadata::UndoRelation as Proto;
+    match proto {
+        Proto::UndoOf(id) => UndoRelation::Undo(OperationId::new(id)),
+        Proto::RedoOf(id) => UndoRelation::Redo(OperationId::new(id)),
     }
 }
 
-fn operation_to_proto(operation: &Operation) -> crate::protos::op_store::Operation {
+pub(crate) fn operation_to_proto(operation: &Operation) -> crate::protos::op_store::Operation {
     let mut proto = crate::protos::op_store::Operation {
         view_id: operation.view_id.as_bytes().to_vec(),
         metadata: Some(operation_metadata_to_proto(&operation.metadata)),
@@ -471,7 +519,7 @@ fn operation_to_proto(operation: &Operation) -> crate::protos::op_store::Operati
     proto
 }
 
-fn operation_from_proto(
+pub(crate) fn operation_from_proto(
     proto: crate::protos::op_store::Operation,
 ) -> Result<Operation, PostDecodeError> {
     let parents = proto
@@ -488,7 +536,7 @@ fn operation_from_proto(
     })
 }
 
-fn view_to_proto(view: &View) -> crate::protos::op_store::View {
+pub(crate) fn view_to_proto(view: &View) -> crate::protos::op_store::View {
     let mut proto = crate::protos::op_store::View {
         ..Default::default()
     };
@@ -523,7 +571,7 @@ fn view_to_proto(view: &View) -> crate::protos::op_store::View {
     proto
 }
 
-fn view_from_proto(proto: crate::protos::op_store::View) -> View {
+pub(crate) fn view_from_proto(proto: crate::protos::op_store::View) -> View {
     // TODO: validate commit id length?
     let mut view = View::empty();
     // For compatibility with old repos before we had support for multiple working
@@ -831,6 +879,7 @@ mod tests {
                     "key1".to_string() => "value1".to_string(),
                     "key2".to_string() => "value2".to_string(),
                 },
+                undo_relation: None,
             },
         }
     }
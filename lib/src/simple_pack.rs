@@ -0,0 +1,627 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pack files of the simple backend.
+//!
+//! A pack file stores zstd-compressed objects, and is laid out as follows:
+//! ```text
+//! "jjpack01"
+//! <compressed object data>...
+//! u32: number of entries
+//! for each entry (sorted by kind and id):
+//!   u8: object kind
+//!   u8[OBJECT_ID_LENGTH]: object id
+//!   u64: offset of the compressed data
+//!   u32: length of the compressed data
+//!   u64: length of the uncompressed data
+//!   u32: position of the delta base entry, or u32::MAX if none
+//! u64: offset of the entry table
+//! ```
+//!
+//! A delta-encoded object is compressed with the uncompressed contents of the
+//! base object as a zstd prefix.
+
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Read as _;
+use std::io::Seek as _;
+use std::io::SeekFrom;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use blake2::Blake2b512;
+use blake2::Digest as _;
+use tempfile::NamedTempFile;
+use zstd::zstd_safe;
+
+use crate::file_util::persist_content_addressed_temp_file;
+
+const PACK_FILE_MAGIC: &[u8] = b"jjpack01";
+const PACK_FILE_EXTENSION: &str = "pack";
+pub(crate) const OBJECT_ID_LENGTH: usize = 64;
+const ENTRY_LENGTH: usize = 1 + OBJECT_ID_LENGTH + 8 + 4 + 8 + 4;
+const NO_BASE: u32 = u32::MAX;
+
+const COMPRESSION_LEVEL: i32 = 3;
+/// Maximum length of delta chains, which bounds the cost of reading objects.
+const MAX_DELTA_DEPTH: u32 = 16;
+/// Maximum total size of base and target objects to be delta-encoded. Larger
+/// windows would require the decoder to allocate more memory than the default.
+const MAX_DELTA_WINDOW_LOG: u32 = 27;
+/// Minimum window size accepted by zstd.
+const MIN_WINDOW_LOG: u32 = 10;
+
+/// Type of object stored in pack files.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub(crate) enum PackObjectKind {
+    Commit = 0,
+    Tree = 1,
+    File = 2,
+    Symlink = 3,
+    Conflict = 4,
+}
+
+impl PackObjectKind {
+    pub const ALL: [Self; 5] = [
+        Self::Commit,
+        Self::Tree,
+        Self::File,
+        Self::Symlink,
+        Self::Conflict,
+    ];
+
+    fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|&kind| kind as u8 == value)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PackEntry {
+    kind: PackObjectKind,
+    id: [u8; OBJECT_ID_LENGTH],
+    offset: u64,
+    compressed_len: u32,
+    len: u64,
+    base_pos: Option<u32>,
+}
+
+impl PackEntry {
+    fn key(&self) -> (PackObjectKind, &[u8]) {
+        (self.kind, &self.id)
+    }
+}
+
+/// Immutable pack file of which entry table is loaded in memory.
+#[derive(Debug)]
+pub(crate) struct PackFile {
+    path: PathBuf,
+    entries: Vec<PackEntry>,
+}
+
+impl PackFile {
+    fn load(path: PathBuf) -> io::Result<Self> {
+        let mut file = File::open(&path)?;
+        let mut magic = [0; PACK_FILE_MAGIC.len()];
+        file.read_exact(&mut magic)?;
+        if magic != PACK_FILE_MAGIC {
+            return Err(invalid_data("unexpected pack file format"));
+        }
+        let table_end = file.seek(SeekFrom::End(-8))?;
+        let table_offset = read_u64(&mut file)?;
+        if table_offset < PACK_FILE_MAGIC.len() as u64
+            || table_offset
+                .checked_add(4)
+                .is_none_or(|end| end > table_end)
+        {
+            return Err(invalid_data("invalid entry table offset"));
+        }
+        file.seek(SeekFrom::Start(table_offset))?;
+        let num_entries = read_u32(&mut file)?;
+        let table_len = usize::try_from(table_end - table_offset - 4)
+            .map_err(|_| invalid_data("entry table is too large"))?;
+        if Some(table_len) != (num_entries as usize).checked_mul(ENTRY_LENGTH) {
+            return Err(invalid_data("invalid number of entries"));
+        }
+        let mut table = vec![0; table_len];
+        file.read_exact(&mut table)?;
+        let entries = table
+            .chunks_exact(ENTRY_LENGTH)
+            .map(|mut buf| {
+                let kind = PackObjectKind::from_u8(buf[0])
+                    .ok_or_else(|| invalid_data("unknown object kind"))?;
+                buf = &buf[1..];
+                let id = buf[..OBJECT_ID_LENGTH].try_into().unwrap();
+                buf = &buf[OBJECT_ID_LENGTH..];
+                let offset = read_u64(&mut buf)?;
+                let compressed_len = read_u32(&mut buf)?;
+                let len = read_u64(&mut buf)?;
+                let base_pos = read_u32(&mut buf)?;
+                if offset < PACK_FILE_MAGIC.len() as u64
+                    || offset
+                        .checked_add(u64::from(compressed_len))
+                        .is_none_or(|end| end > table_offset)
+                {
+                    return Err(invalid_data("object data out of range"));
+                }
+                if base_pos != NO_BASE && base_pos >= num_entries {
+                    return Err(invalid_data("delta base out of range"));
+                }
+                Ok(PackEntry {
+                    kind,
+                    id,
+                    offset,
+                    compressed_len,
+                    len,
+                    base_pos: (base_pos != NO_BASE).then_some(base_pos),
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        if !entries.is_sorted_by(|a, b| a.key() < b.key()) {
+            return Err(invalid_data("entries are not sorted"));
+        }
+        Ok(PackFile { path, entries })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the kinds and ids of all objects stored in this pack file.
+    pub fn object_keys(&self) -> impl Iterator<Item = (PackObjectKind, &[u8])> {
+        self.entries.iter().map(PackEntry::key)
+    }
+
+    /// Reads all objects stored in this pack file.
+    pub fn objects(&self) -> impl Iterator<Item = (PackObjectKind, &[u8], io::Result<Vec<u8>>)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(pos, entry)| (entry.kind, entry.id.as_slice(), self.read_entry(pos)))
+    }
+
+    fn find(&self, kind: PackObjectKind, id: &[u8]) -> Option<usize> {
+        self.entries
+            .binary_search_by(|entry| entry.key().cmp(&(kind, id)))
+            .ok()
+    }
+
+    fn read_entry(&self, pos: usize) -> io::Result<Vec<u8>> {
+        let mut chain = vec![pos];
+        while let Some(base_pos) = self.entries[*chain.last().unwrap()].base_pos {
+            if chain.len() > MAX_DELTA_DEPTH as usize {
+                return Err(invalid_data("delta chain is too long"));
+            }
+            chain.push(base_pos as usize);
+        }
+        let mut file = File::open(&self.path)?;
+        let mut data: Vec<u8> = vec![];
+        for &pos in chain.iter().rev() {
+            let entry = &self.entries[pos];
+            let mut compressed = vec![0; entry.compressed_len as usize];
+            file.seek(SeekFrom::Start(entry.offset))?;
+            file.read_exact(&mut compressed)?;
+            let len =
+                usize::try_from(entry.len).map_err(|_| invalid_data("object is too large"))?;
+            let base = entry.base_pos.map(|_| data.as_slice());
+            data = decompress(&compressed, len, base)?;
+        }
+        Ok(data)
+    }
+}
+
+/// Pack files in a directory.
+#[derive(Debug)]
+pub(crate) struct PackSet {
+    dir: PathBuf,
+    cached_packs: Mutex<Option<Arc<Vec<PackFile>>>>,
+}
+
+impl PackSet {
+    pub fn new(dir: PathBuf) -> Self {
+        PackSet {
+            dir,
+            cached_packs: Mutex::new(None),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the currently known pack files.
+    pub fn packs(&self) -> io::Result<Arc<Vec<PackFile>>> {
+        let mut locked_packs = self.cached_packs.lock().unwrap();
+        if let Some(packs) = &*locked_packs {
+            return Ok(packs.clone());
+        }
+        let packs = Arc::new(self.load_packs()?);
+        *locked_packs = Some(packs.clone());
+        Ok(packs)
+    }
+
+    /// Forgets the cached pack files so they will be reloaded.
+    pub fn invalidate(&self) {
+        *self.cached_packs.lock().unwrap() = None;
+    }
+
+    fn load_packs(&self) -> io::Result<Vec<PackFile>> {
+        let dir_entries = match fs::read_dir(&self.dir) {
+            Ok(dir_entries) => dir_entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+        let mut packs = vec![];
+        for dir_entry in dir_entries {
+            let path = dir_entry?.path();
+            if path
+                .extension()
+                .is_none_or(|ext| ext != PACK_FILE_EXTENSION)
+            {
+                continue;
+            }
+            match PackFile::load(path) {
+                Ok(pack) => packs.push(pack),
+                // Removed by concurrent repack
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(packs)
+    }
+
+    /// Reads object of the given `kind` and `id`. Returns `None` if the object
+    /// isn't packed.
+    pub fn read_object(&self, kind: PackObjectKind, id: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        if let Some(data) = read_object_from(&self.packs()?, kind, id)? {
+            return Ok(Some(data));
+        }
+        // The object might have been packed, or the pack files might have been
+        // replaced, by concurrent process.
+        self.invalidate();
+        read_object_from(&self.packs()?, kind, id)
+    }
+}
+
+fn read_object_from(
+    packs: &[PackFile],
+    kind: PackObjectKind,
+    id: &[u8],
+) -> io::Result<Option<Vec<u8>>> {
+    for pack in packs {
+        let Some(pos) = pack.find(kind, id) else {
+            continue;
+        };
+        match pack.read_entry(pos) {
+            Ok(data) => return Ok(Some(data)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(None)
+}
+
+/// Writes new pack file.
+pub(crate) struct PackWriter {
+    dir: PathBuf,
+    temp_file: NamedTempFile,
+    writer: BufWriter<File>,
+    offset: u64,
+    entries: Vec<PackEntry>,
+    depths: Vec<u32>,
+}
+
+impl PackWriter {
+    pub fn new(dir: &Path) -> io::Result<Self> {
+        let temp_file = NamedTempFile::new_in(dir)?;
+        let mut writer = BufWriter::new(temp_file.reopen()?);
+        writer.write_all(PACK_FILE_MAGIC)?;
+        Ok(PackWriter {
+            dir: dir.to_owned(),
+            temp_file,
+            writer,
+            offset: PACK_FILE_MAGIC.len() as u64,
+            entries: vec![],
+            depths: vec![],
+        })
+    }
+
+    /// Adds object of the given `kind`, `id`, and `data`, and returns the
+    /// position of the new entry.
+    ///
+    /// If the `base` entry position and its data are given, the object may be
+    /// delta-encoded against it.
+    pub fn add(
+        &mut self,
+        kind: PackObjectKind,
+        id: &[u8],
+        data: &[u8],
+        base: Option<(usize, &[u8])>,
+    ) -> io::Result<usize> {
+        let id: [u8; OBJECT_ID_LENGTH] = id
+            .try_into()
+            .map_err(|_| invalid_data("unexpected object id length"))?;
+        let mut compressed = compress(data, None)?;
+        let mut base_pos = None;
+        let mut depth = 0;
+        if let Some((pos, base_data)) = base {
+            if self.depths[pos] < MAX_DELTA_DEPTH
+                && base_data.len() + data.len() <= 1 << MAX_DELTA_WINDOW_LOG
+            {
+                let delta = compress(data, Some(base_data))?;
+                if delta.len() < compressed.len() {
+                    compressed = delta;
+                    base_pos = Some(u32::try_from(pos).unwrap());
+                    depth = self.depths[pos] + 1;
+                }
+            }
+        }
+        self.writer.write_all(&compressed)?;
+        self.entries.push(PackEntry {
+            kind,
+            id,
+            offset: self.offset,
+            compressed_len: u32::try_from(compressed.len())
+                .map_err(|_| invalid_data("object is too large"))?,
+            len: data.len() as u64,
+            base_pos,
+        });
+        self.depths.push(depth);
+        self.offset += compressed.len() as u64;
+        Ok(self.entries.len() - 1)
+    }
+
+    /// Writes the entry table and persists the pack file. Returns the path to
+    /// the new pack file.
+    pub fn finish(mut self) -> io::Result<PathBuf> {
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        order.sort_unstable_by(|&a, &b| self.entries[a].key().cmp(&self.entries[b].key()));
+        order.dedup_by(|a, b| self.entries[*a].key() == self.entries[*b].key());
+        let mut new_positions = vec![NO_BASE; self.entries.len()];
+        for (new_pos, &old_pos) in order.iter().enumerate() {
+            new_positions[old_pos] = u32::try_from(new_pos).unwrap();
+        }
+
+        let mut table = vec![];
+        table.extend(u32::try_from(order.len()).unwrap().to_le_bytes());
+        for &old_pos in &order {
+            let entry = &self.entries[old_pos];
+            table.push(entry.kind as u8);
+            table.extend(entry.id);
+            table.extend(entry.offset.to_le_bytes());
+            table.extend(entry.compressed_len.to_le_bytes());
+            table.extend(entry.len.to_le_bytes());
+            let base_pos = entry.base_pos.map_or(NO_BASE, |pos| {
+                let new_pos = new_positions[pos as usize];
+                assert_ne!(new_pos, NO_BASE, "delta base must not be deduplicated");
+                new_pos
+            });
+            table.extend(base_pos.to_le_bytes());
+        }
+        self.writer.write_all(&table)?;
+        self.writer.write_all(&self.offset.to_le_bytes())?;
+        self.writer.flush()?;
+        drop(self.writer);
+
+        let name = hex::encode(Blake2b512::digest(&table));
+        let path = self.dir.join(format!("{name}.{PACK_FILE_EXTENSION}"));
+        persist_content_addressed_temp_file(self.temp_file, &path)?;
+        Ok(path)
+    }
+}
+
+fn compress(data: &[u8], base: Option<&[u8]>) -> io::Result<Vec<u8>> {
+    let mut context = zstd_safe::CCtx::create();
+    context
+        .set_parameter(zstd_safe::CParameter::CompressionLevel(COMPRESSION_LEVEL))
+        .map_err(zstd_error)?;
+    if let Some(base) = base {
+        // The window has to cover the base data to refer to it.
+        let window_log = (base.len() + data.len())
+            .next_power_of_two()
+            .trailing_zeros()
+            .clamp(MIN_WINDOW_LOG, MAX_DELTA_WINDOW_LOG);
+        context
+            .set_parameter(zstd_safe::CParameter::WindowLog(window_log))
+            .map_err(zstd_error)?;
+        context.ref_prefix(base).map_err(zstd_error)?;
+    }
+    let mut compressed = Vec::with_capacity(zstd_safe::compress_bound(data.len()));
+    context
+        .compress2(&mut compressed, data)
+        .map_err(zstd_error)?;
+    Ok(compressed)
+}
+
+fn decompress(compressed: &[u8], len: usize, base: Option<&[u8]>) -> io::Result<Vec<u8>> {
+    let mut context = zstd_safe::DCtx::create();
+    if let Some(base) = base {
+        context.ref_prefix(base).map_err(zstd_error)?;
+    }
+    // The length is read from the pack file, which might be corrupted.
+    let mut data = Vec::new();
+    data.try_reserve_exact(len)
+        .map_err(|_| invalid_data("object is too large"))?;
+    context
+        .decompress(&mut data, compressed)
+        .map_err(zstd_error)?;
+    if data.len() != len {
+        return Err(invalid_data("unexpected object length"));
+    }
+    Ok(data)
+}
+
+fn read_u32(reader: &mut impl io::Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl io::Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn zstd_error(code: zstd_safe::ErrorCode) -> io::Error {
+    io::Error::other(zstd_safe::get_error_name(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use super::*;
+    use crate::tests::new_temp_dir;
+
+    fn object_id(n: u8) -> [u8; OBJECT_ID_LENGTH] {
+        [n; OBJECT_ID_LENGTH]
+    }
+
+    /// Generates data which can't be compressed well by itself.
+    fn random_data(len: usize) -> Vec<u8> {
+        let mut state: u32 = 1;
+        iter::repeat_with(|| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .take(len)
+        .collect()
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let temp_dir = new_temp_dir();
+        let pack_set = PackSet::new(temp_dir.path().to_owned());
+        assert_eq!(
+            pack_set
+                .read_object(PackObjectKind::File, &object_id(1))
+                .unwrap(),
+            None
+        );
+
+        let base_data = random_data(1000);
+        let mut delta_data = base_data.clone();
+        delta_data.extend(b"more\n");
+        let mut writer = PackWriter::new(temp_dir.path()).unwrap();
+        let base_pos = writer
+            .add(PackObjectKind::File, &object_id(2), &base_data, None)
+            .unwrap();
+        writer
+            .add(
+                PackObjectKind::File,
+                &object_id(1),
+                &delta_data,
+                Some((base_pos, &base_data)),
+            )
+            .unwrap();
+        writer
+            .add(PackObjectKind::Tree, &object_id(1), b"tree", None)
+            .unwrap();
+        writer
+            .add(PackObjectKind::Commit, &object_id(3), b"", None)
+            .unwrap();
+        writer.finish().unwrap();
+
+        // Cached empty pack set should be reloaded
+        let read = |kind, n| pack_set.read_object(kind, &object_id(n)).unwrap();
+        assert_eq!(read(PackObjectKind::File, 1), Some(delta_data));
+        assert_eq!(read(PackObjectKind::File, 2), Some(base_data));
+        assert_eq!(read(PackObjectKind::Tree, 1), Some(b"tree".to_vec()));
+        assert_eq!(read(PackObjectKind::Commit, 3), Some(vec![]));
+        assert_eq!(read(PackObjectKind::Tree, 2), None);
+        assert_eq!(read(PackObjectKind::Symlink, 1), None);
+
+        let packs = pack_set.packs().unwrap();
+        let [pack] = packs.as_slice() else {
+            panic!("single pack file should exist");
+        };
+        let entry = &pack.entries[pack.find(PackObjectKind::File, &object_id(1)).unwrap()];
+        assert!(entry.base_pos.is_some());
+        assert!(entry.compressed_len < 100);
+    }
+
+    #[test]
+    fn test_pack_delta_depth() {
+        let temp_dir = new_temp_dir();
+        let mut writer = PackWriter::new(temp_dir.path()).unwrap();
+        let mut data = random_data(1000);
+        let mut pos = writer
+            .add(PackObjectKind::File, &object_id(0), &data, None)
+            .unwrap();
+        for n in 1..=(MAX_DELTA_DEPTH + 1) {
+            let mut new_data = data.clone();
+            new_data.push(b'a');
+            pos = writer
+                .add(
+                    PackObjectKind::File,
+                    &object_id(n as u8),
+                    &new_data,
+                    Some((pos, &data)),
+                )
+                .unwrap();
+            data = new_data;
+        }
+        assert_eq!(writer.depths.last(), Some(&0));
+        assert_eq!(writer.depths[MAX_DELTA_DEPTH as usize], MAX_DELTA_DEPTH);
+        writer.finish().unwrap();
+
+        let pack_set = PackSet::new(temp_dir.path().to_owned());
+        let read = |n: u32| {
+            pack_set
+                .read_object(PackObjectKind::File, &object_id(n as u8))
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(read(MAX_DELTA_DEPTH + 1), data);
+        assert_eq!(read(MAX_DELTA_DEPTH).len(), data.len() - 1);
+    }
+
+    #[test]
+    fn test_pack_corrupt() {
+        let temp_dir = new_temp_dir();
+        let mut writer = PackWriter::new(temp_dir.path()).unwrap();
+        writer
+            .add(PackObjectKind::File, &object_id(1), b"data", None)
+            .unwrap();
+        let path = writer.finish().unwrap();
+        let data = fs::read(&path).unwrap();
+        let entry_start = data.len() - 8 - ENTRY_LENGTH;
+        let offset_start = entry_start + 1 + OBJECT_ID_LENGTH;
+        let len_start = offset_start + 8 + 4;
+
+        // Values read from the file shouldn't overflow
+        let load_with = |start: usize, value: u64| {
+            let mut data = data.clone();
+            data[start..start + 8].copy_from_slice(&value.to_le_bytes());
+            fs::write(&path, data).unwrap();
+            PackFile::load(path.clone())
+        };
+        let err = load_with(data.len() - 8, u64::MAX).unwrap_err();
+        assert_eq!(err.to_string(), "invalid entry table offset");
+        let err = load_with(offset_start, u64::MAX - 1).unwrap_err();
+        assert_eq!(err.to_string(), "object data out of range");
+        let pack = load_with(len_start, u64::MAX).unwrap();
+        let err = pack.read_entry(0).unwrap_err();
+        assert_eq!(err.to_string(), "object is too large");
+    }
+}
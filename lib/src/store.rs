@@ -23,11 +23,16 @@ use std::sync::Mutex;
 use std::time::SystemTime;
 
 use clru::CLruCache;
+use futures::stream;
 use futures::stream::BoxStream;
+use futures::TryStreamExt as _;
+use itertools::Itertools as _;
 use pollster::FutureExt as _;
 
 use crate::backend;
 use crate::backend::Backend;
+use crate::backend::BackendError;
+use crate::backend::BackendGcStats;
 use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
@@ -39,6 +44,8 @@ use crate::backend::SigningFn;
 use crate::backend::SymlinkId;
 use crate::backend::TreeId;
 use crate::commit::Commit;
+use crate::copies;
+use crate::copies::CopyDetectionOptions;
 use crate::index::Index;
 use crate::merge::Merge;
 use crate::merge::MergedTreeValue;
@@ -59,6 +66,7 @@ const TREE_CACHE_CAPACITY: usize = 1000;
 pub struct Store {
     backend: Box<dyn Backend>,
     signer: Signer,
+    copy_detection: CopyDetectionOptions,
     commit_cache: Mutex<CLruCache<CommitId, Arc<backend::Commit>>>,
     tree_cache: Mutex<CLruCache<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
 }
@@ -72,10 +80,15 @@ impl Debug for Store {
 }
 
 impl Store {
-    pub fn new(backend: Box<dyn Backend>, signer: Signer) -> Arc<Self> {
+    pub fn new(
+        backend: Box<dyn Backend>,
+        signer: Signer,
+        copy_detection: CopyDetectionOptions,
+    ) -> Arc<Self> {
         Arc::new(Store {
             backend,
             signer,
+            copy_detection,
             commit_cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
             tree_cache: Mutex::new(CLruCache::new(TREE_CACHE_CAPACITY.try_into().unwrap())),
         })
@@ -89,13 +102,43 @@ impl Store {
         &self.signer
     }
 
+    /// Gets copy records from the backend. If the backend doesn't support
+    /// copy tracking, copies are detected by comparing file contents instead.
+    ///
+    /// Copies recorded explicitly in the `head` commit take precedence over
+    /// the ones reported by the backend for the same target.
     pub fn get_copy_records(
-        &self,
+        self: &Arc<Self>,
         paths: Option<&[RepoPathBuf]>,
         root: &CommitId,
         head: &CommitId,
-    ) -> BackendResult<BoxStream<BackendResult<CopyRecord>>> {
-        self.backend.get_copy_records(paths, root, head)
+    ) -> BackendResult<BoxStream<'_, BackendResult<CopyRecord>>> {
+        let head_commit = self.get_commit(head)?;
+        let recorded = copies::recorded_copy_records(self, paths, root, &head_commit).block_on()?;
+        let records = match self.backend.get_copy_records(paths, root, head) {
+            Err(BackendError::Unsupported(_)) => {
+                let records =
+                    copies::detect_copy_records(self, paths, root, head, &self.copy_detection)
+                        .block_on()?;
+                Box::pin(stream::iter(records.into_iter().map(Ok)))
+            }
+            result => result?,
+        };
+        if recorded.is_empty() {
+            return Ok(records);
+        }
+        let records: Vec<_> = records.try_collect().block_on()?;
+        let records = recorded
+            .iter()
+            .cloned()
+            .chain(
+                records
+                    .into_iter()
+                    .filter(|record| recorded.iter().all(|r| r.target != record.target)),
+            )
+            .map(Ok)
+            .collect_vec();
+        Ok(Box::pin(stream::iter(records)))
     }
 
     pub fn commit_id_length(&self) -> usize {
@@ -288,7 +331,22 @@ impl Store {
         TreeBuilder::new(self.clone(), base_tree_id)
     }
 
+    /// Reads the tree and all its subtrees ahead of time if the backend
+    /// supports it.
+    pub async fn prefetch_tree(&self, path: &RepoPath, id: &TreeId) -> BackendResult<()> {
+        self.backend.prefetch_tree(path, id).await
+    }
+
     pub fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
         self.backend.gc(index, keep_newer)
     }
+
+    pub fn gc_with_stats(
+        &self,
+        index: &dyn Index,
+        keep_newer: SystemTime,
+        dry_run: bool,
+    ) -> BackendResult<BackendGcStats> {
+        self.backend.gc_with_stats(index, keep_newer, dry_run)
+    }
 }
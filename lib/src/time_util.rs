@@ -72,6 +72,20 @@ pub enum DatePatternParseError {
     ParseError(#[from] DateError),
 }
 
+/// Parses an absolute or relative date, such as `2024-02-01` or `2 weeks ago`.
+///
+/// Relative dates are resolved against `now`.
+pub fn parse_timestamp<Tz: TimeZone>(
+    s: &str,
+    now: DateTime<Tz>,
+) -> Result<MillisSinceEpoch, DateError>
+where
+    Tz::Offset: Copy,
+{
+    let d = parse_date_string(s, now, Dialect::Us)?;
+    Ok(MillisSinceEpoch(d.timestamp_millis()))
+}
+
 /// Represents an range of dates that may be matched against.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum DatePattern {
@@ -103,9 +117,7 @@ impl DatePattern {
     where
         Tz::Offset: Copy,
     {
-        let d =
-            parse_date_string(s, now, Dialect::Us).map_err(DatePatternParseError::ParseError)?;
-        let millis_since_epoch = MillisSinceEpoch(d.timestamp_millis());
+        let millis_since_epoch = parse_timestamp(s, now)?;
         match kind {
             "after" => Ok(DatePattern::AtOrAfter(millis_since_epoch)),
             "before" => Ok(DatePattern::Before(millis_since_epoch)),
@@ -28,6 +28,7 @@ use crate::op_heads_store::OpHeadsStoreError;
 use crate::op_store;
 use crate::op_store::OpStoreError;
 use crate::op_store::OperationMetadata;
+use crate::op_store::UndoRelation;
 use crate::operation::Operation;
 use crate::repo::MutableRepo;
 use crate::repo::ReadonlyRepo;
@@ -114,6 +115,11 @@ impl Transaction {
         self.op_metadata.is_snapshot = is_snapshot;
     }
 
+    /// Records that this transaction undoes or redoes another operation.
+    pub fn set_undo_relation(&mut self, relation: UndoRelation) {
+        self.op_metadata.undo_relation = Some(relation);
+    }
+
     /// Writes the transaction to the operation store and publishes it.
     pub fn commit(
         self,
@@ -177,6 +183,7 @@ pub fn create_op_metadata(
         username,
         is_snapshot,
         tags: Default::default(),
+        undo_relation: None,
     }
 }
 
@@ -28,6 +28,7 @@ use thiserror::Error;
 use crate::backend::BackendInitError;
 use crate::backend::MergedTreeId;
 use crate::commit::Commit;
+use crate::config::ConfigGetError;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
 use crate::local_working_copy::LocalWorkingCopy;
@@ -82,6 +83,8 @@ pub enum WorkspaceInitError {
     #[error(transparent)]
     SignInit(#[from] SignInitError),
     #[error(transparent)]
+    Config(#[from] ConfigGetError),
+    #[error(transparent)]
     TransactionCommit(#[from] TransactionCommitError),
 }
 
@@ -309,6 +312,7 @@ impl Workspace {
             )
             .map_err(|repo_init_err| match repo_init_err {
                 RepoInitError::Backend(err) => WorkspaceInitError::Backend(err),
+                RepoInitError::Config(err) => WorkspaceInitError::Config(err),
                 RepoInitError::OpHeadsStore(err) => WorkspaceInitError::OpHeadsStore(err),
                 RepoInitError::Path(err) => WorkspaceInitError::Path(err),
             })?;
@@ -8,9 +8,12 @@ fn test_no_forgotten_test_files() {
 
 mod test_annotate;
 mod test_bad_locking;
+mod test_bisect;
+mod test_caching_backend;
 mod test_commit_builder;
 mod test_commit_concurrent;
 mod test_conflicts;
+mod test_copies;
 mod test_default_revset_graph_iterator;
 mod test_fix;
 mod test_git;
@@ -28,11 +31,14 @@ mod test_merged_tree;
 mod test_mut_repo;
 mod test_operations;
 mod test_refs;
+#[cfg(feature = "remote-store")]
+mod test_remote_store;
 mod test_revset;
 mod test_rewrite;
 mod test_rewrite_duplicate;
 mod test_rewrite_transform;
 mod test_signing;
+mod test_simple_backend;
 mod test_ssh_signing;
 mod test_view;
 mod test_workspace;
@@ -21,6 +21,7 @@ use jj_lib::annotate::FileAnnotator;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::MillisSinceEpoch;
+use jj_lib::backend::RecordedCopy;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
 use jj_lib::backend::TreeValue;
@@ -71,7 +72,7 @@ fn annotate_within(
 ) -> String {
     let mut annotator = FileAnnotator::from_commit(commit, file_path).unwrap();
     annotator.compute(repo, domain).unwrap();
-    format_annotation(repo, &annotator.to_annotation())
+    format_annotation(repo, file_path, &annotator.to_annotation())
 }
 
 fn annotate_parent_tree(repo: &dyn Repo, commit: &Commit, file_path: &RepoPath) -> String {
@@ -87,17 +88,22 @@ fn annotate_parent_tree(repo: &dyn Repo, commit: &Commit, file_path: &RepoPath)
     };
     let mut annotator = FileAnnotator::with_file_content(commit.id(), file_path, text);
     annotator.compute(repo, &RevsetExpression::all()).unwrap();
-    format_annotation(repo, &annotator.to_annotation())
+    format_annotation(repo, file_path, &annotator.to_annotation())
 }
 
-fn format_annotation(repo: &dyn Repo, annotation: &FileAnnotation) -> String {
+fn format_annotation(repo: &dyn Repo, file_path: &RepoPath, annotation: &FileAnnotation) -> String {
     let mut output = String::new();
-    for (commit_id, line) in annotation.lines() {
-        let id = commit_id.unwrap_or_else(|id| id);
-        let commit = repo.store().get_commit(id).unwrap();
+    for (line_origin, line) in annotation.lines() {
+        let origin = line_origin.unwrap_or_else(|origin| origin);
+        let commit = repo.store().get_commit(&origin.commit_id).unwrap();
         let desc = commit.description().trim_end();
-        let sigil = if commit_id.is_err() { '*' } else { ' ' };
-        write!(output, "{desc}{sigil}: {line}").unwrap();
+        let sigil = if line_origin.is_err() { '*' } else { ' ' };
+        if origin.path.as_ref() == file_path {
+            write!(output, "{desc}{sigil}: {line}").unwrap();
+        } else {
+            let path = origin.path.as_internal_file_string();
+            write!(output, "{desc}{sigil} {path}: {line}").unwrap();
+        }
     }
     output
 }
@@ -139,6 +145,136 @@ fn test_annotate_linear() {
     ");
 }
 
+#[test]
+fn test_annotate_rename() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let root_commit_id = repo.store().root_commit_id();
+    let file_path1 = repo_path("file1");
+    let file_path2 = repo_path("file2");
+
+    // 3    "1 3"   file2
+    // |
+    // 2    "1 2"   file1 -> file2
+    // |
+    // 1    "1"     file1
+    let mut tx = repo.start_transaction();
+    let tree1 = create_tree(repo, &[(file_path1, "1\n")]);
+    let tree2 = create_tree(repo, &[(file_path2, "1\n2\n")]);
+    let tree3 = create_tree(repo, &[(file_path2, "1\n3\n")]);
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let commit1 = create_commit("commit1", &[root_commit_id], tree1.id());
+    drop(create_commit);
+    let commit2 = tx
+        .repo_mut()
+        .new_commit(vec![commit1.id().clone()], tree2.id())
+        .set_description("commit2")
+        .set_copies(vec![RecordedCopy {
+            source: file_path1.to_owned(),
+            target: file_path2.to_owned(),
+        }])
+        .write()
+        .unwrap();
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let commit3 = create_commit("commit3", &[commit2.id()], tree3.id());
+    drop(create_commit);
+
+    insta::assert_snapshot!(annotate(tx.repo(), &commit2, file_path2), @r"
+    commit1  file1: 1
+    commit2 : 2
+    ");
+    insta::assert_snapshot!(annotate(tx.repo(), &commit3, file_path2), @r"
+    commit1  file1: 1
+    commit3 : 3
+    ");
+}
+
+#[test]
+fn test_annotate_ignored_commits() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let root_commit_id = repo.store().root_commit_id();
+    let file_path = repo_path("file");
+
+    let mut tx = repo.start_transaction();
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let content1 = "1a\n1b\n";
+    let content2 = "1a\n2b\n";
+    let content3 = "3a\n3b\n3c\n";
+    let tree1 = create_tree(repo, &[(file_path, content1)]);
+    let tree2 = create_tree(repo, &[(file_path, content2)]);
+    let tree3 = create_tree(repo, &[(file_path, content3)]);
+    let commit1 = create_commit("commit1", &[root_commit_id], tree1.id());
+    let commit2 = create_commit("commit2", &[commit1.id()], tree2.id());
+    let commit3 = create_commit("commit3", &[commit2.id()], tree3.id());
+    drop(create_commit);
+
+    let annotate_ignoring = |ignored: &[&Commit]| {
+        let mut annotator = FileAnnotator::from_commit(&commit3, file_path).unwrap();
+        annotator.set_ignored_commits(ignored.iter().map(|commit| commit.id().clone()));
+        annotator
+            .compute(tx.repo(), &RevsetExpression::all())
+            .unwrap();
+        format_annotation(tx.repo(), file_path, &annotator.to_annotation())
+    };
+    insta::assert_snapshot!(annotate_ignoring(&[]), @r"
+    commit3 : 3a
+    commit3 : 3b
+    commit3 : 3c
+    ");
+    // Changed lines are attributed to the lines at the same position in the
+    // parent. The added line is still attributed to the ignored commit.
+    insta::assert_snapshot!(annotate_ignoring(&[&commit3]), @r"
+    commit1 : 3a
+    commit2 : 3b
+    commit3 : 3c
+    ");
+    insta::assert_snapshot!(annotate_ignoring(&[&commit2, &commit3]), @r"
+    commit1 : 3a
+    commit1 : 3b
+    commit3 : 3c
+    ");
+}
+
+#[test]
+fn test_annotate_restrict_lines() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let root_commit_id = repo.store().root_commit_id();
+    let file_path = repo_path("file");
+
+    let mut tx = repo.start_transaction();
+    let mut create_commit = create_commit_fn(tx.repo_mut());
+    let content1 = "1\n";
+    let content2 = "1\n2\n";
+    let content3 = "1\n2\n3\n";
+    let tree1 = create_tree(repo, &[(file_path, content1)]);
+    let tree2 = create_tree(repo, &[(file_path, content2)]);
+    let tree3 = create_tree(repo, &[(file_path, content3)]);
+    let commit1 = create_commit("commit1", &[root_commit_id], tree1.id());
+    let commit2 = create_commit("commit2", &[commit1.id()], tree2.id());
+    let commit3 = create_commit("commit3", &[commit2.id()], tree3.id());
+    drop(create_commit);
+
+    // Lines outside the range are left unresolved at the starting commit, and
+    // the search stops once the lines in the range are resolved.
+    let mut annotator = FileAnnotator::from_commit(&commit3, file_path).unwrap();
+    annotator.restrict_lines(1..2);
+    annotator
+        .compute(tx.repo(), &RevsetExpression::all())
+        .unwrap();
+    insta::assert_snapshot!(
+        format_annotation(tx.repo(), file_path, &annotator.to_annotation()), @r"
+    commit3*: 1
+    commit2 : 2
+    commit3*: 3
+    ");
+    assert_eq!(annotator.pending_commits().count(), 0);
+}
+
 #[test]
 fn test_annotate_merge_simple() {
     let test_repo = TestRepo::init();
@@ -215,7 +351,7 @@ fn test_annotate_merge_simple() {
     // Calculate incrementally
     let mut annotator = FileAnnotator::from_commit(&commit4, file_path).unwrap();
     assert_eq!(annotator.pending_commits().collect_vec(), [commit4.id()]);
-    insta::assert_snapshot!(format_annotation(tx.repo(), &annotator.to_annotation()), @r"
+    insta::assert_snapshot!(format_annotation(tx.repo(), file_path, &annotator.to_annotation()), @r"
     commit4*: 2
     commit4*: 1
     commit4*: 3
@@ -231,7 +367,7 @@ fn test_annotate_merge_simple() {
         )
         .unwrap();
     assert_eq!(annotator.pending_commits().collect_vec(), [commit1.id()]);
-    insta::assert_snapshot!(format_annotation(tx.repo(), &annotator.to_annotation()), @r"
+    insta::assert_snapshot!(format_annotation(tx.repo(), file_path, &annotator.to_annotation()), @r"
     commit2 : 2
     commit2*: 1
     commit3 : 3
@@ -243,7 +379,7 @@ fn test_annotate_merge_simple() {
         )
         .unwrap();
     assert!(annotator.pending_commits().next().is_none());
-    insta::assert_snapshot!(format_annotation(tx.repo(), &annotator.to_annotation()), @r"
+    insta::assert_snapshot!(format_annotation(tx.repo(), file_path, &annotator.to_annotation()), @r"
     commit2 : 2
     commit1 : 1
     commit3 : 3
@@ -0,0 +1,192 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::BisectionResult;
+use jj_lib::bisect::Bisector;
+use jj_lib::bisect::Evaluation;
+use jj_lib::bisect::NextStep;
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo;
+use testutils::CommitGraphBuilder;
+use testutils::TestRepo;
+
+/// Runs the bisection to completion, evaluating commits with `evaluate`.
+/// Returns the evaluated commits in order and the result.
+fn run_bisect(
+    repo: &dyn Repo,
+    mut bisector: Bisector,
+    evaluate: impl Fn(&CommitId) -> Evaluation,
+) -> (Vec<CommitId>, BisectionResult) {
+    let mut evaluated = vec![];
+    loop {
+        match bisector.next_step().unwrap() {
+            NextStep::Evaluate(id) => {
+                assert!(!evaluated.contains(&id), "{id} evaluated twice");
+                assert!(repo.index().has_id(&id));
+                bisector.mark(id.clone(), evaluate(&id));
+                evaluated.push(id);
+            }
+            NextStep::Done(result) => return (evaluated, result),
+        }
+    }
+}
+
+fn ids(commits: &[&Commit]) -> Vec<CommitId> {
+    commits.iter().map(|commit| commit.id().clone()).collect()
+}
+
+#[test]
+fn test_bisect_linear() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let mut commits = vec![graph_builder.initial_commit()];
+    for _ in 0..15 {
+        let parent = commits.last().unwrap().clone();
+        commits.push(graph_builder.commit_with_parents(&[&parent]));
+    }
+    let repo = tx.commit("test").unwrap();
+
+    // Commit 11 introduced the problem.
+    let first_bad = 11;
+    let mut bisector = Bisector::new(repo.as_ref());
+    bisector.mark(commits[0].id().clone(), Evaluation::Good);
+    bisector.mark(commits[15].id().clone(), Evaluation::Bad);
+    let (evaluated, result) = run_bisect(repo.as_ref(), bisector, |id| {
+        let pos = commits.iter().position(|c| c.id() == id).unwrap();
+        if pos >= first_bad {
+            Evaluation::Bad
+        } else {
+            Evaluation::Good
+        }
+    });
+    assert_eq!(result, BisectionResult::Found(ids(&[&commits[first_bad]])));
+    // 14 candidates need at most 4 evaluations
+    assert!(evaluated.len() <= 4, "{evaluated:?}");
+}
+
+#[test]
+fn test_bisect_no_good_commit() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let repo = tx.commit("test").unwrap();
+
+    // Everything is bad, so the search continues down to the root commit.
+    let root_commit_id = repo.store().root_commit_id().clone();
+    let mut bisector = Bisector::new(repo.as_ref());
+    bisector.mark(commit_b.id().clone(), Evaluation::Bad);
+    let (evaluated, result) = run_bisect(repo.as_ref(), bisector, |_| Evaluation::Bad);
+    assert_eq!(evaluated, vec![root_commit_id.clone()]);
+    assert_eq!(result, BisectionResult::Found(vec![root_commit_id]));
+}
+
+#[test]
+fn test_bisect_merge() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // F
+    // |\
+    // C E
+    // | |
+    // B D
+    // |/
+    // A
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_e = graph_builder.commit_with_parents(&[&commit_d]);
+    let commit_f = graph_builder.commit_with_parents(&[&commit_c, &commit_e]);
+    let repo = tx.commit("test").unwrap();
+
+    // D introduced the problem, so E and F are bad too.
+    let bad = ids(&[&commit_d, &commit_e, &commit_f]);
+    let mut bisector = Bisector::new(repo.as_ref());
+    bisector.mark(commit_a.id().clone(), Evaluation::Good);
+    bisector.mark(commit_f.id().clone(), Evaluation::Bad);
+    let (_evaluated, result) = run_bisect(repo.as_ref(), bisector, |id| {
+        if bad.contains(id) {
+            Evaluation::Bad
+        } else {
+            Evaluation::Good
+        }
+    });
+    assert_eq!(result, BisectionResult::Found(ids(&[&commit_d])));
+}
+
+#[test]
+fn test_bisect_skipped() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_c]);
+    let repo = tx.commit("test").unwrap();
+
+    // C is bad, but B can't be tested, so it could have been B or C.
+    let mut bisector = Bisector::new(repo.as_ref());
+    bisector.mark(commit_a.id().clone(), Evaluation::Good);
+    bisector.mark(commit_d.id().clone(), Evaluation::Bad);
+    let (_evaluated, result) = run_bisect(repo.as_ref(), bisector, |id| {
+        if id == commit_b.id() {
+            Evaluation::Skip
+        } else {
+            Evaluation::Bad
+        }
+    });
+    assert_eq!(
+        result,
+        BisectionResult::Indeterminate(ids(&[&commit_c, &commit_b]))
+    );
+}
+
+#[test]
+fn test_bisect_inconsistent_marks() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_a]);
+    let repo = tx.commit("test").unwrap();
+
+    let mut bisector = Bisector::new(repo.as_ref());
+    bisector.mark(commit_b.id().clone(), Evaluation::Good);
+    bisector.mark(commit_c.id().clone(), Evaluation::Good);
+    bisector.mark(commit_a.id().clone(), Evaluation::Bad);
+    assert_eq!(
+        bisector.inconsistent_marks(),
+        vec![
+            (commit_b.id().clone(), commit_a.id().clone()),
+            (commit_c.id().clone(), commit_a.id().clone()),
+        ]
+    );
+}
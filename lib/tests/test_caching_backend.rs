@@ -0,0 +1,291 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io::Read as _;
+use std::path::Path;
+
+use assert_matches::assert_matches;
+use jj_lib::backend::Backend;
+use jj_lib::backend::BackendError;
+use jj_lib::backend::ChangeId;
+use jj_lib::backend::Commit;
+use jj_lib::backend::FileId;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::backend::MillisSinceEpoch;
+use jj_lib::backend::Signature;
+use jj_lib::backend::Timestamp;
+use jj_lib::backend::Tree;
+use jj_lib::backend::TreeValue;
+use jj_lib::caching_backend::CachingBackend;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
+use jj_lib::git;
+use jj_lib::git_backend::GitBackend;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathComponentBuf;
+use jj_lib::settings::UserSettings;
+use jj_lib::simple_backend::SimpleBackend;
+use pollster::FutureExt as _;
+use testutils::create_tree;
+use testutils::new_temp_dir;
+use testutils::repo_path;
+use testutils::TestRepo;
+use testutils::TestRepoBackend;
+
+fn remove_dir_contents(dir: &Path) {
+    for entry in fs::read_dir(dir).unwrap() {
+        fs::remove_file(entry.unwrap().path()).unwrap();
+    }
+}
+
+fn init_inner_backend(store_path: &Path) -> Box<dyn Backend> {
+    fs::create_dir(store_path).unwrap();
+    Box::new(SimpleBackend::init(store_path))
+}
+
+fn write_file(backend: &dyn Backend, contents: &str) -> FileId {
+    backend
+        .write_file(RepoPath::root(), &mut contents.as_bytes())
+        .block_on()
+        .unwrap()
+}
+
+fn read_file(backend: &dyn Backend, id: &FileId) -> Result<String, BackendError> {
+    let mut reader = backend.read_file(RepoPath::root(), id).block_on()?;
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents).unwrap();
+    Ok(contents)
+}
+
+fn file_value(id: FileId) -> TreeValue {
+    TreeValue::File {
+        id,
+        executable: false,
+    }
+}
+
+fn name(name: &str) -> RepoPathComponentBuf {
+    RepoPathComponentBuf::new(name).unwrap()
+}
+
+#[test]
+fn test_caching_backend_reads_from_cache() {
+    let temp_dir = new_temp_dir();
+    let store_path = temp_dir.path().join("store");
+    let cache_path = temp_dir.path().join("cache");
+    let inner = init_inner_backend(&store_path);
+    let backend = CachingBackend::new(inner, &cache_path, 1 << 20).unwrap();
+
+    let file_id = write_file(&backend, "contents");
+    let mut tree = Tree::default();
+    tree.set(name("file"), file_value(file_id.clone()));
+    let tree_id = backend
+        .write_tree(RepoPath::root(), &tree)
+        .block_on()
+        .unwrap();
+    let signature = Signature {
+        name: "Someone".to_string(),
+        email: "someone@example.com".to_string(),
+        timestamp: Timestamp {
+            timestamp: MillisSinceEpoch(0),
+            tz_offset: 0,
+        },
+    };
+    let commit = Commit {
+        parents: vec![backend.root_commit_id().clone()],
+        predecessors: vec![],
+        root_tree: MergedTreeId::resolved(tree_id.clone()),
+        change_id: ChangeId::from_hex("abc123"),
+        description: "description".to_string(),
+        author: signature.clone(),
+        committer: signature,
+        secure_sig: None,
+        copies: vec![],
+    };
+    let (commit_id, commit) = backend.write_commit(commit, None).block_on().unwrap();
+    // Files are cached when read
+    assert_eq!(read_file(&backend, &file_id).unwrap(), "contents");
+
+    // Objects should be readable even if the inner backend lost them
+    for dir in ["files", "trees", "commits"] {
+        remove_dir_contents(&store_path.join(dir));
+    }
+    assert_matches!(
+        read_file(backend.inner(), &file_id),
+        Err(BackendError::ObjectNotFound { .. })
+    );
+    let backend = CachingBackend::new(
+        Box::new(SimpleBackend::load(&store_path)),
+        &cache_path,
+        1 << 20,
+    )
+    .unwrap();
+    assert_eq!(read_file(&backend, &file_id).unwrap(), "contents");
+    assert_eq!(
+        backend
+            .read_tree(RepoPath::root(), &tree_id)
+            .block_on()
+            .unwrap(),
+        tree
+    );
+    assert_eq!(backend.read_commit(&commit_id).block_on().unwrap(), commit);
+}
+
+#[test]
+fn test_caching_backend_evicts_least_recently_used() {
+    let temp_dir = new_temp_dir();
+    let store_path = temp_dir.path().join("store");
+    let cache_path = temp_dir.path().join("cache");
+    let inner = init_inner_backend(&store_path);
+    let backend = CachingBackend::new(inner, &cache_path, 25).unwrap();
+
+    let id_a = write_file(&backend, "aaaaaaaaaa");
+    let id_b = write_file(&backend, "bbbbbbbbbb");
+    let id_c = write_file(&backend, "cccccccccc");
+    read_file(&backend, &id_a).unwrap();
+    read_file(&backend, &id_b).unwrap();
+    read_file(&backend, &id_a).unwrap();
+    // b is the least recently used
+    read_file(&backend, &id_c).unwrap();
+    let num_cached_files = || fs::read_dir(cache_path.join("files")).unwrap().count();
+    assert_eq!(num_cached_files(), 2);
+
+    remove_dir_contents(&store_path.join("files"));
+    assert_eq!(read_file(&backend, &id_a).unwrap(), "aaaaaaaaaa");
+    assert_eq!(read_file(&backend, &id_c).unwrap(), "cccccccccc");
+    assert_matches!(
+        read_file(&backend, &id_b),
+        Err(BackendError::ObjectNotFound { .. })
+    );
+
+    // Cache size is bounded when reloaded with smaller limit
+    let backend =
+        CachingBackend::new(Box::new(SimpleBackend::load(&store_path)), &cache_path, 10).unwrap();
+    assert_eq!(num_cached_files(), 1);
+    assert_eq!(read_file(&backend, &id_c).unwrap(), "cccccccccc");
+}
+
+#[test]
+fn test_caching_backend_ignores_cache_write_errors() {
+    let temp_dir = new_temp_dir();
+    let store_path = temp_dir.path().join("store");
+    let cache_path = temp_dir.path().join("cache");
+    let inner = init_inner_backend(&store_path);
+    let backend = CachingBackend::new(inner, &cache_path, 1 << 20).unwrap();
+
+    // Make the cache unwritable by replacing its directory with a file
+    fs::remove_dir(cache_path.join("files")).unwrap();
+    fs::write(cache_path.join("files"), "").unwrap();
+    let file_id = write_file(&backend, "contents");
+    assert_eq!(read_file(&backend, &file_id).unwrap(), "contents");
+    assert_eq!(read_file(&backend, &file_id).unwrap(), "contents");
+}
+
+#[test]
+fn test_caching_backend_prefetch_tree() {
+    let temp_dir = new_temp_dir();
+    let store_path = temp_dir.path().join("store");
+    let cache_path = temp_dir.path().join("cache");
+    let inner = init_inner_backend(&store_path);
+
+    // Write trees to the inner backend so they aren't cached
+    let file_id = write_file(inner.as_ref(), "contents");
+    let mut leaf_tree = Tree::default();
+    leaf_tree.set(name("file"), file_value(file_id));
+    let leaf_tree_id = inner
+        .write_tree(repo_path("dir/sub"), &leaf_tree)
+        .block_on()
+        .unwrap();
+    let mut dir_tree = Tree::default();
+    dir_tree.set(name("sub"), TreeValue::Tree(leaf_tree_id.clone()));
+    let dir_tree_id = inner
+        .write_tree(repo_path("dir"), &dir_tree)
+        .block_on()
+        .unwrap();
+    let mut root_tree = Tree::default();
+    root_tree.set(name("dir"), TreeValue::Tree(dir_tree_id.clone()));
+    root_tree.set(name("dir2"), TreeValue::Tree(dir_tree_id.clone()));
+    let root_tree_id = inner
+        .write_tree(RepoPath::root(), &root_tree)
+        .block_on()
+        .unwrap();
+
+    let backend = CachingBackend::new(inner, &cache_path, 1 << 20).unwrap();
+    backend
+        .prefetch_tree(RepoPath::root(), &root_tree_id)
+        .block_on()
+        .unwrap();
+    remove_dir_contents(&store_path.join("trees"));
+    assert_eq!(
+        backend
+            .read_tree(RepoPath::root(), &root_tree_id)
+            .block_on()
+            .unwrap(),
+        root_tree
+    );
+    assert_eq!(
+        backend
+            .read_tree(repo_path("dir2"), &dir_tree_id)
+            .block_on()
+            .unwrap(),
+        dir_tree
+    );
+    assert_eq!(
+        backend
+            .read_tree(repo_path("dir/sub"), &leaf_tree_id)
+            .block_on()
+            .unwrap(),
+        leaf_tree
+    );
+}
+
+#[test]
+fn test_caching_backend_from_settings() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let cache_dir = test_repo.repo_path().join("store").join("cache");
+    let mut config = testutils::base_user_config();
+    config.add_layer(
+        ConfigLayer::parse(ConfigSource::User, "object-cache.max-size = '1MiB'").unwrap(),
+    );
+    let settings = UserSettings::from_config(config).unwrap();
+    let repo = test_repo
+        .env
+        .load_repo_at_head(&settings, test_repo.repo_path());
+
+    // The underlying backend is still accessible
+    let caching_backend: &CachingBackend = repo.store().backend_impl().downcast_ref().unwrap();
+    assert!(caching_backend.inner().as_any().is::<GitBackend>());
+    assert!(git::get_git_backend(repo.store()).is_ok());
+
+    let mut tx = repo.start_transaction();
+    let tree = create_tree(&repo, &[(repo_path("file"), "contents")]);
+    let commit = tx
+        .repo_mut()
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree.id())
+        .write()
+        .unwrap();
+    tx.commit("test").unwrap();
+    assert!(fs::read_dir(cache_dir.join("commits")).unwrap().count() > 0);
+
+    // The repo can still be loaded without the cache
+    let repo = test_repo
+        .env
+        .load_repo_at_head(&testutils::user_settings(), test_repo.repo_path());
+    assert_eq!(
+        repo.store().get_commit(commit.id()).unwrap().tree_id(),
+        commit.tree_id()
+    );
+}
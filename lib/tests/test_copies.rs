@@ -0,0 +1,396 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use assert_matches::assert_matches;
+use futures::executor::block_on_stream;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::CopyRecord;
+use jj_lib::backend::RecordedCopy;
+use jj_lib::commit::Commit;
+use jj_lib::config::ConfigGetError;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
+use jj_lib::copies::detect_copy_records;
+use jj_lib::copies::CopyDetectionOptions;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::settings::UserSettings;
+use jj_lib::store::Store;
+use jj_lib::transaction::Transaction;
+use maplit::hashmap;
+use pollster::FutureExt as _;
+use test_case::test_case;
+use testutils::create_tree;
+use testutils::repo_path;
+use testutils::repo_path_buf;
+use testutils::TestRepo;
+use testutils::TestRepoBackend;
+
+fn make_commit(
+    tx: &mut Transaction,
+    parents: Vec<CommitId>,
+    content: &[(&RepoPath, &str)],
+) -> Commit {
+    let tree = create_tree(tx.base_repo(), content);
+    tx.repo_mut()
+        .new_commit(parents, tree.id())
+        .write()
+        .unwrap()
+}
+
+fn to_path_map(records: impl IntoIterator<Item = CopyRecord>) -> HashMap<String, String> {
+    records
+        .into_iter()
+        .map(|CopyRecord { target, source, .. }| {
+            (
+                target.as_internal_file_string().to_owned(),
+                source.as_internal_file_string().to_owned(),
+            )
+        })
+        .collect()
+}
+
+fn detect_copies(
+    store: &Arc<Store>,
+    paths: Option<&[RepoPathBuf]>,
+    a: &Commit,
+    b: &Commit,
+    options: &CopyDetectionOptions,
+) -> HashMap<String, String> {
+    let records = detect_copy_records(store, paths, a.id(), b.id(), options)
+        .block_on()
+        .unwrap();
+    to_path_map(records)
+}
+
+fn numbered_lines(range: std::ops::Range<usize>) -> String {
+    range.map(|i| format!("line {i}\n")).collect()
+}
+
+fn options_from_config(text: &str) -> Result<CopyDetectionOptions, ConfigGetError> {
+    let mut config = testutils::base_user_config();
+    config.add_layer(ConfigLayer::parse(ConfigSource::User, text).unwrap());
+    let settings = UserSettings::from_config(config).unwrap();
+    CopyDetectionOptions::from_settings(&settings)
+}
+
+#[test]
+fn test_copy_detection_options_from_settings() {
+    let options = options_from_config("").unwrap();
+    assert_eq!(options.similarity_threshold, 0.5);
+    assert_eq!(options.max_candidates, 1000);
+
+    let options = options_from_config(
+        "copy-detection.similarity-threshold = 1.0\ncopy-detection.max-candidates = 1",
+    )
+    .unwrap();
+    assert_eq!(options.similarity_threshold, 1.0);
+    assert_eq!(options.max_candidates, 1);
+
+    assert_matches!(
+        options_from_config("copy-detection.similarity-threshold = 0.0"),
+        Err(ConfigGetError::Type { .. })
+    );
+    assert_matches!(
+        options_from_config("copy-detection.similarity-threshold = 1.5"),
+        Err(ConfigGetError::Type { .. })
+    );
+    assert_matches!(
+        options_from_config("copy-detection.max-candidates = 0"),
+        Err(ConfigGetError::Type { .. })
+    );
+}
+
+#[test]
+fn test_detect_copies_exact() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let options = CopyDetectionOptions::default();
+
+    let mut tx = repo.start_transaction();
+    let commit_a = make_commit(
+        &mut tx,
+        vec![repo.store().root_commit_id().clone()],
+        &[
+            (repo_path("renamed"), "content1"),
+            (repo_path("modified"), "content2"),
+            (repo_path("unchanged"), "content3"),
+        ],
+    );
+    let commit_b = make_commit(
+        &mut tx,
+        vec![commit_a.id().clone()],
+        &[
+            (repo_path("new/renamed"), "content1"),
+            (repo_path("modified"), "content2 modified"),
+            (repo_path("copied"), "content2"),
+            (repo_path("unchanged"), "content3"),
+            (repo_path("unchanged-copy"), "content3"),
+        ],
+    );
+
+    let store = repo.store();
+    // Unchanged files aren't considered as copy sources.
+    assert_eq!(
+        detect_copies(store, None, &commit_a, &commit_b, &options),
+        hashmap! {
+            "new/renamed".to_owned() => "renamed".to_owned(),
+            "copied".to_owned() => "modified".to_owned(),
+        }
+    );
+    assert_eq!(
+        detect_copies(
+            store,
+            Some(&[repo_path_buf("copied")]),
+            &commit_a,
+            &commit_b,
+            &options
+        ),
+        hashmap! {
+            "copied".to_owned() => "modified".to_owned(),
+        }
+    );
+    assert_eq!(
+        detect_copies(store, None, &commit_b, &commit_b, &options),
+        hashmap! {}
+    );
+}
+
+#[test]
+fn test_detect_copies_prefers_deleted_source() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let options = CopyDetectionOptions::default();
+
+    let mut tx = repo.start_transaction();
+    let commit_a = make_commit(
+        &mut tx,
+        vec![repo.store().root_commit_id().clone()],
+        &[
+            (repo_path("deleted"), "content"),
+            (repo_path("modified"), "content"),
+        ],
+    );
+    let commit_b = make_commit(
+        &mut tx,
+        vec![commit_a.id().clone()],
+        &[
+            (repo_path("modified"), "modified"),
+            (repo_path("target1"), "content"),
+            (repo_path("target2"), "content"),
+            (repo_path("target3"), "content"),
+        ],
+    );
+
+    // Each source is used only once.
+    assert_eq!(
+        detect_copies(repo.store(), None, &commit_a, &commit_b, &options),
+        hashmap! {
+            "target1".to_owned() => "deleted".to_owned(),
+            "target2".to_owned() => "modified".to_owned(),
+        }
+    );
+}
+
+#[test]
+fn test_detect_copies_similar() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // "b" shares 8 of 10 lines with "a", and "d" shares 4 of 10 lines with "c".
+    let mut tx = repo.start_transaction();
+    let commit_a = make_commit(
+        &mut tx,
+        vec![repo.store().root_commit_id().clone()],
+        &[
+            (repo_path("a"), &numbered_lines(0..10)),
+            (repo_path("c"), &numbered_lines(100..110)),
+        ],
+    );
+    let commit_b = make_commit(
+        &mut tx,
+        vec![commit_a.id().clone()],
+        &[
+            (
+                repo_path("b"),
+                &(numbered_lines(0..8) + &numbered_lines(20..22)),
+            ),
+            (
+                repo_path("d"),
+                &(numbered_lines(100..104) + &numbered_lines(200..206)),
+            ),
+        ],
+    );
+
+    let store = repo.store();
+    let options = CopyDetectionOptions::default();
+    assert_eq!(
+        detect_copies(store, None, &commit_a, &commit_b, &options),
+        hashmap! {
+            "b".to_owned() => "a".to_owned(),
+        }
+    );
+
+    let options = CopyDetectionOptions {
+        similarity_threshold: 0.3,
+        ..CopyDetectionOptions::default()
+    };
+    assert_eq!(
+        detect_copies(store, None, &commit_a, &commit_b, &options),
+        hashmap! {
+            "b".to_owned() => "a".to_owned(),
+            "d".to_owned() => "c".to_owned(),
+        }
+    );
+
+    let options = CopyDetectionOptions {
+        similarity_threshold: 0.9,
+        ..CopyDetectionOptions::default()
+    };
+    assert_eq!(
+        detect_copies(store, None, &commit_a, &commit_b, &options),
+        hashmap! {}
+    );
+
+    // Too many candidates to compare contents
+    let options = CopyDetectionOptions {
+        max_candidates: 1,
+        ..CopyDetectionOptions::default()
+    };
+    assert_eq!(
+        detect_copies(store, None, &commit_a, &commit_b, &options),
+        hashmap! {}
+    );
+
+    // The limit applies to each of the sources and targets
+    let options = CopyDetectionOptions {
+        similarity_threshold: 0.3,
+        max_candidates: 2,
+    };
+    assert_eq!(
+        detect_copies(store, None, &commit_a, &commit_b, &options),
+        hashmap! {
+            "b".to_owned() => "a".to_owned(),
+            "d".to_owned() => "c".to_owned(),
+        }
+    );
+}
+
+#[test]
+fn test_simple_backend_copy_records() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let commit_a = make_commit(
+        &mut tx,
+        vec![repo.store().root_commit_id().clone()],
+        &[(repo_path("file0"), &numbered_lines(0..10))],
+    );
+    let commit_b = make_commit(
+        &mut tx,
+        vec![commit_a.id().clone()],
+        &[(repo_path("file1"), &numbered_lines(0..9))],
+    );
+
+    // The store falls back to content-based detection.
+    let stream = repo
+        .store()
+        .get_copy_records(None, commit_a.id(), commit_b.id())
+        .unwrap();
+    let records: Vec<CopyRecord> = block_on_stream(stream).map(Result::unwrap).collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].target_commit, *commit_b.id());
+    assert_eq!(records[0].source_commit, *commit_a.id());
+    assert_eq!(
+        to_path_map(records),
+        hashmap! {
+            "file1".to_owned() => "file0".to_owned(),
+        }
+    );
+}
+
+#[test_case(TestRepoBackend::Simple ; "simple backend")]
+#[test_case(TestRepoBackend::Git ; "git backend")]
+fn test_recorded_copies(backend: TestRepoBackend) {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_backend(backend);
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let commit_a = make_commit(
+        &mut tx,
+        vec![repo.store().root_commit_id().clone()],
+        &[
+            (repo_path("file0"), "old contents\n"),
+            (repo_path("file2"), "contents\n"),
+        ],
+    );
+    let tree = create_tree(
+        tx.base_repo(),
+        &[
+            (repo_path("file1"), "rewritten contents\n"),
+            (repo_path("file2"), "contents\n"),
+        ],
+    );
+    let commit_b = tx
+        .repo_mut()
+        .new_commit(vec![commit_a.id().clone()], tree.id())
+        .set_copies(vec![
+            RecordedCopy {
+                source: repo_path_buf("file0"),
+                target: repo_path_buf("file1"),
+            },
+            // Stale record, since the target doesn't exist.
+            RecordedCopy {
+                source: repo_path_buf("file2"),
+                target: repo_path_buf("file3"),
+            },
+        ])
+        .write()
+        .unwrap();
+    tx.repo_mut().add_head(&commit_b).unwrap();
+    tx.commit("test").unwrap();
+
+    // The copies survive a round trip through the backend.
+    let repo = test_repo
+        .env
+        .load_repo_at_head(&settings, test_repo.repo_path());
+    let commit_b = repo.store().get_commit(commit_b.id()).unwrap();
+    assert_eq!(commit_b.copies().len(), 2);
+
+    let stream = repo
+        .store()
+        .get_copy_records(None, commit_a.id(), commit_b.id())
+        .unwrap();
+    let records: Vec<CopyRecord> = block_on_stream(stream).map(Result::unwrap).collect();
+    assert_eq!(
+        to_path_map(records),
+        hashmap! {
+            "file1".to_owned() => "file0".to_owned(),
+        }
+    );
+
+    // Copies are only recorded relative to the parents.
+    let stream = repo
+        .store()
+        .get_copy_records(None, repo.store().root_commit_id(), commit_b.id())
+        .unwrap();
+    let records: Vec<CopyRecord> = block_on_stream(stream).map(Result::unwrap).collect();
+    assert_eq!(to_path_map(records), hashmap! {});
+}
@@ -43,6 +43,7 @@ use jj_lib::git::GitFetch;
 use jj_lib::git::GitFetchError;
 use jj_lib::git::GitImportError;
 use jj_lib::git::GitPushError;
+use jj_lib::git::GitPushOptions;
 use jj_lib::git::GitPushStats;
 use jj_lib::git::GitRefKind;
 use jj_lib::git::GitRefUpdate;
@@ -1708,6 +1709,140 @@ fn test_export_refs_current_bookmark_changed() {
     assert!(git_repo.head().unwrap().is_detached(), "HEAD is detached");
 }
 
+#[test]
+fn test_export_refs_tag_changed() {
+    // We can export changes to tags
+    let test_data = GitRepoData::create();
+    let git_settings = GitSettings::default();
+    let git_repo = test_data.git_repo;
+    let commit1 = empty_git_commit(&git_repo, "refs/heads/main", &[]);
+    git_repo
+        .tag_reference(
+            "v1",
+            commit1,
+            gix::refs::transaction::PreviousValue::MustNotExist,
+        )
+        .unwrap();
+
+    let mut tx = test_data.repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    git::import_refs(mut_repo, &git_settings).unwrap();
+    let new_commit = create_random_commit(mut_repo)
+        .set_parents(vec![jj_id(commit1)])
+        .write()
+        .unwrap();
+    mut_repo.set_tag_target("v1".as_ref(), RefTarget::normal(new_commit.id().clone()));
+    mut_repo.set_tag_target("v2".as_ref(), RefTarget::normal(jj_id(commit1)));
+    let stats = git::export_refs(mut_repo).unwrap();
+    assert!(stats.failed_bookmarks.is_empty());
+    assert!(stats.failed_tags.is_empty());
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v1".as_ref()),
+        RefTarget::normal(new_commit.id().clone())
+    );
+    assert_eq!(
+        git_repo
+            .find_reference("refs/tags/v1")
+            .unwrap()
+            .target()
+            .id(),
+        git_id(&new_commit)
+    );
+    assert_eq!(
+        git_repo
+            .find_reference("refs/tags/v2")
+            .unwrap()
+            .target()
+            .id(),
+        commit1
+    );
+
+    // The exported tags are considered known by the next import
+    git::import_refs(mut_repo, &git_settings).unwrap();
+    assert_eq!(
+        mut_repo.get_tag("v1".as_ref()),
+        RefTarget::normal(new_commit.id().clone())
+    );
+
+    mut_repo.set_tag_target("v1".as_ref(), RefTarget::absent());
+    let stats = git::export_refs(mut_repo).unwrap();
+    assert!(stats.failed_tags.is_empty());
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v1".as_ref()),
+        RefTarget::absent()
+    );
+    assert!(git_repo
+        .try_find_reference("refs/tags/v1")
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_export_refs_annotated_tag() {
+    let test_data = GitRepoData::create();
+    let git_settings = GitSettings::default();
+    let git_repo = test_data.git_repo;
+    let commit1 = empty_git_commit(&git_repo, "refs/heads/main", &[]);
+    let commit2 = empty_git_commit(&git_repo, "refs/heads/main", &[commit1]);
+
+    let mut tx = test_data.repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+    git::import_refs(mut_repo, &git_settings).unwrap();
+    let tagger = Signature {
+        name: "Tagger".to_owned(),
+        email: "tagger@example.com".to_owned(),
+        timestamp: Timestamp {
+            timestamp: MillisSinceEpoch(0),
+            tz_offset: 0,
+        },
+    };
+    mut_repo.set_tag_target("v1".as_ref(), RefTarget::normal(jj_id(commit1)));
+    let stats =
+        git::export_annotated_tags(mut_repo, &["v1".into()], "Release\n", &tagger).unwrap();
+    assert!(stats.failed_tags.is_empty());
+    assert_eq!(
+        mut_repo.get_tag("v1".as_ref()),
+        RefTarget::normal(jj_id(commit1))
+    );
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v1".as_ref()),
+        RefTarget::normal(jj_id(commit1))
+    );
+    let git_ref = git_repo.find_reference("refs/tags/v1").unwrap();
+    let tag = git_repo.find_tag(git_ref.target().id()).unwrap();
+    let tag = tag.decode().unwrap();
+    assert_eq!(tag.target(), commit1);
+    assert_eq!(tag.message, "Release\n");
+    assert_eq!(tag.tagger.unwrap().name, "Tagger");
+
+    // Nothing to export, and the annotated tag is unchanged by import
+    let stats = git::export_refs(mut_repo).unwrap();
+    assert!(stats.failed_tags.is_empty());
+    let stats = git::import_refs(mut_repo, &git_settings).unwrap();
+    assert!(stats.changed_remote_tags.is_empty());
+    assert_eq!(
+        git_repo
+            .find_reference("refs/tags/v1")
+            .unwrap()
+            .target()
+            .id(),
+        git_ref.target().id()
+    );
+
+    // The annotated tag can be moved
+    mut_repo.set_tag_target("v1".as_ref(), RefTarget::normal(jj_id(commit2)));
+    let stats = git::export_refs(mut_repo).unwrap();
+    assert!(stats.failed_tags.is_empty());
+    assert_eq!(
+        git_repo
+            .find_reference("refs/tags/v1")
+            .unwrap()
+            .target()
+            .id(),
+        commit2
+    );
+}
+
 #[test_case(false; "without moved placeholder ref")]
 #[test_case(true; "with moved placeholder ref")]
 fn test_export_refs_unborn_git_bookmark(move_placeholder_ref: bool) {
@@ -3302,6 +3437,7 @@ fn test_push_bookmarks_success(subprocess: bool) {
         &git_settings,
         "origin".as_ref(),
         &targets,
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert_eq!(
@@ -3375,6 +3511,7 @@ fn test_push_bookmarks_deletion(subprocess: bool) {
         &git_settings,
         "origin".as_ref(),
         &targets,
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert_eq!(
@@ -3443,6 +3580,7 @@ fn test_push_bookmarks_mixed_deletion_and_addition(subprocess: bool) {
         &git_settings,
         "origin".as_ref(),
         &targets,
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert_eq!(
@@ -3514,6 +3652,7 @@ fn test_push_bookmarks_not_fast_forward(subprocess: bool) {
         &git_settings,
         "origin".as_ref(),
         &targets,
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert_eq!(
@@ -3530,6 +3669,63 @@ fn test_push_bookmarks_not_fast_forward(subprocess: bool) {
     assert_eq!(new_target.target().id(), git_id(&setup.sideways_commit));
 }
 
+#[test]
+fn test_push_updates_atomic() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+    let git_settings = get_git_settings(true);
+
+    // The main bookmark is at `main_commit` on the remote, so the first update
+    // is rejected. The second one would succeed on its own.
+    let targets = [
+        GitRefUpdate {
+            qualified_name: "refs/heads/main".into(),
+            expected_current_target: Some(setup.sideways_commit.id().clone()),
+            new_target: Some(setup.child_of_main_commit.id().clone()),
+        },
+        GitRefUpdate {
+            qualified_name: "refs/heads/new".into(),
+            expected_current_target: None,
+            new_target: Some(setup.child_of_main_commit.id().clone()),
+        },
+    ];
+    let options = GitPushOptions {
+        atomic: true,
+        ..Default::default()
+    };
+    let result = git::push_updates(
+        setup.jj_repo.as_ref(),
+        &git_settings,
+        "origin".as_ref(),
+        &targets,
+        &options,
+        git::RemoteCallbacks::default(),
+    );
+    assert_eq!(
+        result.unwrap(),
+        GitPushStats {
+            rejected: vec![
+                ("refs/heads/main".into(), Some("stale info".to_owned())),
+                (
+                    "refs/heads/new".into(),
+                    Some("atomic push failed".to_owned())
+                ),
+            ],
+            ..Default::default()
+        }
+    );
+
+    // Check that neither ref got updated in the source repo
+    let source_repo = testutils::git::open(&setup.source_repo_dir);
+    let main_target = source_repo.find_reference("refs/heads/main").unwrap();
+    assert_eq!(main_target.target().id(), git_id(&setup.main_commit));
+    assert!(source_repo
+        .try_find_reference("refs/heads/new")
+        .unwrap()
+        .is_none());
+}
+
 // TODO(ilyagr): More tests for push safety checks were originally planned. We
 // may want to add tests for when a bookmark unexpectedly moved backwards or
 // unexpectedly does not exist for bookmark deletion.
@@ -3564,6 +3760,7 @@ fn test_push_updates_unexpectedly_moved_sideways_on_remote(subprocess: bool) {
             &git_settings,
             "origin".as_ref(),
             &targets,
+            &GitPushOptions::default(),
             git::RemoteCallbacks::default(),
         )
     };
@@ -3643,6 +3840,7 @@ fn test_push_updates_unexpectedly_moved_forward_on_remote(subprocess: bool) {
             &git_settings,
             "origin".as_ref(),
             &targets,
+            &GitPushOptions::default(),
             git::RemoteCallbacks::default(),
         )
     };
@@ -3721,6 +3919,7 @@ fn test_push_updates_unexpectedly_exists_on_remote(subprocess: bool) {
             &git_settings,
             "origin".as_ref(),
             &targets,
+            &GitPushOptions::default(),
             git::RemoteCallbacks::default(),
         )
     };
@@ -3771,6 +3970,7 @@ fn test_push_updates_success(subprocess: bool) {
             expected_current_target: Some(setup.main_commit.id().clone()),
             new_target: Some(setup.child_of_main_commit.id().clone()),
         }],
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert_eq!(
@@ -3812,6 +4012,7 @@ fn test_push_updates_no_such_remote(subprocess: bool) {
             expected_current_target: Some(setup.main_commit.id().clone()),
             new_target: Some(setup.child_of_main_commit.id().clone()),
         }],
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert!(matches!(result, Err(GitPushError::NoSuchRemote(_))));
@@ -3833,6 +4034,7 @@ fn test_push_updates_invalid_remote(subprocess: bool) {
             expected_current_target: Some(setup.main_commit.id().clone()),
             new_target: Some(setup.child_of_main_commit.id().clone()),
         }],
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert!(matches!(result, Err(GitPushError::NoSuchRemote(_))));
@@ -41,6 +41,7 @@ use testutils::create_single_tree;
 use testutils::create_tree;
 use testutils::repo_path;
 use testutils::repo_path_buf;
+use testutils::write_file;
 use testutils::CommitGraphBuilder;
 use testutils::TestRepo;
 use testutils::TestRepoBackend;
@@ -65,7 +66,7 @@ fn collect_no_gc_refs(git_repo_path: &Path) -> HashSet<CommitId> {
 }
 
 fn get_copy_records(
-    store: &Store,
+    store: &Arc<Store>,
     paths: Option<&[RepoPathBuf]>,
     a: &Commit,
     b: &Commit,
@@ -191,7 +192,7 @@ fn test_gc() {
         },
     );
 
-    // G is no longer reachable
+    // G is no longer reachable, but dry run doesn't update refs
     let mut mut_index = base_index.start_modification();
     mut_index.add_commit(&commit_a);
     mut_index.add_commit(&commit_b);
@@ -200,7 +201,26 @@ fn test_gc() {
     mut_index.add_commit(&commit_e);
     mut_index.add_commit(&commit_f);
     mut_index.add_commit(&commit_h);
-    repo.store().gc(mut_index.as_index(), now()).unwrap();
+    let stats = repo
+        .store()
+        .gc_with_stats(mut_index.as_index(), now(), true)
+        .unwrap();
+    assert_eq!(stats.num_commits, 1);
+    assert_eq!(
+        collect_no_gc_refs(git_repo_path),
+        hashset! {
+            commit_d.id().clone(),
+            commit_g.id().clone(),
+            commit_h.id().clone(),
+        },
+    );
+
+    // G is no longer reachable
+    let stats = repo
+        .store()
+        .gc_with_stats(mut_index.as_index(), now(), false)
+        .unwrap();
+    assert_eq!(stats.num_commits, 1);
     assert_eq!(
         collect_no_gc_refs(git_repo_path),
         hashset! {
@@ -241,6 +261,42 @@ fn test_gc() {
     assert_eq!(collect_no_gc_refs(git_repo_path), hashset! {});
 }
 
+#[test]
+fn test_gc_prunes_unreachable_objects() {
+    // TODO: Better way to disable the test if git command couldn't be executed
+    if Command::new("git").arg("--version").status().is_err() {
+        eprintln!("Skipping because git command might fail to run");
+        return;
+    }
+
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = test_repo.repo;
+    let git_repo_path = get_git_backend(&repo).git_repo_path();
+    let file_id = write_file(repo.store(), repo_path("file"), "unreachable");
+    let file_hex = file_id.hex();
+    let loose_object_path = git_repo_path
+        .join("objects")
+        .join(&file_hex[..2])
+        .join(&file_hex[2..]);
+    assert!(loose_object_path.exists());
+    let now = || SystemTime::now() + Duration::from_secs(1);
+
+    // Dry run counts the object without removing it
+    let stats = repo
+        .store()
+        .gc_with_stats(repo.index(), now(), true)
+        .unwrap();
+    assert_eq!(stats.num_objects, 1);
+    assert!(loose_object_path.exists());
+
+    let stats = repo
+        .store()
+        .gc_with_stats(repo.index(), now(), false)
+        .unwrap();
+    assert_eq!(stats.num_objects, 1);
+    assert!(!loose_object_path.exists());
+}
+
 #[test]
 fn test_copy_detection() {
     let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
@@ -14,19 +14,25 @@
 
 use std::collections::HashSet;
 use std::fs;
+use std::process::Command;
 use std::sync::Arc;
 
 use assert_matches::assert_matches;
+use itertools::Itertools as _;
 use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::commit_builder::CommitBuilder;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
 use jj_lib::default_index::AsCompositeIndex as _;
 use jj_lib::default_index::CompositeIndex;
 use jj_lib::default_index::DefaultIndexStore;
 use jj_lib::default_index::DefaultIndexStoreError;
 use jj_lib::default_index::DefaultMutableIndex;
 use jj_lib::default_index::DefaultReadonlyIndex;
+use jj_lib::fileset::FilesetExpression;
+use jj_lib::git_backend::GitBackend;
 use jj_lib::index::Index as _;
 use jj_lib::object_id::HexPrefix;
 use jj_lib::object_id::ObjectId as _;
@@ -40,14 +46,21 @@ use jj_lib::repo::MutableRepo;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo as _;
 use jj_lib::revset::ResolvedExpression;
+use jj_lib::revset::RevsetExpression;
+use jj_lib::revset::RevsetFilterPredicate;
 use jj_lib::revset::GENERATION_RANGE_FULL;
+use jj_lib::settings::UserSettings;
 use maplit::hashset;
 use testutils::commit_transactions;
 use testutils::create_random_commit;
+use testutils::create_tree;
+use testutils::repo_path;
+use testutils::repo_path_buf;
 use testutils::test_backend::TestBackend;
 use testutils::write_random_commit;
 use testutils::CommitGraphBuilder;
 use testutils::TestRepo;
+use testutils::TestRepoBackend;
 
 fn child_commit<'repo>(mut_repo: &'repo mut MutableRepo, commit: &Commit) -> CommitBuilder<'repo> {
     create_random_commit(mut_repo).set_parents(vec![commit.id().clone()])
@@ -644,7 +657,8 @@ fn test_reindex_corrupt_segment_files() {
         // u32: number of local change ids
         // u32: number of overflow parent entries
         // u32: number of overflow change id positions
-        fs::write(entry.path(), b"\0".repeat(24)).unwrap();
+        // u32: number of changed-path filters
+        fs::write(entry.path(), b"\0".repeat(28)).unwrap();
     }
 
     let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
@@ -732,6 +746,167 @@ fn test_reindex_missing_commit() {
     assert_matches!(err, DefaultIndexStoreError::IndexCommits { op_id, .. } if op_id == *bad_op_id);
 }
 
+#[test]
+fn test_changed_path_filters() {
+    let mut config = testutils::base_user_config();
+    config.add_layer(
+        ConfigLayer::parse(ConfigSource::User, "index.changed-path-filters = true").unwrap(),
+    );
+    let settings = UserSettings::from_config(config).unwrap();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let test_env = &test_repo.env;
+    let repo = &test_repo.repo;
+
+    let file_path = repo_path("dir/file");
+    let other_path = repo_path("other");
+    let tree1 = create_tree(repo, &[(file_path, "1")]);
+    let tree2 = create_tree(repo, &[(file_path, "1"), (other_path, "2")]);
+    let mut tx = repo.start_transaction();
+    let commit1 = tx
+        .repo_mut()
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree1.id())
+        .write()
+        .unwrap();
+    let commit2 = tx
+        .repo_mut()
+        .new_commit(vec![commit1.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let commit3 = tx
+        .repo_mut()
+        .new_commit(vec![commit2.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    let evaluate_files = |repo: &Arc<ReadonlyRepo>, expression: FilesetExpression| {
+        RevsetExpression::filter(RevsetFilterPredicate::File(expression))
+            .intersection(&RevsetExpression::commit(commit3.id().clone()).ancestors())
+            .evaluate(repo.as_ref())
+            .unwrap()
+            .iter()
+            .map(Result::unwrap)
+            .collect_vec()
+    };
+    let check_files = |repo: &Arc<ReadonlyRepo>| {
+        assert_eq!(
+            evaluate_files(repo, FilesetExpression::prefix_path(repo_path_buf("dir"))),
+            vec![commit1.id().clone()]
+        );
+        assert_eq!(
+            evaluate_files(repo, FilesetExpression::file_path(file_path.to_owned())),
+            vec![commit1.id().clone()]
+        );
+        assert_eq!(
+            evaluate_files(repo, FilesetExpression::file_path(other_path.to_owned())),
+            vec![commit2.id().clone()]
+        );
+        assert_eq!(
+            evaluate_files(repo, FilesetExpression::prefix_path(repo_path_buf("di"))),
+            vec![]
+        );
+        assert_eq!(
+            evaluate_files(repo, FilesetExpression::all()),
+            vec![commit2.id().clone(), commit1.id().clone()]
+        );
+    };
+
+    // Filters are recorded incrementally
+    check_files(&repo);
+
+    // Filters are recorded when rebuilding the index
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    default_index_store.reinit().unwrap();
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
+    check_files(&repo);
+}
+
+#[test]
+fn test_reindex_from_git_commit_graph() {
+    // TODO: Better way to disable the test if git command couldn't be executed
+    if Command::new("git").arg("--version").status().is_err() {
+        eprintln!("Skipping because git command might fail to run");
+        return;
+    }
+
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let test_env = &test_repo.env;
+    let repo = &test_repo.repo;
+
+    // Set up commits:
+    //
+    //   E (predecessor: C)
+    // D |
+    // |\|
+    // | C
+    // B |
+    // |/
+    // A
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_b, &commit_c]);
+    let commit_e = create_random_commit(tx.repo_mut())
+        .set_parents(vec![commit_c.id().clone()])
+        .set_predecessors(vec![commit_c.id().clone()])
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+    let commits = [&commit_a, &commit_b, &commit_c, &commit_d, &commit_e];
+
+    let collect_entries = |repo: &Arc<ReadonlyRepo>| {
+        let index = as_readonly_composite(repo);
+        assert_eq!(index.num_commits(), 6);
+        commits
+            .iter()
+            .map(|commit| {
+                let entry = index.entry_by_id(commit.id()).unwrap();
+                let parent_ids = entry
+                    .parents()
+                    .map(|parent| parent.commit_id())
+                    .collect_vec();
+                (entry.change_id(), entry.generation_number(), parent_ids)
+            })
+            .collect_vec()
+    };
+    let expected_entries = collect_entries(&repo);
+
+    // All commits are kept reachable by no-gc refs
+    let git_repo_path = repo
+        .store()
+        .backend_impl()
+        .downcast_ref::<GitBackend>()
+        .unwrap()
+        .git_repo_path();
+    let status = Command::new("git")
+        .args(["commit-graph", "write", "--reachable"])
+        .current_dir(git_repo_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // Commits read from the commit-graph don't have to be loaded from the
+    // object database.
+    let object_hex = commit_a.id().hex();
+    fs::remove_file(
+        git_repo_path
+            .join("objects")
+            .join(&object_hex[..2])
+            .join(&object_hex[2..]),
+    )
+    .unwrap();
+
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    default_index_store.reinit().unwrap();
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
+    assert_eq!(collect_entries(&repo), expected_entries);
+}
+
 /// Test that .jj/repo/index/type is created when the repo is created.
 #[test]
 fn test_index_store_type() {
@@ -54,6 +54,7 @@ use jj_lib::workspace::Workspace;
 use pollster::FutureExt as _;
 use test_case::test_case;
 use testutils::commit_with_tree;
+use testutils::create_single_tree;
 use testutils::create_tree;
 use testutils::repo_path;
 use testutils::repo_path_buf;
@@ -417,6 +418,159 @@ fn test_checkout_no_op() {
     assert_eq!(*wc.operation_id(), new_op_id);
 }
 
+#[test]
+fn test_checkout_many_files() {
+    // Check out more files than are written in a single batch, replacing files
+    // in the same directories.
+    fn to_path_contents(files: &[(RepoPathBuf, String)]) -> Vec<(&RepoPath, &str)> {
+        files
+            .iter()
+            .map(|(path, contents)| (path.as_ref(), contents.as_str()))
+            .collect()
+    }
+
+    let mut test_workspace = TestWorkspace::init();
+    let repo = test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let old_files = (0..300)
+        .map(|i| {
+            (
+                repo_path_buf(format!("dir{}/old{i}", i % 10)),
+                format!("old {i}\n"),
+            )
+        })
+        .collect_vec();
+    let new_files = (0..300)
+        .map(|i| {
+            (
+                repo_path_buf(format!("dir{}/new{i}", i % 5)),
+                format!("new {i}\n"),
+            )
+        })
+        .collect_vec();
+    let conflict_paths = (0..3)
+        .map(|i| repo_path_buf(format!("dir{i}/conflict")))
+        .collect_vec();
+    let conflict_side = |side: &str| {
+        let files = conflict_paths
+            .iter()
+            .map(|path| (path.clone(), side.to_owned()))
+            .chain(new_files.iter().cloned())
+            .collect_vec();
+        create_single_tree(&repo, &to_path_contents(&files))
+    };
+    let tree1 = create_tree(&repo, &to_path_contents(&old_files));
+    let tree2 = MergedTree::new(Merge::from_vec(vec![
+        conflict_side("left\n"),
+        conflict_side("base\n"),
+        conflict_side("right\n"),
+    ]));
+    let commit1 = commit_with_tree(repo.store(), tree1.id());
+    let commit2 = commit_with_tree(repo.store(), tree2.id());
+
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit2,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    assert_eq!(
+        stats,
+        CheckoutStats {
+            updated_files: 0,
+            added_files: 303,
+            removed_files: 300,
+            skipped_files: 0,
+        }
+    );
+
+    for (path, contents) in &new_files {
+        let disk_path = path.to_fs_path_unchecked(&workspace_root);
+        assert_eq!(std::fs::read_to_string(disk_path).unwrap(), *contents);
+    }
+    for path in &conflict_paths {
+        let disk_path = path.to_fs_path_unchecked(&workspace_root);
+        assert!(std::fs::read_to_string(disk_path)
+            .unwrap()
+            .starts_with("<<<<<<<"));
+    }
+    for i in 5..10 {
+        assert!(!workspace_root.join(format!("dir{i}")).exists());
+    }
+
+    // The file states should match the files on disk
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree2.id());
+}
+
+#[test]
+fn test_checkout_large_file() {
+    // Files above the batching limit are streamed to disk, interleaved with
+    // small files that are batched.
+    let mut test_workspace = TestWorkspace::init();
+    let repo = test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let large_path = repo_path("large");
+    let small_path = repo_path("small");
+    let executable_path = repo_path("executable");
+    let large_contents = "0123456789abcdef\n".repeat(128 << 10);
+    let tree = create_tree(
+        &repo,
+        &[(large_path, &large_contents), (small_path, "small\n")],
+    );
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    let executable_id = testutils::write_file(repo.store(), executable_path, &large_contents);
+    tree_builder.set_or_remove(
+        executable_path.to_owned(),
+        Merge::normal(TreeValue::File {
+            id: executable_id,
+            executable: true,
+        }),
+    );
+    let tree_id = tree_builder.write_tree(repo.store()).unwrap();
+    let commit = commit_with_tree(repo.store(), tree_id.clone());
+
+    let ws = &mut test_workspace.workspace;
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    assert_eq!(stats.added_files, 3);
+
+    let read = |path: &RepoPath| std::fs::read(path.to_fs_path_unchecked(&workspace_root)).unwrap();
+    assert_eq!(read(large_path), large_contents.as_bytes());
+    assert_eq!(read(small_path), b"small\n");
+    assert_eq!(read(executable_path), large_contents.as_bytes());
+    #[cfg(unix)]
+    {
+        let metadata = executable_path
+            .to_fs_path_unchecked(&workspace_root)
+            .symlink_metadata()
+            .unwrap();
+        assert_ne!(metadata.permissions().mode() & 0o111, 0);
+    }
+
+    // The file states should match the files on disk
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree_id);
+}
+
 // Test case for issue #2165
 #[test]
 fn test_conflict_subdirectory() {
@@ -2140,6 +2294,95 @@ fn test_fsmonitor() {
     }
 }
 
+#[cfg(all(feature = "native-fsmonitor", unix))]
+#[test]
+fn test_native_fsmonitor() {
+    use jj_lib::fsmonitor::native;
+
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let ws = &mut test_workspace.workspace;
+    let state_path = ws
+        .working_copy()
+        .as_any()
+        .downcast_ref::<LocalWorkingCopy>()
+        .unwrap()
+        .state_path()
+        .to_owned();
+
+    let daemon = {
+        let workspace_root = workspace_root.clone();
+        let state_path = state_path.clone();
+        std::thread::spawn(move || native::run_daemon(&workspace_root, &state_path))
+    };
+    while !native::is_running(&state_path) {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    let foo_path = repo_path("foo");
+    let nested_path = repo_path("path/to/nested");
+    testutils::write_working_copy_file(&workspace_root, foo_path, "foo\n");
+    testutils::write_working_copy_file(&workspace_root, nested_path, "nested\n");
+
+    let snapshot = |ws: &mut Workspace| {
+        let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+        let (tree_id, _stats) = locked_ws
+            .locked_wc()
+            .snapshot(&SnapshotOptions {
+                fsmonitor_settings: FsmonitorSettings::Native,
+                ..SnapshotOptions::empty_for_test()
+            })
+            .unwrap();
+        locked_ws.finish(repo.op_id().clone()).unwrap();
+        tree_id
+    };
+
+    // The first query crawls the whole working copy
+    let wc = ws
+        .working_copy()
+        .as_any()
+        .downcast_ref::<LocalWorkingCopy>();
+    assert_eq!(wc.unwrap().query_native_fsmonitor().unwrap().1, None);
+    let tree_id = snapshot(ws);
+    insta::assert_snapshot!(testutils::dump_tree(repo.store(), &tree_id), @r#"
+    tree 1ea648d9caaac2c33693
+      file "foo" (e99c2057c15160add351): "foo\n"
+      file "path/to/nested" (6209060941cd770c8d46): "nested\n"
+    "#);
+
+    // A renamed directory is reported instead of the files in it
+    testutils::write_working_copy_file(&workspace_root, foo_path, "updated foo\n");
+    std::fs::rename(
+        workspace_root.join("path").join("to"),
+        workspace_root.join("path").join("moved"),
+    )
+    .unwrap();
+    let wc = ws
+        .working_copy()
+        .as_any()
+        .downcast_ref::<LocalWorkingCopy>();
+    let (_clock, changed_files) = wc.unwrap().query_native_fsmonitor().unwrap();
+    assert_eq!(
+        changed_files,
+        Some(vec![
+            PathBuf::from("foo"),
+            PathBuf::from("path/moved"),
+            PathBuf::from("path/to"),
+        ])
+    );
+    let tree_id = snapshot(ws);
+    insta::assert_snapshot!(testutils::dump_tree(repo.store(), &tree_id), @r#"
+    tree 9551b807d611ebb1f38d
+      file "foo" (e0fbd106147cc04ccd05): "updated foo\n"
+      file "path/moved/nested" (6209060941cd770c8d46): "nested\n"
+    "#);
+
+    native::stop(&state_path).unwrap();
+    daemon.join().unwrap().unwrap();
+    assert!(!native::is_running(&state_path));
+}
+
 #[test]
 fn test_snapshot_max_new_file_size() {
     let mut test_workspace = TestWorkspace::init();
@@ -416,6 +416,112 @@ fn test_reparent_range_branchy() {
     assert_eq!(new_op_f.parent_ids(), slice::from_ref(repo_d.op_id()));
 }
 
+#[test]
+fn test_compact_range() {
+    let test_repo = TestRepo::init();
+    let repo_0 = test_repo.repo;
+    let loader = repo_0.loader();
+    let op_store = repo_0.op_store();
+
+    let read_op = |id| loader.load_operation(id).unwrap();
+
+    fn op_parents<const N: usize>(op: &Operation) -> [Operation; N] {
+        let parents: Vec<_> = op.parents().try_collect().unwrap();
+        parents.try_into().unwrap()
+    }
+
+    // Set up branchy operation graph:
+    // F
+    // |\
+    // | E
+    // D |
+    // |/
+    // C
+    // B
+    // A
+    // 0 (initial)
+    let random_tx = |repo: &Arc<ReadonlyRepo>| {
+        let mut tx = repo.start_transaction();
+        write_random_commit(tx.repo_mut());
+        tx
+    };
+    let repo_a = random_tx(&repo_0).commit("op A").unwrap();
+    let repo_b = random_tx(&repo_a).commit("op B").unwrap();
+    let repo_c = random_tx(&repo_b).commit("op C").unwrap();
+    let tx_d = random_tx(&repo_c);
+    let tx_e = random_tx(&repo_c);
+    let repo_f = testutils::commit_transactions(vec![tx_d, tx_e]);
+
+    // Compact A..C (=B|C) and reparent F:
+    // F'
+    // |\
+    // | E'
+    // D'|
+    // |/
+    // BC
+    // A
+    // 0 (initial)
+    let stats = op_walk::compact_range(
+        op_store,
+        repo_a.operation(),
+        repo_c.operation(),
+        slice::from_ref(repo_f.operation()),
+    )
+    .unwrap();
+    assert_eq!(stats.new_head_ids.len(), 1);
+    assert_eq!(stats.rewritten_count, 3);
+    assert_eq!(stats.unreachable_count, 2);
+    let new_op_f = read_op(&stats.new_head_ids[0]);
+    assert_eq!(new_op_f.metadata(), repo_f.operation().metadata());
+    assert_eq!(new_op_f.view_id(), repo_f.operation().view_id());
+    let [new_op_d, new_op_e] = op_parents(&new_op_f);
+    assert_eq!(new_op_d.parent_ids(), new_op_e.parent_ids());
+    let [new_op_bc] = op_parents(&new_op_d);
+    assert_eq!(new_op_bc.view_id(), repo_c.operation().view_id());
+    assert_eq!(new_op_bc.parent_ids(), slice::from_ref(repo_a.op_id()));
+    let metadata = new_op_bc.metadata();
+    assert_eq!(metadata.description, "compact 2 operations");
+    assert_eq!(
+        metadata.start_time,
+        repo_b.operation().metadata().start_time
+    );
+    assert_eq!(metadata.end_time, repo_c.operation().metadata().end_time);
+    assert_eq!(metadata.undo_relation, None);
+
+    // Compact ..F (=A|B|C|D|E|F), where F is the head:
+    // ABCDEF
+    // 0 (initial)
+    let stats = op_walk::compact_range(
+        op_store,
+        &loader.root_operation(),
+        repo_f.operation(),
+        slice::from_ref(repo_f.operation()),
+    )
+    .unwrap();
+    assert_eq!(stats.new_head_ids.len(), 1);
+    assert_eq!(stats.rewritten_count, 0);
+    assert_eq!(stats.unreachable_count, 6);
+    let new_op = read_op(&stats.new_head_ids[0]);
+    assert_eq!(new_op.metadata().description, "compact 6 operations");
+    assert_eq!(new_op.view_id(), repo_f.operation().view_id());
+    assert_eq!(
+        new_op.parent_ids(),
+        slice::from_ref(op_store.root_operation_id())
+    );
+
+    // Single operation can't be compacted
+    let stats = op_walk::compact_range(
+        op_store,
+        repo_b.operation(),
+        repo_c.operation(),
+        slice::from_ref(repo_f.operation()),
+    )
+    .unwrap();
+    assert_eq!(stats.new_head_ids, vec![repo_f.op_id().clone()]);
+    assert_eq!(stats.rewritten_count, 0);
+    assert_eq!(stats.unreachable_count, 0);
+}
+
 fn stable_op_id_settings() -> UserSettings {
     let mut config = testutils::base_user_config();
     config.add_layer(
@@ -654,8 +760,21 @@ fn test_gc() {
     assert_eq!(list_dir(&op_dir), expected_op_entries);
     assert_eq!(list_dir(&view_dir), expected_view_entries);
 
+    // Dry run reports E|F and F's view, but doesn't remove them
+    let stats = op_store
+        .gc_with_stats(slice::from_ref(repo_d.op_id()), now, true)
+        .unwrap();
+    assert_eq!(stats.num_operations, 2);
+    assert_eq!(stats.num_views, 1);
+    assert_eq!(list_dir(&op_dir), expected_op_entries);
+    assert_eq!(list_dir(&view_dir), expected_view_entries);
+
     // E|F are no longer reachable, but E's view is still reachable
-    op_store.gc(slice::from_ref(repo_d.op_id()), now).unwrap();
+    let stats = op_store
+        .gc_with_stats(slice::from_ref(repo_d.op_id()), now, false)
+        .unwrap();
+    assert_eq!(stats.num_operations, 2);
+    assert_eq!(stats.num_views, 1);
     expected_op_entries
         .retain(|name| *name != repo_e.op_id().hex() && *name != repo_f.op_id().hex());
     expected_view_entries.retain(|name| *name != repo_f.operation().view_id().hex());
@@ -663,7 +782,11 @@ fn test_gc() {
     assert_eq!(list_dir(&view_dir), expected_view_entries);
 
     // B|C|D are no longer reachable
-    op_store.gc(slice::from_ref(repo_a.op_id()), now).unwrap();
+    let stats = op_store
+        .gc_with_stats(slice::from_ref(repo_a.op_id()), now, false)
+        .unwrap();
+    assert_eq!(stats.num_operations, 3);
+    assert_eq!(stats.num_views, 3);
     expected_op_entries.retain(|name| {
         *name != repo_b.op_id().hex()
             && *name != repo_c.op_id().hex()
@@ -0,0 +1,266 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::path::Path;
+use std::path::PathBuf;
+use std::thread;
+
+use assert_matches::assert_matches;
+use jj_lib::backend::Backend as _;
+use jj_lib::backend::BackendError;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::FileId;
+use jj_lib::backend::Tree;
+use jj_lib::backend::TreeValue;
+use jj_lib::object_id::HexPrefix;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::object_id::PrefixResolution;
+use jj_lib::op_store::OpStoreError;
+use jj_lib::op_store::RootOperationData;
+use jj_lib::op_store::ViewId;
+use jj_lib::remote_store::RemoteBackend;
+use jj_lib::remote_store::RemoteOpStore;
+use jj_lib::remote_store::RemoteStoreServer;
+use jj_lib::repo::ReadonlyRepo;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo::RepoLoader;
+use jj_lib::repo::StoreFactories;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathComponentBuf;
+use jj_lib::signing::Signer;
+use jj_lib::simple_backend::SimpleBackend;
+use jj_lib::simple_op_store::SimpleOpStore;
+use pollster::FutureExt as _;
+use testutils::create_tree;
+use testutils::new_temp_dir;
+use testutils::repo_path;
+use testutils::user_settings;
+
+/// Starts server backed by simple stores in `server_path`, and returns the
+/// address to connect to.
+fn start_server(server_path: &Path) -> String {
+    let store_path = server_path.join("store");
+    let op_store_path = server_path.join("op_store");
+    fs::create_dir_all(&store_path).unwrap();
+    fs::create_dir_all(&op_store_path).unwrap();
+    let backend = SimpleBackend::init(&store_path);
+    let root_data = RootOperationData {
+        root_commit_id: backend.root_commit_id().clone(),
+    };
+    let op_store = SimpleOpStore::init(&op_store_path, root_data).unwrap();
+    let server = RemoteStoreServer::new(Box::new(backend), Box::new(op_store));
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || server.serve(&listener));
+    address
+}
+
+fn init_client_dir(path: PathBuf) -> PathBuf {
+    fs::create_dir(&path).unwrap();
+    path
+}
+
+#[test]
+fn test_remote_backend_read_write() {
+    let temp_dir = new_temp_dir();
+    let address = start_server(&temp_dir.path().join("server"));
+    let backend =
+        RemoteBackend::init(&init_client_dir(temp_dir.path().join("store")), &address).unwrap();
+    let local_backend = SimpleBackend::load(&temp_dir.path().join("server").join("store"));
+    assert_eq!(backend.root_commit_id(), local_backend.root_commit_id());
+    assert_eq!(backend.empty_tree_id(), local_backend.empty_tree_id());
+    assert_eq!(backend.commit_id_length(), local_backend.commit_id_length());
+
+    // File path with characters to be escaped
+    let path = repo_path("dir/a b&c?d=%");
+    let file_id = backend
+        .write_file(path, &mut b"contents".as_slice())
+        .block_on()
+        .unwrap();
+    let mut contents = vec![];
+    backend
+        .read_file(path, &file_id)
+        .block_on()
+        .unwrap()
+        .read_to_end(&mut contents)
+        .unwrap();
+    assert_eq!(contents, b"contents");
+
+    let symlink_id = backend.write_symlink(path, "target").block_on().unwrap();
+    assert_eq!(
+        backend.read_symlink(path, &symlink_id).block_on().unwrap(),
+        "target"
+    );
+
+    let mut tree = Tree::default();
+    tree.set(
+        RepoPathComponentBuf::new("file").unwrap(),
+        TreeValue::File {
+            id: file_id.clone(),
+            executable: true,
+        },
+    );
+    let tree_id = backend
+        .write_tree(RepoPath::root(), &tree)
+        .block_on()
+        .unwrap();
+    // Objects should be stored in the server
+    assert_eq!(
+        local_backend
+            .read_tree(RepoPath::root(), &tree_id)
+            .block_on()
+            .unwrap(),
+        tree
+    );
+    assert_eq!(
+        backend
+            .read_tree(RepoPath::root(), &tree_id)
+            .block_on()
+            .unwrap(),
+        tree
+    );
+
+    let missing_id = FileId::from_bytes(&[0; 64]);
+    assert_matches!(
+        backend.read_file(path, &missing_id).block_on().map(|_| ()),
+        Err(BackendError::ObjectNotFound { .. })
+    );
+    assert_matches!(
+        backend
+            .read_commit(&CommitId::from_bytes(&[1; 64]))
+            .block_on(),
+        Err(BackendError::ObjectNotFound { .. })
+    );
+}
+
+#[test]
+fn test_remote_store_repo() {
+    let settings = user_settings();
+    let temp_dir = new_temp_dir();
+    let address = start_server(&temp_dir.path().join("server"));
+    let repo_dir = init_client_dir(temp_dir.path().join("repo"));
+
+    let repo = ReadonlyRepo::init(
+        &settings,
+        &repo_dir,
+        &|_settings, store_path| Ok(Box::new(RemoteBackend::init(store_path, &address)?)),
+        Signer::from_settings(&settings).unwrap(),
+        &|_settings, store_path, _root_data| {
+            Ok(Box::new(RemoteOpStore::init(store_path, &address)?))
+        },
+        ReadonlyRepo::default_op_heads_store_initializer(),
+        ReadonlyRepo::default_index_store_initializer(),
+        ReadonlyRepo::default_submodule_store_initializer(),
+    )
+    .unwrap();
+
+    let file_path = repo_path("file");
+    let tree = create_tree(&repo, &[(file_path, "contents")]);
+    let mut tx = repo.start_transaction();
+    let commit = tx
+        .repo_mut()
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree.id())
+        .set_description("remote commit")
+        .write()
+        .unwrap();
+    let repo = tx.commit("add commit").unwrap();
+
+    // Reload the repo from the remote stores
+    let loader =
+        RepoLoader::init_from_file_system(&settings, &repo_dir, &StoreFactories::default())
+            .unwrap();
+    let reloaded_repo = loader.load_at_head().unwrap();
+    assert_eq!(reloaded_repo.op_id(), repo.op_id());
+    let reloaded_commit = reloaded_repo.store().get_commit(commit.id()).unwrap();
+    assert_eq!(reloaded_commit.description(), "remote commit");
+    assert_eq!(reloaded_commit.tree_id(), commit.tree_id());
+    assert!(reloaded_repo.view().heads().contains(commit.id()));
+
+    // Operations are resolvable by prefix
+    let op_store = reloaded_repo.op_store();
+    let op_id = repo.op_id();
+    assert_eq!(
+        op_store
+            .resolve_operation_id_prefix(&HexPrefix::new(&op_id.hex()[..12]).unwrap())
+            .unwrap(),
+        PrefixResolution::SingleMatch(op_id.clone())
+    );
+    assert_eq!(
+        op_store
+            .resolve_operation_id_prefix(&HexPrefix::new("").unwrap())
+            .unwrap(),
+        PrefixResolution::AmbiguousMatch
+    );
+    assert_matches!(
+        op_store.read_view(&ViewId::from_bytes(&[1; 64])),
+        Err(OpStoreError::ObjectNotFound { .. })
+    );
+}
+
+/// Reads HTTP response, and returns the status line and the body.
+fn read_response(reader: &mut impl BufRead) -> (String, Vec<u8>) {
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line).unwrap();
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length: ") {
+            content_length = value.parse().unwrap();
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    (status_line.trim_end().to_owned(), body)
+}
+
+#[test]
+fn test_remote_store_server_connection() {
+    let temp_dir = new_temp_dir();
+    let address = start_server(&temp_dir.path().join("server"));
+    let mut stream = TcpStream::connect(&address).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    // Multiple requests can be sent over the same connection
+    for _ in 0..2 {
+        write!(stream, "GET /info HTTP/1.1\r\nContent-Length: 0\r\n\r\n").unwrap();
+        let (status_line, body) = read_response(&mut reader);
+        assert_eq!(status_line, "HTTP/1.1 200 OK");
+        assert!(body.starts_with(b"root-commit-id "));
+    }
+
+    // Large request body is rejected without reading it
+    write!(
+        stream,
+        "POST /files HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+        u64::MAX
+    )
+    .unwrap();
+    let (status_line, _) = read_response(&mut reader);
+    assert_eq!(status_line, "HTTP/1.1 413 Payload Too Large");
+    let mut rest = vec![];
+    reader.read_to_end(&mut rest).unwrap();
+    assert!(rest.is_empty());
+}
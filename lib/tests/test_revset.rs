@@ -2750,6 +2750,56 @@ fn test_evaluate_expression_description() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_trailer() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+
+    let commit1 = create_random_commit(mut_repo)
+        .set_description("commit 1\n\nFixes: #123\n")
+        .write()
+        .unwrap();
+    let commit2 = create_random_commit(mut_repo)
+        .set_parents(vec![commit1.id().clone()])
+        .set_description("commit 2\n\nFixes: #456\nReviewed-by: Alice <alice@example.com>\n")
+        .write()
+        .unwrap();
+    let commit3 = create_random_commit(mut_repo)
+        .set_parents(vec![commit2.id().clone()])
+        .set_description("commit 3\n\nFixes: #123 in the body isn't a trailer\nblah\n")
+        .write()
+        .unwrap();
+
+    // Match by key only
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "trailer(Fixes)"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(resolve_commit_ids(mut_repo, "trailer(Fix)"), vec![]);
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "trailer(glob:'*-by')"),
+        vec![commit2.id().clone()]
+    );
+    // Match by key and value
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "trailer(Fixes, '#123')"),
+        vec![commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "trailer(Reviewed-by, 'alice@')"),
+        vec![commit2.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "trailer(Fixes, exact:'#45')"),
+        vec![]
+    );
+    // Paragraph with non-trailer lines has no trailers
+    assert!(!resolve_commit_ids(mut_repo, "trailer(Fixes)").contains(commit3.id()));
+}
+
 #[test]
 fn test_evaluate_expression_author() {
     let test_repo = TestRepo::init();
@@ -3101,6 +3151,74 @@ fn test_evaluate_expression_signed() {
     assert!(unsigned_commits.contains(commit2.id()));
 }
 
+#[test]
+fn test_evaluate_expression_signature() {
+    let signer = Signer::new(Some(Box::new(TestSigningBackend)), vec![]);
+    let settings = testutils::user_settings();
+    let test_workspace =
+        TestWorkspace::init_with_backend_and_signer(TestRepoBackend::Test, signer, &settings);
+    let repo = test_workspace.repo.clone();
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+
+    let commit1 = create_random_commit(mut_repo)
+        .set_sign_behavior(SignBehavior::Own)
+        .set_sign_key("alice-key".to_owned())
+        .write()
+        .unwrap();
+    let commit2 = create_random_commit(mut_repo)
+        .set_parents(vec![commit1.id().clone()])
+        .set_sign_behavior(SignBehavior::Own)
+        .set_sign_key("bob-key".to_owned())
+        .write()
+        .unwrap();
+    let commit3 = create_random_commit(mut_repo)
+        .set_parents(vec![commit2.id().clone()])
+        .set_sign_behavior(SignBehavior::Drop)
+        .write()
+        .unwrap();
+    assert!(commit1.is_signed());
+    assert!(commit2.is_signed());
+    assert!(!commit3.is_signed());
+
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signed_by(alice)"),
+        vec![commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signed_by(glob:'*-key')"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    // Matches display string
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signed_by(exact:'test-display')"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(resolve_commit_ids(mut_repo, "signed_by(carol)"), vec![]);
+
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signature_status(good)"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signature_status(bad)"),
+        vec![]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signature_status(unknown)"),
+        vec![]
+    );
+    // Filters can be combined, and unsigned commits never match
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("{}:: & ~signature_status(good)", commit1.id())
+        ),
+        vec![commit3.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_committer() {
     let test_repo = TestRepo::init();
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use itertools::Itertools as _;
+use jj_lib::backend::RecordedCopy;
 use jj_lib::commit::Commit;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::matchers::FilesMatcher;
@@ -27,6 +28,7 @@ use jj_lib::ref_name::RemoteRefSymbol;
 use jj_lib::ref_name::WorkspaceName;
 use jj_lib::ref_name::WorkspaceNameBuf;
 use jj_lib::repo::Repo as _;
+use jj_lib::rewrite::rebase_commit;
 use jj_lib::rewrite::rebase_commit_with_options;
 use jj_lib::rewrite::restore_tree;
 use jj_lib::rewrite::CommitRewriter;
@@ -925,6 +927,53 @@ fn test_rebase_descendants_contents() {
     );
 }
 
+#[test]
+fn test_rebase_follows_recorded_rename() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // Commit B renames file1 to file2, and commit C modifies file1. Rebasing
+    // either commit onto the other should apply the modification to file2.
+    //
+    // B C
+    // |/
+    // A
+    let mut tx = repo.start_transaction();
+    let path1 = repo_path("file1");
+    let path2 = repo_path("file2");
+    let tree_a = create_tree(repo, &[(path1, "a\nb\nc\n")]);
+    let commit_a = tx
+        .repo_mut()
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree_a.id())
+        .write()
+        .unwrap();
+    let tree_b = create_tree(repo, &[(path2, "a\nb\nc\n")]);
+    let commit_b = tx
+        .repo_mut()
+        .new_commit(vec![commit_a.id().clone()], tree_b.id())
+        .set_copies(vec![RecordedCopy {
+            source: path1.to_owned(),
+            target: path2.to_owned(),
+        }])
+        .write()
+        .unwrap();
+    let tree_c = create_tree(repo, &[(path1, "a\nB\nc\n")]);
+    let commit_c = tx
+        .repo_mut()
+        .new_commit(vec![commit_a.id().clone()], tree_c.id())
+        .write()
+        .unwrap();
+    let expected_tree = create_tree(repo, &[(path2, "a\nB\nc\n")]);
+
+    let new_commit_b =
+        rebase_commit(tx.repo_mut(), commit_b.clone(), vec![commit_c.id().clone()]).unwrap();
+    assert_eq!(new_commit_b.tree_id(), &expected_tree.id());
+    assert_eq!(new_commit_b.copies(), commit_b.copies());
+
+    let new_commit_c = rebase_commit(tx.repo_mut(), commit_c, vec![commit_b.id().clone()]).unwrap();
+    assert_eq!(new_commit_c.tree_id(), &expected_tree.id());
+}
+
 #[test]
 fn test_rebase_descendants_basic_bookmark_update() {
     let test_repo = TestRepo::init();
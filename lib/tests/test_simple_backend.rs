@@ -0,0 +1,210 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::SystemTime;
+
+use jj_lib::backend::TreeValue;
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo as _;
+use testutils::create_tree;
+use testutils::read_file;
+use testutils::repo_path;
+use testutils::user_settings;
+use testutils::TestRepo;
+use testutils::TestRepoBackend;
+
+fn count_dir_entries(dir: &Path) -> usize {
+    fs::read_dir(dir).unwrap().count()
+}
+
+fn count_loose_objects(store_path: &Path) -> usize {
+    ["commits", "trees", "files", "symlinks", "conflicts"]
+        .iter()
+        .map(|name| count_dir_entries(&store_path.join(name)))
+        .sum()
+}
+
+#[test]
+fn test_gc_packs_loose_objects() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let repo = &test_repo.repo;
+    let store_path = test_repo.repo_path().join("store");
+    let file_path = repo_path("dir/file");
+    let other_path = repo_path("other");
+
+    // Create a chain of commits modifying the same file
+    let mut tx = repo.start_transaction();
+    let mut parent_id = repo.store().root_commit_id().clone();
+    let mut contents = vec![];
+    let mut commits: Vec<Commit> = vec![];
+    for i in 0..5 {
+        contents.push(format!("line {i}\n").repeat(100));
+        let tree = create_tree(
+            repo,
+            &[
+                (file_path, &contents.concat()),
+                (other_path, &i.to_string()),
+            ],
+        );
+        let commit = tx
+            .repo_mut()
+            .new_commit(vec![parent_id], tree.id())
+            .write()
+            .unwrap();
+        parent_id = commit.id().clone();
+        commits.push(commit);
+    }
+    let repo = tx.commit("test").unwrap();
+    let num_loose_objects = count_loose_objects(&store_path);
+    assert!(num_loose_objects > 0);
+    assert_eq!(count_dir_entries(&store_path.join("packs")), 0);
+
+    // Dry run shouldn't change anything
+    let stats = repo
+        .store()
+        .gc_with_stats(repo.index(), SystemTime::now(), true)
+        .unwrap();
+    assert_eq!(stats.num_objects, 0);
+    assert_eq!(stats.num_packed_objects, num_loose_objects);
+    assert_eq!(count_loose_objects(&store_path), num_loose_objects);
+    assert_eq!(count_dir_entries(&store_path.join("packs")), 0);
+
+    let stats = repo
+        .store()
+        .gc_with_stats(repo.index(), SystemTime::now(), false)
+        .unwrap();
+    assert_eq!(stats.num_objects, 0);
+    assert_eq!(stats.num_packed_objects, num_loose_objects);
+    assert_eq!(count_loose_objects(&store_path), 0);
+    assert_eq!(count_dir_entries(&store_path.join("packs")), 1);
+
+    // Packed objects should be readable from a freshly loaded store
+    let repo = test_repo
+        .env
+        .load_repo_at_head(&user_settings(), test_repo.repo_path());
+    for (i, commit) in commits.iter().enumerate() {
+        let commit = repo.store().get_commit(commit.id()).unwrap();
+        let tree = commit.tree().unwrap();
+        let value = tree.path_value(file_path).unwrap().into_resolved();
+        let Ok(Some(TreeValue::File { id, .. })) = value else {
+            panic!("file should exist");
+        };
+        assert_eq!(
+            read_file(repo.store(), file_path, &id),
+            contents[..=i].concat().into_bytes()
+        );
+    }
+
+    // New objects are written as loose files, and can be packed together
+    let mut tx = repo.start_transaction();
+    let tree = create_tree(&repo, &[(file_path, "new\n")]);
+    let new_commit = tx
+        .repo_mut()
+        .new_commit(vec![parent_id], tree.id())
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+    let num_new_loose_objects = count_loose_objects(&store_path);
+    assert!(num_new_loose_objects > 0);
+    // The existing pack is rewritten as well
+    let dry_run_stats = repo
+        .store()
+        .gc_with_stats(repo.index(), SystemTime::now(), true)
+        .unwrap();
+    assert!(dry_run_stats.num_packed_objects > num_new_loose_objects);
+    let stats = repo
+        .store()
+        .gc_with_stats(repo.index(), SystemTime::now(), false)
+        .unwrap();
+    assert_eq!(stats, dry_run_stats);
+    assert_eq!(count_loose_objects(&store_path), 0);
+    assert_eq!(count_dir_entries(&store_path.join("packs")), 1);
+
+    let repo = test_repo
+        .env
+        .load_repo_at_head(&user_settings(), test_repo.repo_path());
+    for commit in commits.iter().chain([&new_commit]) {
+        let loaded = repo.store().get_commit(commit.id()).unwrap();
+        assert_eq!(loaded.tree_id(), commit.tree_id());
+        assert!(loaded.tree().is_ok());
+    }
+}
+
+#[test]
+fn test_gc_without_packs_dir() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let repo = &test_repo.repo;
+    let store_path = test_repo.repo_path().join("store");
+
+    let mut tx = repo.start_transaction();
+    let tree = create_tree(repo, &[(repo_path("file"), "contents\n")]);
+    let commit = tx
+        .repo_mut()
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree.id())
+        .write()
+        .unwrap();
+    tx.commit("test").unwrap();
+
+    // Repos created by older versions don't have the packs directory
+    fs::remove_dir(store_path.join("packs")).unwrap();
+    let repo = test_repo
+        .env
+        .load_repo_at_head(&user_settings(), test_repo.repo_path());
+    repo.store().gc(repo.index(), SystemTime::now()).unwrap();
+    assert_eq!(count_loose_objects(&store_path), 0);
+    assert_eq!(count_dir_entries(&store_path.join("packs")), 1);
+
+    let repo = test_repo
+        .env
+        .load_repo_at_head(&user_settings(), test_repo.repo_path());
+    let loaded = repo.store().get_commit(commit.id()).unwrap();
+    assert_eq!(loaded.tree_id(), commit.tree_id());
+}
+
+#[test]
+fn test_gc_concurrent() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Simple);
+    let repo = &test_repo.repo;
+    let store_path = test_repo.repo_path().join("store");
+
+    let mut tx = repo.start_transaction();
+    let mut parent_id = repo.store().root_commit_id().clone();
+    for i in 0..100 {
+        let tree = create_tree(repo, &[(repo_path("file"), &i.to_string())]);
+        let commit = tx
+            .repo_mut()
+            .new_commit(vec![parent_id], tree.id())
+            .write()
+            .unwrap();
+        parent_id = commit.id().clone();
+    }
+    tx.commit("test").unwrap();
+
+    // Each thread loads its own store, as concurrent processes would
+    thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                let repo = test_repo
+                    .env
+                    .load_repo_at_head(&user_settings(), test_repo.repo_path());
+                repo.store().gc(repo.index(), SystemTime::now()).unwrap();
+            });
+        }
+    });
+    assert_eq!(count_loose_objects(&store_path), 0);
+    assert_eq!(count_dir_entries(&store_path.join("packs")), 1);
+}